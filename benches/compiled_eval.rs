@@ -0,0 +1,143 @@
+//! Compiled vs Tree-Walking Evaluation
+//!
+//! Evaluates the same expressions under many bindings, as generators and
+//! verifiers do, once through `Expr::evaluate` and once through the
+//! `Program` from `Expr::compile`, and checks the results are identical.
+//!
+//! Expressions:
+//! - Polynomial with shared subexpressions
+//! - Transcendental formula with foldable constants
+//! - Piecewise with short-circuit guards
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use veritas::symbolic::{constants, Context, Evaluate, Expr};
+
+const BINDINGS: usize = 10_000;
+const WARMUP_BINDINGS: usize = 500;
+
+/// (x + 1)⁴ + 3(x + 1)² - 2(x + 1) + 7
+fn polynomial() -> Expr {
+    let x = Expr::var("x");
+    let u = Expr::add(x, Expr::number(1));
+    Expr::add(
+        Expr::sub(
+            Expr::add(
+                Expr::pow(u.clone(), Expr::number(4)),
+                Expr::mul(Expr::number(3), Expr::pow(u.clone(), Expr::number(2))),
+            ),
+            Expr::mul(Expr::number(2), u),
+        ),
+        Expr::number(7),
+    )
+}
+
+/// sin(2πx)·exp(-x/√2) + cos(2πx)·ln(1 + x²)
+fn transcendental() -> Expr {
+    let x = Expr::var("x");
+    let theta = Expr::mul(Expr::mul(Expr::number(2), constants::pi()), x.clone());
+    Expr::add(
+        Expr::mul(
+            Expr::sin(theta.clone()),
+            Expr::exp(Expr::neg(Expr::div(x.clone(), Expr::sqrt(Expr::number(2))))),
+        ),
+        Expr::mul(
+            Expr::cos(theta),
+            Expr::ln(Expr::add(Expr::number(1), Expr::pow(x, Expr::number(2)))),
+        ),
+    )
+}
+
+/// x ≠ 0 ∧ 1/x > 2 ? 1/x : clamp(x², 0, 3)
+fn piecewise() -> Expr {
+    let x = Expr::var("x");
+    let recip = Expr::div(Expr::number(1), x.clone());
+    Expr::piecewise(
+        vec![(
+            Expr::and(
+                Expr::not_equals(x.clone(), Expr::number(0)),
+                Expr::greater(recip.clone(), Expr::number(2)),
+            ),
+            recip,
+        )],
+        Expr::clamp(Expr::mul(x.clone(), x), Expr::number(0), Expr::number(3)),
+    )
+}
+
+fn contexts(count: usize) -> Vec<Context> {
+    (0..count)
+        .map(|i| {
+            let mut ctx = Context::new();
+            ctx.bind("x", (i % 200) as f64 / 64.0 - 1.5);
+            ctx
+        })
+        .collect()
+}
+
+fn benchmark(name: &str, expr: &Expr) {
+    println!("=== {} ===\n", name);
+
+    let bindings = contexts(BINDINGS);
+
+    // Compilation is paid once
+    let start = Instant::now();
+    let program = expr.compile();
+    let compile_time = start.elapsed();
+
+    // Correctness: bit-identical results, and the same error where one fails
+    for ctx in &bindings {
+        assert_eq!(
+            program.evaluate(ctx),
+            expr.evaluate(ctx),
+            "compiled result differs"
+        );
+    }
+
+    // Warmup
+    for ctx in &bindings[..WARMUP_BINDINGS] {
+        black_box(expr.evaluate(ctx).ok());
+        black_box(program.evaluate(ctx).ok());
+    }
+
+    let start = Instant::now();
+    for ctx in &bindings {
+        black_box(expr.evaluate(ctx).ok());
+    }
+    let tree_time = start.elapsed();
+
+    let start = Instant::now();
+    for ctx in &bindings {
+        black_box(program.evaluate(ctx).ok());
+    }
+    let compiled_time = start.elapsed();
+
+    let per = |time: Duration| time.as_secs_f64() * 1e9 / BINDINGS as f64;
+    println!("Instructions:        {:8}  ({} registers)", program.len(), program.registers());
+    println!("Compile:             {:8.2}µs", compile_time.as_secs_f64() * 1e6);
+    println!("Tree walk:           {:8.0}ns per evaluation", per(tree_time));
+    println!(
+        "Compiled:            {:8.0}ns per evaluation  {:.2}× faster",
+        per(compiled_time),
+        tree_time.as_secs_f64() / compiled_time.as_secs_f64()
+    );
+    println!();
+}
+
+fn main() {
+    println!();
+    println!("═══════════════════════════════════════════════════════════════");
+    println!("  Compiled vs Tree-Walking Evaluation");
+    println!("═══════════════════════════════════════════════════════════════");
+    println!();
+
+    benchmark("Polynomial", &polynomial());
+    benchmark("Transcendental", &transcendental());
+    benchmark("Piecewise", &piecewise());
+
+    println!("═══════════════════════════════════════════════════════════════");
+    println!();
+    println!("Configuration:");
+    println!("  - {} bindings of x per expression", BINDINGS);
+    println!("  - Results compared before timing");
+    println!();
+}
@@ -65,12 +65,7 @@ fn generate_dataset() -> Vec<Example> {
             examples.push(Example {
                 input: input_str.as_bytes().to_vec(),
                 is_math: true,
-                metadata: Some(ParsedExpression {
-                    base: 12,
-                    operand_a: a,
-                    operand_b: b,
-                    operation: *op,
-                }),
+                metadata: Some(ParsedExpression::binary(12, a as i64, *op, b as i64)),
             });
         }
     }
@@ -146,11 +141,10 @@ fn generate_test_set() -> Vec<Example> {
             examples.push(Example {
                 input: input_str.as_bytes().to_vec(),
                 is_math: true,
-                metadata: Some(ParsedExpression {
-                    base: 12,
-                    operand_a: if template.contains("Subtract") { b } else { a },
-                    operand_b: if template.contains("Subtract") { a } else { b },
-                    operation: *op,
+                metadata: Some(if template.contains("Subtract") {
+                    ParsedExpression::binary(12, b as i64, *op, a as i64)
+                } else {
+                    ParsedExpression::binary(12, a as i64, *op, b as i64)
                 }),
             });
         }
@@ -110,9 +110,9 @@ fn main() {
             if ex.is_math {
                 let input_str = String::from_utf8_lossy(&ex.input);
                 if let Some(parsed) = parse_math_expression(&ex.input) {
-                    let result = call_basecalc(&parsed);
-                    println!("  \"{}\" → base={}, {}+{}={} (basecalc)",
-                        input_str.trim(), parsed.base, parsed.operand_a, parsed.operand_b, result);
+                    let result = call_basecalc(&parsed).unwrap();
+                    println!("  \"{}\" → base={}, {}={} (basecalc)",
+                        input_str.trim(), parsed.base, parsed, result);
                 }
             }
         }
@@ -65,12 +65,7 @@ fn generate_train_dataset() -> Vec<Example> {
             examples.push(Example {
                 input: input_str.as_bytes().to_vec(),
                 is_math: true,
-                metadata: Some(ParsedExpression {
-                    base: 12,
-                    operand_a: a,
-                    operand_b: b,
-                    operation: *op,
-                }),
+                metadata: Some(ParsedExpression::binary(12, a as i64, *op, b as i64)),
             });
         }
     }
@@ -155,11 +150,10 @@ fn generate_test_dataset() -> Vec<Example> {
             examples.push(Example {
                 input: input_str.as_bytes().to_vec(),
                 is_math: true,
-                metadata: Some(ParsedExpression {
-                    base: 12,
-                    operand_a: if template.contains("from") { b } else { a },
-                    operand_b: if template.contains("from") { a } else { b },
-                    operation: *op,
+                metadata: Some(if template.contains("from") {
+                    ParsedExpression::binary(12, b as i64, *op, a as i64)
+                } else {
+                    ParsedExpression::binary(12, a as i64, *op, b as i64)
                 }),
             });
         }
@@ -9,6 +9,7 @@
 use spirix::{ScalarF4E4, CircleF4E5};
 use std::sync::Arc;
 use crate::error::{Result, VeritasError};
+use crate::numeric::fft;
 
 /// Tensor shape
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -357,6 +358,75 @@ impl Tensor {
         }
     }
 
+    /// Discrete Fourier transform along the last axis
+    ///
+    /// Each row of a complex tensor is transformed independently; see
+    /// `numeric::fft` for the radix-2 and mixed-radix paths.
+    pub fn fft(&self) -> Result<Tensor> {
+        self.map_rows("fft", fft::fft)
+    }
+
+    /// Inverse Fourier transform along the last axis
+    pub fn ifft(&self) -> Result<Tensor> {
+        self.map_rows("ifft", fft::ifft)
+    }
+
+    /// Linear convolution of two complex vectors, via FFT
+    ///
+    /// For lengths M and N the result has length M + N - 1
+    pub fn convolve(&self, other: &Tensor) -> Result<Tensor> {
+        if self.shape.rank() != 1 || other.shape.rank() != 1 {
+            return Err(VeritasError::InvalidInput(
+                format!("convolve() requires 1D tensors, got {:?} and {:?}",
+                    self.shape.dims, other.shape.dims)
+            ));
+        }
+
+        match (&self.data, &other.data) {
+            (TensorData::CpuComplex(a), TensorData::CpuComplex(b)) => {
+                let result = fft::convolve(a, b)?;
+                Ok(Tensor {
+                    shape: Shape::vector(result.len()),
+                    data: TensorData::CpuComplex(result),
+                    grad: None,
+                    requires_grad: self.requires_grad || other.requires_grad,
+                })
+            }
+            _ => Err(VeritasError::InvalidInput(
+                "convolve() only supports CpuComplex tensors currently".to_string()
+            ))
+        }
+    }
+
+    /// Apply a transform to each row along the last axis of complex data
+    fn map_rows(
+        &self,
+        name: &str,
+        transform: fn(&[CircleF4E5]) -> Result<Vec<CircleF4E5>>,
+    ) -> Result<Tensor> {
+        match &self.data {
+            TensorData::CpuComplex(data) => {
+                let row_len = self.shape.dims.last().copied().unwrap_or(1);
+                let mut transformed = Vec::with_capacity(data.len());
+                if row_len > 0 {
+                    for row in data.chunks(row_len) {
+                        transformed.extend(transform(row)?);
+                    }
+                }
+
+                Ok(Tensor {
+                    shape: self.shape.clone(),
+                    data: TensorData::CpuComplex(transformed),
+                    grad: None,
+                    requires_grad: self.requires_grad,
+                })
+            }
+            _ => Err(VeritasError::InvalidInput(
+                format!("{}() only supports CpuComplex tensors currently", name)
+            ))
+        }
+    }
+
 }
 
 impl std::fmt::Debug for Tensor {
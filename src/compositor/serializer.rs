@@ -95,6 +95,9 @@ impl NaturalLanguageSerializer {
                 Justification::ProvenFact(id) => {
                     format!(" (by proven fact #{})", id)
                 }
+                Justification::ConditionalIdentity { rule, conditions } => {
+                    format!(" (by {}, assuming {})", rule, conditions.join(", "))
+                }
             };
             format!("{}{}", transformation_text, justification_text)
         } else {
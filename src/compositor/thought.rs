@@ -80,6 +80,12 @@ pub enum Justification {
 
     /// Previously proven fact
     ProvenFact(usize),
+
+    /// Identity that only holds under stated conditions (e.g. "x ≥ 0")
+    ConditionalIdentity {
+        rule: String,
+        conditions: Vec<String>,
+    },
 }
 
 /// Dependency between thoughts
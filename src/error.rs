@@ -2,6 +2,8 @@
 //!
 //! All errors are strongly typed. No string errors, no wildcards.
 
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, VeritasError>;
@@ -21,6 +23,15 @@ pub enum VeritasError {
     #[error("Expression too complex: depth {0} exceeds limit")]
     ComplexityLimit(usize),
 
+    #[error("Dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: String, actual: String },
+
+    #[error("Shape mismatch: expected {expected}, got {actual}")]
+    ShapeMismatch { expected: String, actual: String },
+
+    #[error("Evaluation budget exceeded: {resource} limit passed after {used}")]
+    BudgetExceeded { resource: Resource, used: Usage },
+
     // Verification errors
     #[error("Verification failed: expected {expected}, got {actual}")]
     VerificationFailed { expected: String, actual: String },
@@ -44,6 +55,13 @@ pub enum VeritasError {
     #[error("Undefined numeric operation: {0}")]
     UndefinedOperation(String),
 
+    #[error("Integration stopped at t = {t} in step {step}: {cause}")]
+    IntegrationStopped {
+        t: String,
+        step: usize,
+        cause: Box<VeritasError>,
+    },
+
     // Persistence errors
     #[error("Failed to encode: {0}")]
     EncodingError(String),
@@ -102,3 +120,50 @@ impl VeritasError {
         )
     }
 }
+
+/// Which limit of a `symbolic::EvalBudget` was passed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Operations,
+    Depth,
+    Digits,
+    Time,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Resource::Operations => "operation",
+            Resource::Depth => "depth",
+            Resource::Digits => "digit",
+            Resource::Time => "time",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Work done by one evaluation or simplification, up to where it stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub operations: u64,
+    /// Deepest nesting reached
+    pub depth: usize,
+    /// Most decimal digits before the point in any value produced
+    pub digits: u64,
+    /// Time since the call started, when the budget has a deadline
+    pub elapsed: Option<Duration>,
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} operations, depth {}, {} digits",
+            self.operations, self.depth, self.digits
+        )?;
+        if let Some(elapsed) = self.elapsed {
+            write!(f, ", {:?}", elapsed)?;
+        }
+        Ok(())
+    }
+}
@@ -42,6 +42,9 @@ pub mod gpu;
 pub mod autograd;
 pub mod iteration;
 pub mod encoding;
+pub mod logic;
+pub mod number_theory;
+pub mod optimization;
 pub mod transformer;
 
 pub mod error;
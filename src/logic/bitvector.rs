@@ -0,0 +1,427 @@
+//! Bit-vector constraint solving
+//!
+//! Constraints such as `x & 0x0F == 5, x ^ y == 0xAA` are bit-blasted:
+//! every variable becomes one propositional variable per bit, every
+//! operation a small circuit whose gates are encoded as clauses, and each
+//! constraint asserts that the two sides agree (or differ) bit by bit.
+//! The clauses go to the DPLL solver in `sat`. A model is read back as
+//! numbers and every constraint is re-evaluated through
+//! `BitwiseProblem::solve` before the solution is returned.
+//!
+//! Shift and rotate amounts must be constant; they select wires rather
+//! than build a barrel shifter.
+
+use super::cnf::{Cnf, Literal};
+use super::sat::{solve, Outcome};
+use crate::error::{Result, VeritasError};
+use crate::symbolic::bitwise::{
+    check_arity, check_width, BitExpr, BitRelation, BitwiseOp, BitwiseProblem, Parser,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Value of every variable in a problem
+pub type Solution = BTreeMap<String, u64>;
+
+/// Equation or disequation between two bit-vector expressions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub left: BitExpr,
+    pub relation: BitRelation,
+    pub right: BitExpr,
+}
+
+impl Constraint {
+    pub fn new(left: BitExpr, relation: BitRelation, right: BitExpr) -> Self {
+        Constraint {
+            left,
+            relation,
+            right,
+        }
+    }
+
+    /// Evaluate both sides with `values` substituted
+    pub fn holds(&self, width: u32, values: &Solution) -> Result<bool> {
+        let values: HashMap<String, u64> = values.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        let side = |expr: &BitExpr| -> Result<u64> {
+            let problem = BitwiseProblem::with_width(width, expr.substitute(&values))?;
+            Ok(problem.solve()?.answer)
+        };
+
+        let (left, right) = (side(&self.left)?, side(&self.right)?);
+        Ok(match self.relation {
+            BitRelation::Eq => left == right,
+            BitRelation::Ne => left != right,
+        })
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let relation = match self.relation {
+            BitRelation::Eq => "==",
+            BitRelation::Ne => "!=",
+        };
+        write!(f, "{} {} {}", self.left, relation, self.right)
+    }
+}
+
+/// Conjunction of constraints over variables of one width
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitVectorProblem {
+    width: u32,
+    constraints: Vec<Constraint>,
+}
+
+impl BitVectorProblem {
+    pub fn new(width: u32) -> Result<Self> {
+        check_width(width)?;
+        Ok(BitVectorProblem {
+            width,
+            constraints: Vec::new(),
+        })
+    }
+
+    /// Parse constraints joined by `and`, `&&` or `,`
+    ///
+    /// A leading "find x such that" is allowed and ignored; the solution
+    /// always covers every variable.
+    pub fn parse(width: u32, text: &str) -> Result<Self> {
+        let text = text.trim();
+        let body = match (text.strip_prefix("find "), text.find("such that")) {
+            (Some(_), Some(at)) => &text[at + "such that".len()..],
+            _ => text,
+        };
+
+        let mut parser = Parser::new(body)?;
+        let constraints = parser.constraints()?;
+        parser.finish()?;
+
+        let mut problem = BitVectorProblem::new(width)?;
+        for (left, relation, right) in constraints {
+            problem.constrain(Constraint::new(left, relation, right));
+        }
+        Ok(problem)
+    }
+
+    pub fn constrain(&mut self, constraint: Constraint) {
+        self.constraints.push(constraint);
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Variable names, sorted
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .constraints
+            .iter()
+            .flat_map(|c| c.left.variables().into_iter().chain(c.right.variables()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Values satisfying every constraint, or `None` if there are none
+    pub fn solve(&self) -> Result<Option<Solution>> {
+        let mut blaster = Blaster {
+            cnf: Cnf::new(),
+            width: self.width as usize,
+            variables: BTreeMap::new(),
+            gates: 0,
+        };
+        for name in self.variables() {
+            blaster.declare(&name);
+        }
+        for constraint in &self.constraints {
+            blaster.constrain(constraint)?;
+        }
+
+        let model = match solve(&blaster.cnf)? {
+            Outcome::Satisfiable(model) => model,
+            Outcome::Unsatisfiable(_) => return Ok(None),
+        };
+
+        let solution: Solution = blaster
+            .variables
+            .iter()
+            .map(|(name, bits)| {
+                let value = bits
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bit)| bit.value(&model))
+                    .fold(0u64, |acc, (i, _)| acc | (1 << i));
+                (name.clone(), value)
+            })
+            .collect();
+
+        for constraint in &self.constraints {
+            if !constraint.holds(self.width, &solution)? {
+                return Err(VeritasError::VerificationFailed {
+                    expected: constraint.to_string(),
+                    actual: format!("false for {:?}", solution),
+                });
+            }
+        }
+        Ok(Some(solution))
+    }
+}
+
+/// Wire in a circuit: a known value or a literal of the CNF
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bit {
+    Const(bool),
+    Lit(Literal),
+}
+
+impl Bit {
+    fn value(&self, model: &[bool]) -> bool {
+        match self {
+            Bit::Const(b) => *b,
+            Bit::Lit(l) => l.holds(model[l.var]),
+        }
+    }
+}
+
+impl std::ops::Not for Bit {
+    type Output = Bit;
+
+    fn not(self) -> Bit {
+        match self {
+            Bit::Const(b) => Bit::Const(!b),
+            Bit::Lit(l) => Bit::Lit(l.negate()),
+        }
+    }
+}
+
+/// Builds clauses for circuits, least significant bit first
+struct Blaster {
+    cnf: Cnf,
+    width: usize,
+    variables: BTreeMap<String, Vec<Bit>>,
+    gates: usize,
+}
+
+impl Blaster {
+    fn declare(&mut self, name: &str) {
+        let bits = (0..self.width)
+            .map(|i| {
+                Bit::Lit(Literal::new(
+                    self.cnf.fresh(format!("{}[{}]", name, i)),
+                    true,
+                ))
+            })
+            .collect();
+        self.variables.insert(name.to_string(), bits);
+    }
+
+    fn gate(&mut self) -> Literal {
+        self.gates += 1;
+        Literal::new(self.cnf.fresh(format!("g{}", self.gates)), true)
+    }
+
+    /// Require at least one of `bits` to be true
+    fn require(&mut self, bits: &[Bit]) {
+        if bits.contains(&Bit::Const(true)) {
+            return;
+        }
+        let literals = bits
+            .iter()
+            .filter_map(|bit| match bit {
+                Bit::Lit(l) => Some(*l),
+                Bit::Const(_) => None,
+            })
+            .collect();
+        self.cnf.add(literals);
+    }
+
+    fn and(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(false), _) | (_, Bit::Const(false)) => Bit::Const(false),
+            (Bit::Const(true), x) | (x, Bit::Const(true)) => x,
+            (Bit::Lit(x), Bit::Lit(y)) if x == y => a,
+            (Bit::Lit(x), Bit::Lit(y)) if x == y.negate() => Bit::Const(false),
+            (Bit::Lit(x), Bit::Lit(y)) => {
+                // g ↔ x ∧ y
+                let g = self.gate();
+                self.cnf.add(vec![g.negate(), x]);
+                self.cnf.add(vec![g.negate(), y]);
+                self.cnf.add(vec![g, x.negate(), y.negate()]);
+                Bit::Lit(g)
+            }
+        }
+    }
+
+    fn or(&mut self, a: Bit, b: Bit) -> Bit {
+        !self.and(!a, !b)
+    }
+
+    fn xor(&mut self, a: Bit, b: Bit) -> Bit {
+        match (a, b) {
+            (Bit::Const(p), x) | (x, Bit::Const(p)) => {
+                if p {
+                    !x
+                } else {
+                    x
+                }
+            }
+            (Bit::Lit(x), Bit::Lit(y)) if x == y => Bit::Const(false),
+            (Bit::Lit(x), Bit::Lit(y)) if x == y.negate() => Bit::Const(true),
+            (Bit::Lit(x), Bit::Lit(y)) => {
+                // g ↔ x ⊕ y
+                let g = self.gate();
+                self.cnf.add(vec![g.negate(), x, y]);
+                self.cnf.add(vec![g.negate(), x.negate(), y.negate()]);
+                self.cnf.add(vec![g, x.negate(), y]);
+                self.cnf.add(vec![g, x, y.negate()]);
+                Bit::Lit(g)
+            }
+        }
+    }
+
+    fn blast(&mut self, expr: &BitExpr) -> Result<Vec<Bit>> {
+        let width = self.width;
+        let (op, args) = match expr {
+            BitExpr::Const(_) => {
+                let value = expr.evaluate(width as u32)?;
+                return Ok((0..width)
+                    .map(|i| Bit::Const(value >> i & 1 == 1))
+                    .collect());
+            }
+            BitExpr::Var(name) => {
+                if !self.variables.contains_key(name) {
+                    self.declare(name);
+                }
+                return Ok(self.variables[name].clone());
+            }
+            BitExpr::Apply(op, args) => (*op, args),
+        };
+        check_arity(op, args.len())?;
+        let a = self.blast(&args[0])?;
+
+        let bits = match op {
+            BitwiseOp::And | BitwiseOp::Or | BitwiseOp::Xor => {
+                let b = self.blast(&args[1])?;
+                let mut bits = Vec::with_capacity(width);
+                for (x, y) in a.into_iter().zip(b) {
+                    bits.push(match op {
+                        BitwiseOp::And => self.and(x, y),
+                        BitwiseOp::Or => self.or(x, y),
+                        _ => self.xor(x, y),
+                    });
+                }
+                bits
+            }
+            BitwiseOp::Not => a.into_iter().map(|x| !x).collect(),
+            BitwiseOp::Shl | BitwiseOp::Shr | BitwiseOp::Rotl | BitwiseOp::Rotr => {
+                if !args[1].variables().is_empty() {
+                    return Err(VeritasError::InvalidInput(format!(
+                        "{} amount must be constant, got {}",
+                        op.name(),
+                        args[1]
+                    )));
+                }
+                let amount = args[1].evaluate(width as u32)?;
+                let k = (amount - amount / width as u64 * width as u64) as usize;
+                (0..width)
+                    .map(|i| match op {
+                        BitwiseOp::Shl if i >= k => a[i - k],
+                        BitwiseOp::Shr if i + k < width => a[i + k],
+                        BitwiseOp::Rotl => a[if i >= k { i - k } else { i + width - k }],
+                        BitwiseOp::Rotr => a[if i + k < width { i + k } else { i + k - width }],
+                        _ => Bit::Const(false),
+                    })
+                    .collect()
+            }
+            BitwiseOp::Popcount => {
+                // Add the bits one at a time into a counter of the same width
+                let mut count = vec![Bit::Const(false); width];
+                for bit in a {
+                    let mut carry = bit;
+                    for digit in count.iter_mut() {
+                        let sum = self.xor(*digit, carry);
+                        carry = self.and(*digit, carry);
+                        *digit = sum;
+                    }
+                }
+                count
+            }
+        };
+        Ok(bits)
+    }
+
+    fn constrain(&mut self, constraint: &Constraint) -> Result<()> {
+        let left = self.blast(&constraint.left)?;
+        let right = self.blast(&constraint.right)?;
+        let mut differences = Vec::with_capacity(self.width);
+        for (x, y) in left.into_iter().zip(right) {
+            differences.push(self.xor(x, y));
+        }
+
+        match constraint.relation {
+            BitRelation::Eq => {
+                for difference in differences {
+                    self.require(&[!difference]);
+                }
+            }
+            BitRelation::Ne => self.require(&differences),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_byte() {
+        let problem =
+            BitVectorProblem::parse(8, "find x such that x & 0x0F == 5 and x ^ y == 0xAA").unwrap();
+        let solution = problem.solve().unwrap().unwrap();
+        let (x, y) = (solution["x"], solution["y"]);
+        assert_eq!(x & 0x0F, 5);
+        assert_eq!(x ^ y, 0xAA);
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        // Shifting left always clears the lowest bit
+        let problem = BitVectorProblem::parse(8, "x << 1 & 1 == 1").unwrap();
+        assert_eq!(problem.solve().unwrap(), None);
+
+        let contradiction = BitVectorProblem::parse(4, "x == 3, x != 3").unwrap();
+        assert_eq!(contradiction.solve().unwrap(), None);
+    }
+
+    #[test]
+    fn test_wide_compositions() {
+        let text = "(a ^ b) << 2 & c == 0x30, c == 0xFF00FF, a != b, popcount(a) == 3, \
+                    rotr(b, 8) == 0x01000000";
+        let problem = BitVectorProblem::parse(32, text).unwrap();
+        let solution = problem.solve().unwrap().unwrap();
+
+        assert_eq!(solution["b"], 1);
+        assert_eq!(solution["a"].count_ones(), 3);
+        assert_eq!(((solution["a"] ^ solution["b"]) << 2) & 0xFF00FF, 0x30);
+        for constraint in problem.constraints() {
+            assert!(constraint.holds(32, &solution).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rejects_variable_shift() {
+        let problem = BitVectorProblem::parse(8, "x << y == 4").unwrap();
+        assert!(matches!(
+            problem.solve(),
+            Err(VeritasError::InvalidInput(_))
+        ));
+        assert!(BitVectorProblem::parse(8, "x & 1").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Conjunctive normal form
+//!
+//! A formula is rewritten without → and ↔, negations are pushed down to
+//! the variables and ∨ is distributed over ∧. Distribution can blow up
+//! exponentially, so the number of clauses is capped. Encoders that build
+//! clauses directly (bit-blasting, for instance) use `fresh` and `add`.
+
+use super::formula::{Assignment, Formula};
+use crate::error::{Result, VeritasError};
+use std::collections::HashMap;
+
+/// Most clauses a formula may distribute into
+pub const MAX_CLAUSES: usize = 1 << 16;
+
+/// Variable (by index) or its negation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Literal {
+    pub var: usize,
+    pub positive: bool,
+}
+
+impl Literal {
+    pub fn new(var: usize, positive: bool) -> Self {
+        Literal { var, positive }
+    }
+
+    pub fn negate(self) -> Self {
+        Literal::new(self.var, !self.positive)
+    }
+
+    /// Truth value when the variable is set to `value`
+    pub fn holds(self, value: bool) -> bool {
+        self.positive == value
+    }
+}
+
+/// Disjunction of literals, kept sorted and free of duplicates
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Clause(Vec<Literal>);
+
+impl Clause {
+    pub fn new(mut literals: Vec<Literal>) -> Self {
+        literals.sort();
+        literals.dedup();
+        Clause(literals)
+    }
+
+    pub fn literals(&self) -> &[Literal] {
+        &self.0
+    }
+
+    /// The empty clause is false
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, literal: Literal) -> bool {
+        self.0.binary_search(&literal).is_ok()
+    }
+
+    /// Contains some variable and its negation, so is always true
+    pub fn is_tautology(&self) -> bool {
+        self.0.windows(2).any(|w| w[0].var == w[1].var)
+    }
+
+    /// Resolvent on `pivot`: one clause must contain the variable and the
+    /// other its negation; the result keeps every other literal
+    pub fn resolve(&self, other: &Clause, pivot: usize) -> Option<Clause> {
+        let (pos, neg) = (Literal::new(pivot, true), Literal::new(pivot, false));
+        let matched = (self.contains(pos) && other.contains(neg))
+            || (self.contains(neg) && other.contains(pos));
+        if !matched {
+            return None;
+        }
+
+        let literals = self
+            .0
+            .iter()
+            .chain(other.0.iter())
+            .filter(|l| l.var != pivot)
+            .copied()
+            .collect();
+        Some(Clause::new(literals))
+    }
+
+    /// Combine two clauses into their disjunction
+    fn union(&self, other: &Clause) -> Clause {
+        Clause::new(self.0.iter().chain(other.0.iter()).copied().collect())
+    }
+}
+
+/// Conjunction of clauses over named variables
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cnf {
+    variables: Vec<String>,
+    clauses: Vec<Clause>,
+}
+
+impl Cnf {
+    pub fn new() -> Self {
+        Cnf::default()
+    }
+
+    /// Convert a formula; variables are numbered in sorted name order
+    pub fn from_formula(formula: &Formula) -> Result<Cnf> {
+        let variables = formula.variables();
+        let index: HashMap<&str, usize> = variables
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let mut clauses = clauses_of(formula, true, &index)?;
+        clauses.sort();
+        clauses.dedup();
+        Ok(Cnf { variables, clauses })
+    }
+
+    /// Add a new variable and return its index
+    pub fn fresh(&mut self, name: impl Into<String>) -> usize {
+        self.variables.push(name.into());
+        self.variables.len() - 1
+    }
+
+    /// Add a clause; tautologies are dropped
+    pub fn add(&mut self, literals: Vec<Literal>) {
+        let clause = Clause::new(literals);
+        if !clause.is_tautology() {
+            self.clauses.push(clause);
+        }
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    pub fn clauses(&self) -> &[Clause] {
+        &self.clauses
+    }
+
+    /// Name the values of a model, indexed like the variables
+    pub fn assignment(&self, model: &[bool]) -> Assignment {
+        self.variables
+            .iter()
+            .cloned()
+            .zip(model.iter().copied())
+            .collect()
+    }
+
+    /// Clause with variable names, e.g. "{¬p, q}"; the empty clause is "□"
+    pub fn describe(&self, clause: &Clause) -> String {
+        if clause.is_empty() {
+            return "□".to_string();
+        }
+        let literals: Vec<String> = clause
+            .literals()
+            .iter()
+            .map(|l| {
+                let name = self
+                    .variables
+                    .get(l.var)
+                    .cloned()
+                    .unwrap_or_else(|| format!("x{}", l.var));
+                if l.positive {
+                    name
+                } else {
+                    format!("¬{}", name)
+                }
+            })
+            .collect();
+        format!("{{{}}}", literals.join(", "))
+    }
+}
+
+/// Clauses equivalent to `formula` (or its negation when `positive` is
+/// false). No clauses means true; a single empty clause means false.
+fn clauses_of(
+    formula: &Formula,
+    positive: bool,
+    index: &HashMap<&str, usize>,
+) -> Result<Vec<Clause>> {
+    let recurse = |f: &Formula, p: bool| clauses_of(f, p, index);
+
+    match (formula, positive) {
+        (Formula::Const(b), _) => Ok(if *b == positive {
+            Vec::new()
+        } else {
+            vec![Clause::new(Vec::new())]
+        }),
+        (Formula::Var(name), _) => Ok(vec![Clause::new(vec![Literal::new(
+            index[name.as_str()],
+            positive,
+        )])]),
+        (Formula::Not(a), _) => recurse(a, !positive),
+
+        (Formula::And(a, b), true) | (Formula::Or(a, b), false) => {
+            conjunction(recurse(a, positive)?, recurse(b, positive)?)
+        }
+        (Formula::Or(a, b), true) | (Formula::And(a, b), false) => {
+            disjunction(&recurse(a, positive)?, &recurse(b, positive)?)
+        }
+
+        // a → b is ¬a ∨ b, and its negation a ∧ ¬b
+        (Formula::Implies(a, b), true) => disjunction(&recurse(a, false)?, &recurse(b, true)?),
+        (Formula::Implies(a, b), false) => conjunction(recurse(a, true)?, recurse(b, false)?),
+
+        // a ↔ b is (¬a ∨ b) ∧ (a ∨ ¬b), and its negation (a ∨ b) ∧ (¬a ∨ ¬b)
+        (Formula::Iff(a, b), _) => {
+            let (na, nb) = (recurse(a, false)?, recurse(b, false)?);
+            let (pa, pb) = (recurse(a, true)?, recurse(b, true)?);
+            if positive {
+                conjunction(disjunction(&na, &pb)?, disjunction(&pa, &nb)?)
+            } else {
+                conjunction(disjunction(&pa, &pb)?, disjunction(&na, &nb)?)
+            }
+        }
+    }
+}
+
+fn conjunction(mut a: Vec<Clause>, b: Vec<Clause>) -> Result<Vec<Clause>> {
+    a.extend(b);
+    if a.len() > MAX_CLAUSES {
+        return Err(VeritasError::ComplexityLimit(a.len()));
+    }
+    Ok(a)
+}
+
+/// Distribute: every clause of `a` joined with every clause of `b`
+fn disjunction(a: &[Clause], b: &[Clause]) -> Result<Vec<Clause>> {
+    if a.len().saturating_mul(b.len()) > MAX_CLAUSES {
+        return Err(VeritasError::ComplexityLimit(a.len() * b.len()));
+    }
+
+    let mut clauses = Vec::with_capacity(a.len() * b.len());
+    for left in a {
+        for right in b {
+            let clause = left.union(right);
+            if !clause.is_tautology() {
+                clauses.push(clause);
+            }
+        }
+    }
+    Ok(clauses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cnf_of_implication() {
+        // ¬(p → q) is p ∧ ¬q
+        let cnf = Cnf::from_formula(&Formula::parse("~(p -> q)").unwrap()).unwrap();
+        assert_eq!(cnf.variables(), ["p", "q"]);
+        let described: Vec<String> = cnf.clauses().iter().map(|c| cnf.describe(c)).collect();
+        assert_eq!(described, ["{p}", "{¬q}"]);
+    }
+
+    #[test]
+    fn test_constants_and_tautologies() {
+        let valid = Cnf::from_formula(&Formula::parse("p | ~p").unwrap()).unwrap();
+        assert!(valid.clauses().is_empty());
+
+        let invalid = Cnf::from_formula(&Formula::parse("false").unwrap()).unwrap();
+        assert_eq!(invalid.clauses(), [Clause::new(Vec::new())]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let a = Clause::new(vec![Literal::new(0, true), Literal::new(1, true)]);
+        let b = Clause::new(vec![Literal::new(0, false), Literal::new(2, true)]);
+        let r = a.resolve(&b, 0).unwrap();
+        assert_eq!(r, Clause::new(vec![Literal::new(1, true), Literal::new(2, true)]));
+        assert!(a.resolve(&b, 1).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+//! Propositional formulas
+
+use crate::error::{Result, VeritasError};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Truth values for named variables
+pub type Assignment = BTreeMap<String, bool>;
+
+/// Propositional formula
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Formula {
+    /// ⊤ or ⊥
+    Const(bool),
+    Var(String),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    pub fn var(name: impl Into<String>) -> Self {
+        Formula::Var(name.into())
+    }
+
+    pub fn and(a: Formula, b: Formula) -> Self {
+        Formula::And(Box::new(a), Box::new(b))
+    }
+
+    pub fn or(a: Formula, b: Formula) -> Self {
+        Formula::Or(Box::new(a), Box::new(b))
+    }
+
+    pub fn implies(a: Formula, b: Formula) -> Self {
+        Formula::Implies(Box::new(a), Box::new(b))
+    }
+
+    pub fn iff(a: Formula, b: Formula) -> Self {
+        Formula::Iff(Box::new(a), Box::new(b))
+    }
+
+    /// Variable names, sorted
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_variables(&mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn collect_variables(&self, names: &mut Vec<String>) {
+        match self {
+            Formula::Const(_) => {}
+            Formula::Var(name) => names.push(name.clone()),
+            Formula::Not(a) => a.collect_variables(names),
+            Formula::And(a, b)
+            | Formula::Or(a, b)
+            | Formula::Implies(a, b)
+            | Formula::Iff(a, b) => {
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+        }
+    }
+
+    /// Truth value under an assignment of every variable
+    pub fn evaluate(&self, assignment: &Assignment) -> Result<bool> {
+        Ok(match self {
+            Formula::Const(b) => *b,
+            Formula::Var(name) => *assignment
+                .get(name)
+                .ok_or_else(|| VeritasError::VariableNotFound(name.clone()))?,
+            Formula::Not(a) => !a.evaluate(assignment)?,
+            Formula::And(a, b) => a.evaluate(assignment)? && b.evaluate(assignment)?,
+            Formula::Or(a, b) => a.evaluate(assignment)? || b.evaluate(assignment)?,
+            Formula::Implies(a, b) => !a.evaluate(assignment)? || b.evaluate(assignment)?,
+            Formula::Iff(a, b) => a.evaluate(assignment)? == b.evaluate(assignment)?,
+        })
+    }
+
+    /// Binding strength: ¬ > ∧ > ∨ > → > ↔
+    fn precedence(&self) -> u8 {
+        match self {
+            Formula::Iff(..) => 1,
+            Formula::Implies(..) => 2,
+            Formula::Or(..) => 3,
+            Formula::And(..) => 4,
+            Formula::Not(_) => 5,
+            Formula::Const(_) | Formula::Var(_) => 6,
+        }
+    }
+}
+
+impl std::ops::Not for Formula {
+    type Output = Formula;
+
+    fn not(self) -> Formula {
+        Formula::Not(Box::new(self))
+    }
+}
+
+/// Minimal parentheses, so the output parses back to the same tree:
+/// → groups to the right, the other connectives to the left
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (a, b, symbol) = match self {
+            Formula::Const(true) => return write!(f, "⊤"),
+            Formula::Const(false) => return write!(f, "⊥"),
+            Formula::Var(name) => return write!(f, "{}", name),
+            Formula::Not(a) => {
+                return if a.precedence() < self.precedence() {
+                    write!(f, "¬({})", a)
+                } else {
+                    write!(f, "¬{}", a)
+                };
+            }
+            Formula::And(a, b) => (a, b, "∧"),
+            Formula::Or(a, b) => (a, b, "∨"),
+            Formula::Implies(a, b) => (a, b, "→"),
+            Formula::Iff(a, b) => (a, b, "↔"),
+        };
+
+        let right_assoc = matches!(self, Formula::Implies(..));
+        let level = self.precedence();
+        let left_parens = a.precedence() < level || (right_assoc && a.precedence() == level);
+        let right_parens = b.precedence() < level || (!right_assoc && b.precedence() == level);
+
+        if left_parens {
+            write!(f, "({})", a)?;
+        } else {
+            write!(f, "{}", a)?;
+        }
+        write!(f, " {} ", symbol)?;
+        if right_parens {
+            write!(f, "({})", b)
+        } else {
+            write!(f, "{}", b)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let (p, q, r) = (Formula::var("p"), Formula::var("q"), Formula::var("r"));
+        let chain = Formula::implies(p.clone(), Formula::implies(q.clone(), r.clone()));
+        assert_eq!(chain.to_string(), "p → q → r");
+
+        let nested = Formula::implies(Formula::implies(p.clone(), q.clone()), r);
+        assert_eq!(nested.to_string(), "(p → q) → r");
+
+        let negated = !Formula::and(p, q);
+        assert_eq!(negated.to_string(), "¬(p ∧ q)");
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let f = Formula::implies(Formula::var("p"), Formula::var("q"));
+        let mut assignment = Assignment::new();
+        assignment.insert("p".to_string(), true);
+        assignment.insert("q".to_string(), false);
+        assert!(!f.evaluate(&assignment).unwrap());
+
+        assignment.remove("q");
+        assert!(matches!(
+            f.evaluate(&assignment),
+            Err(VeritasError::VariableNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+//! Propositional logic engine
+//!
+//! Backs `QueryType::Logic`: "prove (p → q) ∧ p → q" is parsed into a
+//! `Formula`, converted to clauses and handed to a DPLL solver. The answer
+//! is a `Verdict`, either a counterexample assignment or a resolution
+//! refutation of the negation stored as a `verification::Proof` whose
+//! steps can be replayed by `check_refutation`.
+//!
+//! Key types:
+//! - `Formula`: ⊤, ⊥, variables, ¬, ∧, ∨, →, ↔
+//! - `Cnf`: Clauses over numbered variables, also built directly by encoders
+//! - `sat::solve`: Model or refutation for a set of clauses
+//! - `Verdict`: Checked answer to "is this a tautology?"
+//! - `BitVectorProblem`: Bit-vector constraints, bit-blasted to clauses
+
+pub mod bitvector;
+pub mod cnf;
+pub mod formula;
+pub mod parser;
+pub mod sat;
+pub mod tautology;
+
+pub use bitvector::{BitVectorProblem, Constraint, Solution};
+pub use cnf::{Clause, Cnf, Literal};
+pub use formula::{Assignment, Formula};
+pub use tautology::{check_refutation, prove, satisfy, Verdict};
+
+use crate::error::Result;
+
+/// Answer a logic query such as "prove: p | ~p" or "verify p -> p"
+///
+/// A leading "prove" or "verify" (with an optional colon or "that") is
+/// dropped; the rest must be a formula.
+pub fn answer(query: &str) -> Result<Verdict> {
+    let mut text = query.trim();
+    for keyword in ["prove", "verify"] {
+        if text.len() >= keyword.len() && text[..keyword.len()].eq_ignore_ascii_case(keyword) {
+            text = text[keyword.len()..].trim_start();
+            text = text.strip_prefix(':').unwrap_or(text).trim_start();
+            text = text.strip_prefix("that ").unwrap_or(text);
+            break;
+        }
+    }
+    prove(&Formula::parse(text.trim_end_matches(['?', '.']))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_query() {
+        assert!(answer("Prove: p | ~p").unwrap().is_tautology());
+        assert!(answer("verify that (p -> q) & ~q -> ~p").unwrap().is_tautology());
+        assert!(!answer("prove p -> q?").unwrap().is_tautology());
+        assert!(answer("prove").is_err());
+    }
+}
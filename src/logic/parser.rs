@@ -0,0 +1,250 @@
+//! Parser for propositional formulas
+//!
+//! Accepts the symbols `Display` writes as well as ASCII and word forms:
+//!
+//! | Connective | Accepted                      |
+//! |------------|-------------------------------|
+//! | ¬          | `¬` `~` `!` `not`             |
+//! | ∧          | `∧` `&` `&&` `/\` `and`       |
+//! | ∨          | `∨` `|` `||` `\/` `or`        |
+//! | →          | `→` `->` `=>` `⇒` `implies`   |
+//! | ↔          | `↔` `<->` `<=>` `⇔` `iff`     |
+//! | ⊤ ⊥        | `⊤` `⊥` `true` `false`        |
+//!
+//! Precedence from tightest: ¬, ∧, ∨, →, ↔. Implication groups to the
+//! right (p → q → r is p → (q → r)), everything else to the left.
+
+use super::formula::Formula;
+use crate::error::{Result, VeritasError};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Const(bool),
+    Not,
+    And,
+    Or,
+    Implies,
+    Iff,
+    Open,
+    Close,
+}
+
+/// Multi-character symbols, longest first so `<->` wins over `->`
+const SYMBOLS: &[(&str, Token)] = &[
+    ("<->", Token::Iff),
+    ("<=>", Token::Iff),
+    ("->", Token::Implies),
+    ("=>", Token::Implies),
+    ("&&", Token::And),
+    ("||", Token::Or),
+    ("/\\", Token::And),
+    ("\\/", Token::Or),
+    ("¬", Token::Not),
+    ("~", Token::Not),
+    ("!", Token::Not),
+    ("∧", Token::And),
+    ("&", Token::And),
+    ("∨", Token::Or),
+    ("|", Token::Or),
+    ("→", Token::Implies),
+    ("⇒", Token::Implies),
+    ("↔", Token::Iff),
+    ("⇔", Token::Iff),
+    ("⊤", Token::Const(true)),
+    ("⊥", Token::Const(false)),
+    ("(", Token::Open),
+    (")", Token::Close),
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    'scan: while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            tokens.push(match word {
+                "not" => Token::Not,
+                "and" => Token::And,
+                "or" => Token::Or,
+                "implies" => Token::Implies,
+                "iff" => Token::Iff,
+                "true" => Token::Const(true),
+                "false" => Token::Const(false),
+                _ => Token::Name(word.to_string()),
+            });
+            rest = &rest[end..];
+            continue;
+        }
+
+        for (symbol, token) in SYMBOLS {
+            if let Some(after) = rest.strip_prefix(symbol) {
+                tokens.push(token.clone());
+                rest = after;
+                continue 'scan;
+            }
+        }
+
+        return Err(VeritasError::InvalidInput(format!(
+            "Unexpected character '{}' in formula",
+            c
+        )));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn iff(&mut self) -> Result<Formula> {
+        let mut left = self.implies()?;
+        while self.eat(&Token::Iff) {
+            left = Formula::iff(left, self.implies()?);
+        }
+        Ok(left)
+    }
+
+    fn implies(&mut self) -> Result<Formula> {
+        let left = self.or()?;
+        if self.eat(&Token::Implies) {
+            Ok(Formula::implies(left, self.implies()?))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn or(&mut self) -> Result<Formula> {
+        let mut left = self.and()?;
+        while self.eat(&Token::Or) {
+            left = Formula::or(left, self.and()?);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Formula> {
+        let mut left = self.unary()?;
+        while self.eat(&Token::And) {
+            left = Formula::and(left, self.unary()?);
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Formula> {
+        if self.eat(&Token::Not) {
+            return Ok(!self.unary()?);
+        }
+
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Name(name)) => Ok(Formula::Var(name)),
+            Some(Token::Const(b)) => Ok(Formula::Const(b)),
+            Some(Token::Open) => {
+                let inner = self.iff()?;
+                if !self.eat(&Token::Close) {
+                    return Err(VeritasError::InvalidInput(
+                        "Missing closing parenthesis in formula".to_string(),
+                    ));
+                }
+                Ok(inner)
+            }
+            Some(other) => Err(VeritasError::InvalidInput(format!(
+                "Expected a variable or '(', found {:?}",
+                other
+            ))),
+            None => Err(VeritasError::InvalidInput(
+                "Formula ends unexpectedly".to_string(),
+            )),
+        }
+    }
+}
+
+impl Formula {
+    /// Parse a formula such as "(p -> q) & p -> q"
+    pub fn parse(text: &str) -> Result<Formula> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let formula = parser.iff()?;
+        if let Some(extra) = parser.peek() {
+            return Err(VeritasError::InvalidInput(format!(
+                "Unexpected {:?} after formula",
+                extra
+            )));
+        }
+        Ok(formula)
+    }
+}
+
+impl FromStr for Formula {
+    type Err = VeritasError;
+
+    fn from_str(text: &str) -> Result<Formula> {
+        Formula::parse(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forms() {
+        let (p, q) = (Formula::var("p"), Formula::var("q"));
+        let expected = Formula::implies(Formula::and(p.clone(), !q.clone()), p);
+
+        for text in ["p & ~q -> p", "p ∧ ¬q → p", "p and not q implies p", "(p /\\ !q) => p"] {
+            assert_eq!(Formula::parse(text).unwrap(), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in [
+            "p -> q -> r",
+            "(p -> q) -> r",
+            "p | q & r <-> ~(p | q)",
+            "p & (q & r)",
+            "true | false",
+        ] {
+            let formula = Formula::parse(text).unwrap();
+            assert_eq!(Formula::parse(&formula.to_string()).unwrap(), formula);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Formula::parse("p &").is_err());
+        assert!(Formula::parse("(p | q").is_err());
+        assert!(Formula::parse("p q").is_err());
+        assert!(Formula::parse("p + q").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! DPLL satisfiability with resolution refutations
+//!
+//! The search branches on one variable at a time, preferring variables
+//! forced by a unit clause. Every failed branch ends at a clause the
+//! current assignment falsifies; when both values of a variable fail,
+//! the two clauses are resolved on it, giving a clause falsified one level
+//! up. A branch whose clause does not mention its variable failed for
+//! reasons above it, so the other value is never tried. At the root the
+//! derived clause is empty: the steps form a tree-like resolution
+//! refutation that `check_refutation` can replay.
+
+use super::cnf::{Clause, Cnf, Literal};
+use crate::error::{Result, VeritasError};
+use crate::verification::proof::Inference;
+use std::collections::HashMap;
+
+/// Most branching decisions before the search gives up
+pub const MAX_DECISIONS: usize = 1 << 20;
+
+/// Result of a satisfiability check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// Value for every variable, indexed like `Cnf::variables`
+    Satisfiable(Vec<bool>),
+    /// Resolution steps ending in the empty clause
+    Unsatisfiable(Vec<Inference>),
+}
+
+/// Decide whether some assignment satisfies every clause
+pub fn solve(cnf: &Cnf) -> Result<Outcome> {
+    let mut search = Search {
+        clauses: cnf.clauses(),
+        values: vec![None; cnf.variables().len()],
+        steps: Vec::new(),
+        premises: HashMap::new(),
+        decisions: 0,
+    };
+
+    match search.run()? {
+        None => Ok(Outcome::Satisfiable(
+            search.values.iter().map(|v| v.unwrap_or(false)).collect(),
+        )),
+        Some(root) => Ok(Outcome::Unsatisfiable(prune(search.steps, root))),
+    }
+}
+
+/// What the clauses say about the current partial assignment
+enum Status {
+    /// Clause (by index) with every literal false
+    Conflict(usize),
+    /// Only literal left in an otherwise false clause
+    Unit(Literal),
+    /// Some unassigned literal of an unsatisfied clause
+    Open(Literal),
+    Satisfied,
+}
+
+struct Search<'a> {
+    clauses: &'a [Clause],
+    values: Vec<Option<bool>>,
+    steps: Vec<Inference>,
+    /// Step recording each clause used as a premise
+    premises: HashMap<usize, usize>,
+    decisions: usize,
+}
+
+impl Search<'_> {
+    fn status(&self) -> Status {
+        let mut unit = None;
+        let mut open = None;
+
+        for (i, clause) in self.clauses.iter().enumerate() {
+            let mut unassigned = None;
+            let mut count = 0;
+            let mut satisfied = false;
+            for &literal in clause.literals() {
+                match self.values[literal.var] {
+                    Some(value) if literal.holds(value) => {
+                        satisfied = true;
+                        break;
+                    }
+                    Some(_) => {}
+                    None => {
+                        count += 1;
+                        unassigned.get_or_insert(literal);
+                    }
+                }
+            }
+
+            match (satisfied, count) {
+                (true, _) => {}
+                (false, 0) => return Status::Conflict(i),
+                (false, 1) => unit = unit.or(unassigned),
+                (false, _) => open = open.or(unassigned),
+            }
+        }
+
+        match (unit, open) {
+            (Some(literal), _) => Status::Unit(literal),
+            (None, Some(literal)) => Status::Open(literal),
+            (None, None) => Status::Satisfied,
+        }
+    }
+
+    fn premise(&mut self, clause: usize) -> usize {
+        if let Some(&step) = self.premises.get(&clause) {
+            return step;
+        }
+        self.steps
+            .push(Inference::Premise(self.clauses[clause].clone()));
+        let step = self.steps.len() - 1;
+        self.premises.insert(clause, step);
+        step
+    }
+
+    /// `None` if the assignment extends to a model, otherwise the step
+    /// deriving a clause the current assignment falsifies
+    fn run(&mut self) -> Result<Option<usize>> {
+        let literal = match self.status() {
+            Status::Satisfied => return Ok(None),
+            Status::Conflict(clause) => return Ok(Some(self.premise(clause))),
+            Status::Unit(literal) | Status::Open(literal) => literal,
+        };
+
+        self.decisions += 1;
+        if self.decisions > MAX_DECISIONS {
+            return Err(VeritasError::ComplexityLimit(self.decisions));
+        }
+
+        let var = literal.var;
+        let mut failed = [0; 2];
+        for (branch, value) in [literal.positive, !literal.positive].into_iter().enumerate() {
+            self.values[var] = Some(value);
+            let Some(step) = self.run()? else {
+                return Ok(None);
+            };
+            if !self.steps[step].clause().contains(Literal::new(var, !value)) {
+                self.values[var] = None;
+                return Ok(Some(step));
+            }
+            failed[branch] = step;
+        }
+        self.values[var] = None;
+
+        let [left, right] = failed;
+        let clause = self.steps[left]
+            .clause()
+            .resolve(self.steps[right].clause(), var)
+            .ok_or_else(|| {
+                VeritasError::ProofInvalid("branch clauses do not resolve".to_string())
+            })?;
+        self.steps.push(Inference::Resolution {
+            left,
+            right,
+            pivot: var,
+            clause,
+        });
+        Ok(Some(self.steps.len() - 1))
+    }
+}
+
+/// Keep only the steps `root` depends on, renumbered in order
+fn prune(steps: Vec<Inference>, root: usize) -> Vec<Inference> {
+    let mut needed = vec![false; steps.len()];
+    needed[root] = true;
+    for i in (0..=root).rev() {
+        if let (true, Inference::Resolution { left, right, .. }) = (needed[i], &steps[i]) {
+            needed[*left] = true;
+            needed[*right] = true;
+        }
+    }
+
+    let mut renumber = vec![0; steps.len()];
+    let mut kept = Vec::new();
+    for (i, step) in steps.into_iter().enumerate().take(root + 1) {
+        if !needed[i] {
+            continue;
+        }
+        renumber[i] = kept.len();
+        kept.push(match step {
+            Inference::Resolution {
+                left,
+                right,
+                pivot,
+                clause,
+            } => Inference::Resolution {
+                left: renumber[left],
+                right: renumber[right],
+                pivot,
+                clause,
+            },
+            premise => premise,
+        });
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::Formula;
+
+    fn cnf(text: &str) -> Cnf {
+        Cnf::from_formula(&Formula::parse(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_satisfiable() {
+        let cnf = cnf("(p | q) & (~p | r) & ~r");
+        match solve(&cnf).unwrap() {
+            Outcome::Satisfiable(model) => {
+                let assignment = cnf.assignment(&model);
+                let formula = Formula::parse("(p | q) & (~p | r) & ~r").unwrap();
+                assert!(formula.evaluate(&assignment).unwrap());
+            }
+            other => panic!("expected a model, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_refutation_ends_in_empty_clause() {
+        let cnf = cnf("(p | q) & (~p | q) & (p | ~q) & (~p | ~q)");
+        match solve(&cnf).unwrap() {
+            Outcome::Unsatisfiable(steps) => {
+                assert!(steps.last().unwrap().clause().is_empty());
+                // Four premises and three resolutions
+                assert_eq!(steps.len(), 7);
+            }
+            other => panic!("expected a refutation, got {:?}", other),
+        }
+    }
+}
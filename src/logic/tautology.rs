@@ -0,0 +1,199 @@
+//! Tautology checking with certificates
+//!
+//! φ is a tautology exactly when ¬φ is unsatisfiable. The solver either
+//! finds a model of ¬φ, which is an assignment making φ false, or refutes
+//! the clauses of ¬φ by resolution. Both answers are checked before they
+//! are returned: the counterexample by evaluating φ, the refutation by
+//! replaying it with `check_refutation`.
+
+use super::cnf::{Clause, Cnf};
+use super::formula::{Assignment, Formula};
+use super::sat::{solve, Outcome};
+use crate::error::{Result, VeritasError};
+use crate::verification::proof::Inference;
+use crate::verification::{Claim, Proof};
+use std::collections::HashSet;
+
+/// Answer to "is this formula true under every assignment?"
+#[derive(Debug, Clone)]
+pub enum Verdict {
+    /// Verified resolution refutation of the negation
+    Tautology(Proof),
+    /// Assignment under which the formula is false
+    Counterexample(Assignment),
+}
+
+impl Verdict {
+    pub fn is_tautology(&self) -> bool {
+        matches!(self, Verdict::Tautology(_))
+    }
+}
+
+/// Decide whether `formula` is a tautology
+pub fn prove(formula: &Formula) -> Result<Verdict> {
+    let cnf = Cnf::from_formula(&!formula.clone())?;
+
+    match solve(&cnf)? {
+        Outcome::Satisfiable(model) => {
+            let assignment = cnf.assignment(&model);
+            if formula.evaluate(&assignment)? {
+                return Err(VeritasError::VerificationFailed {
+                    expected: format!("{} false", formula),
+                    actual: "true under the counterexample".to_string(),
+                });
+            }
+            Ok(Verdict::Counterexample(assignment))
+        }
+        Outcome::Unsatisfiable(steps) => {
+            let mut proof = Proof::new(Claim::new(formula.to_string()));
+            for inference in steps {
+                let description = cnf.describe(inference.clause());
+                let justification = match &inference {
+                    Inference::Premise(_) => "clause of the negation".to_string(),
+                    Inference::Resolution {
+                        left, right, pivot, ..
+                    } => format!(
+                        "resolve steps {} and {} on {}",
+                        left + 1,
+                        right + 1,
+                        cnf.variables()[*pivot]
+                    ),
+                };
+                proof.add_inference(description, justification, inference);
+            }
+            proof.check_resolution()?;
+            Ok(Verdict::Tautology(proof))
+        }
+    }
+}
+
+/// Decide whether some assignment makes `formula` true, and find one
+pub fn satisfy(formula: &Formula) -> Result<Option<Assignment>> {
+    let cnf = Cnf::from_formula(formula)?;
+    match solve(&cnf)? {
+        Outcome::Satisfiable(model) => Ok(Some(cnf.assignment(&model))),
+        Outcome::Unsatisfiable(_) => Ok(None),
+    }
+}
+
+/// Replay a resolution refutation of the negation of a claimed tautology
+///
+/// The claim statement is parsed as a formula φ. Every premise must be a
+/// clause of ¬φ, every resolvent must follow from two earlier steps on
+/// its pivot, and the last step must derive the empty clause.
+pub fn check_refutation(proof: &Proof) -> Result<()> {
+    let formula = Formula::parse(&proof.claim.statement)?;
+    let cnf = Cnf::from_formula(&!formula)?;
+    let premises: HashSet<_> = cnf.clauses().iter().collect();
+
+    let mut derived: Vec<&Clause> = Vec::with_capacity(proof.steps.len());
+    for (i, step) in proof.steps.iter().enumerate() {
+        let inference = step.inference.as_ref().ok_or_else(|| {
+            VeritasError::ProofInvalid(format!("step {} has no inference", i + 1))
+        })?;
+
+        match inference {
+            Inference::Premise(clause) => {
+                if !premises.contains(clause) {
+                    return Err(VeritasError::ProofInvalid(format!(
+                        "step {}: {} is not a clause of the negation",
+                        i + 1,
+                        cnf.describe(clause)
+                    )));
+                }
+            }
+            Inference::Resolution {
+                left,
+                right,
+                pivot,
+                clause,
+            } => {
+                let resolvent = match (derived.get(*left), derived.get(*right)) {
+                    (Some(a), Some(b)) => Clause::resolve(a, b, *pivot),
+                    _ => {
+                        return Err(VeritasError::ProofInvalid(format!(
+                            "step {} refers to a later step",
+                            i + 1
+                        )))
+                    }
+                };
+                if resolvent.as_ref() != Some(clause) {
+                    return Err(VeritasError::ProofInvalid(format!(
+                        "step {}: {} does not follow by resolution",
+                        i + 1,
+                        cnf.describe(clause)
+                    )));
+                }
+            }
+        }
+        derived.push(inference.clause());
+    }
+
+    match derived.last() {
+        Some(clause) if clause.is_empty() => Ok(()),
+        _ => Err(VeritasError::ProofInvalid(
+            "refutation does not derive the empty clause".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proves_tautologies() {
+        for text in [
+            "p | ~p",
+            "(p -> q) & p -> q",
+            "(p -> q) <-> (~q -> ~p)",
+            "~(p & q) <-> ~p | ~q",
+            "true",
+        ] {
+            let verdict = prove(&Formula::parse(text).unwrap()).unwrap();
+            match verdict {
+                Verdict::Tautology(proof) => {
+                    assert!(proof.verified, "{}", text);
+                    assert_eq!(proof.steps.last().unwrap().description, "□");
+                }
+                Verdict::Counterexample(a) => panic!("{} refuted by {:?}", text, a),
+            }
+        }
+    }
+
+    #[test]
+    fn test_counterexample() {
+        let formula = Formula::parse("(p -> q) -> q").unwrap();
+        match prove(&formula).unwrap() {
+            Verdict::Counterexample(assignment) => {
+                assert!(!formula.evaluate(&assignment).unwrap());
+                assert_eq!(assignment.get("q"), Some(&false));
+            }
+            Verdict::Tautology(_) => panic!("not a tautology"),
+        }
+        assert!(satisfy(&Formula::parse("p & ~p").unwrap()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_tampered_proof() {
+        let Verdict::Tautology(proof) = prove(&Formula::parse("p -> p | q").unwrap()).unwrap()
+        else {
+            panic!("expected a proof");
+        };
+
+        // Same steps, different claim
+        let mut wrong_claim = proof.clone();
+        wrong_claim.claim.statement = "p -> q".to_string();
+        assert!(matches!(
+            wrong_claim.check_resolution(),
+            Err(VeritasError::ProofInvalid(_))
+        ));
+
+        // Dropping the final resolution leaves no empty clause
+        let mut truncated = proof.clone();
+        truncated.steps.pop();
+        truncated.verified = false;
+        assert!(truncated.check_resolution().is_err());
+        assert!(!truncated.verified);
+    }
+}
@@ -0,0 +1,93 @@
+//! Continued fractions of rationals
+//!
+//! p/q = a₀ + 1/(a₁ + 1/(a₂ + …)), with the terms read off the quotients
+//! of Euclid's algorithm on p and q. The expansion is finite and, with
+//! the last term at least 2, unique. The convergents hᵢ/kᵢ are the best
+//! rational approximations with denominators up to kᵢ.
+
+use super::Certified;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Integer;
+use crate::verification::Certificate;
+
+/// Terms [a₀; a₁, …, aₖ] of numer/denom, a₀ rounded toward -∞
+pub fn continued_fraction(numer: &Integer, denom: &Integer) -> Result<Certified<Vec<Integer>>> {
+    if denom.is_zero() {
+        return Err(VeritasError::DivisionByZero);
+    }
+    // Keep the denominator positive so every floor is an ordinary one
+    let (numer, denom) = if denom.is_negative() {
+        (-numer, -denom)
+    } else {
+        (numer.clone(), denom.clone())
+    };
+
+    let mut terms = Vec::new();
+    let (mut p, mut q) = (numer.clone(), denom.clone());
+    while !q.is_zero() {
+        let (a, rest) = p.div_rem_euclid(&q)?;
+        terms.push(a);
+        (p, q) = (q, rest);
+    }
+
+    let certificate = Certificate::ContinuedFraction {
+        numer,
+        denom,
+        terms: terms.clone(),
+    };
+    Certified::checked(terms, certificate)
+}
+
+/// Convergents hᵢ/kᵢ of [a₀; a₁, …], in lowest terms
+pub fn convergents(terms: &[Integer]) -> Vec<(Integer, Integer)> {
+    let (mut h, mut h_prev) = (Integer::one(), Integer::ZERO);
+    let (mut k, mut k_prev) = (Integer::ZERO, Integer::one());
+    terms
+        .iter()
+        .map(|a| {
+            (h, h_prev) = (a * &h + &h_prev, h.clone());
+            (k, k_prev) = (a * &k + &k_prev, k.clone());
+            (h.clone(), k.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(values: &[i64]) -> Vec<Integer> {
+        values.iter().map(|&v| Integer::from(v)).collect()
+    }
+
+    #[test]
+    fn test_expansion() {
+        let result = continued_fraction(&Integer::from(415), &Integer::from(93)).unwrap();
+        assert!(result.is_verified());
+        assert_eq!(result.value, ints(&[4, 2, 6, 7]));
+
+        // Negative values floor the first term; the sign of q is moved to p
+        let result = continued_fraction(&Integer::from(7), &Integer::from(-3)).unwrap();
+        assert_eq!(result.value, ints(&[-3, 1, 2]));
+        assert_eq!(
+            continued_fraction(&Integer::from(6), &Integer::from(3)).unwrap().value,
+            ints(&[2])
+        );
+        assert!(continued_fraction(&Integer::one(), &Integer::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_convergents() {
+        // 355/113 appears in the expansion of 103993/33102 ≈ π
+        let terms = continued_fraction(&Integer::from(103993), &Integer::from(33102))
+            .unwrap()
+            .value;
+        assert_eq!(terms, ints(&[3, 7, 15, 1, 292]));
+        let convergents = convergents(&terms);
+        assert_eq!(convergents[3], (Integer::from(355), Integer::from(113)));
+        assert_eq!(
+            convergents.last(),
+            Some(&(Integer::from(103993), Integer::from(33102)))
+        );
+    }
+}
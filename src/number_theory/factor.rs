@@ -0,0 +1,329 @@
+//! Integer factorization
+//!
+//! Small primes are divided out first, then what remains is split with
+//! Pollard's rho in Brent's form: iterate x ↦ x² + c modulo n until two
+//! iterates agree modulo a hidden factor p, which shows up as gcd of
+//! their difference with n after about √p steps. Differences are
+//! multiplied together in batches so a gcd is taken once per batch.
+//!
+//! A factor is only reported prime with a Pratt certificate, and building
+//! one means factoring p - 1 in turn, so `Certifier` keeps the primes it
+//! has certified for the rest of the query.
+
+use super::gcd::gcd_of;
+use super::modular::power_mod;
+use super::primality::strong_witness;
+use super::Certified;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Integer;
+use crate::verification::{Certificate, PrattCertificate};
+use std::collections::{BTreeMap, HashMap};
+
+/// Divisors tried before Pollard's rho
+const TRIAL_LIMIT: u32 = 1000;
+
+/// Iterations of x ↦ x² + c before trying another c
+const RHO_STEPS: u64 = 1 << 18;
+
+/// Values of c tried
+const RHO_CURVES: u32 = 4;
+
+/// Differences multiplied together between gcds
+const RHO_BATCH: u64 = 64;
+
+/// Candidates tried for an element of order p - 1
+const WITNESS_LIMIT: u32 = 1000;
+
+/// Prime factorization of a positive integer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Factorization {
+    pub n: Integer,
+    /// Primes in increasing order, with exponents
+    pub factors: Vec<(Integer, u32)>,
+    /// What is left of n after the certified primes; one when complete
+    pub cofactor: Integer,
+}
+
+impl Factorization {
+    pub fn is_complete(&self) -> bool {
+        self.cofactor.is_one()
+    }
+}
+
+/// Factor n ≥ 1 into certified primes
+pub fn factor(n: &Integer) -> Result<Certified<Factorization>> {
+    if !n.is_positive() {
+        return Err(VeritasError::InvalidInput(format!(
+            "can only factor positive integers, not {}",
+            n
+        )));
+    }
+    let mut certifier = Certifier::default();
+    let (primes, unsplit) = split(n)?;
+
+    let mut factors = Vec::new();
+    let mut certificates = Vec::new();
+    let mut cofactor = unsplit.iter().fold(Integer::one(), |acc, m| acc * m);
+    for (p, exponent) in primes {
+        match certifier.certify(&p)? {
+            Some(pratt) => {
+                certificates.push((pratt, exponent));
+                factors.push((p, exponent));
+            }
+            None => cofactor = cofactor * p.pow(exponent),
+        }
+    }
+
+    let factorization = Factorization {
+        n: n.clone(),
+        factors,
+        cofactor,
+    };
+    if factorization.is_complete() {
+        let certificate = Certificate::Factorization {
+            n: n.clone(),
+            factors: certificates,
+        };
+        return Certified::checked(factorization, certificate);
+    }
+    let statement = format!("factorization of {}", n);
+    let reason = format!(
+        "{} could not be split or proven prime within the search budget",
+        factorization.cofactor
+    );
+    Ok(Certified::uncertain(factorization, statement, reason))
+}
+
+/// Pratt certificates found so far, shared by one query
+#[derive(Default)]
+pub(crate) struct Certifier {
+    certified: HashMap<Integer, Option<PrattCertificate>>,
+}
+
+impl Certifier {
+    /// Pratt certificate for a probable prime p, if p - 1 can be factored
+    /// and an element of order p - 1 found
+    pub(crate) fn certify(&mut self, p: &Integer) -> Result<Option<PrattCertificate>> {
+        if let Some(known) = self.certified.get(p) {
+            return Ok(known.clone());
+        }
+        let certificate = self.build(p)?;
+        self.certified.insert(p.clone(), certificate.clone());
+        Ok(certificate)
+    }
+
+    fn build(&mut self, p: &Integer) -> Result<Option<PrattCertificate>> {
+        let two = Integer::from(2);
+        if *p == two {
+            return Ok(Some(PrattCertificate::two()));
+        }
+        if *p < two {
+            return Ok(None);
+        }
+
+        let order = p - Integer::one();
+        let (primes, unsplit) = split(&order)?;
+        if !unsplit.is_empty() {
+            return Ok(None);
+        }
+        let mut factors = Vec::with_capacity(primes.len());
+        let mut cofactors = Vec::with_capacity(primes.len());
+        for (q, exponent) in primes {
+            let Some(pratt) = self.certify(&q)? else {
+                return Ok(None);
+            };
+            cofactors.push(order.div_rem(&q)?.0);
+            factors.push((pratt, exponent));
+        }
+
+        // Primitive roots are common, and the least is almost always small
+        for a in 2..WITNESS_LIMIT {
+            let a = Integer::from(a);
+            if a >= *p {
+                break;
+            }
+            if !power_mod(&a, &order, p)?.is_one() {
+                // Fermat witness: p is composite after all
+                return Ok(None);
+            }
+            let mut has_full_order = true;
+            for cofactor in &cofactors {
+                if power_mod(&a, cofactor, p)?.is_one() {
+                    has_full_order = false;
+                    break;
+                }
+            }
+            if has_full_order {
+                return Ok(Some(PrattCertificate {
+                    prime: p.clone(),
+                    witness: a,
+                    factors,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Primes with exponents, in increasing order
+type Powers = Vec<(Integer, u32)>;
+
+/// Probable primes dividing n ≥ 1 with exponents, and the composite parts
+/// Pollard's rho could not split
+fn split(n: &Integer) -> Result<(Powers, Vec<Integer>)> {
+    let mut primes = BTreeMap::new();
+    let mut unsplit = Vec::new();
+    let (small, rest) = trial_division(n)?;
+    for p in small {
+        *primes.entry(p).or_insert(0) += 1;
+    }
+
+    let mut pending = vec![rest];
+    while let Some(m) = pending.pop() {
+        if m.is_one() {
+            continue;
+        }
+        if strong_witness(&m)?.is_none() {
+            *primes.entry(m).or_insert(0) += 1;
+            continue;
+        }
+        match rho(&m)? {
+            Some(d) => {
+                pending.push(m.div_rem(&d)?.0);
+                pending.push(d);
+            }
+            None => unsplit.push(m),
+        }
+    }
+    Ok((primes.into_iter().collect(), unsplit))
+}
+
+/// Primes found by dividing by everything below `TRIAL_LIMIT`, with
+/// multiplicity, and the cofactor they leave
+pub(crate) fn trial_division(n: &Integer) -> Result<(Vec<Integer>, Integer)> {
+    let mut found = Vec::new();
+    let mut rest = n.clone();
+    let mut divisor = 2u32;
+    while divisor < TRIAL_LIMIT {
+        let d = Integer::from(divisor);
+        if &d * &d > rest {
+            break;
+        }
+        let (quotient, remainder) = rest.div_rem(&d)?;
+        if remainder.is_zero() {
+            found.push(d);
+            rest = quotient;
+        } else {
+            // 2, then the odd numbers
+            divisor += if divisor == 2 { 1 } else { 2 };
+        }
+    }
+    // Whatever survived trial division up to its square root is prime
+    let limit = Integer::from(TRIAL_LIMIT);
+    if !rest.is_one() && &limit * &limit > rest {
+        found.push(rest);
+        rest = Integer::one();
+    }
+    Ok((found, rest))
+}
+
+/// A proper divisor of a composite n, by Brent's variant of Pollard's rho
+fn rho(n: &Integer) -> Result<Option<Integer>> {
+    let step = |x: &Integer, c: &Integer| (x * x + c).rem_euclid(n);
+    let distance = |x: &Integer, y: &Integer| (x - y).abs();
+
+    for c in 1..=RHO_CURVES {
+        let c = Integer::from(c);
+        let mut y = Integer::from(2);
+        let mut x = y.clone();
+        let mut saved = y.clone();
+        let mut g = Integer::one();
+        let mut length = 1u64;
+
+        // Compare y against x, the iterate at the last power of two
+        while g.is_one() && length <= RHO_STEPS {
+            x = y.clone();
+            for _ in 0..length {
+                y = step(&y, &c)?;
+            }
+            let mut done = 0;
+            while done < length && g.is_one() {
+                saved = y.clone();
+                let mut product = Integer::one();
+                for _ in 0..RHO_BATCH.min(length - done) {
+                    y = step(&y, &c)?;
+                    product = (product * distance(&x, &y)).rem_euclid(n)?;
+                }
+                g = gcd_of(&product, n)?;
+                done += RHO_BATCH;
+            }
+            length *= 2;
+        }
+
+        // The batch overshot to a multiple of n: redo it one gcd at a time
+        if g == *n {
+            for _ in 0..RHO_BATCH {
+                saved = step(&saved, &c)?;
+                g = gcd_of(&distance(&x, &saved), n)?;
+                if !g.is_one() {
+                    break;
+                }
+            }
+        }
+        if !g.is_one() && g != *n {
+            return Ok(Some(g));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> Integer {
+        text.parse().unwrap()
+    }
+
+    fn factors(result: &Certified<Factorization>) -> Vec<(String, u32)> {
+        let factors = &result.value.factors;
+        factors.iter().map(|(p, e)| (p.to_string(), *e)).collect()
+    }
+
+    #[test]
+    fn test_small_factors() {
+        let result = factor(&int("360")).unwrap();
+        assert!(result.is_verified());
+        assert!(result.value.is_complete());
+        let expected = [("2", 3), ("3", 2), ("5", 1)];
+        assert_eq!(factors(&result), expected.map(|(p, e)| (p.to_string(), e)));
+
+        let one = factor(&Integer::one()).unwrap();
+        assert!(one.is_verified());
+        assert!(one.value.factors.is_empty());
+        assert!(factor(&Integer::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_pollard_rho() {
+        // 2^64 + 1 = 274177 · 67280421310721, both beyond trial division
+        let result = factor(&int("18446744073709551617")).unwrap();
+        assert!(result.is_verified());
+        let expected = [("274177", 1), ("67280421310721", 1)];
+        assert_eq!(factors(&result), expected.map(|(p, e)| (p.to_string(), e)));
+
+        // A square of a prime beyond trial division
+        let result = factor(&int("1018081")).unwrap(); // 1009²
+        assert_eq!(factors(&result), vec![("1009".to_string(), 2)]);
+        assert!(result.is_verified());
+    }
+
+    #[test]
+    fn test_certificate_replays() {
+        let result = factor(&int("600851475143")).unwrap();
+        let certificate = result.certificate.clone().unwrap();
+        let proof = certificate.check().unwrap();
+        assert_eq!(proof.id(), result.proof.id());
+        assert!(proof.steps.iter().any(|step| step.description == "6857 is prime"));
+    }
+}
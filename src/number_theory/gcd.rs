@@ -0,0 +1,118 @@
+//! Greatest common divisors and least common multiples
+//!
+//! The extended Euclidean algorithm keeps a·x + b·y = r for every
+//! remainder r, so the last nonzero one arrives with its Bézout
+//! coefficients. Those are the certificate: a common divisor of a and b
+//! that is also a combination of them is divisible by every common
+//! divisor, so it is the greatest.
+
+use super::Certified;
+use crate::error::Result;
+use crate::numeric::Integer;
+use crate::verification::Certificate;
+
+/// gcd = a·x + b·y
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bezout {
+    pub gcd: Integer,
+    pub x: Integer,
+    pub y: Integer,
+}
+
+/// Greatest common divisor, never negative; gcd(0, 0) = 0
+pub fn gcd(a: &Integer, b: &Integer) -> Result<Certified<Bezout>> {
+    let bezout = extended_gcd(a, b)?;
+    let certificate = Certificate::Bezout {
+        a: a.clone(),
+        b: b.clone(),
+        gcd: bezout.gcd.clone(),
+        x: bezout.x.clone(),
+        y: bezout.y.clone(),
+    };
+    Certified::checked(bezout, certificate)
+}
+
+/// Least common multiple, never negative; zero if either argument is
+pub fn lcm(a: &Integer, b: &Integer) -> Result<Certified<Integer>> {
+    let Bezout { gcd, x, y } = extended_gcd(a, b)?;
+    let lcm = if gcd.is_zero() {
+        Integer::ZERO
+    } else {
+        (a * b).abs().div_rem(&gcd)?.0
+    };
+    let certificate = Certificate::Lcm {
+        a: a.clone(),
+        b: b.clone(),
+        lcm: lcm.clone(),
+        gcd,
+        x,
+        y,
+    };
+    Certified::checked(lcm, certificate)
+}
+
+pub(crate) fn extended_gcd(a: &Integer, b: &Integer) -> Result<Bezout> {
+    // (r, x, y) rows with a·x + b·y = r
+    let (mut r, mut r_next) = (a.clone(), b.clone());
+    let (mut x, mut x_next) = (Integer::one(), Integer::ZERO);
+    let (mut y, mut y_next) = (Integer::ZERO, Integer::one());
+    while !r_next.is_zero() {
+        let (q, rest) = r.div_rem(&r_next)?;
+        (r, r_next) = (r_next, rest);
+        (x, x_next) = (x_next.clone(), x - &q * &x_next);
+        (y, y_next) = (y_next.clone(), y - &q * &y_next);
+    }
+    Ok(if r.is_negative() {
+        Bezout {
+            gcd: -r,
+            x: -x,
+            y: -y,
+        }
+    } else {
+        Bezout { gcd: r, x, y }
+    })
+}
+
+/// Plain gcd, without coefficients
+pub(crate) fn gcd_of(a: &Integer, b: &Integer) -> Result<Integer> {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        let rest = a.div_rem(&b)?.1;
+        (a, b) = (b, rest);
+    }
+    Ok(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> Integer {
+        Integer::from(n)
+    }
+
+    #[test]
+    fn test_gcd_with_coefficients() {
+        for (a, b, g) in [(240, 46, 2), (-12, 18, 6), (0, -5, 5), (17, 0, 17), (0, 0, 0)] {
+            let result = gcd(&int(a), &int(b)).unwrap();
+            assert!(result.is_verified());
+            let Bezout { gcd, x, y } = result.value;
+            assert_eq!(gcd, int(g));
+            assert_eq!(int(a) * x + int(b) * y, gcd);
+        }
+
+        // Fibonacci neighbours are coprime and the slowest case for Euclid
+        let a: Integer = "354224848179261915075".parse().unwrap();
+        let b: Integer = "218922995834555169026".parse().unwrap();
+        assert!(gcd(&a, &b).unwrap().value.gcd.is_one());
+        assert_eq!(gcd_of(&a, &(&a * &b)).unwrap(), a);
+    }
+
+    #[test]
+    fn test_lcm() {
+        let result = lcm(&int(4), &int(-6)).unwrap();
+        assert!(result.is_verified());
+        assert_eq!(result.value, int(12));
+        assert_eq!(lcm(&int(0), &int(9)).unwrap().value, Integer::ZERO);
+    }
+}
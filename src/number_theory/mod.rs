@@ -0,0 +1,71 @@
+//! Number theory with checkable certificates
+//!
+//! Primes, divisors, gcds and modular arithmetic over `Integer`, of any
+//! size. Each query returns a `Certified` answer: the value, the
+//! `verification::Certificate` that vouches for it, and the proof the
+//! independent checker produced from that certificate. Where the search
+//! gives up first (a cofactor Pollard's rho cannot split, a probable
+//! prime whose p - 1 cannot be factored) the answer says so and is
+//! `Uncertain` instead.
+//!
+//! Key functions:
+//! - `gcd` / `lcm`: Extended Euclid, with Bézout coefficients
+//! - `mod_pow` / `mod_inverse`: Square and multiply, inverses by Bézout
+//! - `is_prime`: Trial division and Miller–Rabin to refute, Pratt to prove
+//! - `factor`: Trial division, then Pollard's rho (Brent's variant)
+//! - `continued_fraction`: Terms and convergents of a rational
+
+pub mod continued_fraction;
+pub mod factor;
+pub mod gcd;
+pub mod modular;
+pub mod primality;
+
+pub use continued_fraction::{continued_fraction, convergents};
+pub use factor::{factor, Factorization};
+pub use gcd::{gcd, lcm, Bezout};
+pub use modular::{mod_inverse, mod_pow};
+pub use primality::{is_prime, Primality};
+
+use crate::error::Result;
+use crate::verification::{Certificate, Claim, Proof, VerificationState};
+
+/// An answer together with the certificate it was checked against
+#[derive(Debug, Clone)]
+pub struct Certified<T> {
+    pub value: T,
+    /// None when the search ran out before a certificate was found
+    pub certificate: Option<Certificate>,
+    /// Facts checked from the certificate
+    pub proof: Proof,
+    pub state: VerificationState,
+}
+
+impl<T> Certified<T> {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+
+    /// Check `certificate`; a failure is a bug in the search that built it
+    pub(crate) fn checked(value: T, certificate: Certificate) -> Result<Self> {
+        let proof = certificate.check()?;
+        let state = VerificationState::Verified {
+            proof_id: proof.id(),
+        };
+        Ok(Certified {
+            value,
+            certificate: Some(certificate),
+            proof,
+            state,
+        })
+    }
+
+    pub(crate) fn uncertain(value: T, statement: String, reason: String) -> Self {
+        Certified {
+            value,
+            certificate: None,
+            proof: Proof::new(Claim::new(statement)),
+            state: VerificationState::Uncertain { reason },
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Modular exponentiation and inverses
+//!
+//! Powers are taken by left-to-right square and multiply, one squaring
+//! per exponent bit, reducing after every product so the operands never
+//! grow past twice the modulus. The partial results are kept as the
+//! certificate, so the checker verifies each with one multiplication.
+
+use super::gcd::extended_gcd;
+use super::Certified;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Integer;
+use crate::verification::Certificate;
+
+/// base^exponent mod modulus, in 0..modulus
+pub fn mod_pow(
+    base: &Integer,
+    exponent: &Integer,
+    modulus: &Integer,
+) -> Result<Certified<Integer>> {
+    if exponent.is_negative() {
+        return Err(VeritasError::InvalidInput(format!(
+            "negative exponent {}; invert the base with mod_inverse",
+            exponent
+        )));
+    }
+    check_modulus(modulus)?;
+    let steps = square_and_multiply(base, exponent, modulus)?;
+    let result = match steps.last() {
+        Some(last) => last.clone(),
+        None => Integer::one().rem_euclid(modulus)?,
+    };
+    let certificate = Certificate::Power {
+        base: base.clone(),
+        exponent: exponent.clone(),
+        modulus: modulus.clone(),
+        result: result.clone(),
+        steps,
+    };
+    Certified::checked(result, certificate)
+}
+
+/// x in 0..modulus with a·x ≡ 1; an error unless gcd(a, modulus) = 1
+pub fn mod_inverse(a: &Integer, modulus: &Integer) -> Result<Certified<Integer>> {
+    check_modulus(modulus)?;
+    let bezout = extended_gcd(a, modulus)?;
+    if !bezout.gcd.is_one() {
+        return Err(VeritasError::InvalidInput(format!(
+            "{} has no inverse mod {}: they share the factor {}",
+            a, modulus, bezout.gcd
+        )));
+    }
+    // a·x + m·y = 1, so a·x ≡ 1
+    let inverse = bezout.x.rem_euclid(modulus)?;
+    let certificate = Certificate::Inverse {
+        a: a.clone(),
+        modulus: modulus.clone(),
+        inverse: inverse.clone(),
+    };
+    Certified::checked(inverse, certificate)
+}
+
+fn check_modulus(modulus: &Integer) -> Result<()> {
+    if modulus.is_positive() {
+        Ok(())
+    } else {
+        Err(VeritasError::InvalidInput(format!("modulus {} is not positive", modulus)))
+    }
+}
+
+/// base raised to each prefix of the exponent's bits, most significant first
+fn square_and_multiply(
+    base: &Integer,
+    exponent: &Integer,
+    modulus: &Integer,
+) -> Result<Vec<Integer>> {
+    let base = base.rem_euclid(modulus)?;
+    let mut steps = Vec::with_capacity(exponent.bits() as usize);
+    let mut current = Integer::one();
+    for i in (0..exponent.bits()).rev() {
+        current = (&current * &current).rem_euclid(modulus)?;
+        if exponent.bit(i) {
+            current = (&current * &base).rem_euclid(modulus)?;
+        }
+        steps.push(current.clone());
+    }
+    Ok(steps)
+}
+
+/// base^exponent mod modulus without the certificate, for the searches
+pub(crate) fn power_mod(base: &Integer, exponent: &Integer, modulus: &Integer) -> Result<Integer> {
+    match square_and_multiply(base, exponent, modulus)?.pop() {
+        Some(result) => Ok(result),
+        None => Integer::one().rem_euclid(modulus),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> Integer {
+        Integer::from(n)
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        let result = mod_pow(&int(4), &int(13), &int(497)).unwrap();
+        assert!(result.is_verified());
+        assert_eq!(result.value, int(445));
+        assert_eq!(mod_pow(&int(-2), &int(3), &int(5)).unwrap().value, int(2));
+        assert_eq!(mod_pow(&int(7), &int(0), &int(1)).unwrap().value, Integer::ZERO);
+        assert!(mod_pow(&int(2), &int(-1), &int(5)).is_err());
+        assert!(mod_pow(&int(2), &int(3), &int(0)).is_err());
+
+        // Fermat: 2^(p-1) ≡ 1 for the Mersenne prime p = 2^127 - 1
+        let p = Integer::from(2).pow(127) - Integer::one();
+        let order = &p - Integer::one();
+        assert!(mod_pow(&int(2), &order, &p).unwrap().value.is_one());
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        let result = mod_inverse(&int(17), &int(3120)).unwrap();
+        assert!(result.is_verified());
+        assert_eq!(result.value, int(2753));
+        assert_eq!(mod_inverse(&int(-3), &int(7)).unwrap().value, int(2));
+        assert!(mod_inverse(&int(6), &int(9)).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Primality testing
+//!
+//! Refuting primality is cheap: trial division finds small factors, and
+//! the Miller–Rabin strong probable-prime test finds a witness base for
+//! any other composite, usually the first one tried. Proving primality
+//! takes a Pratt certificate, which needs p - 1 factored; when that
+//! factorization is out of reach the answer is a probable prime, marked
+//! uncertain.
+
+use super::factor::{trial_division, Certifier};
+use super::modular::power_mod;
+use super::Certified;
+use crate::error::Result;
+use crate::numeric::Integer;
+use crate::verification::Certificate;
+
+/// Miller–Rabin bases; together they admit no strong pseudoprime below
+/// 318 665 857 834 031 151 167 461 ≈ 3.18·10²³, and past that a composite
+/// passing all twelve is rare enough that Pratt's certificate is left to
+/// expose it
+const BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Whether an integer is prime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primality {
+    Prime,
+    Composite,
+    /// Below two: zero, one and the negatives
+    Neither,
+    /// Passed every Miller–Rabin base but has no Pratt certificate
+    ProbablePrime,
+}
+
+/// Decide whether n is prime, with a certificate either way
+pub fn is_prime(n: &Integer) -> Result<Certified<Primality>> {
+    if *n < Integer::from(2) {
+        return Certified::checked(Primality::Neither, Certificate::BelowTwo { n: n.clone() });
+    }
+
+    let (small, _) = trial_division(n)?;
+    if let Some(factor) = small.first().filter(|&factor| factor != n) {
+        let certificate = Certificate::Composite {
+            n: n.clone(),
+            factor: factor.clone(),
+        };
+        return Certified::checked(Primality::Composite, certificate);
+    }
+    if small.is_empty() {
+        if let Some(base) = strong_witness(n)? {
+            let certificate = Certificate::StrongWitness { n: n.clone(), base };
+            return Certified::checked(Primality::Composite, certificate);
+        }
+    }
+
+    match Certifier::default().certify(n)? {
+        Some(pratt) => Certified::checked(Primality::Prime, Certificate::Prime(pratt)),
+        None => Ok(Certified::uncertain(
+            Primality::ProbablePrime,
+            format!("{} is prime", n),
+            format!(
+                "{} passed Miller–Rabin for every base up to 37, but {} - 1 could not be \
+                 factored far enough for a Pratt certificate",
+                n, n
+            ),
+        )),
+    }
+}
+
+/// First base for which odd n ≥ 5 fails the strong probable-prime test
+///
+/// Writing n - 1 = 2ˢ·d, a prime n has a^d ≡ 1 or a^(2ʳ·d) ≡ -1 for some
+/// r < s, for every base a.
+pub(crate) fn strong_witness(n: &Integer) -> Result<Option<Integer>> {
+    let minus_one = n - Integer::one();
+    let s = minus_one.trailing_zeros().unwrap_or(0);
+    let d = &minus_one >> s;
+    'bases: for base in BASES {
+        let base = Integer::from(base);
+        if base >= minus_one {
+            break;
+        }
+        let mut x = power_mod(&base, &d, n)?;
+        if x.is_one() || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = (&x * &x).rem_euclid(n)?;
+            if x == minus_one {
+                continue 'bases;
+            }
+        }
+        return Ok(Some(base));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> Integer {
+        text.parse().unwrap()
+    }
+
+    fn primality(text: &str) -> Certified<Primality> {
+        let result = is_prime(&int(text)).unwrap();
+        assert!(result.is_verified(), "{} has no certificate", text);
+        result
+    }
+
+    #[test]
+    fn test_small_numbers() {
+        for n in ["-7", "0", "1"] {
+            assert_eq!(primality(n).value, Primality::Neither);
+        }
+        for n in ["2", "3", "5", "97", "7919"] {
+            assert_eq!(primality(n).value, Primality::Prime);
+        }
+        for n in ["4", "91", "1001"] {
+            assert_eq!(primality(n).value, Primality::Composite);
+        }
+    }
+
+    #[test]
+    fn test_large_primes() {
+        // Mersenne primes 2^61 - 1 and 2^127 - 1
+        for n in ["2305843009213693951", "170141183460469231731687303715884105727"] {
+            let result = primality(n);
+            assert_eq!(result.value, Primality::Prime);
+            assert!(matches!(result.certificate, Some(Certificate::Prime(_))));
+        }
+    }
+
+    #[test]
+    fn test_composites_without_small_factors() {
+        // 3215031751 = 151·751·28351 is a strong pseudoprime to bases 2, 3,
+        // 5 and 7 and is caught by trial division; 1000003·1000033 is not
+        let result = primality("3215031751");
+        assert!(matches!(result.certificate, Some(Certificate::Composite { .. })));
+
+        let result = primality("1000036000099");
+        assert_eq!(result.value, Primality::Composite);
+        assert!(matches!(result.certificate, Some(Certificate::StrongWitness { .. })));
+    }
+}
@@ -0,0 +1,449 @@
+//! Fast Fourier transforms over Spirix circles
+//!
+//! X[k] = Σⱼ x[j]·e^(-2πi·jk/n). Lengths that are powers of two go through
+//! the iterative radix-2 transform, in place after a bit-reversal
+//! permutation. Any other length is split by Cooley–Tukey over its prime
+//! factors, with a direct DFT of each factor's size as the butterfly, so
+//! a large prime factor p costs n·p rather than n·log n.
+//!
+//! The transforms run in `CircleF4E5`, the format of complex tensors, or
+//! in `CircleF6E5`. Roots of unity are computed in F6E5 and narrowed to
+//! the working format. Inputs and outputs are checked: a vanished,
+//! exploded or undefined value is an error, not a spectrum.
+
+use super::{Circle, Scalar};
+use crate::error::{Result, VeritasError};
+use crate::verification::{Claim, Proof, VerificationState};
+use spirix::{CircleF4E5, CircleF6E5, ScalarF4E5};
+use std::ops::{Add, Mul, Sub};
+
+/// Complex Spirix format the transforms can run in
+pub trait Spectral:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+
+    /// Fraction bits that survive rounding in one butterfly, used to
+    /// scale the default round-trip tolerance
+    const PRECISION: u32;
+
+    /// The value in F6E5, or the state that makes it unusable as an error
+    fn widen(self) -> Result<Circle>;
+
+    /// Nearest value in this format
+    fn narrow(value: Circle) -> Result<Self>;
+}
+
+impl Spectral for CircleF6E5 {
+    const ZERO: Self = CircleF6E5::ZERO;
+    const PRECISION: u32 = 40;
+
+    fn widen(self) -> Result<Circle> {
+        let value = Circle(self);
+        value.real().representable()?;
+        value.imag().representable()?;
+        Ok(value)
+    }
+
+    fn narrow(value: Circle) -> Result<Self> {
+        Ok(value.0)
+    }
+}
+
+impl Spectral for CircleF4E5 {
+    const ZERO: Self = CircleF4E5::ZERO;
+    const PRECISION: u32 = 12;
+
+    fn widen(self) -> Result<Circle> {
+        let part = |s: ScalarF4E5| {
+            if s.is_undefined() {
+                Err(VeritasError::UndefinedOperation(format!("{:?}", s)))
+            } else if s.exploded() {
+                Err(VeritasError::NumericOverflow)
+            } else if s.vanished() {
+                Err(VeritasError::NumericUnderflow)
+            } else {
+                Ok(Scalar::from_f4e5(s).expect("zero or normal"))
+            }
+        };
+        Ok(Circle::from_parts(part(self.r())?, part(self.i())?))
+    }
+
+    fn narrow(value: Circle) -> Result<Self> {
+        let part = |s: Scalar| s.representable().map(|s| s.to_f4e5().expect("zero or normal"));
+        Ok(CircleF4E5::from((part(value.real())?, part(value.imag())?)))
+    }
+}
+
+/// Discrete Fourier transform, X[k] = Σⱼ x[j]·e^(-2πi·jk/n)
+pub fn fft<C: Spectral>(input: &[C]) -> Result<Vec<C>> {
+    transform(input, false)
+}
+
+/// Inverse transform, x[j] = (1/n)·Σₖ X[k]·e^(2πi·jk/n)
+pub fn ifft<C: Spectral>(input: &[C]) -> Result<Vec<C>> {
+    let mut output = transform(input, true)?;
+    if output.len() > 1 {
+        let scale = C::narrow(Circle::from(Scalar::ONE / length(output.len())))?;
+        for value in &mut output {
+            *value = *value * scale;
+            value.widen()?;
+        }
+    }
+    Ok(output)
+}
+
+/// Linear convolution (a ∗ b)[k] = Σⱼ a[j]·b[k - j], of length
+/// |a| + |b| - 1, by multiplying zero-padded radix-2 spectra
+pub fn convolve<C: Spectral>(a: &[C], b: &[C]) -> Result<Vec<C>> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(Vec::new());
+    }
+    let len = a.len() + b.len() - 1;
+    let size = len.next_power_of_two();
+    let padded = |x: &[C]| {
+        let mut padded = x.to_vec();
+        padded.resize(size, C::ZERO);
+        padded
+    };
+    let (fa, fb) = (fft(&padded(a))?, fft(&padded(b))?);
+    let product: Vec<C> = fa.iter().zip(&fb).map(|(&x, &y)| x * y).collect();
+    let mut result = ifft(&product)?;
+    result.truncate(len);
+    Ok(result)
+}
+
+fn length(n: usize) -> Scalar {
+    Scalar::from_i64(i64::try_from(n).expect("slice lengths fit in i64"))
+}
+
+fn transform<C: Spectral>(input: &[C], inverse: bool) -> Result<Vec<C>> {
+    for value in input {
+        value.widen()?;
+    }
+    let n = input.len();
+    if n <= 1 {
+        return Ok(input.to_vec());
+    }
+    let roots = roots::<C>(n, inverse)?;
+    let output = if n.is_power_of_two() {
+        let mut data = input.to_vec();
+        radix2(&mut data, &roots);
+        data
+    } else {
+        mixed_radix(input, &prime_factors(n), &roots)
+    };
+    for value in &output {
+        value.widen()?;
+    }
+    Ok(output)
+}
+
+/// e^(∓2πi·j/n) for j in 0..n, the sign positive for the inverse
+fn roots<C: Spectral>(n: usize, inverse: bool) -> Result<Vec<C>> {
+    let turn = Scalar::TWO * Scalar::PI / length(n);
+    let turn = if inverse { turn } else { -turn };
+    (0..n)
+        .map(|j| {
+            let angle = turn * length(j);
+            C::narrow(Circle::from_parts(angle.cos()?, angle.sin()?))
+        })
+        .collect()
+}
+
+/// In-place transform of a power-of-two length, `roots` its n-th roots
+fn radix2<C: Spectral>(data: &mut [C], roots: &[C]) {
+    let n = data.len();
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let (half, stride) = (len / 2, n / len);
+        for block in data.chunks_mut(len) {
+            for k in 0..half {
+                let even = block[k];
+                let odd = block[k + half] * roots[k * stride];
+                block[k] = even + odd;
+                block[k + half] = even - odd;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Transform of `input` by decimation in time over `factors`, whose
+/// product is its length; `roots` are the n-th roots for a multiple n
+fn mixed_radix<C: Spectral>(input: &[C], factors: &[usize], roots: &[C]) -> Vec<C> {
+    let Some((&p, rest)) = factors.split_first() else {
+        return input.to_vec();
+    };
+    let len = input.len();
+    let m = len / p;
+    let step = roots.len() / len;
+
+    // Transforms of the p interleaved subsequences x[p·j + r]
+    let parts: Vec<Vec<C>> = (0..p)
+        .map(|r| {
+            let part: Vec<C> = input.iter().skip(r).step_by(p).copied().collect();
+            mixed_radix(&part, rest, roots)
+        })
+        .collect();
+
+    // X[k] = Σᵣ roots[r·k·step mod n]·parts[r][k mod m]; since k·step < n,
+    // both indices stay reduced with one subtraction each time they grow
+    let n = roots.len();
+    let mut output = vec![C::ZERO; len];
+    let mut j = 0;
+    for (k, value) in output.iter_mut().enumerate() {
+        let turn = k * step;
+        let mut index = 0;
+        *value = parts.iter().fold(C::ZERO, |sum, part| {
+            let term = sum + part[j] * roots[index];
+            index += turn;
+            if index >= n {
+                index -= n;
+            }
+            term
+        });
+        j += 1;
+        if j == m {
+            j = 0;
+        }
+    }
+    output
+}
+
+/// Prime factors of n ≥ 1 in increasing order, with multiplicity
+fn prime_factors(mut n: usize) -> Vec<usize> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// How far a round trip may stray: |IFFT(FFT(x))[j] - x[j]| must stay
+/// within `absolute + relative·max|x|`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub relative: Scalar,
+    pub absolute: Scalar,
+}
+
+impl Tolerance {
+    /// Rounding of `len` points in format C through both transforms
+    ///
+    /// Each radix-p pass adds at most about p roundings relative to the
+    /// largest input, in each direction, and the roots and the 1/n scale
+    /// add one more each.
+    pub fn for_format<C: Spectral>(len: usize) -> Self {
+        let passes: usize = prime_factors(len.max(1)).iter().sum();
+        let roundings = length(2 * passes + 2);
+        let unit = Scalar::ONE / Scalar::from_i64(1 << C::PRECISION);
+        Tolerance {
+            relative: roundings * unit,
+            absolute: Scalar::ZERO,
+        }
+    }
+}
+
+/// Result of checking IFFT(FFT(x)) against x
+#[derive(Debug, Clone)]
+pub struct RoundTrip {
+    pub len: usize,
+    /// Largest |IFFT(FFT(x))[j] - x[j]|
+    pub max_error: Scalar,
+    /// What the tolerance allowed for this input
+    pub bound: Scalar,
+    pub proof: Proof,
+    pub state: VerificationState,
+}
+
+impl RoundTrip {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+}
+
+/// Transform x forward and back and compare the result with x
+pub fn verify_round_trip<C: Spectral>(input: &[C], tolerance: &Tolerance) -> Result<RoundTrip> {
+    let len = input.len();
+    let spectrum = fft(input)?;
+    let back = ifft(&spectrum)?;
+
+    let mut max_error = Scalar::ZERO;
+    let mut scale = Scalar::ZERO;
+    for (original, returned) in input.iter().zip(&back) {
+        let original = original.widen()?;
+        let error = (returned.widen()? - original).magnitude().representable()?;
+        if max_error.inner() < error.inner() {
+            max_error = error;
+        }
+        let magnitude = original.magnitude();
+        if scale.inner() < magnitude.inner() {
+            scale = magnitude;
+        }
+    }
+    let bound = tolerance.absolute + tolerance.relative * scale;
+
+    let claim = Claim::new(format!("IFFT(FFT(x)) = x for {} points within {}", len, bound));
+    let mut proof = Proof::new(claim);
+    let method = if len.is_power_of_two() {
+        "radix 2".to_string()
+    } else {
+        let factors: Vec<String> =
+            prime_factors(len.max(1)).iter().map(|p| p.to_string()).collect();
+        format!("mixed radix {}", factors.join("·"))
+    };
+    proof.add_step(format!("forward transform of {} points", len), method.clone());
+    proof.add_step(format!("inverse transform of {} points", len), method);
+    proof.add_step(
+        format!("largest deviation {} against bound {}", max_error, bound),
+        format!(
+            "{} relative to max |x| = {}, plus {}",
+            tolerance.relative, scale, tolerance.absolute
+        ),
+    );
+
+    let state = if max_error.inner() <= bound.inner() {
+        proof.verified = true;
+        VerificationState::Verified {
+            proof_id: proof.id(),
+        }
+    } else {
+        VerificationState::Contradicted {
+            expected: format!("deviation at most {}", bound),
+            actual: format!("deviation {}", max_error),
+            error: max_error,
+        }
+    };
+
+    Ok(RoundTrip {
+        len,
+        max_error,
+        bound,
+        proof,
+        state,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(re: i32, im: i32) -> Circle {
+        Circle::from_parts(Scalar::from(re), Scalar::from(im))
+    }
+
+    fn close<C: Spectral>(actual: &[C], expected: &[Circle], tolerance: Scalar) -> bool {
+        actual.len() == expected.len()
+            && actual.iter().zip(expected).all(|(&a, &e)| {
+                let difference = (a.widen().unwrap() - e).magnitude();
+                difference.inner() < tolerance.inner()
+            })
+    }
+
+    /// Direct O(n²) DFT in F6E5
+    fn dft(input: &[Circle]) -> Vec<Circle> {
+        let n = input.len();
+        (0..n)
+            .map(|k| {
+                input.iter().enumerate().fold(Circle::ZERO, |sum, (j, &x)| {
+                    let angle = -(Scalar::TWO * Scalar::PI * length(j * k) / length(n));
+                    let root = Circle::from_parts(angle.cos().unwrap(), angle.sin().unwrap());
+                    sum + x * root
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_radix2() {
+        let tolerance = Scalar::ONE / Scalar::from(1 << 20);
+        let impulse: Vec<CircleF6E5> = [1, 0, 0, 0].iter().map(|&re| circle(re, 0).0).collect();
+        assert!(close(&fft(&impulse).unwrap(), &[circle(1, 0); 4], tolerance));
+
+        let constant = vec![circle(1, 0).0; 4];
+        let expected = [circle(4, 0), Circle::ZERO, Circle::ZERO, Circle::ZERO];
+        assert!(close(&fft(&constant).unwrap(), &expected, tolerance));
+
+        let input: Vec<Circle> = (0..16).map(|j| circle(j * j - 7, 3 - j)).collect();
+        let raw: Vec<CircleF6E5> = input.iter().map(|c| c.0).collect();
+        assert!(close(&fft(&raw).unwrap(), &dft(&input), tolerance));
+        assert!(close(&ifft(&fft(&raw).unwrap()).unwrap(), &input, tolerance));
+    }
+
+    #[test]
+    fn test_mixed_radix() {
+        let tolerance = Scalar::ONE / Scalar::from(1 << 20);
+        for n in [3, 6, 7, 12, 45] {
+            let input: Vec<Circle> = (0..n).map(|j| circle(2 * j - n, j * j - 5)).collect();
+            let raw: Vec<CircleF6E5> = input.iter().map(|c| c.0).collect();
+            assert!(close(&fft(&raw).unwrap(), &dft(&input), tolerance), "length {}", n);
+        }
+        assert_eq!(prime_factors(360), vec![2, 2, 2, 3, 3, 5]);
+    }
+
+    #[test]
+    fn test_convolution() {
+        // (1 + 2x + 3x²)(4 + 5x) = 4 + 13x + 22x² + 15x³
+        let a: Vec<Circle> = [1, 2, 3].iter().map(|&re| circle(re, 0)).collect();
+        let b: Vec<Circle> = [4, 5].iter().map(|&re| circle(re, 0)).collect();
+        let expected: Vec<Circle> = [4, 13, 22, 15].iter().map(|&re| circle(re, 0)).collect();
+
+        let wide: Vec<CircleF6E5> = convolve(
+            &a.iter().map(|c| c.0).collect::<Vec<_>>(),
+            &b.iter().map(|c| c.0).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert!(close(&wide, &expected, Scalar::ONE / Scalar::from(1 << 20)));
+
+        let narrow = |x: &[Circle]| -> Vec<CircleF4E5> {
+            x.iter().map(|&c| CircleF4E5::narrow(c).unwrap()).collect()
+        };
+        let tensor_width = convolve(&narrow(&a), &narrow(&b)).unwrap();
+        assert!(close(&tensor_width, &expected, Scalar::ONE / Scalar::from(1 << 6)));
+        assert!(convolve::<CircleF4E5>(&[], &narrow(&b)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_verification() {
+        let third = Scalar::ONE / Scalar::from(3);
+        let input: Vec<CircleF4E5> = (0..24)
+            .map(|j| {
+                let value = Circle::from_parts(third * Scalar::from(j), Scalar::from(5 - j));
+                CircleF4E5::narrow(value).unwrap()
+            })
+            .collect();
+        let result = verify_round_trip(&input, &Tolerance::for_format::<CircleF4E5>(24)).unwrap();
+        assert!(result.is_verified(), "{} > {}", result.max_error, result.bound);
+        assert_eq!(result.proof.steps.len(), 3);
+
+        // An exact policy is contradicted by the rounding of 16-bit fractions
+        let exact = Tolerance {
+            relative: Scalar::ZERO,
+            absolute: Scalar::ZERO,
+        };
+        let result = verify_round_trip(&input, &exact).unwrap();
+        assert!(result.state.is_contradicted());
+
+        // Undefined input is an error, not a spectrum
+        let undefined = CircleF6E5::ONE / CircleF6E5::ZERO;
+        let error = fft(&[CircleF6E5::ONE, undefined]).unwrap_err();
+        assert!(matches!(error, VeritasError::UndefinedOperation(_)));
+    }
+}
@@ -0,0 +1,634 @@
+//! Arbitrary-size integers
+//!
+//! Scalars are floating point, so they cannot hold the exact integers
+//! number theory works with: a 40-digit prime, or the product of two
+//! 20-digit ones. `Integer` is a sign and a magnitude of 32-bit limbs,
+//! least significant first, with no upper bound on size. Division is the
+//! schoolbook long division of Knuth's Algorithm D, built on 64-bit limb
+//! products; remainders come from subtracting quotients back out rather
+//! than from a hardware modulo.
+
+use crate::error::{Result, VeritasError};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Shl, Shr, Sub};
+use std::str::FromStr;
+
+const LIMB_BITS: u32 = 32;
+
+/// Largest power of ten in a limb, for decimal conversion
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+/// Integer of any size
+///
+/// The representation is canonical: no most significant zero limbs, and
+/// zero is never negative, so derived equality and hashing are exact.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct Integer {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl Integer {
+    pub const ZERO: Self = Integer {
+        negative: false,
+        limbs: Vec::new(),
+    };
+
+    pub fn one() -> Self {
+        Integer::from(1u32)
+    }
+
+    fn from_limbs(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        let negative = negative && !limbs.is_empty();
+        Integer { negative, limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        !self.negative && self.limbs == [1]
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_positive(&self) -> bool {
+        !self.negative && !self.is_zero()
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|limb| limb & 1 == 0)
+    }
+
+    pub fn is_odd(&self) -> bool {
+        !self.is_even()
+    }
+
+    pub fn abs(&self) -> Integer {
+        Integer {
+            negative: false,
+            limbs: self.limbs.clone(),
+        }
+    }
+
+    /// Bits in the magnitude; zero has none
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => {
+                (self.limbs.len() as u64) * LIMB_BITS as u64 - top.leading_zeros() as u64
+            }
+        }
+    }
+
+    /// Bit `index` of the magnitude
+    pub fn bit(&self, index: u64) -> bool {
+        let limb = (index / LIMB_BITS as u64) as usize;
+        let shift = (index - limb as u64 * LIMB_BITS as u64) as u32;
+        self.limbs.get(limb).is_some_and(|l| (l >> shift) & 1 == 1)
+    }
+
+    /// Zero bits below the lowest one bit; None for zero
+    pub fn trailing_zeros(&self) -> Option<u64> {
+        let limb = self.limbs.iter().position(|&l| l != 0)?;
+        Some(limb as u64 * LIMB_BITS as u64 + self.limbs[limb].trailing_zeros() as u64)
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.negative || self.limbs.len() > 2 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0u64, |acc, &limb| (acc << LIMB_BITS) | limb as u64),
+        )
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+        let magnitude = self.abs().to_u64()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// Quotient rounded toward zero and remainder with the sign of `self`
+    pub fn div_rem(&self, divisor: &Integer) -> Result<(Integer, Integer)> {
+        if divisor.is_zero() {
+            return Err(VeritasError::DivisionByZero);
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &divisor.limbs);
+        Ok((
+            Integer::from_limbs(self.negative != divisor.negative, quotient),
+            Integer::from_limbs(self.negative, remainder),
+        ))
+    }
+
+    /// Quotient and remainder with 0 ≤ remainder < |divisor|
+    pub fn div_rem_euclid(&self, divisor: &Integer) -> Result<(Integer, Integer)> {
+        let (quotient, remainder) = self.div_rem(divisor)?;
+        if !remainder.is_negative() {
+            return Ok((quotient, remainder));
+        }
+        if divisor.is_negative() {
+            Ok((quotient + Integer::one(), remainder - divisor))
+        } else {
+            Ok((quotient - Integer::one(), remainder + divisor))
+        }
+    }
+
+    /// Remainder in 0..|divisor|
+    pub fn rem_euclid(&self, divisor: &Integer) -> Result<Integer> {
+        Ok(self.div_rem_euclid(divisor)?.1)
+    }
+
+    /// Whether `divisor` divides `self`; only zero is divisible by zero
+    pub fn is_divisible_by(&self, divisor: &Integer) -> bool {
+        match self.div_rem(divisor) {
+            Ok((_, remainder)) => remainder.is_zero(),
+            Err(_) => self.is_zero(),
+        }
+    }
+
+    pub fn pow(&self, exponent: u32) -> Integer {
+        let mut result = Integer::one();
+        for i in (0..u32::BITS - exponent.leading_zeros()).rev() {
+            result = &result * &result;
+            if (exponent >> i) & 1 == 1 {
+                result = &result * self;
+            }
+        }
+        result
+    }
+}
+
+// ============================================================================
+// Magnitudes
+// ============================================================================
+
+fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = limb as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> LIMB_BITS;
+    }
+    sum.push(carry as u32);
+    trim(sum)
+}
+
+/// a - b for |a| ≥ |b|
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let total = limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        difference.push(total as u32);
+        borrow = (total < 0) as i64;
+    }
+    trim(difference)
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let total = x as u64 * y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> LIMB_BITS;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(product)
+}
+
+fn shl_magnitude(a: &[u32], bits: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let limbs = (bits / LIMB_BITS as u64) as usize;
+    let shift = (bits - limbs as u64 * LIMB_BITS as u64) as u32;
+    let mut shifted = vec![0u32; limbs];
+    let mut carry = 0u32;
+    for &limb in a {
+        let wide = (limb as u64) << shift;
+        shifted.push(wide as u32 | carry);
+        carry = (wide >> LIMB_BITS) as u32;
+    }
+    shifted.push(carry);
+    trim(shifted)
+}
+
+fn shr_magnitude(a: &[u32], bits: u64) -> Vec<u32> {
+    let limbs = (bits / LIMB_BITS as u64) as usize;
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let shift = (bits - limbs as u64 * LIMB_BITS as u64) as u32;
+    let a = &a[limbs..];
+    let shifted = (0..a.len())
+        .map(|i| {
+            let wide = a[i] as u64 | (a.get(i + 1).copied().unwrap_or(0) as u64) << LIMB_BITS;
+            (wide >> shift) as u32
+        })
+        .collect();
+    trim(shifted)
+}
+
+/// Divide by a single limb
+fn div_rem_limb(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, &limb) in a.iter().enumerate().rev() {
+        let current = (remainder << LIMB_BITS) | limb as u64;
+        let digit = current / divisor as u64;
+        quotient[i] = digit as u32;
+        remainder = current - digit * divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+/// Knuth's Algorithm D; `b` must be nonzero
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = div_rem_limb(a, b[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+
+    // Normalize so the divisor's top limb has its high bit set, which
+    // keeps each estimated quotient digit within two of the true one
+    let shift = b[b.len() - 1].leading_zeros() as u64;
+    let v = shl_magnitude(b, shift);
+    let mut u = shl_magnitude(a, shift);
+    u.resize(a.len() + 1, 0);
+    let n = v.len();
+    let m = u.len() - n;
+    let base = 1u64 << LIMB_BITS;
+    let (top, next) = (v[n - 1] as u64, v[n - 2] as u64);
+
+    let mut quotient = vec![0u32; m];
+    for j in (0..m).rev() {
+        let numerator = (u[j + n] as u64) << LIMB_BITS | u[j + n - 1] as u64;
+        let mut estimate = numerator / top;
+        let mut rest = numerator - estimate * top;
+        while rest < base
+            && (estimate >= base || estimate * next > (rest << LIMB_BITS | u[j + n - 2] as u64))
+        {
+            estimate -= 1;
+            rest += top;
+        }
+
+        // u[j..=j+n] -= estimate · v
+        let mut borrow = 0i64;
+        for (i, &limb) in v.iter().enumerate() {
+            let product = estimate * limb as u64;
+            let total = u[i + j] as i64 - borrow - (product & (base - 1)) as i64;
+            u[i + j] = total as u32;
+            borrow = (product >> LIMB_BITS) as i64 - (total >> LIMB_BITS);
+        }
+        let total = u[j + n] as i64 - borrow;
+        u[j + n] = total as u32;
+
+        // The estimate was one too large: add the divisor back
+        if total < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for (i, &limb) in v.iter().enumerate() {
+                let sum = u[i + j] as u64 + limb as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> LIMB_BITS;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    u.truncate(n);
+    (trim(quotient), shr_magnitude(&u, shift))
+}
+
+// ============================================================================
+// Operators
+// ============================================================================
+
+/// (-1)^a_negative·|a| + (-1)^b_negative·|b|
+fn add_signed(a_negative: bool, a: &[u32], b_negative: bool, b: &[u32]) -> Integer {
+    if a_negative == b_negative {
+        return Integer::from_limbs(a_negative, add_magnitude(a, b));
+    }
+    match compare_magnitude(a, b) {
+        Ordering::Less => Integer::from_limbs(b_negative, sub_magnitude(b, a)),
+        _ => Integer::from_limbs(a_negative, sub_magnitude(a, b)),
+    }
+}
+
+impl Add<&Integer> for &Integer {
+    type Output = Integer;
+
+    fn add(self, rhs: &Integer) -> Integer {
+        add_signed(self.negative, &self.limbs, rhs.negative, &rhs.limbs)
+    }
+}
+
+impl Sub<&Integer> for &Integer {
+    type Output = Integer;
+
+    fn sub(self, rhs: &Integer) -> Integer {
+        add_signed(self.negative, &self.limbs, !rhs.negative, &rhs.limbs)
+    }
+}
+
+impl Mul<&Integer> for &Integer {
+    type Output = Integer;
+
+    fn mul(self, rhs: &Integer) -> Integer {
+        Integer::from_limbs(self.negative != rhs.negative, mul_magnitude(&self.limbs, &rhs.limbs))
+    }
+}
+
+/// The remaining owned/borrowed combinations forward to `&a op &b`
+macro_rules! forward_binary {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait<Integer> for Integer {
+            type Output = Integer;
+
+            fn $method(self, rhs: Integer) -> Integer {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl $trait<&Integer> for Integer {
+            type Output = Integer;
+
+            fn $method(self, rhs: &Integer) -> Integer {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl $trait<Integer> for &Integer {
+            type Output = Integer;
+
+            fn $method(self, rhs: Integer) -> Integer {
+                self.$method(&rhs)
+            }
+        }
+    )*};
+}
+
+forward_binary!(Add add, Sub sub, Mul mul);
+
+impl Neg for Integer {
+    type Output = Integer;
+
+    fn neg(self) -> Integer {
+        Integer::from_limbs(!self.negative, self.limbs)
+    }
+}
+
+impl Neg for &Integer {
+    type Output = Integer;
+
+    fn neg(self) -> Integer {
+        -self.clone()
+    }
+}
+
+/// Shifts act on the magnitude and keep the sign, so `>>` rounds toward zero
+impl Shl<u64> for &Integer {
+    type Output = Integer;
+
+    fn shl(self, bits: u64) -> Integer {
+        Integer::from_limbs(self.negative, shl_magnitude(&self.limbs, bits))
+    }
+}
+
+impl Shr<u64> for &Integer {
+    type Output = Integer;
+
+    fn shr(self, bits: u64) -> Integer {
+        Integer::from_limbs(self.negative, shr_magnitude(&self.limbs, bits))
+    }
+}
+
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+impl From<u64> for Integer {
+    fn from(n: u64) -> Self {
+        Integer::from_limbs(false, vec![n as u32, (n >> LIMB_BITS) as u32])
+    }
+}
+
+impl From<i64> for Integer {
+    fn from(n: i64) -> Self {
+        let magnitude = Integer::from(n.unsigned_abs());
+        if n < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<u32> for Integer {
+    fn from(n: u32) -> Self {
+        Integer::from(n as u64)
+    }
+}
+
+impl From<i32> for Integer {
+    fn from(n: i32) -> Self {
+        Integer::from(n as i64)
+    }
+}
+
+impl FromStr for Integer {
+    type Err = VeritasError;
+
+    /// Decimal digits with an optional sign
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || VeritasError::InvalidInput(format!("not an integer: {:?}", text));
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let mut limbs: Vec<u32> = Vec::new();
+        for digit in digits.bytes() {
+            // limbs = limbs·10 + digit
+            let mut carry = (digit - b'0') as u64;
+            for limb in limbs.iter_mut() {
+                let total = *limb as u64 * 10 + carry;
+                *limb = total as u32;
+                carry = total >> LIMB_BITS;
+            }
+            if carry != 0 {
+                limbs.push(carry as u32);
+            }
+        }
+        Ok(Integer::from_limbs(negative, limbs))
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.limbs.clone();
+        while !rest.is_empty() {
+            let (quotient, chunk) = div_rem_limb(&rest, DECIMAL_CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let (first, lower) = chunks.split_last().expect("nonzero has a chunk");
+        write!(f, "{}", first)?;
+        for chunk in lower.iter().rev() {
+            write!(f, "{:0width$}", chunk, width = DECIMAL_DIGITS)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Integer({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> Integer {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for text in ["0", "7", "-42", "4294967296", "-18446744073709551617"] {
+            assert_eq!(int(text).to_string(), text);
+        }
+        let big = "123456789012345678901234567890123456789";
+        assert_eq!(int(big).to_string(), big);
+        assert_eq!(int("+5"), Integer::from(5));
+        assert_eq!(int("-0"), Integer::ZERO);
+        assert!("12a".parse::<Integer>().is_err());
+        assert!("-".parse::<Integer>().is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = int("340282366920938463463374607431768211457"); // 2^128 + 1
+        let b = int("-18446744073709551616"); // -2^64
+        assert_eq!(&a + &b, int("340282366920938463444927863358058659841"));
+        assert_eq!(&b - &a, int("-340282366920938463481821351505477763073"));
+        assert_eq!(&b * &b, int("340282366920938463463374607431768211456"));
+        assert_eq!(&a - &a, Integer::ZERO);
+        assert!(!(&a - &a).is_negative());
+        assert_eq!(Integer::from(3).pow(40), int("12157665459056928801"));
+        assert_eq!((&a >> 64).to_string(), "18446744073709551616");
+        assert_eq!(&Integer::one() << 128, &a - Integer::one());
+        assert_eq!(int("96").trailing_zeros(), Some(5));
+        assert_eq!(a.bits(), 129);
+        assert!(b < Integer::ZERO && Integer::ZERO < a);
+    }
+
+    #[test]
+    fn test_division() {
+        // Truncated and Euclidean division agree with i64 on every sign
+        for (x, y) in [(7i64, 3i64), (-7, 3), (7, -3), (-7, -3), (6, 3), (-6, 3)] {
+            let (q, r) = Integer::from(x).div_rem(&Integer::from(y)).unwrap();
+            assert_eq!((q.to_i64(), r.to_i64()), (Some(x / y), Some(x - (x / y) * y)));
+            let (q, r) = Integer::from(x).div_rem_euclid(&Integer::from(y)).unwrap();
+            assert_eq!((q.to_i64(), r.to_i64()), (Some(x.div_euclid(y)), Some(x.rem_euclid(y))));
+        }
+        assert!(Integer::one().div_rem(&Integer::ZERO).is_err());
+
+        // Multi-limb divisors, including a quotient digit that needs add-back
+        let a = int("1234567890123456789012345678901234567890123456789");
+        let b = int("98765432109876543210987");
+        let (q, r) = a.div_rem(&b).unwrap();
+        assert_eq!(&q * &b + &r, a);
+        assert!(!r.is_negative() && r < b);
+
+        let a = int("340282366920938463463374607431768211455"); // 2^128 - 1
+        let b = int("18446744073709551617"); // 2^64 + 1
+        let (q, r) = a.div_rem(&b).unwrap();
+        assert_eq!(q, int("18446744073709551615"));
+        assert_eq!(r, Integer::ZERO);
+        assert!(a.is_divisible_by(&b));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Integer::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(Integer::from(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!(Integer::from(u64::MAX).to_i64(), None);
+        assert_eq!(Integer::from(-1).to_u64(), None);
+        assert!(Integer::from(10).is_even() && Integer::from(-3).is_odd());
+    }
+}
@@ -6,6 +6,8 @@
 //! Key types:
 //! - `Scalar`: Real numbers (ScalarF6E5 from Spirix)
 //! - `Circle`: Complex numbers (CircleF6E5 from Spirix)
+//! - `Integer`: Exact integers of any size, for number theory
+//! - `fft` / `convolve`: Spectral methods over `CircleF4E5` and `CircleF6E5`
 //!
 //! Why Spirix?
 //! - Two's complement thruout (no sign bit branches)
@@ -16,9 +18,13 @@
 
 pub mod circle;
 pub mod conversion;
+pub mod fft;
+pub mod integer;
 pub mod scalar;
 
 pub use circle::{Circle, Complex};
+pub use fft::{convolve, fft, ifft, verify_round_trip, RoundTrip, Spectral, Tolerance};
+pub use integer::Integer;
 pub use scalar::Scalar;
 
 use crate::error::{Result, VeritasError};
@@ -3,7 +3,7 @@
 //! Provides a clean API for real number arithmetic
 
 use crate::error::{Result, VeritasError};
-use spirix::ScalarF6E5;
+use spirix::{ScalarF4E4, ScalarF4E5, ScalarF6E5};
 
 /// Real number using Spirix two's complement floats
 ///
@@ -72,6 +72,21 @@ impl Scalar {
         }
     }
 
+    /// Check result, also failing on vanished and exploded values
+    ///
+    /// For results that must keep their magnitude, not just a sign.
+    pub fn representable(&self) -> Result<Self> {
+        if self.is_undefined() {
+            self.check()
+        } else if self.is_exploded() {
+            Err(VeritasError::NumericOverflow)
+        } else if self.is_vanished() {
+            Err(VeritasError::NumericUnderflow)
+        } else {
+            Ok(*self)
+        }
+    }
+
     // Arithmetic operations that return Result
 
     /// Checked addition
@@ -143,6 +158,188 @@ impl Scalar {
     pub fn abs(&self) -> Self {
         Scalar(self.0.magnitude())
     }
+
+    /// Check if value is strictly below zero
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.0 < ScalarF6E5::ZERO
+    }
+
+    /// Order two scalars, respecting Spirix states
+    ///
+    /// Vanished values sit strictly between zero and the smallest normal
+    /// of their sign; exploded values lie beyond every normal of theirs.
+    /// Two vanished (or two exploded) values of the same sign have lost
+    /// their magnitudes and cannot be ordered, and undefined values
+    /// compare with nothing; both are errors rather than a guess.
+    pub fn compare(&self, other: &Self) -> Result<std::cmp::Ordering> {
+        use std::cmp::Ordering;
+
+        if self.is_undefined() || other.is_undefined() {
+            return Err(VeritasError::UndefinedOperation(format!(
+                "cannot compare {} with {}",
+                self, other
+            )));
+        }
+
+        let (a, b) = (self.rank(), other.rank());
+        if a != b {
+            return Ok(a.cmp(&b));
+        }
+
+        match a {
+            0 => Ok(Ordering::Equal),
+            -2 | 2 if self.0 < other.0 => Ok(Ordering::Less),
+            -2 | 2 if self.0 > other.0 => Ok(Ordering::Greater),
+            -2 | 2 => Ok(Ordering::Equal),
+            _ => Err(VeritasError::UndefinedOperation(format!(
+                "cannot order {} and {}: magnitudes are lost",
+                self, other
+            ))),
+        }
+    }
+
+    /// Position of the value's class on the line:
+    /// exploded (±3), normal (±2), vanished (±1), zero (0)
+    fn rank(&self) -> i8 {
+        let position = if self.is_zero() {
+            return 0;
+        } else if self.is_vanished() {
+            1
+        } else if self.is_exploded() {
+            3
+        } else {
+            2
+        };
+        if self.is_negative() {
+            -position
+        } else {
+            position
+        }
+    }
+
+    /// Create from a 64-bit integer (exact)
+    ///
+    /// Built from 32/16-bit pieces so every bit lands in the fraction;
+    /// no IEEE round trip.
+    pub fn from_i64(n: i64) -> Self {
+        let two_16 = Scalar::from(1 << 16);
+        let hi = Scalar::from((n >> 32) as i32);
+        let lo = n & 0xFFFF_FFFF;
+        let lo_hi = Scalar::from((lo >> 16) as i32);
+        let lo_lo = Scalar::from((lo & 0xFFFF) as i32);
+        hi * two_16 * two_16 + lo_hi * two_16 + lo_lo
+    }
+
+    /// Exact integer value, if this scalar holds one
+    ///
+    /// Peels powers of two off the magnitude, so the answer is exact.
+    /// Returns None for fractional, out-of-range or non-normal values.
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.is_zero() {
+            return Some(0);
+        }
+        if !self.is_normal() {
+            return None;
+        }
+
+        let mut powers = Vec::with_capacity(63);
+        let mut power = Scalar::ONE;
+        for _ in 0..63 {
+            powers.push(power);
+            power = power * Scalar::TWO;
+        }
+
+        let mut rest = self.abs();
+        if rest.0 >= power.0 {
+            return None;
+        }
+
+        let mut n: i64 = 0;
+        for bit in (0..63).rev() {
+            if rest.0 >= powers[bit].0 {
+                rest = rest - powers[bit];
+                n |= 1 << bit;
+            }
+        }
+
+        if !rest.is_zero() {
+            None
+        } else if self.is_negative() {
+            Some(-n)
+        } else {
+            Some(n)
+        }
+    }
+
+    /// Widen from F4E4, the precision of `autograd` tensors (exact)
+    ///
+    /// Both are a two's complement fraction and a power of two, so the
+    /// fraction moves up 48 bits and the exponent carries over. Returns
+    /// None for vanished, exploded and undefined values.
+    pub fn from_f4e4(value: ScalarF4E4) -> Option<Self> {
+        if value.is_zero() {
+            return Some(Scalar::ZERO);
+        }
+        if !value.is_normal() {
+            return None;
+        }
+        Some(Scalar(ScalarF6E5 {
+            fraction: i64::from(value.fraction) << 48,
+            exponent: i32::from(value.exponent),
+        }))
+    }
+
+    /// Narrow to F4E4, dropping the low 48 bits of the fraction
+    ///
+    /// Returns None for non-normal values and for exponents outside what
+    /// F4E4 can hold.
+    pub fn to_f4e4(&self) -> Option<ScalarF4E4> {
+        if self.is_zero() {
+            return Some(ScalarF4E4::ZERO);
+        }
+        if !self.is_normal() {
+            return None;
+        }
+        let exponent = i16::try_from(self.0.exponent)
+            .ok()
+            .filter(|&e| e != i16::MIN)?;
+        Some(ScalarF4E4 {
+            fraction: (self.0.fraction >> 48) as i16,
+            exponent,
+        })
+    }
+
+    /// Widen a Spirix F4E5 scalar, the component type of `CircleF4E5`
+    ///
+    /// The exponents already agree, so only the fraction moves. Returns
+    /// None for vanished, exploded and undefined values.
+    pub fn from_f4e5(value: ScalarF4E5) -> Option<Self> {
+        if value.is_zero() {
+            return Some(Scalar::ZERO);
+        }
+        if !value.is_normal() {
+            return None;
+        }
+        Some(Scalar(ScalarF6E5 {
+            fraction: i64::from(value.fraction) << 48,
+            exponent: value.exponent,
+        }))
+    }
+
+    /// Narrow to F4E5, dropping the low 48 bits of the fraction
+    pub fn to_f4e5(&self) -> Option<ScalarF4E5> {
+        if self.is_zero() {
+            return Some(ScalarF4E5::ZERO);
+        }
+        if !self.is_normal() {
+            return None;
+        }
+        Some(ScalarF4E5 {
+            fraction: (self.0.fraction >> 48) as i16,
+            exponent: self.0.exponent,
+        })
+    }
 }
 
 // Implement arithmetic operators (unchecked, for convenience)
@@ -253,6 +450,30 @@ mod tests {
         assert!(x.cos().is_ok());
     }
 
+    #[test]
+    fn test_integer_round_trip() {
+        for n in [0i64, 1, -1, 12, -144, 1 << 40, -(1 << 52) + 7] {
+            assert_eq!(Scalar::from_i64(n).to_i64(), Some(n));
+        }
+        assert_eq!(Scalar::from(-7), Scalar::from_i64(-7));
+        assert_eq!((Scalar::ONE / Scalar::TWO).to_i64(), None);
+    }
+
+    #[test]
+    fn test_f4e4_round_trip() {
+        let third = ScalarF4E4::ONE / ScalarF4E4::from(3);
+        for value in [ScalarF4E4::ZERO, ScalarF4E4::from(-12), third] {
+            assert_eq!(Scalar::from_f4e4(value).unwrap().to_f4e4(), Some(value));
+        }
+        assert_eq!(Scalar::from_f4e4(ScalarF4E4::from(40)), Some(Scalar::from(40)));
+
+        // Narrowing keeps the leading 16 bits of 1/3
+        let exact = Scalar::ONE / Scalar::from(3);
+        let narrowed = Scalar::from_f4e4(exact.to_f4e4().unwrap()).unwrap();
+        assert!((narrowed - exact).abs().inner() < (Scalar::ONE / Scalar::from(1 << 15)).inner());
+        assert_eq!(Scalar::from_f4e4(ScalarF4E4::ZERO / ScalarF4E4::ZERO), None);
+    }
+
     #[test]
     fn test_vanished_detection() {
         let tiny = Scalar::new(ScalarF6E5::MIN_POS);
@@ -261,4 +482,32 @@ mod tests {
         assert!(!product.is_zero());
         assert!(product.is_vanished());
     }
+
+    #[test]
+    fn test_representable() {
+        let tiny = Scalar::new(ScalarF6E5::MIN_POS);
+        let huge = Scalar::new(ScalarF6E5::MAX);
+        assert_eq!(tiny.representable(), Ok(tiny));
+        assert_eq!((tiny * tiny).representable(), Err(VeritasError::NumericUnderflow));
+        assert_eq!((huge * huge).representable(), Err(VeritasError::NumericOverflow));
+        assert!(matches!(
+            (Scalar::ONE / Scalar::ZERO).representable(),
+            Err(VeritasError::UndefinedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_compare_states() {
+        use std::cmp::Ordering;
+
+        let tiny = Scalar::new(ScalarF6E5::MIN_POS);
+        let vanished = tiny * tiny;
+
+        assert_eq!(Scalar::from(3).compare(&Scalar::PI).unwrap(), Ordering::Less);
+        // Vanished values keep their sign but not their size
+        assert_eq!(vanished.compare(&Scalar::ZERO).unwrap(), Ordering::Greater);
+        assert_eq!(vanished.compare(&tiny).unwrap(), Ordering::Less);
+        assert_eq!((-vanished).compare(&vanished).unwrap(), Ordering::Less);
+        assert!(vanished.compare(&(vanished * Scalar::TWO)).is_err());
+    }
 }
@@ -0,0 +1,419 @@
+//! Linear programming over exact rationals
+//!
+//! A `LinearProgram` is read from an objective `Expr` and constraints
+//! `a <= b`, `a >= b` or `a = b` (joined with `and` if convenient) whose
+//! sides are linear in the decision variables. A constraint `x >= 0` on
+//! a single variable becomes a sign restriction; a variable without one
+//! is free.
+//!
+//! `solve` runs the two-phase simplex method on `Rational`s with Bland's
+//! rule, so it cannot cycle. Every outcome comes with a
+//! `DualCertificate`, which `verification` checks on its own before the
+//! answer is returned:
+//! - Optimal: the point, and dual weights on the constraints whose bound
+//!   equals the objective there
+//! - Infeasible: a Farkas combination of the constraints that reads 0 < 0
+//! - Unbounded: a feasible point and a ray along which the objective grows
+//!
+//! Key types:
+//! - `LinearProgram`: Objective, constraint rows and sign restrictions
+//! - `LpSolution`: The `Outcome` with its certificate and proof
+
+pub mod simplex;
+
+use crate::error::{Result, VeritasError};
+use crate::symbolic::polynomial::Polynomial;
+use crate::symbolic::{Expr, Rational};
+use crate::verification::{DualCertificate, Proof, VerificationState};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Direction of optimization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+    Maximize,
+    Minimize,
+}
+
+/// How a constraint row compares with its bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Le,
+    Ge,
+    Eq,
+}
+
+impl Relation {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Relation::Le => "≤",
+            Relation::Ge => "≥",
+            Relation::Eq => "=",
+        }
+    }
+}
+
+/// Σⱼ coefficients[j]·xⱼ (relation) bound
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub coefficients: Vec<Rational>,
+    pub relation: Relation,
+    pub bound: Rational,
+}
+
+/// Optimize objective·x + constant subject to the constraint rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearProgram {
+    /// Decision variables in sorted order; every vector is indexed by them
+    pub variables: Vec<String>,
+    pub sense: Sense,
+    pub objective: Vec<Rational>,
+    pub constant: Rational,
+    pub constraints: Vec<Constraint>,
+    /// Variables restricted to xⱼ ≥ 0; the rest are free
+    pub nonnegative: Vec<bool>,
+}
+
+impl LinearProgram {
+    pub fn maximize(objective: &Expr, constraints: &[Expr]) -> Result<Self> {
+        LinearProgram::new(Sense::Maximize, objective, constraints)
+    }
+
+    pub fn minimize(objective: &Expr, constraints: &[Expr]) -> Result<Self> {
+        LinearProgram::new(Sense::Minimize, objective, constraints)
+    }
+
+    /// Read the objective and constraints, which must be linear
+    pub fn new(sense: Sense, objective: &Expr, constraints: &[Expr]) -> Result<Self> {
+        let objective = Linear::from_expr(objective)?;
+        let mut relations = Vec::new();
+        for constraint in constraints {
+            comparisons(constraint, &mut relations)?;
+        }
+
+        let mut names: BTreeSet<&String> = objective.coefficients.keys().collect();
+        for (form, _) in &relations {
+            names.extend(form.coefficients.keys());
+        }
+        let variables: Vec<String> = names.into_iter().cloned().collect();
+
+        let mut nonnegative = vec![false; variables.len()];
+        let mut rows = Vec::new();
+        for (form, relation) in &relations {
+            let coefficients = form.dense(&variables);
+            let bound = -form.constant;
+            match sign_restriction(&coefficients, *relation, bound) {
+                Some(j) => nonnegative[j] = true,
+                None => rows.push(Constraint {
+                    coefficients,
+                    relation: *relation,
+                    bound,
+                }),
+            }
+        }
+
+        Ok(LinearProgram {
+            objective: objective.dense(&variables),
+            constant: objective.constant,
+            variables,
+            sense,
+            constraints: rows,
+            nonnegative,
+        })
+    }
+
+    /// Solve exactly and check the certificate of the outcome
+    pub fn solve(&self) -> Result<LpSolution> {
+        let (certificate, pivots) = simplex::solve(self)?;
+        // A certificate that fails its check is a bug in the solver
+        let proof = certificate.check()?;
+        let outcome = match &certificate {
+            DualCertificate::Optimal { primal, value, .. } => Outcome::Optimal {
+                point: primal.clone(),
+                value: *value,
+            },
+            DualCertificate::Infeasible { .. } => Outcome::Infeasible,
+            DualCertificate::Unbounded { point, ray, .. } => Outcome::Unbounded {
+                point: point.clone(),
+                ray: ray.clone(),
+            },
+        };
+        let state = VerificationState::Verified {
+            proof_id: proof.id(),
+        };
+        Ok(LpSolution {
+            outcome,
+            certificate,
+            proof,
+            state,
+            pivots,
+        })
+    }
+
+    /// Σⱼ coefficients[j]·xⱼ + constant, e.g. "3·x - y + 2"
+    pub fn render(&self, coefficients: &[Rational], constant: Rational) -> String {
+        let mut text = String::new();
+        let terms = coefficients.iter().zip(&self.variables);
+        for (&c, name) in terms.filter(|(c, _)| !c.is_zero()) {
+            let magnitude = c.abs();
+            let sign = match (text.is_empty(), c.is_negative()) {
+                (true, true) => "-",
+                (true, false) => "",
+                (false, true) => " - ",
+                (false, false) => " + ",
+            };
+            if magnitude == Rational::ONE {
+                text.push_str(&format!("{}{}", sign, name));
+            } else {
+                text.push_str(&format!("{}{}·{}", sign, magnitude, name));
+            }
+        }
+        if text.is_empty() {
+            return constant.to_string();
+        }
+        if !constant.is_zero() {
+            let sign = if constant.is_negative() { "-" } else { "+" };
+            text.push_str(&format!(" {} {}", sign, constant.abs()));
+        }
+        text
+    }
+}
+
+/// Result of solving a linear program
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// An optimal point and the objective there
+    Optimal {
+        point: Vec<Rational>,
+        value: Rational,
+    },
+    /// No point satisfies every constraint
+    Infeasible,
+    /// The objective improves without limit from `point` along `ray`
+    Unbounded {
+        point: Vec<Rational>,
+        ray: Vec<Rational>,
+    },
+}
+
+/// An outcome with the certificate it was checked against
+#[derive(Debug, Clone)]
+pub struct LpSolution {
+    pub outcome: Outcome,
+    pub certificate: DualCertificate,
+    /// Facts checked from the certificate
+    pub proof: Proof,
+    pub state: VerificationState,
+    /// Simplex pivots over both phases
+    pub pivots: usize,
+}
+
+impl LpSolution {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+
+    /// Optimal value of `variable`, if there is an optimum
+    pub fn value(&self, variable: &str) -> Option<Rational> {
+        let Outcome::Optimal { point, .. } = &self.outcome else {
+            return None;
+        };
+        let DualCertificate::Optimal { program, .. } = &self.certificate else {
+            return None;
+        };
+        let index = program.variables.iter().position(|v| v == variable)?;
+        point.get(index).copied()
+    }
+}
+
+/// Linear expression Σ cᵥ·v + constant over named variables
+struct Linear {
+    coefficients: BTreeMap<String, Rational>,
+    constant: Rational,
+}
+
+impl Linear {
+    fn from_expr(expr: &Expr) -> Result<Self> {
+        let poly = Polynomial::from_expr(expr)?;
+        if poly.degree() > 1 {
+            return Err(VeritasError::InvalidInput(format!("{} is not linear", expr)));
+        }
+        let mut coefficients = BTreeMap::new();
+        let mut rest = poly.clone();
+        for atom in poly.atoms() {
+            let Expr::Variable(name) = atom else {
+                return Err(VeritasError::InvalidInput(format!(
+                    "{} is not a decision variable or a rational number",
+                    atom
+                )));
+            };
+            let coefficient = poly.coefficients_in(atom)[1]
+                .as_constant()
+                .expect("degree one");
+            rest = rest.sub(&Polynomial::atom(atom.clone()).scale(coefficient)?)?;
+            coefficients.insert(name.clone(), coefficient);
+        }
+        let constant = rest.as_constant().expect("only the constant term is left");
+        Ok(Linear {
+            coefficients,
+            constant,
+        })
+    }
+
+    /// Coefficients in the order of `variables`
+    fn dense(&self, variables: &[String]) -> Vec<Rational> {
+        variables
+            .iter()
+            .map(|v| self.coefficients.get(v).copied().unwrap_or(Rational::ZERO))
+            .collect()
+    }
+}
+
+/// Collect `lhs - rhs` (relation) 0 for each comparison in a conjunction
+fn comparisons(expr: &Expr, out: &mut Vec<(Linear, Relation)>) -> Result<()> {
+    let (lhs, rhs, relation) = match expr {
+        Expr::And(a, b) => {
+            comparisons(a, out)?;
+            return comparisons(b, out);
+        }
+        Expr::Le(a, b) => (a, b, Relation::Le),
+        Expr::Ge(a, b) => (a, b, Relation::Ge),
+        Expr::Eq(a, b) => (a, b, Relation::Eq),
+        Expr::Lt(..) | Expr::Gt(..) => {
+            return Err(VeritasError::InvalidInput(format!(
+                "{} is strict; an optimum needs closed constraints, use <= or >=",
+                expr
+            )))
+        }
+        _ => {
+            return Err(VeritasError::InvalidInput(format!(
+                "{} is not a constraint; expected <=, >= or =",
+                expr
+            )))
+        }
+    };
+    let difference = Expr::sub((**lhs).clone(), (**rhs).clone());
+    out.push((Linear::from_expr(&difference)?, relation));
+    Ok(())
+}
+
+/// Variable j if the row reads c·xⱼ ≥ 0 with c > 0, or c·xⱼ ≤ 0 with c < 0
+fn sign_restriction(
+    coefficients: &[Rational],
+    relation: Relation,
+    bound: Rational,
+) -> Option<usize> {
+    if !bound.is_zero() {
+        return None;
+    }
+    let mut nonzero = coefficients.iter().enumerate().filter(|(_, c)| !c.is_zero());
+    let (j, c) = nonzero.next()?;
+    if nonzero.next().is_some() {
+        return None;
+    }
+    match relation {
+        Relation::Ge if !c.is_negative() => Some(j),
+        Relation::Le if c.is_negative() => Some(j),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Expr {
+        Expr::var(name)
+    }
+
+    fn num(n: i32) -> Expr {
+        Expr::number(n)
+    }
+
+    fn rational(num: i128, den: i128) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn test_read_program() {
+        // maximize 3x + 2y + 1 subject to x + y ≤ 4, 0 ≤ x, y ≥ 0, x - y = 1/2
+        let objective = Expr::add(
+            Expr::add(Expr::mul(num(3), var("x")), Expr::mul(num(2), var("y"))),
+            num(1),
+        );
+        let constraints = [
+            Expr::less_eq(Expr::add(var("x"), var("y")), num(4)),
+            Expr::and(
+                Expr::less_eq(num(0), var("x")),
+                Expr::greater_eq(var("y"), num(0)),
+            ),
+            Expr::equals(Expr::sub(var("x"), var("y")), Expr::div(num(1), num(2))),
+        ];
+        let program = LinearProgram::maximize(&objective, &constraints).unwrap();
+        assert_eq!(program.variables, ["x", "y"]);
+        assert_eq!(program.nonnegative, [true, true]);
+        assert_eq!(program.constraints.len(), 2);
+        assert_eq!(program.constraints[1].bound, rational(1, 2));
+        assert_eq!(program.render(&program.objective, program.constant), "3·x + 2·y + 1");
+
+        let square = Expr::less_eq(Expr::mul(var("x"), var("x")), num(1));
+        assert!(LinearProgram::maximize(&var("x"), &[square]).is_err());
+        let strict = Expr::less(var("x"), num(1));
+        assert!(LinearProgram::maximize(&var("x"), &[strict]).is_err());
+    }
+
+    #[test]
+    fn test_optimum() {
+        // Profit 3x + 5y under x ≤ 4, 2y ≤ 12, 3x + 2y ≤ 18: 36 at (2, 6)
+        let constraints = [
+            Expr::less_eq(var("x"), num(4)),
+            Expr::less_eq(Expr::mul(num(2), var("y")), num(12)),
+            Expr::less_eq(
+                Expr::add(Expr::mul(num(3), var("x")), Expr::mul(num(2), var("y"))),
+                num(18),
+            ),
+            Expr::greater_eq(var("x"), num(0)),
+            Expr::greater_eq(var("y"), num(0)),
+        ];
+        let profit = Expr::add(Expr::mul(num(3), var("x")), Expr::mul(num(5), var("y")));
+        let solution = LinearProgram::maximize(&profit, &constraints).unwrap().solve().unwrap();
+        assert!(solution.is_verified());
+        let optimum = Outcome::Optimal {
+            point: vec![rational(2, 1), rational(6, 1)],
+            value: rational(36, 1),
+        };
+        assert_eq!(solution.outcome, optimum);
+        assert_eq!(solution.value("x"), Some(rational(2, 1)));
+        assert_eq!(solution.value("y"), Some(rational(6, 1)));
+
+        // Minimizing a free variable with fractional data: x ≥ 1/3 - y, y = 1/4
+        let constraints = [
+            Expr::greater_eq(var("x"), Expr::sub(Expr::div(num(1), num(3)), var("y"))),
+            Expr::equals(var("y"), Expr::div(num(1), num(4))),
+        ];
+        let solution = LinearProgram::minimize(&var("x"), &constraints).unwrap().solve().unwrap();
+        assert_eq!(solution.value("x"), Some(rational(1, 12)));
+    }
+
+    #[test]
+    fn test_infeasible_and_unbounded() {
+        // x + y ≤ 1 and x + y ≥ 3 contradict each other
+        let sum = Expr::add(var("x"), var("y"));
+        let constraints = [
+            Expr::less_eq(sum.clone(), num(1)),
+            Expr::greater_eq(sum.clone(), num(3)),
+        ];
+        let solution = LinearProgram::maximize(&var("x"), &constraints).unwrap().solve().unwrap();
+        assert_eq!(solution.outcome, Outcome::Infeasible);
+        assert!(solution.is_verified());
+
+        // x - y ≤ 1 with x, y ≥ 0 lets x + y grow along (1, 1)
+        let constraints = [
+            Expr::less_eq(Expr::sub(var("x"), var("y")), num(1)),
+            Expr::greater_eq(var("x"), num(0)),
+            Expr::greater_eq(var("y"), num(0)),
+        ];
+        let solution = LinearProgram::maximize(&sum, &constraints).unwrap().solve().unwrap();
+        assert!(matches!(solution.outcome, Outcome::Unbounded { .. }));
+        assert!(matches!(solution.certificate, DualCertificate::Unbounded { .. }));
+    }
+}
@@ -0,0 +1,399 @@
+//! Two-phase simplex on a dense tableau
+//!
+//! The program is first put in equality form over z ≥ 0: a free variable
+//! becomes the difference of two columns, each inequality gets a slack
+//! column, and rows with a negative bound are negated. Phase one starts
+//! from a basis of artificial columns, one per row, and drives their
+//! sum to zero; phase two optimizes the objective from wherever phase
+//! one stopped, with the artificial columns barred from entering.
+//!
+//! The artificial columns stay in the tableau: they began as the
+//! identity, so they hold B⁻¹, and the dual weights are read off them
+//! as c_B·B⁻¹.
+
+use super::{LinearProgram, Relation, Sense};
+use crate::error::Result;
+use crate::symbolic::Rational;
+use crate::verification::DualCertificate;
+
+/// Solve `program`, returning the certificate of its outcome and the
+/// number of pivots taken
+pub(crate) fn solve(program: &LinearProgram) -> Result<(DualCertificate, usize)> {
+    let mut tableau = Tableau::new(program);
+
+    // Phase one: maximize minus the sum of the artificial columns
+    let mut artificial_cost = vec![Rational::ZERO; tableau.width()];
+    for cost in &mut artificial_cost[tableau.structural..] {
+        *cost = -Rational::ONE;
+    }
+    tableau.optimize(&artificial_cost, tableau.width())?;
+    if tableau.value(&artificial_cost)?.is_negative() {
+        let farkas = tableau.row_weights(&tableau.duals(&artificial_cost)?);
+        let certificate = DualCertificate::Infeasible {
+            program: program.clone(),
+            farkas,
+        };
+        return Ok((certificate, tableau.pivots));
+    }
+    tableau.drive_out_artificials()?;
+
+    // Phase two on the program's own objective, as a maximization
+    let cost = tableau.cost(program);
+    let certificate = match tableau.optimize(&cost, tableau.structural)? {
+        Some(entering) => DualCertificate::Unbounded {
+            program: program.clone(),
+            point: tableau.point()?,
+            ray: tableau.ray(entering)?,
+        },
+        None => {
+            let primal = tableau.point()?;
+            let mut value = program.constant;
+            for (c, x) in program.objective.iter().zip(&primal) {
+                value = value.checked_add(c.checked_mul(*x)?)?;
+            }
+            DualCertificate::Optimal {
+                program: program.clone(),
+                dual: tableau.row_weights(&tableau.duals(&cost)?),
+                primal,
+                value,
+            }
+        }
+    };
+    Ok((certificate, tableau.pivots))
+}
+
+/// Rows B⁻¹·[M | I] with right-hand side B⁻¹·b and the basic column of each
+struct Tableau {
+    rows: Vec<Vec<Rational>>,
+    rhs: Vec<Rational>,
+    basis: Vec<usize>,
+    /// Columns before the artificial ones
+    structural: usize,
+    /// Column of xⱼ, and of its negative part when xⱼ is free
+    positive: Vec<usize>,
+    negative: Vec<Option<usize>>,
+    /// Whether each row was negated to make its bound nonnegative
+    negated: Vec<bool>,
+    pivots: usize,
+}
+
+impl Tableau {
+    fn new(program: &LinearProgram) -> Self {
+        let mut next = 0;
+        let mut positive = Vec::with_capacity(program.variables.len());
+        let mut negative = Vec::with_capacity(program.variables.len());
+        for &nonnegative in &program.nonnegative {
+            positive.push(next);
+            negative.push((!nonnegative).then_some(next + 1));
+            next += if nonnegative { 1 } else { 2 };
+        }
+        let mut slacks = Vec::with_capacity(program.constraints.len());
+        for row in &program.constraints {
+            if row.relation == Relation::Eq {
+                slacks.push(None);
+            } else {
+                slacks.push(Some(next));
+                next += 1;
+            }
+        }
+
+        let structural = next;
+        let m = program.constraints.len();
+        let mut rows = Vec::with_capacity(m);
+        let mut rhs = Vec::with_capacity(m);
+        let mut negated = Vec::with_capacity(m);
+        for (i, row) in program.constraints.iter().enumerate() {
+            let mut entries = vec![Rational::ZERO; structural + m];
+            for (j, &a) in row.coefficients.iter().enumerate() {
+                entries[positive[j]] = a;
+                if let Some(column) = negative[j] {
+                    entries[column] = -a;
+                }
+            }
+            if let Some(column) = slacks[i] {
+                entries[column] = match row.relation {
+                    Relation::Le => Rational::ONE,
+                    _ => -Rational::ONE,
+                };
+            }
+            let flip = row.bound.is_negative();
+            if flip {
+                entries.iter_mut().for_each(|entry| *entry = -*entry);
+            }
+            entries[structural + i] = Rational::ONE;
+            rows.push(entries);
+            rhs.push(if flip { -row.bound } else { row.bound });
+            negated.push(flip);
+        }
+
+        Tableau {
+            rows,
+            rhs,
+            basis: (structural..structural + m).collect(),
+            structural,
+            positive,
+            negative,
+            negated,
+            pivots: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.structural + self.rows.len()
+    }
+
+    /// Costs of the program's objective over the columns, as a maximization
+    fn cost(&self, program: &LinearProgram) -> Vec<Rational> {
+        let mut cost = vec![Rational::ZERO; self.width()];
+        for (j, &c) in program.objective.iter().enumerate() {
+            let c = if program.sense == Sense::Minimize { -c } else { c };
+            cost[self.positive[j]] = c;
+            if let Some(column) = self.negative[j] {
+                cost[column] = -c;
+            }
+        }
+        cost
+    }
+
+    /// c_q - c_B·B⁻¹·M_q, the gain per unit of column q entering
+    fn reduced_cost(&self, cost: &[Rational], q: usize) -> Result<Rational> {
+        let mut reduced = cost[q];
+        for (row, &basic) in self.rows.iter().zip(&self.basis) {
+            reduced = reduced.checked_sub(cost[basic].checked_mul(row[q])?)?;
+        }
+        Ok(reduced)
+    }
+
+    /// Pivot until no column below `allowed` improves the objective;
+    /// returns the entering column if it can grow without limit
+    fn optimize(&mut self, cost: &[Rational], allowed: usize) -> Result<Option<usize>> {
+        loop {
+            // Bland: the lowest improving column enters...
+            let mut entering = None;
+            for q in 0..allowed {
+                let reduced = self.reduced_cost(cost, q)?;
+                if !reduced.is_zero() && !reduced.is_negative() {
+                    entering = Some(q);
+                    break;
+                }
+            }
+            let Some(q) = entering else {
+                return Ok(None);
+            };
+
+            // ...and among rows tied in the ratio test, the lowest basic column leaves
+            let mut leaving: Option<(usize, Rational)> = None;
+            for (i, row) in self.rows.iter().enumerate() {
+                if row[q].is_zero() || row[q].is_negative() {
+                    continue;
+                }
+                let ratio = self.rhs[i].checked_div(row[q])?;
+                let better = match leaving {
+                    None => true,
+                    Some((k, best)) => {
+                        let difference = ratio.checked_sub(best)?;
+                        difference.is_negative()
+                            || (difference.is_zero() && self.basis[i] < self.basis[k])
+                    }
+                };
+                if better {
+                    leaving = Some((i, ratio));
+                }
+            }
+            match leaving {
+                Some((r, _)) => self.pivot(r, q)?,
+                None => return Ok(Some(q)),
+            }
+        }
+    }
+
+    fn pivot(&mut self, r: usize, q: usize) -> Result<()> {
+        let scale = self.rows[r][q].recip()?;
+        for entry in &mut self.rows[r] {
+            *entry = entry.checked_mul(scale)?;
+        }
+        self.rhs[r] = self.rhs[r].checked_mul(scale)?;
+
+        let pivot_row = self.rows[r].clone();
+        let pivot_rhs = self.rhs[r];
+        for (i, (row, rhs)) in self.rows.iter_mut().zip(&mut self.rhs).enumerate() {
+            let factor = row[q];
+            if i == r || factor.is_zero() {
+                continue;
+            }
+            for (entry, &p) in row.iter_mut().zip(&pivot_row) {
+                *entry = entry.checked_sub(factor.checked_mul(p)?)?;
+            }
+            *rhs = rhs.checked_sub(factor.checked_mul(pivot_rhs)?)?;
+        }
+        self.basis[r] = q;
+        self.pivots += 1;
+        Ok(())
+    }
+
+    /// Swap artificial columns left in the basis at zero for structural
+    /// ones; a row with no structural entry is redundant and keeps its own
+    fn drive_out_artificials(&mut self) -> Result<()> {
+        for r in 0..self.rows.len() {
+            if self.basis[r] < self.structural {
+                continue;
+            }
+            if let Some(q) = (0..self.structural).find(|&q| !self.rows[r][q].is_zero()) {
+                self.pivot(r, q)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// c_B·B⁻¹·b, the objective at the current basis
+    fn value(&self, cost: &[Rational]) -> Result<Rational> {
+        let mut value = Rational::ZERO;
+        for (&basic, &b) in self.basis.iter().zip(&self.rhs) {
+            value = value.checked_add(cost[basic].checked_mul(b)?)?;
+        }
+        Ok(value)
+    }
+
+    /// u = c_B·B⁻¹, with B⁻¹ read from the artificial columns
+    fn duals(&self, cost: &[Rational]) -> Result<Vec<Rational>> {
+        (0..self.rows.len())
+            .map(|i| {
+                let mut u = Rational::ZERO;
+                for (row, &basic) in self.rows.iter().zip(&self.basis) {
+                    u = u.checked_add(cost[basic].checked_mul(row[self.structural + i])?)?;
+                }
+                Ok(u)
+            })
+            .collect()
+    }
+
+    /// Weights on the program's rows, undoing the negation of rows
+    fn row_weights(&self, duals: &[Rational]) -> Vec<Rational> {
+        duals
+            .iter()
+            .zip(&self.negated)
+            .map(|(&u, &flip)| if flip { -u } else { u })
+            .collect()
+    }
+
+    /// The program's variables from column values
+    fn variables(&self, z: &[Rational]) -> Result<Vec<Rational>> {
+        self.positive
+            .iter()
+            .zip(&self.negative)
+            .map(|(&plus, &minus)| match minus {
+                Some(minus) => z[plus].checked_sub(z[minus]),
+                None => Ok(z[plus]),
+            })
+            .collect()
+    }
+
+    /// Current basic solution
+    fn point(&self) -> Result<Vec<Rational>> {
+        let mut z = vec![Rational::ZERO; self.width()];
+        for (&basic, &b) in self.basis.iter().zip(&self.rhs) {
+            z[basic] = b;
+        }
+        self.variables(&z)
+    }
+
+    /// Direction in which column q grows and the basic columns follow
+    fn ray(&self, q: usize) -> Result<Vec<Rational>> {
+        let mut z = vec![Rational::ZERO; self.width()];
+        z[q] = Rational::ONE;
+        for (row, &basic) in self.rows.iter().zip(&self.basis) {
+            z[basic] = -row[q];
+        }
+        self.variables(&z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::Constraint;
+
+    fn int(n: i64) -> Rational {
+        Rational::from_i64(n)
+    }
+
+    fn row(coefficients: &[i64], relation: Relation, bound: i64) -> Constraint {
+        Constraint {
+            coefficients: coefficients.iter().map(|&c| int(c)).collect(),
+            relation,
+            bound: int(bound),
+        }
+    }
+
+    #[test]
+    fn test_degenerate_cycling_example() {
+        // Beale's example cycles under the largest-coefficient rule; Bland's
+        // rule reaches the optimum 1/20 at x = (1/25, 0, 1, 0)
+        let program = LinearProgram {
+            variables: ["a", "b", "c", "d"].map(String::from).to_vec(),
+            sense: Sense::Maximize,
+            objective: vec![
+                Rational::new(3, 4).unwrap(),
+                int(-150),
+                Rational::new(1, 50).unwrap(),
+                int(-6),
+            ],
+            constant: Rational::ZERO,
+            constraints: vec![
+                Constraint {
+                    coefficients: vec![
+                        Rational::new(1, 4).unwrap(),
+                        int(-60),
+                        Rational::new(-1, 25).unwrap(),
+                        int(9),
+                    ],
+                    relation: Relation::Le,
+                    bound: int(0),
+                },
+                Constraint {
+                    coefficients: vec![
+                        Rational::new(1, 2).unwrap(),
+                        int(-90),
+                        Rational::new(-1, 50).unwrap(),
+                        int(3),
+                    ],
+                    relation: Relation::Le,
+                    bound: int(0),
+                },
+                row(&[0, 0, 1, 0], Relation::Le, 1),
+            ],
+            nonnegative: vec![true; 4],
+        };
+        let (certificate, _) = solve(&program).unwrap();
+        let DualCertificate::Optimal { primal, value, .. } = &certificate else {
+            panic!("expected an optimum, got {:?}", certificate);
+        };
+        assert_eq!(*value, Rational::new(1, 20).unwrap());
+        assert_eq!(primal[2], int(1));
+        assert!(certificate.check().is_ok());
+    }
+
+    #[test]
+    fn test_equality_rows_and_negative_bounds() {
+        // minimize x + y with x - y = -2, x + y ≥ 4 and both free: 4 at (1, 3)
+        let program = LinearProgram {
+            variables: vec!["x".to_string(), "y".to_string()],
+            sense: Sense::Minimize,
+            objective: vec![int(1), int(1)],
+            constant: int(0),
+            constraints: vec![
+                row(&[1, -1], Relation::Eq, -2),
+                row(&[1, 1], Relation::Ge, 4),
+                // A redundant copy of the first row
+                row(&[-2, 2], Relation::Eq, 4),
+            ],
+            nonnegative: vec![false, false],
+        };
+        let (certificate, _) = solve(&program).unwrap();
+        let DualCertificate::Optimal { value, .. } = &certificate else {
+            panic!("expected an optimum, got {:?}", certificate);
+        };
+        assert_eq!(*value, int(4));
+        assert!(certificate.check().is_ok());
+    }
+}
@@ -0,0 +1,325 @@
+//! Assumptions about symbolic variables
+//!
+//! Some rewrites are only identities under conditions: √(x²) = x needs
+//! x ≥ 0, ln(eˣ) = x needs x real. `Assumptions` records what is known
+//! about each variable and decides simple facts about whole expressions,
+//! so `Simplify` can apply those rewrites and state what they rely on.
+
+use super::context::Value;
+use super::units::Quantity;
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use std::collections::HashMap;
+
+/// A fact about a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assumption {
+    /// x > 0
+    Positive,
+    /// x ≥ 0
+    Nonnegative,
+    /// x ∈ ℤ
+    Integer,
+    /// x ∈ ℝ
+    Real,
+    /// x ≠ 0
+    Nonzero,
+}
+
+impl Assumption {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assumption::Positive => "positive",
+            Assumption::Nonnegative => "nonnegative",
+            Assumption::Integer => "integer",
+            Assumption::Real => "real",
+            Assumption::Nonzero => "nonzero",
+        }
+    }
+
+    /// Facts that follow from this one (including itself)
+    pub fn implied(&self) -> &'static [Assumption] {
+        match self {
+            Assumption::Positive => &[
+                Assumption::Positive,
+                Assumption::Nonnegative,
+                Assumption::Nonzero,
+                Assumption::Real,
+            ],
+            Assumption::Nonnegative => &[Assumption::Nonnegative, Assumption::Real],
+            Assumption::Integer => &[Assumption::Integer, Assumption::Real],
+            Assumption::Real => &[Assumption::Real],
+            Assumption::Nonzero => &[Assumption::Nonzero],
+        }
+    }
+
+    /// State the condition for an expression, e.g. "x ≥ 0"
+    pub fn describe(&self, expr: &Expr) -> String {
+        match self {
+            Assumption::Positive => format!("{} > 0", expr),
+            Assumption::Nonnegative => format!("{} ≥ 0", expr),
+            Assumption::Integer => format!("{} ∈ ℤ", expr),
+            Assumption::Real => format!("{} ∈ ℝ", expr),
+            Assumption::Nonzero => format!("{} ≠ 0", expr),
+        }
+    }
+
+    /// Check a concrete value against this fact
+    pub fn holds_for(&self, value: &Value) -> bool {
+        let s = match value {
+            Value::Scalar(s) => *s,
+            Value::Circle(c) => {
+                if !c.imag().is_zero() {
+                    return *self == Assumption::Nonzero;
+                }
+                c.real()
+            }
+            // Absolute magnitude, so -5 °C is positive
+            Value::Quantity(q) => q.magnitude(),
+            Value::Bool(_) | Value::Array(_) => return false,
+        };
+
+        if s.is_undefined() {
+            return false;
+        }
+
+        match self {
+            Assumption::Positive => !s.is_zero() && !s.is_negative(),
+            Assumption::Nonnegative => !s.is_negative(),
+            Assumption::Integer => s.to_i64().is_some(),
+            Assumption::Real => true,
+            Assumption::Nonzero => !s.is_zero(),
+        }
+    }
+}
+
+/// Known facts about variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Assumptions {
+    facts: HashMap<String, Vec<Assumption>>,
+}
+
+impl Assumptions {
+    pub fn new() -> Self {
+        Assumptions {
+            facts: HashMap::new(),
+        }
+    }
+
+    /// Record a fact (and everything it implies) about a variable
+    pub fn assume(&mut self, var: impl Into<String>, assumption: Assumption) {
+        let facts = self.facts.entry(var.into()).or_default();
+        for implied in assumption.implied() {
+            if !facts.contains(implied) {
+                facts.push(*implied);
+            }
+        }
+    }
+
+    /// Builder-style `assume`
+    pub fn with(mut self, var: impl Into<String>, assumption: Assumption) -> Self {
+        self.assume(var, assumption);
+        self
+    }
+
+    /// Check whether a fact was recorded (or implied) for a variable
+    pub fn has(&self, var: &str, assumption: Assumption) -> bool {
+        self.facts
+            .get(var)
+            .is_some_and(|facts| facts.contains(&assumption))
+    }
+
+    /// All facts recorded for a variable
+    pub fn of(&self, var: &str) -> &[Assumption] {
+        self.facts.get(var).map(|facts| facts.as_slice()).unwrap_or(&[])
+    }
+
+    /// Facts inside a sum or product over `index`: the index is an
+    /// integer, and nothing known about its name outside applies
+    pub fn for_index(&self, index: &str) -> Assumptions {
+        let mut scoped = self.clone();
+        scoped.facts.remove(index);
+        scoped.with(index, Assumption::Integer)
+    }
+
+    /// Check whether any facts are recorded
+    pub fn is_empty(&self) -> bool {
+        self.facts.is_empty()
+    }
+
+    /// Check a bound value against the facts for its variable
+    pub fn check(&self, var: &str, value: &Value) -> Result<()> {
+        for fact in self.of(var) {
+            if !fact.holds_for(value) {
+                return Err(VeritasError::Contradiction(format!(
+                    "{} = {:?} violates assumption {}",
+                    var,
+                    value,
+                    fact.describe(&Expr::var(var))
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decide whether a fact holds for an expression
+    ///
+    /// Sound but incomplete: `false` means "not shown", not "false".
+    pub fn proves(&self, expr: &Expr, fact: Assumption) -> bool {
+        use Assumption::*;
+
+        match expr {
+            Expr::Number(n) => fact.holds_for(&Value::Scalar(*n)),
+            Expr::Complex(c) => fact.holds_for(&Value::Circle(*c)),
+            Expr::Quantity(n, unit) => Quantity::new(*n, unit)
+                .map_or(false, |q| fact.holds_for(&Value::Quantity(q))),
+            Expr::Variable(name) => self.has(name, fact),
+            Expr::Constant(name) => match name.as_str() {
+                "π" | "pi" | "e" => fact != Integer,
+                "i" => fact == Nonzero,
+                _ => false,
+            },
+
+            Expr::Add(a, b) => match fact {
+                Positive => {
+                    (self.proves(a, Positive) && self.proves(b, Nonnegative))
+                        || (self.proves(a, Nonnegative) && self.proves(b, Positive))
+                }
+                Nonnegative | Integer | Real => self.proves(a, fact) && self.proves(b, fact),
+                Nonzero => self.proves(expr, Positive),
+            },
+            Expr::Sub(a, b) => match fact {
+                Integer | Real => self.proves(a, fact) && self.proves(b, fact),
+                _ => false,
+            },
+            // Every fact here is closed under multiplication
+            Expr::Mul(a, b) => self.proves(a, fact) && self.proves(b, fact),
+            Expr::Div(a, b) => match fact {
+                Positive | Nonzero => self.proves(a, fact) && self.proves(b, fact),
+                Nonnegative => self.proves(a, Nonnegative) && self.proves(b, Positive),
+                Real => self.proves(a, Real) && self.proves(b, Real) && self.proves(b, Nonzero),
+                Integer => false,
+            },
+            Expr::Neg(a) => match fact {
+                Integer | Real | Nonzero => self.proves(a, fact),
+                _ => false,
+            },
+            Expr::Pow(base, exp) => {
+                let even = matches!(&**exp, Expr::Number(n)
+                    if n.to_i64().is_some_and(|k| k % 2 == 0));
+                match fact {
+                    Positive => {
+                        (self.proves(base, Positive) && self.proves(exp, Real))
+                            || (even && self.proves(base, Real) && self.proves(base, Nonzero))
+                    }
+                    Nonnegative => {
+                        (self.proves(base, Nonnegative) && self.proves(exp, Real))
+                            || (even && self.proves(base, Real))
+                    }
+                    Real => {
+                        (self.proves(base, Positive) && self.proves(exp, Real))
+                            || (self.proves(base, Real) && self.proves(exp, Integer))
+                    }
+                    Integer => self.proves(base, Integer)
+                        && self.proves(exp, Integer)
+                        && self.proves(exp, Nonnegative),
+                    Nonzero => self.proves(base, Nonzero),
+                }
+            }
+            Expr::Sqrt(a) => match fact {
+                Nonnegative | Real => self.proves(a, Nonnegative),
+                Positive | Nonzero => self.proves(a, Positive),
+                Integer => false,
+            },
+            Expr::Exp(a) => match fact {
+                Positive | Nonnegative | Real | Nonzero => self.proves(a, Real),
+                Integer => false,
+            },
+            Expr::Ln(a) => fact == Real && self.proves(a, Positive),
+            Expr::Sin(a) | Expr::Cos(a) => fact == Real && self.proves(a, Real),
+            // Parts, magnitude and argument of a complex number are real
+            Expr::Function(name, args) if args.len() == 1 => match name.as_str() {
+                "mag" => fact == Real || fact == Nonnegative,
+                "re" | "im" | "arg" => fact == Real,
+                _ => false,
+            },
+            Expr::Tan(_) | Expr::Function(_, _) => false,
+
+            // Whichever branch fires, the fact must hold for it
+            Expr::Piecewise(branches, otherwise) => {
+                branches.iter().all(|(_, value)| self.proves(value, fact))
+                    && self.proves(otherwise, fact)
+            }
+
+            // The index ranges over integers, whatever is known about its name
+            // outside; an empty sum is 0 and an empty product 1
+            Expr::Sum(index, _, _, body) | Expr::Product(index, _, _, body) => {
+                let scoped = self.for_index(index);
+                let holds_when_empty = match expr {
+                    Expr::Sum(..) => matches!(fact, Nonnegative | Real | Integer),
+                    _ => true,
+                };
+                holds_when_empty && scoped.proves(body, fact)
+            }
+
+            // Facts about an outer name of the same spelling do not apply inside
+            Expr::Let(name, value, body) => self.proves(&body.substitute(name, value), fact),
+
+            // Arrays and truth values are not numbers
+            Expr::Vector(_)
+            | Expr::Matrix(_)
+            | Expr::MatMul(..)
+            | Expr::Bool(_)
+            | Expr::Eq(..)
+            | Expr::Ne(..)
+            | Expr::Lt(..)
+            | Expr::Le(..)
+            | Expr::Gt(..)
+            | Expr::Ge(..)
+            | Expr::And(..)
+            | Expr::Or(..)
+            | Expr::Not(_)
+            | Expr::Implies(..) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_implications() {
+        let assumptions = Assumptions::new().with("x", Assumption::Positive);
+        assert!(assumptions.has("x", Assumption::Nonnegative));
+        assert!(assumptions.has("x", Assumption::Nonzero));
+        assert!(assumptions.has("x", Assumption::Real));
+        assert!(!assumptions.has("x", Assumption::Integer));
+        assert!(!assumptions.has("y", Assumption::Real));
+    }
+
+    #[test]
+    fn test_proves_expressions() {
+        let assumptions = Assumptions::new().with("x", Assumption::Real);
+        let x = Expr::var("x");
+
+        // x² ≥ 0 and eˣ > 0 for real x
+        assert!(assumptions.proves(&Expr::pow(x.clone(), Expr::number(2)), Assumption::Nonnegative));
+        assert!(assumptions.proves(&Expr::exp(x.clone()), Assumption::Positive));
+        // but x itself is not known to be nonnegative
+        assert!(!assumptions.proves(&x, Assumption::Nonnegative));
+        // x² + 1 > 0
+        let shifted = Expr::add(Expr::pow(x, Expr::number(2)), Expr::number(1));
+        assert!(assumptions.proves(&shifted, Assumption::Positive));
+    }
+
+    #[test]
+    fn test_check_value() {
+        let assumptions = Assumptions::new().with("n", Assumption::Integer);
+        assert!(assumptions.check("n", &Value::from(3)).is_ok());
+        assert!(matches!(
+            assumptions.check("n", &Value::from(2.5)),
+            Err(VeritasError::Contradiction(_))
+        ));
+    }
+}
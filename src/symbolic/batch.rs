@@ -0,0 +1,228 @@
+//! Batched evaluation over tensors
+//!
+//! Evaluates one expression for every row of a dataset: each variable is
+//! bound to a column of an `autograd::Tensor`, and the results come back
+//! as a tensor with one value per row. The work is done in F6E5 through
+//! the compiled `Program`, so each row gets exactly the value `Evaluate`
+//! would give, narrowed to the tensor's F4E4 at the end.
+//!
+//! A row whose value does not exist (an undefined input, 0/0, ln of a
+//! negative number) or does not fit F4E4 is marked in the batch's mask
+//! and stored as zero; it does not fail the batch. Errors that would hit
+//! every row, such as a variable with no column, still do.
+
+use super::linalg::shape_mismatch;
+use super::{Context, Evaluate, Expr, Program};
+use crate::autograd::{Shape, Tensor};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use spirix::ScalarF4E4;
+
+/// How the value of one row came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    /// Stored in the output tensor
+    Defined,
+    /// No value: an undefined input, or the expression is undefined there
+    Undefined,
+    /// Too small in magnitude for F4E4, or vanished
+    Vanished,
+    /// Too large in magnitude for F4E4, or exploded
+    Exploded,
+}
+
+/// Values of an expression over the rows of a dataset
+#[derive(Debug)]
+pub struct Batch {
+    /// One value per row, zero where the row is not `Defined`
+    pub values: Tensor,
+    /// What happened in each row
+    pub mask: Vec<Element>,
+}
+
+impl Batch {
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.mask.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask.is_empty()
+    }
+
+    /// Number of rows with a value
+    pub fn defined(&self) -> usize {
+        self.mask.iter().filter(|&&e| e == Element::Defined).count()
+    }
+
+    /// Whether every row has a value
+    pub fn is_complete(&self) -> bool {
+        self.defined() == self.len()
+    }
+}
+
+impl Program {
+    /// Evaluate once per row of `data`, binding `columns[j]` to column j
+    ///
+    /// `data` is a matrix with one column per name, or a vector when
+    /// there is a single name. Other variables are taken from `ctx`.
+    pub fn evaluate_batch(&self, columns: &[&str], data: &Tensor, ctx: &Context) -> Result<Batch> {
+        let width = columns.len();
+        let rows = match data.shape().dims() {
+            [rows] if width == 1 => *rows,
+            [rows, cols] if *cols == width && width > 0 => *rows,
+            _ => {
+                let expected = format!("a matrix with {} columns", width);
+                return Err(shape_mismatch(&expected, data.shape()));
+            }
+        };
+        let entries = data.as_scalars().ok_or_else(|| {
+            VeritasError::InvalidInput("Batch data must be real CPU scalars".to_string())
+        })?;
+
+        let mut ctx = ctx.clone();
+        let mut values = Vec::with_capacity(rows);
+        let mut mask = Vec::with_capacity(rows);
+        for row in entries.chunks(width) {
+            let mut bound = true;
+            for (name, &entry) in columns.iter().zip(row) {
+                match Scalar::from_f4e4(entry) {
+                    Some(value) => ctx.bind(*name, value),
+                    None => bound = false,
+                }
+            }
+
+            let (value, element) = if !bound {
+                (ScalarF4E4::ZERO, Element::Undefined)
+            } else {
+                match self.evaluate_scalar(&ctx) {
+                    Ok(value) => narrow(value),
+                    Err(e) if affects_every_row(&e) => return Err(e),
+                    Err(_) => (ScalarF4E4::ZERO, Element::Undefined),
+                }
+            };
+            values.push(value);
+            mask.push(element);
+        }
+
+        Ok(Batch {
+            values: Tensor::from_scalars(values, Shape::vector(rows))?,
+            mask,
+        })
+    }
+}
+
+impl Expr {
+    /// Compile, then evaluate once per row of `data`
+    pub fn evaluate_batch(&self, columns: &[&str], data: &Tensor, ctx: &Context) -> Result<Batch> {
+        self.compile().evaluate_batch(columns, data, ctx)
+    }
+}
+
+/// F4E4 value of a result, or why there is none
+fn narrow(value: Scalar) -> (ScalarF4E4, Element) {
+    if let Some(narrowed) = value.to_f4e4() {
+        return (narrowed, Element::Defined);
+    }
+    let element = if value.is_undefined() {
+        Element::Undefined
+    } else if value.is_vanished() || value.abs().inner() < Scalar::ONE.inner() {
+        Element::Vanished
+    } else {
+        Element::Exploded
+    };
+    (ScalarF4E4::ZERO, element)
+}
+
+/// Errors from the expression or the bindings rather than from the data
+fn affects_every_row(e: &VeritasError) -> bool {
+    matches!(
+        e,
+        VeritasError::VariableNotFound(_)
+            | VeritasError::ShapeMismatch { .. }
+            | VeritasError::DimensionMismatch { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f4e4(n: i32) -> ScalarF4E4 {
+        ScalarF4E4::from(n)
+    }
+
+    fn half() -> ScalarF4E4 {
+        ScalarF4E4::ONE / f4e4(2)
+    }
+
+    #[test]
+    fn test_batch_matches_rows() {
+        // x² + y over four rows
+        let expr = Expr::add(
+            Expr::pow(Expr::var("x"), Expr::number(2)),
+            Expr::var("y"),
+        );
+        let data = Tensor::from_scalars(
+            vec![f4e4(1), f4e4(2), f4e4(3), f4e4(4), half(), f4e4(0), f4e4(-2), f4e4(1)],
+            Shape::matrix(4, 2),
+        )
+        .unwrap();
+
+        let batch = expr.evaluate_batch(&["x", "y"], &data, &Context::new()).unwrap();
+        assert!(batch.is_complete());
+        let quarter = ScalarF4E4::ONE / f4e4(4);
+        assert_eq!(
+            batch.values.as_scalars().unwrap(),
+            &[f4e4(3), f4e4(13), quarter, f4e4(5)]
+        );
+    }
+
+    #[test]
+    fn test_batch_masks_rows() {
+        // 1/x is undefined at 0; 2^x leaves F4E4 for |x| = 40000
+        let data = Tensor::from_scalars(
+            vec![f4e4(2), f4e4(0), f4e4(40000), f4e4(-40000)],
+            Shape::vector(4),
+        )
+        .unwrap();
+        let ctx = Context::new();
+
+        let recip = Expr::div(Expr::number(1), Expr::var("x"));
+        let batch = recip.evaluate_batch(&["x"], &data, &ctx).unwrap();
+        assert_eq!(batch.mask[..2], [Element::Defined, Element::Undefined]);
+        assert_eq!(batch.values.as_scalars().unwrap()[..2], [half(), ScalarF4E4::ZERO]);
+
+        let power = Expr::pow(Expr::number(2), Expr::var("x"));
+        let batch = power.evaluate_batch(&["x"], &data, &ctx).unwrap();
+        assert_eq!(
+            batch.mask,
+            [Element::Defined, Element::Defined, Element::Exploded, Element::Vanished]
+        );
+        assert_eq!(batch.defined(), 2);
+    }
+
+    #[test]
+    fn test_batch_errors() {
+        let data = Tensor::from_scalars(vec![f4e4(1), f4e4(2)], Shape::vector(2)).unwrap();
+        let expr = Expr::add(Expr::var("x"), Expr::var("y"));
+
+        // y has no column and no binding
+        assert!(matches!(
+            expr.evaluate_batch(&["x"], &data, &Context::new()),
+            Err(VeritasError::VariableNotFound(_))
+        ));
+
+        // ...unless the context binds it
+        let mut ctx = Context::new();
+        ctx.bind("y", 10);
+        let batch = expr.evaluate_batch(&["x"], &data, &ctx).unwrap();
+        assert_eq!(batch.values.as_scalars().unwrap(), &[f4e4(11), f4e4(12)]);
+
+        // Two names need two columns
+        assert!(matches!(
+            expr.evaluate_batch(&["x", "y"], &data, &Context::new()),
+            Err(VeritasError::ShapeMismatch { .. })
+        ));
+    }
+}
@@ -8,9 +8,18 @@
 //! - Shift left/right
 //! - Rotate left/right
 //! - Count bits (popcount)
+//!
+//! Problems are expressions over bit vectors of any width from 1 to 64,
+//! so operations compose: `(a ^ b) << 2 & c`. Shift and rotate amounts
+//! wrap modulo the width, as Rust's `wrapping_shl` does for native widths.
 
 use crate::error::{Result, VeritasError};
 use spirix::ScalarF4E4;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Widest supported bit vector
+pub const MAX_WIDTH: u32 = 64;
 
 /// Bitwise operation types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -26,98 +35,293 @@ pub enum BitwiseOp {
     Popcount, // count set bits (unary)
 }
 
-/// Bitwise problem
-#[derive(Debug, Clone)]
+impl BitwiseOp {
+    /// Number of operands
+    pub fn arity(&self) -> usize {
+        match self {
+            BitwiseOp::Not | BitwiseOp::Popcount => 1,
+            _ => 2,
+        }
+    }
+
+    /// Upper-case name used in messages ("AND", "ROTL", ...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            BitwiseOp::And => "AND",
+            BitwiseOp::Or => "OR",
+            BitwiseOp::Xor => "XOR",
+            BitwiseOp::Not => "NOT",
+            BitwiseOp::Shl => "SHL",
+            BitwiseOp::Shr => "SHR",
+            BitwiseOp::Rotl => "ROTL",
+            BitwiseOp::Rotr => "ROTR",
+            BitwiseOp::Popcount => "POPCOUNT",
+        }
+    }
+
+    /// Apply to operands already reduced to `width` bits
+    ///
+    /// Unary operations ignore `b`.
+    pub fn apply(&self, width: u32, a: u64, b: u64) -> u64 {
+        let mask = mask(width);
+        // Amounts wrap like wrapping_shl; width ≤ 64 so the cast is exact
+        let bits = u64::from(width);
+        let amount = (b - b / bits * bits) as u32;
+        let value = match self {
+            BitwiseOp::And => a & b,
+            BitwiseOp::Or => a | b,
+            BitwiseOp::Xor => a ^ b,
+            BitwiseOp::Not => !a,
+            BitwiseOp::Shl => a.checked_shl(amount).unwrap_or(0),
+            BitwiseOp::Shr => a >> amount,
+            BitwiseOp::Rotl | BitwiseOp::Rotr if amount == 0 => a,
+            BitwiseOp::Rotl => (a << amount) | (a >> (width - amount)),
+            BitwiseOp::Rotr => (a >> amount) | (a << (width - amount)),
+            BitwiseOp::Popcount => a.count_ones() as u64,
+        };
+        value & mask
+    }
+
+    /// Binding strength of the infix and prefix forms; calls bind tightest
+    fn precedence(&self) -> u8 {
+        match self {
+            BitwiseOp::Or => 1,
+            BitwiseOp::Xor => 2,
+            BitwiseOp::And => 3,
+            BitwiseOp::Shl | BitwiseOp::Shr => 4,
+            BitwiseOp::Not => 5,
+            BitwiseOp::Rotl | BitwiseOp::Rotr | BitwiseOp::Popcount => 6,
+        }
+    }
+}
+
+/// All ones in the low `width` bits
+pub fn mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << width) - 1
+    }
+}
+
+/// Check that a width is between 1 and `MAX_WIDTH`
+pub fn check_width(width: u32) -> Result<()> {
+    if width == 0 || width > MAX_WIDTH {
+        return Err(VeritasError::InvalidInput(format!(
+            "Bit width must be between 1 and {}, got {}",
+            MAX_WIDTH, width
+        )));
+    }
+    Ok(())
+}
+
+/// Expression over fixed-width bit vectors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitExpr {
+    Const(u64),
+    Var(String),
+    /// Operation and its operands (arity is checked when evaluated)
+    Apply(BitwiseOp, Vec<BitExpr>),
+}
+
+impl BitExpr {
+    pub fn constant(value: u64) -> Self {
+        BitExpr::Const(value)
+    }
+
+    pub fn var(name: impl Into<String>) -> Self {
+        BitExpr::Var(name.into())
+    }
+
+    pub fn unary(op: BitwiseOp, a: BitExpr) -> Self {
+        BitExpr::Apply(op, vec![a])
+    }
+
+    pub fn binary(op: BitwiseOp, a: BitExpr, b: BitExpr) -> Self {
+        BitExpr::Apply(op, vec![a, b])
+    }
+
+    /// Parse an expression such as "(a ^ b) << 2 & 0x3C"
+    pub fn parse(text: &str) -> Result<BitExpr> {
+        let mut parser = Parser::new(text)?;
+        let expr = parser.expr()?;
+        parser.finish()?;
+        Ok(expr)
+    }
+
+    /// Variable names, sorted
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_variables(&mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn collect_variables(&self, names: &mut Vec<String>) {
+        match self {
+            BitExpr::Const(_) => {}
+            BitExpr::Var(name) => names.push(name.clone()),
+            BitExpr::Apply(_, args) => args.iter().for_each(|a| a.collect_variables(names)),
+        }
+    }
+
+    /// Replace bound variables with constants
+    pub fn substitute(&self, values: &HashMap<String, u64>) -> BitExpr {
+        match self {
+            BitExpr::Var(name) => values
+                .get(name)
+                .map_or_else(|| self.clone(), |v| BitExpr::Const(*v)),
+            BitExpr::Const(_) => self.clone(),
+            BitExpr::Apply(op, args) => {
+                BitExpr::Apply(*op, args.iter().map(|a| a.substitute(values)).collect())
+            }
+        }
+    }
+
+    /// Value at `width` bits; every variable must have been substituted
+    pub fn evaluate(&self, width: u32) -> Result<u64> {
+        match self {
+            BitExpr::Const(value) => {
+                if *value > mask(width) {
+                    return Err(VeritasError::InvalidInput(format!(
+                        "{} does not fit in {} bits",
+                        value, width
+                    )));
+                }
+                Ok(*value)
+            }
+            BitExpr::Var(name) => Err(VeritasError::VariableNotFound(name.clone())),
+            BitExpr::Apply(op, args) => {
+                check_arity(*op, args.len())?;
+                let a = args[0].evaluate(width)?;
+                let b = match args.get(1) {
+                    Some(b) => b.evaluate(width)?,
+                    None => 0,
+                };
+                Ok(op.apply(width, a, b))
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BitExpr::Apply(op, args) if args.len() == op.arity() => op.precedence(),
+            _ => 7,
+        }
+    }
+}
+
+/// Error unless an operation gets the right number of operands
+pub(crate) fn check_arity(op: BitwiseOp, count: usize) -> Result<()> {
+    if count != op.arity() {
+        let expected = if op.arity() == 1 { "one operand" } else { "two operands" };
+        return Err(VeritasError::InvalidInput(format!(
+            "{} requires {}",
+            op.name(),
+            expected
+        )));
+    }
+    Ok(())
+}
+
+/// Decimal constants, C-like operators with minimal parentheses
+impl fmt::Display for BitExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (op, args) = match self {
+            BitExpr::Const(value) => return write!(f, "{}", value),
+            BitExpr::Var(name) => return write!(f, "{}", name),
+            BitExpr::Apply(op, args) => (op, args),
+        };
+
+        let infix = match op {
+            BitwiseOp::And => "&",
+            BitwiseOp::Or => "|",
+            BitwiseOp::Xor => "^",
+            BitwiseOp::Shl => "<<",
+            BitwiseOp::Shr => ">>",
+            _ => "",
+        };
+        let level = self.precedence();
+        let operand = |f: &mut fmt::Formatter, a: &BitExpr, strict: bool| {
+            if a.precedence() < level || (strict && a.precedence() == level) {
+                write!(f, "({})", a)
+            } else {
+                write!(f, "{}", a)
+            }
+        };
+
+        match (op, args.as_slice()) {
+            (BitwiseOp::Not, [a]) => {
+                write!(f, "!")?;
+                operand(f, a, false)
+            }
+            (_, [a, b]) if !infix.is_empty() => {
+                // Left-associative: an equal-precedence right operand needs parentheses
+                operand(f, a, false)?;
+                write!(f, " {} ", infix)?;
+                operand(f, b, true)
+            }
+            _ => {
+                write!(f, "{}(", op.name().to_lowercase())?;
+                for (i, a) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", a)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Bitwise problem: an expression evaluated at a fixed width
+#[derive(Debug, Clone, PartialEq)]
 pub struct BitwiseProblem {
-    pub left: u8,
-    pub right: Option<u8>, // None for unary ops
-    pub op: BitwiseOp,
+    pub width: u32,
+    pub expr: BitExpr,
 }
 
 /// Verified bitwise result
 #[derive(Debug, Clone)]
 pub struct BitwiseResult {
-    pub answer: u8,
+    pub answer: u64,
     pub problem: BitwiseProblem,
     pub expr: String,
     pub dozenal: String, // Display in base 12
 }
 
 impl BitwiseProblem {
+    /// Single operation on bytes; `right` is None for unary ops
     pub fn new(left: u8, right: Option<u8>, op: BitwiseOp) -> Self {
-        Self { left, right, op }
+        let mut args = vec![BitExpr::Const(left as u64)];
+        args.extend(right.map(|r| BitExpr::Const(r as u64)));
+        Self {
+            width: 8,
+            expr: BitExpr::Apply(op, args),
+        }
+    }
+
+    /// Expression evaluated at `width` bits
+    pub fn with_width(width: u32, expr: BitExpr) -> Result<Self> {
+        check_width(width)?;
+        Ok(Self { width, expr })
+    }
+
+    /// Parse an expression and evaluate it at `width` bits
+    pub fn parse(width: u32, text: &str) -> Result<Self> {
+        Self::with_width(width, BitExpr::parse(text)?)
     }
 
     /// Compute verified answer using integer bitwise operations
     pub fn solve(&self) -> Result<BitwiseResult> {
-        let answer = match self.op {
-            BitwiseOp::And => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "AND requires two operands".to_string()
-                ))?;
-                self.left & right
-            }
-            BitwiseOp::Or => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "OR requires two operands".to_string()
-                ))?;
-                self.left | right
-            }
-            BitwiseOp::Xor => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "XOR requires two operands".to_string()
-                ))?;
-                self.left ^ right
-            }
-            BitwiseOp::Not => !self.left,
-            BitwiseOp::Shl => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "SHL requires two operands".to_string()
-                ))?;
-                self.left.wrapping_shl(right as u32)
-            }
-            BitwiseOp::Shr => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "SHR requires two operands".to_string()
-                ))?;
-                self.left.wrapping_shr(right as u32)
-            }
-            BitwiseOp::Rotl => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "ROTL requires two operands".to_string()
-                ))?;
-                self.left.rotate_left(right as u32)
-            }
-            BitwiseOp::Rotr => {
-                let right = self.right.ok_or(VeritasError::InvalidInput(
-                    "ROTR requires two operands".to_string()
-                ))?;
-                self.left.rotate_right(right as u32)
-            }
-            BitwiseOp::Popcount => self.left.count_ones() as u8,
-        };
-
-        let (op_str, expr) = match self.op {
-            BitwiseOp::And => ("&", format!("{} & {}", self.left, self.right.unwrap())),
-            BitwiseOp::Or => ("|", format!("{} | {}", self.left, self.right.unwrap())),
-            BitwiseOp::Xor => ("^", format!("{} ^ {}", self.left, self.right.unwrap())),
-            BitwiseOp::Not => ("!", format!("!{}", self.left)),
-            BitwiseOp::Shl => ("<<", format!("{} << {}", self.left, self.right.unwrap())),
-            BitwiseOp::Shr => (">>", format!("{} >> {}", self.left, self.right.unwrap())),
-            BitwiseOp::Rotl => ("rotl", format!("rotl({}, {})", self.left, self.right.unwrap())),
-            BitwiseOp::Rotr => ("rotr", format!("rotr({}, {})", self.left, self.right.unwrap())),
-            BitwiseOp::Popcount => ("popcount", format!("popcount({})", self.left)),
-        };
+        check_width(self.width)?;
+        let answer = self.expr.evaluate(self.width)?;
+        let expr = self.expr.to_string();
 
         // Format in dozenal (base 12)
-        let dozenal = match self.op {
-            BitwiseOp::Not | BitwiseOp::Popcount => {
-                format!("{} = {} (base C)", expr, to_dozenal(answer))
-            }
-            _ => {
-                format!("{} = {} (base C)", expr, to_dozenal(answer))
-            }
-        };
+        let dozenal = format!("{} = {} (base C)", expr, to_dozenal(answer));
 
         Ok(BitwiseResult {
             answer,
@@ -128,16 +332,39 @@ impl BitwiseProblem {
     }
 
     /// Convert to Spirix scalars for neural training
-    pub fn to_scalars(&self) -> (ScalarF4E4, Option<ScalarF4E4>, ScalarF4E4) {
-        let left_scalar = ScalarF4E4::from(self.left);
-        let right_scalar = self.right.map(|r| ScalarF4E4::from(r));
+    ///
+    /// Only single operations on byte constants have this encoding.
+    pub fn to_scalars(&self) -> Result<(ScalarF4E4, Option<ScalarF4E4>, ScalarF4E4)> {
+        let (op, operands) = match &self.expr {
+            BitExpr::Apply(op, args) if self.width <= 8 => {
+                let operands: Option<Vec<u8>> = args
+                    .iter()
+                    .map(|a| match a {
+                        BitExpr::Const(v) => u8::try_from(*v).ok(),
+                        _ => None,
+                    })
+                    .collect();
+                (*op, operands)
+            }
+            _ => (BitwiseOp::And, None),
+        };
+        let operands = operands.ok_or_else(|| {
+            VeritasError::InvalidInput(format!(
+                "{} is not a single operation on bytes",
+                self.expr
+            ))
+        })?;
+        check_arity(op, operands.len())?;
+
+        let left_scalar = ScalarF4E4::from(operands[0]);
+        let right_scalar = operands.get(1).map(|r| ScalarF4E4::from(*r));
 
         // Encode operation as scalar
         let half = ScalarF4E4::ONE / ScalarF4E4::from(2u8);
         let eighth = ScalarF4E4::ONE / ScalarF4E4::from(8u8);
         let quarter = ScalarF4E4::ONE / ScalarF4E4::from(4u8);
 
-        let op_scalar = match self.op {
+        let op_scalar = match op {
             BitwiseOp::And => ScalarF4E4::ZERO,
             BitwiseOp::Or => eighth,
             BitwiseOp::Xor => quarter,
@@ -149,12 +376,12 @@ impl BitwiseProblem {
             BitwiseOp::Popcount => ScalarF4E4::ONE,
         };
 
-        (left_scalar, right_scalar, op_scalar)
+        Ok((left_scalar, right_scalar, op_scalar))
     }
 }
 
-/// Convert u8 to dozenal (base 12) string
-fn to_dozenal(n: u8) -> String {
+/// Convert to dozenal (base 12) string
+fn to_dozenal(n: u64) -> String {
     if n == 0 {
         return "0".to_string();
     }
@@ -163,7 +390,7 @@ fn to_dozenal(n: u8) -> String {
     let mut value = n;
 
     while value > 0 {
-        let digit = value % 12;
+        let digit = (value % 12) as u8;
         let ch = match digit {
             0..=9 => (b'0' + digit) as char,
             10 => 'A', // Ten in dozenal
@@ -177,6 +404,233 @@ fn to_dozenal(n: u8) -> String {
     result
 }
 
+/// Relation between two sides of a constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitRelation {
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u64),
+    Name(String),
+    Op(BitwiseOp),
+    Relation(BitRelation),
+    /// `and`, `&&` or `,` between constraints
+    Conjunction,
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+            let lower = literal.to_ascii_lowercase();
+            let (digits, radix) = match lower.get(..2) {
+                Some("0x") => (&lower[2..], 16),
+                Some("0b") => (&lower[2..], 2),
+                Some("0o") => (&lower[2..], 8),
+                _ => (lower.as_str(), 10),
+            };
+            let value = u64::from_str_radix(digits, radix).map_err(|_| {
+                VeritasError::InvalidInput(format!("Invalid number '{}'", literal))
+            })?;
+            tokens.push(Token::Number(value));
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(if word == "and" {
+                Token::Conjunction
+            } else {
+                Token::Name(word)
+            });
+            continue;
+        }
+
+        let (token, len) = match (c, next) {
+            ('<', Some('<')) => (Token::Op(BitwiseOp::Shl), 2),
+            ('>', Some('>')) => (Token::Op(BitwiseOp::Shr), 2),
+            ('=', Some('=')) => (Token::Relation(BitRelation::Eq), 2),
+            ('!', Some('=')) => (Token::Relation(BitRelation::Ne), 2),
+            ('&', Some('&')) => (Token::Conjunction, 2),
+            ('=', _) => (Token::Relation(BitRelation::Eq), 1),
+            ('&', _) => (Token::Op(BitwiseOp::And), 1),
+            ('|', _) => (Token::Op(BitwiseOp::Or), 1),
+            ('^', _) => (Token::Op(BitwiseOp::Xor), 1),
+            ('!', _) | ('~', _) => (Token::Op(BitwiseOp::Not), 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            (',', _) => (Token::Comma, 1),
+            _ => {
+                return Err(VeritasError::InvalidInput(format!(
+                    "Unexpected character '{}' in bitwise expression",
+                    c
+                )))
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent over C-like precedence: | < ^ < & < shifts < ! < calls
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(text: &str) -> Result<Self> {
+        Ok(Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(VeritasError::InvalidInput(format!(
+                "Expected {:?}, found {:?}",
+                token,
+                self.peek()
+            )))
+        }
+    }
+
+    /// Error unless every token was consumed
+    pub(crate) fn finish(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(extra) => Err(VeritasError::InvalidInput(format!(
+                "Unexpected {:?} after expression",
+                extra
+            ))),
+        }
+    }
+
+    /// Constraints `lhs == rhs` or `lhs != rhs`, joined by `and`, `&&` or `,`
+    pub(crate) fn constraints(&mut self) -> Result<Vec<(BitExpr, BitRelation, BitExpr)>> {
+        let mut constraints = Vec::new();
+        loop {
+            let left = self.expr()?;
+            let relation = match self.peek() {
+                Some(Token::Relation(r)) => *r,
+                other => {
+                    return Err(VeritasError::InvalidInput(format!(
+                        "Expected == or !=, found {:?}",
+                        other
+                    )))
+                }
+            };
+            self.pos += 1;
+            constraints.push((left, relation, self.expr()?));
+
+            if !self.eat(&Token::Conjunction) && !self.eat(&Token::Comma) {
+                return Ok(constraints);
+            }
+        }
+    }
+
+    pub(crate) fn expr(&mut self) -> Result<BitExpr> {
+        self.binary(1)
+    }
+
+    /// Left-associative operators binding at least as tightly as `level`
+    fn binary(&mut self, level: u8) -> Result<BitExpr> {
+        if level > 4 {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if op.arity() != 2 || op.precedence() != level {
+                break;
+            }
+            self.pos += 1;
+            left = BitExpr::binary(op, left, self.binary(level + 1)?);
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<BitExpr> {
+        if self.eat(&Token::Op(BitwiseOp::Not)) {
+            return Ok(BitExpr::unary(BitwiseOp::Not, self.unary()?));
+        }
+
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(BitExpr::Const(value)),
+            Some(Token::Open) => {
+                let inner = self.expr()?;
+                self.expect(Token::Close)?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) => {
+                let op = match name.as_str() {
+                    "rotl" => BitwiseOp::Rotl,
+                    "rotr" => BitwiseOp::Rotr,
+                    "popcount" => BitwiseOp::Popcount,
+                    _ => return Ok(BitExpr::Var(name)),
+                };
+                self.expect(Token::Open)?;
+                let mut args = vec![self.expr()?];
+                while self.eat(&Token::Comma) {
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::Close)?;
+                check_arity(op, args.len())?;
+                Ok(BitExpr::Apply(op, args))
+            }
+            other => Err(VeritasError::InvalidInput(format!(
+                "Expected a number, variable or '(', found {:?}",
+                other
+            ))),
+        }
+    }
+}
+
 /// Bitwise problem generator for training
 pub struct BitwiseGenerator {
     max_value: u8,
@@ -287,6 +741,43 @@ mod tests {
         assert_eq!(to_dozenal(255), "193"); // Max u8 in dozenal
     }
 
+    #[test]
+    fn test_composition_and_width() {
+        let prob = BitwiseProblem::parse(16, "(0xF0F0 ^ 0x0FF0) << 2 & 0xFFFF").unwrap();
+        let result = prob.solve().unwrap();
+        assert_eq!(result.answer, (0xFF00u64 << 2) & 0xFFFF);
+        assert_eq!(result.expr, "(61680 ^ 4080) << 2 & 65535");
+
+        // Rotation wraps within the width, not within a native integer
+        let rot = BitwiseProblem::parse(5, "rotl(0b10011, 2)").unwrap();
+        assert_eq!(rot.solve().unwrap().answer, 0b01110);
+        let wide = BitwiseProblem::parse(64, "!0").unwrap();
+        assert_eq!(wide.solve().unwrap().answer, u64::MAX);
+    }
+
+    #[test]
+    fn test_width_and_operand_errors() {
+        assert!(BitwiseProblem::parse(0, "1").is_err());
+        assert!(BitwiseProblem::parse(65, "1").is_err());
+        // 256 does not fit in a byte
+        assert!(BitwiseProblem::parse(8, "256 & 1").unwrap().solve().is_err());
+        // Unbound variables cannot be evaluated
+        assert!(matches!(
+            BitwiseProblem::parse(8, "x & 1").unwrap().solve(),
+            Err(VeritasError::VariableNotFound(_))
+        ));
+        assert!(BitwiseProblem::new(1, None, BitwiseOp::And).solve().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in ["a & (b | c)", "(a ^ b) << 2 & c", "a ^ (b ^ c)", "!(a | b) >> rotr(c, 3)"] {
+            let expr = BitExpr::parse(text).unwrap();
+            assert_eq!(BitExpr::parse(&expr.to_string()).unwrap(), expr);
+        }
+        assert!(BitExpr::parse("a - 1").is_err());
+    }
+
     #[test]
     fn test_generator() {
         let gen = BitwiseGenerator::new(255);
@@ -0,0 +1,264 @@
+//! Resource budgets for evaluation and simplification
+//!
+//! `check_complexity` only bounds the depth of the tree it is given.
+//! Untrusted input can still ask for a lot of work from a small tree:
+//! nested sums over large ranges, functions whose bodies call earlier
+//! functions several times, or powers whose values have more digits than
+//! anything downstream can write out. An `EvalBudget` caps the work one
+//! call may do, and the call fails with `VeritasError::BudgetExceeded`,
+//! reporting what was used, as soon as any limit is passed.
+
+use super::context::Value;
+pub use crate::error::{Resource, Usage};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::time::{Duration, Instant};
+
+/// 10 to this power is beyond any F6E5 value
+const MAX_SCALAR_DIGITS: u64 = 1 << 32;
+
+/// Limits on the work one evaluation or simplification may do
+///
+/// `Default` gives limits suited to untrusted input; `unlimited` is what
+/// `Evaluate::evaluate` and `Simplify::simplify` use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalBudget {
+    /// Most operations: nodes evaluated or simplified, or instructions run
+    pub max_operations: u64,
+    /// Deepest nesting of subexpressions, function bodies and range bodies
+    pub max_depth: usize,
+    /// Most decimal digits before the point in any value produced
+    pub max_digits: u64,
+    /// Wall-clock time allowed from the start of the call
+    pub deadline: Option<Duration>,
+}
+
+impl EvalBudget {
+    /// No limits
+    pub fn unlimited() -> Self {
+        EvalBudget {
+            max_operations: u64::MAX,
+            max_depth: usize::MAX,
+            max_digits: u64::MAX,
+            deadline: None,
+        }
+    }
+}
+
+impl Default for EvalBudget {
+    fn default() -> Self {
+        EvalBudget {
+            max_operations: 1_000_000,
+            max_depth: 1000,
+            max_digits: 1000,
+            deadline: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Work charged so far against a budget
+#[derive(Debug, Clone)]
+pub(crate) struct Meter {
+    budget: EvalBudget,
+    /// 10^max_digits, or None when no scalar can reach it
+    limit: Option<Scalar>,
+    /// Only read when there is a deadline to check
+    start: Option<Instant>,
+    operations: u64,
+    depth: usize,
+    deepest: usize,
+    /// Largest magnitude produced
+    largest: Scalar,
+}
+
+impl Meter {
+    pub(crate) fn new(budget: &EvalBudget) -> Self {
+        Meter {
+            budget: *budget,
+            limit: power_of_ten(budget.max_digits),
+            start: budget.deadline.map(|_| Instant::now()),
+            operations: 0,
+            depth: 0,
+            deepest: 0,
+            largest: Scalar::ZERO,
+        }
+    }
+
+    /// Charge one operation
+    pub(crate) fn tick(&mut self) -> Result<()> {
+        self.operations += 1;
+        if self.operations > self.budget.max_operations {
+            return Err(self.exceeded(Resource::Operations));
+        }
+        // The clock costs more than most operations, so it is read every 1024
+        if self.operations & 1023 == 0 {
+            self.check_time()?;
+        }
+        Ok(())
+    }
+
+    /// Charge one operation one level deeper than the current one
+    pub(crate) fn enter(&mut self) -> Result<()> {
+        self.tick()?;
+        self.depth += 1;
+        self.deepest = self.deepest.max(self.depth);
+        if self.depth > self.budget.max_depth {
+            return Err(self.exceeded(Resource::Depth));
+        }
+        Ok(())
+    }
+
+    /// Return from the level of the matching `enter`
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Charge the digits of a value produced
+    pub(crate) fn check(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Scalar(n) => self.check_scalar(*n),
+            Value::Circle(c) => self.check_scalar(c.magnitude()),
+            Value::Quantity(q) => self.check_scalar(q.magnitude()),
+            Value::Array(a) => a.entries().iter().try_for_each(|&n| self.check_scalar(n)),
+            Value::Bool(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_scalar(&mut self, n: Scalar) -> Result<()> {
+        let magnitude = n.abs();
+        if magnitude.inner() <= self.largest.inner() {
+            return Ok(());
+        }
+        self.largest = magnitude;
+        match self.limit {
+            Some(limit) if magnitude.inner() >= limit.inner() => {
+                Err(self.exceeded(Resource::Digits))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_time(&self) -> Result<()> {
+        match (self.start, self.budget.deadline) {
+            (Some(start), Some(deadline)) if start.elapsed() > deadline => {
+                Err(self.exceeded(Resource::Time))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn usage(&self) -> Usage {
+        Usage {
+            operations: self.operations,
+            depth: self.deepest,
+            digits: digits(self.largest),
+            elapsed: self.start.map(|start| start.elapsed()),
+        }
+    }
+
+    fn exceeded(&self, resource: Resource) -> VeritasError {
+        VeritasError::BudgetExceeded {
+            resource,
+            used: self.usage(),
+        }
+    }
+}
+
+impl Default for Meter {
+    fn default() -> Self {
+        Meter::new(&EvalBudget::unlimited())
+    }
+}
+
+/// 10^d, or None past what a scalar can hold
+///
+/// Built by squaring, so small powers are exact.
+fn power_of_ten(d: u64) -> Option<Scalar> {
+    if d >= MAX_SCALAR_DIGITS {
+        return None;
+    }
+    let (mut power, mut square, mut rest) = (Scalar::ONE, Scalar::from(10), d);
+    while rest > 0 {
+        if rest & 1 == 1 {
+            power = power.checked_mul(square).ok()?;
+        }
+        rest >>= 1;
+        if rest > 0 {
+            square = square.checked_mul(square).ok()?;
+        }
+    }
+    Some(power)
+}
+
+/// Decimal digits before the point: the least d with magnitude < 10^d
+fn digits(magnitude: Scalar) -> u64 {
+    let below = |d: u64| match power_of_ten(d) {
+        Some(power) => magnitude.inner() < power.inner(),
+        None => true,
+    };
+    if below(0) {
+        return 0;
+    }
+    // below(lo) fails and below(hi) holds
+    let (mut lo, mut hi) = (0, MAX_SCALAR_DIGITS);
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if below(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits(Scalar::ZERO), 0);
+        assert_eq!(digits(Scalar::ONE / Scalar::from(3)), 0);
+        assert_eq!(digits(Scalar::ONE), 1);
+        assert_eq!(digits(Scalar::from(999)), 3);
+        assert_eq!(digits(Scalar::from(1000)), 4);
+    }
+
+    #[test]
+    fn test_meter_limits() {
+        let budget = EvalBudget {
+            max_operations: 3,
+            max_depth: 2,
+            max_digits: 2,
+            deadline: None,
+        };
+
+        let mut meter = Meter::new(&budget);
+        meter.enter().unwrap();
+        meter.enter().unwrap();
+        let error = meter.enter().unwrap_err();
+        assert!(matches!(
+            error,
+            VeritasError::BudgetExceeded {
+                resource: Resource::Depth,
+                used: Usage { operations: 3, depth: 3, .. },
+            }
+        ));
+        assert!(matches!(
+            meter.tick(),
+            Err(VeritasError::BudgetExceeded { resource: Resource::Operations, .. })
+        ));
+
+        let mut meter = Meter::new(&budget);
+        meter.check(&Value::Scalar(Scalar::from(-99))).unwrap();
+        let error = meter.check(&Value::Scalar(Scalar::from(100))).unwrap_err();
+        assert!(matches!(
+            error,
+            VeritasError::BudgetExceeded {
+                resource: Resource::Digits,
+                used: Usage { digits: 3, elapsed: None, .. },
+            }
+        ));
+    }
+}
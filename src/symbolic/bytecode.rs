@@ -0,0 +1,758 @@
+//! Compiled expressions
+//!
+//! `Expr::compile` flattens an expression tree into a register program
+//! for evaluating the same formula under many bindings. Closed subtrees
+//! are folded to constants when the program is built, equal subtrees
+//! share one register, and variables become slots loaded from the
+//! `Context` at run time.
+//!
+//! Every instruction goes through the same operations as `Evaluate`, in
+//! the same order, so a program gives bit-identical values and the same
+//! errors. Connectives, piecewise branches and user-defined functions
+//! keep their laziness through jumps: an operand that the tree would not
+//! evaluate is not executed, and a register computed only on one path is
+//! never reused off it. Under `evaluate_within` every instruction run is
+//! one operation of the budget, and closed subtrees are computed by
+//! instructions instead of folded, so the budget pays for them as it
+//! would on the tree.
+
+use super::budget::{EvalBudget, Meter};
+use super::context::{is_builtin, Value};
+use super::eval::{
+    add, as_bool, as_circle, as_scalar, bound, builtin, check_base, div, equal, exp, matmul,
+    mul, neg, order, pow, range, sqrt, sub, tan, variable,
+};
+use super::linalg::Array;
+use super::{Context, Evaluate, Expr};
+use crate::error::Result;
+use crate::numeric::{Circle, Scalar};
+use std::collections::HashMap;
+
+type Reg = usize;
+
+/// Expression compiled for repeated evaluation
+#[derive(Debug, Clone)]
+pub struct Program {
+    code: Vec<Op>,
+    /// Initial register file, with the folded constants in place
+    registers: Vec<Value>,
+    /// Variable names, indexed by slot
+    slots: Vec<String>,
+    /// Subtrees kept for operations that need the expression itself
+    origins: Vec<Expr>,
+    output: Reg,
+    /// The same program without folded constants, if any were folded
+    metered: Option<Box<Program>>,
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Load { dst: Reg, slot: usize },
+    Unary { dst: Reg, op: Unary, src: Reg },
+    Binary { dst: Reg, op: Binary, a: Reg, b: Reg },
+    /// Division keeps its quotient for the 0/0 limit
+    Div { dst: Reg, a: Reg, b: Reg, origin: usize },
+    /// Fail early where the tree converts an operand before the next one
+    Check { src: Reg, check: Check },
+    Vector { dst: Reg, entries: Vec<Reg> },
+    Matrix { dst: Reg, rows: Vec<Vec<Reg>> },
+    Builtin { dst: Reg, name: String, args: Vec<Reg> },
+    /// A definition in the context shadows the built-in of that name
+    Defined { dst: Reg, name: String, origin: usize, skip: usize },
+    /// Evaluate the original subtree
+    Tree { dst: Reg, origin: usize },
+    /// Truth value of the left operand decides a connective on its own
+    ShortCircuit { dst: Reg, src: Reg, when: bool, value: bool, skip: usize },
+    Truth { dst: Reg, src: Reg },
+    /// Skip a piecewise branch whose condition fails
+    Branch { cond: Reg, skip: usize },
+    Move { dst: Reg, src: Reg },
+    Jump { target: usize },
+    Range { dst: Reg, product: bool, index: String, lower: Reg, upper: Reg, body: Box<Program> },
+    /// Run the body with the name bound to a register in a new scope
+    Let { dst: Reg, name: String, value: Reg, body: Box<Program> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Unary {
+    Neg,
+    Sqrt,
+    Ln,
+    Exp,
+    Sin,
+    Cos,
+    Tan,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Binary {
+    Add,
+    Sub,
+    Mul,
+    Pow,
+    MatMul,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Check {
+    /// Real number (vector and matrix entries)
+    Scalar,
+    /// Something that can be raised to a power
+    Base,
+    /// Integer bound of a range
+    Bound,
+}
+
+/// What a register holds, for sharing equal subtrees
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Load(usize),
+    Unary(Unary, Reg),
+    Binary(Binary, Reg, Reg),
+    Div(Reg, Reg),
+    Check(Check, Reg),
+    Vector(Vec<Reg>),
+    Matrix(Vec<Vec<Reg>>),
+}
+
+impl Op {
+    /// Register holding a newly computed number, if any
+    fn output(&self) -> Option<Reg> {
+        match self {
+            Op::Load { dst, .. }
+            | Op::Unary { dst, .. }
+            | Op::Binary { dst, .. }
+            | Op::Div { dst, .. }
+            | Op::Vector { dst, .. }
+            | Op::Matrix { dst, .. }
+            | Op::Builtin { dst, .. }
+            | Op::Range { dst, .. }
+            | Op::Let { dst, .. } => Some(*dst),
+            // Defined and Tree are checked as the tree evaluates them
+            _ => None,
+        }
+    }
+}
+
+impl Unary {
+    fn apply(self, a: Value) -> Result<Value> {
+        match self {
+            Unary::Neg => neg(a),
+            Unary::Sqrt => sqrt(a),
+            Unary::Ln => Ok(Value::Scalar(as_scalar(a)?.ln()?)),
+            Unary::Exp => exp(a),
+            Unary::Sin => Ok(Value::Scalar(as_scalar(a)?.sin()?)),
+            Unary::Cos => Ok(Value::Scalar(as_scalar(a)?.cos()?)),
+            Unary::Tan => Ok(Value::Scalar(tan(as_scalar(a)?)?)),
+            Unary::Not => Ok(Value::Bool(!as_bool(a)?)),
+        }
+    }
+}
+
+impl Binary {
+    fn apply(self, a: Value, b: Value) -> Result<Value> {
+        match self {
+            Binary::Add => add(a, b),
+            Binary::Sub => sub(a, b),
+            Binary::Mul => mul(a, b),
+            Binary::Pow => pow(a, as_scalar(b)?),
+            Binary::MatMul => matmul(a, b),
+            Binary::Eq => Ok(Value::Bool(equal(&a, &b)?)),
+            Binary::Ne => Ok(Value::Bool(!equal(&a, &b)?)),
+            Binary::Lt => Ok(Value::Bool(order(a, b)?.is_lt())),
+            Binary::Le => Ok(Value::Bool(order(a, b)?.is_le())),
+            Binary::Gt => Ok(Value::Bool(order(a, b)?.is_gt())),
+            Binary::Ge => Ok(Value::Bool(order(a, b)?.is_ge())),
+        }
+    }
+}
+
+impl Check {
+    fn apply(self, value: &Value) -> Result<()> {
+        match self {
+            Check::Scalar => as_scalar(value.clone()).map(|_| ()),
+            Check::Base => check_base(value),
+            Check::Bound => bound(value.clone()).map(|_| ()),
+        }
+    }
+}
+
+impl Expr {
+    /// Compile for repeated evaluation
+    pub fn compile(&self) -> Program {
+        let mut compiler = Compiler::new(true);
+        let mut program = compiler.program(self);
+        // Folding was free; a budget is charged by a copy that computes
+        if compiler.folded {
+            program.metered = Some(Box::new(Compiler::new(false).program(self)));
+        }
+        program
+    }
+}
+
+impl Program {
+    /// Number of instructions
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    /// Number of registers, constants included
+    pub fn registers(&self) -> usize {
+        self.registers.len()
+    }
+
+    /// Variables the program loads, in slot order
+    pub fn variables(&self) -> &[String] {
+        &self.slots
+    }
+
+    fn run(&self, ctx: &Context, meter: &mut Meter) -> Result<Value> {
+        let mut regs = self.registers.clone();
+        let mut pc = 0;
+
+        while let Some(op) = self.code.get(pc) {
+            meter.tick()?;
+            pc += 1;
+            match op {
+                Op::Load { dst, slot } => {
+                    let name = &self.slots[*slot];
+                    regs[*dst] = variable(name, ctx, meter)?;
+                }
+                Op::Unary { dst, op, src } => regs[*dst] = op.apply(regs[*src].clone())?,
+                Op::Binary { dst, op, a, b } => {
+                    regs[*dst] = op.apply(regs[*a].clone(), regs[*b].clone())?;
+                }
+                Op::Div { dst, a, b, origin } => {
+                    let quotient = &self.origins[*origin];
+                    let (a, b) = (regs[*a].clone(), regs[*b].clone());
+                    regs[*dst] = div(a, b, || quotient, ctx, meter)?;
+                }
+                Op::Check { src, check } => check.apply(&regs[*src])?,
+                Op::Vector { dst, entries } => {
+                    let entries: Result<Vec<Scalar>> =
+                        entries.iter().map(|&r| as_scalar(regs[r].clone())).collect();
+                    regs[*dst] = Value::Array(Array::vector(entries?));
+                }
+                Op::Matrix { dst, rows } => {
+                    let rows: Result<Vec<Vec<Scalar>>> = rows
+                        .iter()
+                        .map(|row| row.iter().map(|&r| as_scalar(regs[r].clone())).collect())
+                        .collect();
+                    regs[*dst] = Value::Array(Array::from_rows(rows?)?);
+                }
+                Op::Builtin { dst, name, args } => {
+                    let args: Vec<Value> = args.iter().map(|&r| regs[r].clone()).collect();
+                    regs[*dst] = builtin(name, &args)?;
+                }
+                Op::Defined { dst, name, origin, skip } => {
+                    if ctx.function(name).is_some() {
+                        regs[*dst] = self.origins[*origin].eval(ctx, meter)?;
+                        pc = *skip;
+                    }
+                }
+                Op::Tree { dst, origin } => regs[*dst] = self.origins[*origin].eval(ctx, meter)?,
+                Op::ShortCircuit { dst, src, when, value, skip } => {
+                    if as_bool(regs[*src].clone())? == *when {
+                        regs[*dst] = Value::Bool(*value);
+                        pc = *skip;
+                    }
+                }
+                Op::Truth { dst, src } => regs[*dst] = Value::Bool(as_bool(regs[*src].clone())?),
+                Op::Branch { cond, skip } => {
+                    if !as_bool(regs[*cond].clone())? {
+                        pc = *skip;
+                    }
+                }
+                Op::Move { dst, src } => regs[*dst] = regs[*src].clone(),
+                Op::Jump { target } => pc = *target,
+                Op::Range { dst, product, index, lower, upper, body } => {
+                    let (lo, hi) = (bound(regs[*lower].clone())?, bound(regs[*upper].clone())?);
+                    let mut inner = ctx.clone();
+                    inner.push_scope();
+                    let empty = if *product { Scalar::ONE } else { Scalar::ZERO };
+                    let mut total = Value::Scalar(empty);
+                    for k in range(lo, hi)? {
+                        inner.bind(index.clone(), Scalar::from_i64(k));
+                        meter.enter()?;
+                        let term = body.run(&inner, meter)?;
+                        meter.leave();
+                        total = if *product { mul(total, term)? } else { add(total, term)? };
+                    }
+                    regs[*dst] = total;
+                }
+                Op::Let { dst, name, value, body } => {
+                    let mut inner = ctx.clone();
+                    inner.push_scope();
+                    inner.bind(name.clone(), regs[*value].clone());
+                    meter.enter()?;
+                    regs[*dst] = body.run(&inner, meter)?;
+                    meter.leave();
+                }
+            }
+            if let Some(dst) = op.output() {
+                meter.check(&regs[dst])?;
+            }
+        }
+
+        Ok(regs.swap_remove(self.output))
+    }
+}
+
+impl Evaluate for Program {
+    fn evaluate(&self, ctx: &Context) -> Result<Value> {
+        self.run(ctx, &mut Meter::default())
+    }
+
+    fn evaluate_within(&self, ctx: &Context, budget: &EvalBudget) -> Result<Value> {
+        let program = self.metered.as_deref().unwrap_or(self);
+        program.run(ctx, &mut Meter::new(budget))
+    }
+
+    fn evaluate_scalar(&self, ctx: &Context) -> Result<Scalar> {
+        as_scalar(self.evaluate(ctx)?)
+    }
+
+    fn evaluate_circle(&self, ctx: &Context) -> Result<Circle> {
+        as_circle(self.evaluate(ctx)?)
+    }
+
+    fn evaluate_bool(&self, ctx: &Context) -> Result<bool> {
+        as_bool(self.evaluate(ctx)?)
+    }
+}
+
+#[derive(Default)]
+struct Compiler {
+    /// Whether closed subtrees become constants
+    fold: bool,
+    /// Whether a closed subtree with an operation in it was folded
+    folded: bool,
+    code: Vec<Op>,
+    registers: Vec<Value>,
+    slots: Vec<String>,
+    origins: Vec<Expr>,
+    /// Registers already holding a value on every path from here
+    memo: HashMap<Key, Reg>,
+    /// Registers holding folded constants
+    constants: Vec<Reg>,
+}
+
+impl Compiler {
+    fn new(fold: bool) -> Self {
+        Compiler {
+            fold,
+            ..Compiler::default()
+        }
+    }
+
+    /// Program for `expr`, folding as this compiler does
+    fn program(&mut self, expr: &Expr) -> Program {
+        let mut compiler = Compiler::new(self.fold);
+        let output = compiler.compile(expr);
+        self.folded |= compiler.folded;
+        Program {
+            code: compiler.code,
+            registers: compiler.registers,
+            slots: compiler.slots,
+            origins: compiler.origins,
+            output,
+            metered: None,
+        }
+    }
+
+    fn compile(&mut self, expr: &Expr) -> Reg {
+        let leaf = expr.children().is_empty();
+        if is_closed(expr) && (self.fold || leaf) {
+            self.folded |= !leaf;
+            return match expr.evaluate(&Context::new()) {
+                Ok(value) => self.constant(value),
+                // Left to fail at run time, as the tree would
+                Err(_) => self.tree(expr),
+            };
+        }
+
+        match expr {
+            Expr::Variable(name) => {
+                let slot = match self.slots.iter().position(|s| s == name) {
+                    Some(slot) => slot,
+                    None => {
+                        self.slots.push(name.clone());
+                        self.slots.len() - 1
+                    }
+                };
+                self.emit(Key::Load(slot), |dst| Op::Load { dst, slot })
+            }
+
+            Expr::Add(a, b) => self.binary(Binary::Add, a, b),
+            Expr::Sub(a, b) => self.binary(Binary::Sub, a, b),
+            Expr::Mul(a, b) => self.binary(Binary::Mul, a, b),
+            Expr::MatMul(a, b) => self.binary(Binary::MatMul, a, b),
+            Expr::Eq(a, b) => self.binary(Binary::Eq, a, b),
+            Expr::Ne(a, b) => self.binary(Binary::Ne, a, b),
+            Expr::Lt(a, b) => self.binary(Binary::Lt, a, b),
+            Expr::Le(a, b) => self.binary(Binary::Le, a, b),
+            Expr::Gt(a, b) => self.binary(Binary::Gt, a, b),
+            Expr::Ge(a, b) => self.binary(Binary::Ge, a, b),
+
+            Expr::Div(a, b) => {
+                let (a, b) = (self.compile(a), self.compile(b));
+                if let Some(&reg) = self.memo.get(&Key::Div(a, b)) {
+                    return reg;
+                }
+                let origin = self.origin(expr);
+                self.emit(Key::Div(a, b), |dst| Op::Div { dst, a, b, origin })
+            }
+
+            Expr::Pow(base, exponent) => {
+                let base = self.compile(base);
+                self.check(Check::Base, base);
+                let exponent = self.compile(exponent);
+                self.emit(Key::Binary(Binary::Pow, base, exponent), |dst| Op::Binary {
+                    dst,
+                    op: Binary::Pow,
+                    a: base,
+                    b: exponent,
+                })
+            }
+
+            Expr::Neg(a) => self.unary(Unary::Neg, a),
+            Expr::Sqrt(a) => self.unary(Unary::Sqrt, a),
+            Expr::Ln(a) => self.unary(Unary::Ln, a),
+            Expr::Exp(a) => self.unary(Unary::Exp, a),
+            Expr::Sin(a) => self.unary(Unary::Sin, a),
+            Expr::Cos(a) => self.unary(Unary::Cos, a),
+            Expr::Tan(a) => self.unary(Unary::Tan, a),
+            Expr::Not(a) => self.unary(Unary::Not, a),
+
+            Expr::Vector(entries) => {
+                let entries = self.entries(entries);
+                self.emit(Key::Vector(entries.clone()), |dst| Op::Vector { dst, entries })
+            }
+            Expr::Matrix(rows) => {
+                let rows: Vec<Vec<Reg>> = rows.iter().map(|row| self.entries(row)).collect();
+                self.emit(Key::Matrix(rows.clone()), |dst| Op::Matrix { dst, rows })
+            }
+
+            // The arguments only run when the context does not define the name
+            Expr::Function(name, args) if is_builtin(name) => {
+                let (dst, origin) = (self.register(), self.origin(expr));
+                let guard = self.code.len();
+                self.code.push(Op::Defined {
+                    dst,
+                    name: name.clone(),
+                    origin,
+                    skip: 0,
+                });
+                let outer = self.memo.clone();
+                let args = args.iter().map(|arg| self.compile(arg)).collect();
+                self.code.push(Op::Builtin {
+                    dst,
+                    name: name.clone(),
+                    args,
+                });
+                self.memo = outer;
+                self.patch(guard);
+                dst
+            }
+
+            Expr::And(a, b) => self.connective(a, b, false, false),
+            Expr::Or(a, b) => self.connective(a, b, true, true),
+            Expr::Implies(a, b) => self.connective(a, b, false, true),
+
+            Expr::Piecewise(branches, otherwise) => {
+                let dst = self.register();
+                let outer = self.memo.clone();
+                let mut exits = Vec::with_capacity(branches.len());
+                for (condition, value) in branches {
+                    let cond = self.compile(condition);
+                    let branch = self.code.len();
+                    self.code.push(Op::Branch { cond, skip: 0 });
+                    let before = self.memo.clone();
+                    let src = self.compile(value);
+                    self.code.push(Op::Move { dst, src });
+                    exits.push(self.code.len());
+                    self.code.push(Op::Jump { target: 0 });
+                    self.memo = before;
+                    self.patch(branch);
+                }
+                let src = self.compile(otherwise);
+                self.code.push(Op::Move { dst, src });
+                for exit in exits {
+                    self.patch(exit);
+                }
+                self.memo = outer;
+                dst
+            }
+
+            // The body runs as its own program, once per index value
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                let lower = self.compile(lower);
+                self.check(Check::Bound, lower);
+                let upper = self.compile(upper);
+                let dst = self.register();
+                self.code.push(Op::Range {
+                    dst,
+                    product: matches!(expr, Expr::Product(..)),
+                    index: index.clone(),
+                    lower,
+                    upper,
+                    body: Box::new(self.program(body)),
+                });
+                dst
+            }
+
+            // So does a let body, once, with the value computed here
+            Expr::Let(name, value, body) => {
+                let value = self.compile(value);
+                let dst = self.register();
+                self.code.push(Op::Let {
+                    dst,
+                    name: name.clone(),
+                    value,
+                    body: Box::new(self.program(body)),
+                });
+                dst
+            }
+
+            // Defined or unknown functions, and leaves that failed to fold
+            Expr::Function(..)
+            | Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Quantity(..)
+            | Expr::Constant(_)
+            | Expr::Bool(_) => self.tree(expr),
+        }
+    }
+
+    fn unary(&mut self, op: Unary, a: &Expr) -> Reg {
+        let src = self.compile(a);
+        self.emit(Key::Unary(op, src), |dst| Op::Unary { dst, op, src })
+    }
+
+    fn binary(&mut self, op: Binary, a: &Expr, b: &Expr) -> Reg {
+        let (a, b) = (self.compile(a), self.compile(b));
+        self.emit(Key::Binary(op, a, b), |dst| Op::Binary { dst, op, a, b })
+    }
+
+    /// Entries of a vector or matrix row, each checked as it is computed
+    fn entries(&mut self, entries: &[Expr]) -> Vec<Reg> {
+        entries
+            .iter()
+            .map(|entry| {
+                let reg = self.compile(entry);
+                self.check(Check::Scalar, reg);
+                reg
+            })
+            .collect()
+    }
+
+    /// a ∧ b, a ∨ b, a ⇒ b: `value` without evaluating b when a is `when`
+    fn connective(&mut self, a: &Expr, b: &Expr, when: bool, value: bool) -> Reg {
+        let src = self.compile(a);
+        let dst = self.register();
+        let guard = self.code.len();
+        self.code.push(Op::ShortCircuit {
+            dst,
+            src,
+            when,
+            value,
+            skip: 0,
+        });
+        let outer = self.memo.clone();
+        let src = self.compile(b);
+        self.code.push(Op::Truth { dst, src });
+        self.memo = outer;
+        self.patch(guard);
+        dst
+    }
+
+    fn check(&mut self, check: Check, src: Reg) {
+        self.emit(Key::Check(check, src), |_| Op::Check { src, check });
+    }
+
+    /// Emit an instruction unless a register already holds its value
+    fn emit(&mut self, key: Key, op: impl FnOnce(Reg) -> Op) -> Reg {
+        if let Some(&reg) = self.memo.get(&key) {
+            return reg;
+        }
+        let dst = self.register();
+        self.code.push(op(dst));
+        self.memo.insert(key, dst);
+        dst
+    }
+
+    fn tree(&mut self, expr: &Expr) -> Reg {
+        let (dst, origin) = (self.register(), self.origin(expr));
+        self.code.push(Op::Tree { dst, origin });
+        dst
+    }
+
+    fn constant(&mut self, value: Value) -> Reg {
+        if let Some(&reg) = self.constants.iter().find(|&&r| self.registers[r] == value) {
+            return reg;
+        }
+        self.registers.push(value);
+        let reg = self.registers.len() - 1;
+        self.constants.push(reg);
+        reg
+    }
+
+    fn register(&mut self) -> Reg {
+        // Overwritten before it is read
+        self.registers.push(Value::Bool(false));
+        self.registers.len() - 1
+    }
+
+    fn origin(&mut self, expr: &Expr) -> usize {
+        self.origins.push(expr.clone());
+        self.origins.len() - 1
+    }
+
+    /// Point a forward jump at the next instruction
+    fn patch(&mut self, at: usize) {
+        let next = self.code.len();
+        match &mut self.code[at] {
+            Op::Defined { skip, .. } | Op::ShortCircuit { skip, .. } | Op::Branch { skip, .. } => {
+                *skip = next;
+            }
+            Op::Jump { target } => *target = next,
+            _ => unreachable!("only jumps are patched"),
+        }
+    }
+}
+
+/// No variables, calls or ranges: the value cannot depend on the context
+fn is_closed(expr: &Expr) -> bool {
+    match expr {
+        Expr::Variable(_) | Expr::Function(..) | Expr::Sum(..) | Expr::Product(..) => false,
+        _ => expr.children().into_iter().all(is_closed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    /// Same value, or the same error, as walking the tree
+    fn assert_agrees(expr: &Expr, ctx: &Context) {
+        let program = expr.compile();
+        match (program.evaluate(ctx), expr.evaluate(ctx)) {
+            (Ok(compiled), Ok(tree)) => assert_eq!(compiled, tree, "{}", expr),
+            (compiled, tree) => {
+                assert_eq!(format!("{:?}", compiled), format!("{:?}", tree), "{}", expr)
+            }
+        }
+    }
+
+    #[test]
+    fn test_shares_subexpressions() {
+        // (x + 1)·(x + 1) + sin(x + 1): load, add, mul, sin, add
+        let shifted = Expr::add(x(), Expr::number(1));
+        let expr = Expr::add(
+            Expr::mul(shifted.clone(), shifted.clone()),
+            Expr::sin(shifted),
+        );
+        let program = expr.compile();
+        assert_eq!(program.len(), 5);
+        assert_eq!(program.variables(), &["x".to_string()]);
+
+        let mut ctx = Context::new();
+        ctx.bind("x", 2);
+        assert_eq!(program.evaluate(&ctx).unwrap(), expr.evaluate(&ctx).unwrap());
+    }
+
+    #[test]
+    fn test_folds_constants() {
+        // 2π + √2·x keeps only the load, the product and the sum
+        let expr = Expr::add(
+            Expr::mul(Expr::number(2), crate::symbolic::constants::pi()),
+            Expr::mul(Expr::sqrt(Expr::number(2)), x()),
+        );
+        assert_eq!(expr.compile().len(), 3);
+
+        // 1/0 is not folded; it fails when run, like the tree
+        let expr = Expr::add(x(), Expr::div(Expr::number(1), Expr::number(0)));
+        let mut ctx = Context::new();
+        ctx.bind("x", 1);
+        assert_agrees(&expr, &ctx);
+    }
+
+    #[test]
+    fn test_matches_tree() {
+        let sinc = Expr::div(Expr::sin(x()), x());
+        let guarded = Expr::and(
+            Expr::not_equals(x(), Expr::number(0)),
+            Expr::greater(Expr::div(Expr::number(1), x()), Expr::number(2)),
+        );
+        let clamp = Expr::clamp(Expr::mul(x(), x()), Expr::number(0), Expr::number(3));
+        let log = Expr::piecewise(
+            vec![(Expr::greater(x(), Expr::number(0)), Expr::ln(x()))],
+            Expr::var("unbound"),
+        );
+        let root = Expr::pow(Expr::sqrt(x()), Expr::number(2));
+        let squares = Expr::sum(
+            "k",
+            Expr::number(1),
+            x(),
+            Expr::pow(Expr::var("k"), Expr::number(2)),
+        );
+        let vector = Expr::dot(
+            Expr::vector(vec![x(), Expr::number(1)]),
+            Expr::vector(vec![Expr::number(2), Expr::sin(x())]),
+        );
+        let shadow = Expr::let_in(
+            "k",
+            Expr::add(x(), Expr::number(1)),
+            Expr::mul(Expr::var("k"), Expr::sqrt(Expr::var("k"))),
+        );
+        let expressions = [sinc, guarded, clamp, log, root, squares, vector, shadow];
+
+        let mut ctx = Context::new();
+        ctx.bind("k", 100);
+        for value in [-2.0, -0.5, 0.0, 0.25, 1.0, 3.0, 2.5] {
+            ctx.bind("x", value);
+            for expr in &expressions {
+                assert_agrees(expr, &ctx);
+            }
+        }
+    }
+
+    #[test]
+    fn test_defined_functions() {
+        // f(x) = x² + 1 runs through its definition
+        let mut ctx = Context::new();
+        ctx.define(
+            "f",
+            vec!["x".to_string()],
+            Expr::add(Expr::pow(x(), Expr::number(2)), Expr::number(1)),
+        )
+        .unwrap();
+        ctx.bind("y", 3);
+        let expr = Expr::mul(
+            Expr::Function("f".to_string(), vec![Expr::var("y")]),
+            Expr::var("y"),
+        );
+        assert_eq!(
+            expr.compile().evaluate_scalar(&ctx).unwrap(),
+            Scalar::from(30)
+        );
+
+        // Undefined functions fail the same way
+        assert_agrees(&Expr::Function("g".to_string(), vec![x()]), &ctx);
+    }
+}
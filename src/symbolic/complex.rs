@@ -0,0 +1,228 @@
+//! Complex-domain functions and exact values
+//!
+//! The built-in functions conj, re, im, mag and arg work on any number
+//! without a definition in `Context`. The helpers here back the complex
+//! simplification rules: folding literals into one `Expr::Complex`, and
+//! the exact cosine and sine at multiples of π/2 that Euler's formula
+//! needs to turn e^(iπ) into -1.
+
+use super::context::Value;
+use super::{constants, Expr};
+use crate::error::{Result, VeritasError};
+use crate::numeric::{Circle, Scalar};
+
+/// Functions available without a definition
+pub const BUILTINS: [&str; 5] = ["conj", "re", "im", "mag", "arg"];
+
+/// Check whether `name` is a built-in function
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.contains(&name)
+}
+
+impl Expr {
+    /// Complex conjugate: conj(a + bi) = a - bi
+    pub fn conj(z: Expr) -> Self {
+        Expr::Function("conj".to_string(), vec![z])
+    }
+
+    /// Real part
+    pub fn re(z: Expr) -> Self {
+        Expr::Function("re".to_string(), vec![z])
+    }
+
+    /// Imaginary part
+    pub fn im(z: Expr) -> Self {
+        Expr::Function("im".to_string(), vec![z])
+    }
+
+    /// Magnitude: |a + bi| = √(a² + b²)
+    pub fn mag(z: Expr) -> Self {
+        Expr::Function("mag".to_string(), vec![z])
+    }
+
+    /// Argument, in (-π, π]
+    pub fn arg(z: Expr) -> Self {
+        Expr::Function("arg".to_string(), vec![z])
+    }
+}
+
+/// Evaluate a built-in function
+pub fn apply_builtin(name: &str, args: &[Value]) -> Result<Value> {
+    let z = match args {
+        [Value::Scalar(s)] => Circle::from(*s),
+        [Value::Circle(c)] => *c,
+        [_] => {
+            return Err(VeritasError::SimplificationError(format!(
+                "{} needs a number",
+                name
+            )))
+        }
+        _ => {
+            return Err(VeritasError::InvalidInput(format!(
+                "{} takes 1 argument, got {}",
+                name,
+                args.len()
+            )))
+        }
+    };
+
+    Ok(match name {
+        "conj" => value_of(z.conjugate()),
+        "re" => Value::Scalar(z.real()),
+        "im" => Value::Scalar(z.imag()),
+        "mag" => Value::Scalar(z.magnitude()),
+        "arg" => Value::Scalar(atan2(z.imag(), z.real())?),
+        _ => {
+            return Err(VeritasError::SimplificationError(format!(
+                "Unknown function: {}",
+                name
+            )))
+        }
+    })
+}
+
+/// Real values stay scalars
+fn value_of(z: Circle) -> Value {
+    if z.imag().is_zero() {
+        Value::Scalar(z.real())
+    } else {
+        Value::Circle(z)
+    }
+}
+
+/// Angle of the point (x, y), in (-π, π]
+///
+/// Spirix has no inverse tangent, so this bisects on the sign of
+/// x·sin θ - y·cos θ = r·sin(θ - φ), which is negative below the angle φ
+/// and positive above it within the half-turn that contains it.
+pub fn atan2(y: Scalar, x: Scalar) -> Result<Scalar> {
+    if y.is_zero() && x.is_zero() {
+        return Err(VeritasError::UndefinedOperation(
+            "Argument of zero".to_string(),
+        ));
+    }
+
+    let (mut lo, mut hi) = if y.is_negative() {
+        (-Scalar::PI, Scalar::ZERO)
+    } else {
+        (Scalar::ZERO, Scalar::PI)
+    };
+    for _ in 0..96 {
+        let mid = lo.checked_add(hi)?.checked_div(Scalar::TWO)?;
+        let g = x
+            .checked_mul(mid.sin()?)?
+            .checked_sub(y.checked_mul(mid.cos()?)?)?;
+        if g.is_negative() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo.checked_add(hi)?.checked_div(Scalar::TWO)
+}
+
+/// Numeric value of a literal: a number, a complex number or i
+pub(crate) fn literal(expr: &Expr) -> Option<Circle> {
+    match expr {
+        Expr::Number(n) => Some(Circle::from(*n)),
+        Expr::Complex(c) => Some(*c),
+        _ if *expr == constants::i() => Some(Circle::I),
+        _ => None,
+    }
+}
+
+/// Check that both operands are literals and at least one is not real
+pub(crate) fn is_complex_pair(a: &Expr, b: &Expr) -> bool {
+    let non_real = |e: &Expr| !matches!(e, Expr::Number(_));
+    literal(a).is_some() && literal(b).is_some() && (non_real(a) || non_real(b))
+}
+
+/// Expression for a complex value (a number when the imaginary part is 0)
+pub(crate) fn complex_literal(z: Circle) -> Expr {
+    if z.imag().is_zero() {
+        Expr::Number(z.real())
+    } else {
+        Expr::Complex(z)
+    }
+}
+
+/// θ as a count of quarter turns, if θ is written as kπ/2
+pub(crate) fn quarter_turns(theta: &Expr) -> Option<i64> {
+    let integer = |e: &Expr| match e {
+        Expr::Number(n) => n.to_i64(),
+        _ => None,
+    };
+
+    match theta {
+        Expr::Number(n) if n.is_zero() => Some(0),
+        _ if *theta == constants::pi() => Some(2),
+        Expr::Mul(a, b) if **b == constants::pi() => integer(a)?.checked_mul(2),
+        Expr::Mul(a, b) if **a == constants::pi() => integer(b)?.checked_mul(2),
+        Expr::Div(a, b) if integer(b) == Some(2) => {
+            let k = quarter_turns(a)?;
+            (k / 2 * 2 == k).then_some(k / 2)
+        }
+        Expr::Neg(a) => quarter_turns(a)?.checked_neg(),
+        _ => None,
+    }
+}
+
+/// (cos θ, sin θ) at θ = kπ/2
+pub(crate) fn unit_at(k: i64) -> (i64, i64) {
+    match k - k.div_euclid(4) * 4 {
+        0 => (1, 0),
+        1 => (0, 1),
+        2 => (-1, 0),
+        _ => (0, -1),
+    }
+}
+
+/// θ in iθ or θi
+pub(crate) fn times_i(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Mul(a, b) if **a == constants::i() => Some(b),
+        Expr::Mul(a, b) if **b == constants::i() => Some(a),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_turns() {
+        let pi = constants::pi();
+        assert_eq!(quarter_turns(&pi), Some(2));
+        assert_eq!(quarter_turns(&Expr::div(pi.clone(), Expr::number(2))), Some(1));
+        assert_eq!(quarter_turns(&Expr::mul(Expr::number(3), pi.clone())), Some(6));
+        assert_eq!(quarter_turns(&Expr::div(pi, Expr::number(3))), None);
+        assert_eq!(unit_at(-1), (0, -1));
+    }
+
+    #[test]
+    fn test_builtins() {
+        let z = Value::Circle(Circle::from_parts(Scalar::from(3), Scalar::from(4)));
+        assert_eq!(apply_builtin("mag", &[z.clone()]).unwrap(), Value::Scalar(Scalar::from(5)));
+        assert_eq!(apply_builtin("im", &[z.clone()]).unwrap(), Value::Scalar(Scalar::from(4)));
+        assert_eq!(
+            apply_builtin("conj", &[z]).unwrap(),
+            Value::Circle(Circle::from_parts(Scalar::from(3), Scalar::from(-4)))
+        );
+    }
+
+    #[test]
+    fn test_atan2_axes() {
+        // arg(-1) = π, arg(i) = π/2, to within a few ulps
+        let close = |a: Scalar, b: Scalar| {
+            let diff = a.checked_sub(b).unwrap().abs();
+            diff.compare(&Scalar::from(1e-12)).unwrap() == std::cmp::Ordering::Less
+        };
+        assert!(close(atan2(Scalar::ZERO, Scalar::from(-1)).unwrap(), Scalar::PI));
+        assert!(close(
+            atan2(Scalar::ONE, Scalar::ZERO).unwrap(),
+            Scalar::PI / Scalar::TWO
+        ));
+        assert!(atan2(Scalar::ZERO, Scalar::ZERO).is_err());
+    }
+}
@@ -1,14 +1,34 @@
 //! Variable context for expression evaluation
+//!
+//! Names are bound in a stack of scopes: `push_scope` opens a scope whose
+//! bindings shadow the ones outside it until `pop_scope` drops them. A
+//! name may be bound to a value or to an expression; an expression is
+//! evaluated wherever the name is used, but in the scope it was bound in,
+//! so a later inner binding of one of its names does not change it.
+//! Binding an expression that would refer back to its own name, directly
+//! or through other bindings, is an error.
 
+use super::assumptions::{Assumption, Assumptions};
+use super::complex;
+use super::linalg::{self, shape_mismatch, Array};
+use super::units::{mismatch, Dimension, Quantity};
+use super::Expr;
 use crate::error::{Result, VeritasError};
 use crate::numeric::{Circle, Scalar};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 /// Value that can be bound to a variable
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Scalar(Scalar),
     Circle(Circle),
+    /// Result of a comparison or logical expression
+    Bool(bool),
+    /// Magnitude with a physical dimension
+    Quantity(Quantity),
+    /// Vector or matrix of real numbers
+    Array(Array),
 }
 
 impl From<Scalar> for Value {
@@ -35,34 +55,319 @@ impl From<f64> for Value {
     }
 }
 
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<Array> for Value {
+    fn from(a: Array) -> Self {
+        Value::Array(a)
+    }
+}
+
+/// Dimensionless quantities are plain numbers (1 kg / 500 g = 2)
+impl From<Quantity> for Value {
+    fn from(q: Quantity) -> Self {
+        if q.dimension().is_dimensionless() {
+            Value::Scalar(q.magnitude())
+        } else {
+            Value::Quantity(q)
+        }
+    }
+}
+
+/// What a name is bound to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    Value(Value),
+    /// Evaluated where the name is used, in the scope it was bound in
+    Expr(Expr),
+}
+
+/// Check whether `name` is a built-in function (complex or linear algebra)
+pub(crate) fn is_builtin(name: &str) -> bool {
+    complex::is_builtin(name) || linalg::is_builtin(name)
+}
+
+/// User-defined function: f(params) = body
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+impl FunctionDef {
+    /// Body with the parameters replaced by `args`
+    ///
+    /// All parameters are replaced at once, so an argument that mentions
+    /// another parameter's name (f(y, 1) for f(x, y) = x + y) is not
+    /// captured by it.
+    pub fn apply(&self, name: &str, args: &[Expr]) -> Result<Expr> {
+        if args.len() != self.params.len() {
+            return Err(VeritasError::InvalidInput(format!(
+                "{} takes {} arguments, got {}",
+                name,
+                self.params.len(),
+                args.len()
+            )));
+        }
+
+        let replacements: HashMap<String, Expr> = self
+            .params
+            .iter()
+            .cloned()
+            .zip(args.iter().cloned())
+            .collect();
+        Ok(self.body.substitute_all(&replacements))
+    }
+}
+
 /// Context for expression evaluation
 ///
-/// Maps variable names to numeric values
+/// Maps variable names to values or expressions in nested scopes, records
+/// what is assumed about variables (bound or not), and holds user-defined
+/// functions
 #[derive(Debug, Clone)]
 pub struct Context {
-    bindings: HashMap<String, Value>,
+    /// Innermost last; the outermost scope is never popped
+    scopes: Vec<HashMap<String, Binding>>,
+    assumptions: Assumptions,
+    functions: HashMap<String, FunctionDef>,
 }
 
 impl Context {
     /// Create empty context
     pub fn new() -> Self {
         Context {
-            bindings: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            assumptions: Assumptions::new(),
+            functions: HashMap::new(),
         }
     }
 
-    /// Bind a variable to a value
+    /// Open a scope; names bound from now on shadow the outer ones
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Drop the innermost scope and everything bound in it
+    pub fn pop_scope(&mut self) -> Result<()> {
+        if self.scopes.len() == 1 {
+            return Err(VeritasError::InvalidInput(
+                "No scope to close: only the outermost is open".to_string(),
+            ));
+        }
+        self.scopes.pop();
+        Ok(())
+    }
+
+    /// Number of open scopes, the outermost included
+    pub fn scope_depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Define a function f(params) = body
+    ///
+    /// The body may only call functions defined before it, and names
+    /// cannot be redefined, so definitions can never recurse. Fails if the
+    /// body closes a loop through an expression binding that calls it.
+    pub fn define(
+        &mut self,
+        name: impl Into<String>,
+        params: Vec<String>,
+        body: Expr,
+    ) -> Result<()> {
+        let name = name.into();
+        if self.functions.contains_key(&name) || is_builtin(&name) {
+            return Err(VeritasError::InvalidInput(format!(
+                "Function {} is already defined",
+                name
+            )));
+        }
+        if let Some(undefined) = body
+            .functions()
+            .into_iter()
+            .find(|f| !self.functions.contains_key(f) && !is_builtin(f))
+        {
+            return Err(VeritasError::InvalidInput(format!(
+                "Function {} calls {}, which is not defined yet",
+                name, undefined
+            )));
+        }
+
+        // An expression bound before f was defined may call it, and f may
+        // use that binding's name: a = f(1), then f(t) = t + a
+        self.functions.insert(name.clone(), FunctionDef { params, body });
+        if let Some(binding) = self.circular_binding() {
+            self.functions.remove(&name);
+            return Err(VeritasError::InvalidInput(format!(
+                "Function {} would make the binding {} circular",
+                name, binding
+            )));
+        }
+        Ok(())
+    }
+
+    /// Look up a user-defined function
+    pub fn function(&self, name: &str) -> Option<&FunctionDef> {
+        self.functions.get(name)
+    }
+
+    /// Record a fact about a variable
+    pub fn assume(&mut self, name: impl Into<String>, assumption: Assumption) {
+        self.assumptions.assume(name, assumption);
+    }
+
+    /// Facts recorded about variables
+    pub fn assumptions(&self) -> &Assumptions {
+        &self.assumptions
+    }
+
+    /// Bind a variable to a value in the innermost scope
     pub fn bind(&mut self, name: impl Into<String>, value: impl Into<Value>) {
-        self.bindings.insert(name.into(), value.into());
+        self.innermost().insert(name.into(), Binding::Value(value.into()));
     }
 
-    /// Get value of a variable
-    pub fn get(&self, name: &str) -> Result<&Value> {
-        self.bindings
-            .get(name)
+    /// Bind a variable to an expression in the innermost scope
+    ///
+    /// Fails if the expression refers back to `name`, directly (x = x + 1)
+    /// or through other bindings and function bodies (a = b, then b = a).
+    pub fn bind_expr(&mut self, name: impl Into<String>, expr: Expr) -> Result<()> {
+        let name = name.into();
+        let scope = self.scopes.len() - 1;
+        let mut seen = HashSet::new();
+        if self.refers_to(&name, scope, &expr, scope, &mut seen) {
+            return Err(VeritasError::InvalidInput(format!(
+                "Binding {} = {} is circular",
+                name, expr
+            )));
+        }
+        self.innermost().insert(name, Binding::Expr(expr));
+        Ok(())
+    }
+
+    fn innermost(&mut self) -> &mut HashMap<String, Binding> {
+        self.scopes.last_mut().expect("the outermost scope is never popped")
+    }
+
+    /// Whether `expr`, seen from `scope`, reaches the binding of `name` in
+    /// scope `home`
+    fn refers_to(
+        &self,
+        name: &str,
+        home: usize,
+        expr: &Expr,
+        scope: usize,
+        seen: &mut HashSet<(String, usize)>,
+    ) -> bool {
+        self.free_names(expr).into_iter().any(|var| {
+            if var == name && scope == home {
+                return true;
+            }
+            match self.resolve_within(&var, scope) {
+                Some((found, Binding::Expr(bound))) if seen.insert((var.clone(), found)) => {
+                    self.refers_to(name, home, bound, found, seen)
+                }
+                _ => false,
+            }
+        })
+    }
+
+    /// Free variables of `expr` and of the bodies of the functions it calls
+    fn free_names(&self, expr: &Expr) -> Vec<String> {
+        let mut names = expr.variables();
+        let mut calls = expr.functions();
+        let mut visited = HashSet::new();
+        while let Some(f) = calls.pop() {
+            if !visited.insert(f.clone()) {
+                continue;
+            }
+            if let Some(def) = self.functions.get(&f) {
+                let params = &def.params;
+                names.extend(def.body.variables().into_iter().filter(|v| !params.contains(v)));
+                calls.extend(def.body.functions());
+            }
+        }
+        names
+    }
+
+    /// Some expression binding, "name = expr", that reaches itself
+    fn circular_binding(&self) -> Option<String> {
+        for (scope, bindings) in self.scopes.iter().enumerate() {
+            for (var, binding) in bindings {
+                if let Binding::Expr(bound) = binding {
+                    if self.refers_to(var, scope, bound, scope, &mut HashSet::new()) {
+                        return Some(format!("{} = {}", var, bound));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Innermost binding of `name` in scopes 0..=scope, and its scope
+    fn resolve_within(&self, name: &str, scope: usize) -> Option<(usize, &Binding)> {
+        self.scopes[..=scope]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, bindings)| bindings.get(name).map(|binding| (i, binding)))
+    }
+
+    /// Innermost binding of a variable, and the scope it is in
+    pub fn resolve(&self, name: &str) -> Result<(usize, &Binding)> {
+        self.resolve_within(name, self.scopes.len() - 1)
             .ok_or_else(|| VeritasError::VariableNotFound(name.to_string()))
     }
 
+    /// This context seen from `scope`: the scopes inside it closed
+    pub(crate) fn enclosing(&self, scope: usize) -> Cow<'_, Context> {
+        if scope + 1 == self.scopes.len() {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(Context {
+            scopes: self.scopes[..=scope].to_vec(),
+            assumptions: self.assumptions.clone(),
+            functions: self.functions.clone(),
+        })
+    }
+
+    /// Replace every bound variable with what it is bound to
+    ///
+    /// Bound expressions are expanded in the scope they were bound in, so
+    /// with a = 3x + 1 bound, a = 7 becomes 3x + 1 = 7, ready to solve.
+    pub fn expand(&self, expr: &Expr) -> Expr {
+        self.expand_within(expr, self.scopes.len() - 1)
+    }
+
+    fn expand_within(&self, expr: &Expr, scope: usize) -> Expr {
+        let replacements: HashMap<String, Expr> = expr
+            .variables()
+            .into_iter()
+            .filter_map(|var| {
+                let replacement = match self.resolve_within(&var, scope)? {
+                    (_, Binding::Value(value)) => Expr::from(value.clone()),
+                    (found, Binding::Expr(bound)) => self.expand_within(bound, found),
+                };
+                Some((var, replacement))
+            })
+            .collect();
+        expr.substitute_all(&replacements)
+    }
+
+    /// Get value of a variable bound to a value
+    pub fn get(&self, name: &str) -> Result<&Value> {
+        match self.resolve(name)? {
+            (_, Binding::Value(value)) => Ok(value),
+            (_, Binding::Expr(expr)) => Err(VeritasError::SimplificationError(format!(
+                "Variable {} is bound to the expression {}, not a value",
+                name, expr
+            ))),
+        }
+    }
+
     /// Get scalar value (error if complex)
     pub fn get_scalar(&self, name: &str) -> Result<Scalar> {
         match self.get(name)? {
@@ -71,6 +376,12 @@ impl Context {
                 "Variable {} is complex, not scalar",
                 name
             ))),
+            Value::Bool(_) => Err(VeritasError::SimplificationError(format!(
+                "Variable {} is boolean, not scalar",
+                name
+            ))),
+            Value::Quantity(q) => Err(mismatch(Dimension::NONE, q.dimension())),
+            Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
         }
     }
 
@@ -79,17 +390,26 @@ impl Context {
         match self.get(name)? {
             Value::Scalar(s) => Ok(Circle::from(*s)),
             Value::Circle(c) => Ok(*c),
+            Value::Bool(_) => Err(VeritasError::SimplificationError(format!(
+                "Variable {} is boolean, not complex",
+                name
+            ))),
+            Value::Quantity(q) => Err(mismatch(Dimension::NONE, q.dimension())),
+            Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
         }
     }
 
-    /// Check if variable is bound
+    /// Check if variable is bound, to a value or an expression
     pub fn contains(&self, name: &str) -> bool {
-        self.bindings.contains_key(name)
+        self.scopes.iter().any(|bindings| bindings.contains_key(name))
     }
 
     /// Get all bound variables
     pub fn variables(&self) -> Vec<String> {
-        self.bindings.keys().cloned().collect()
+        let mut names: Vec<String> = self.scopes.iter().flat_map(|b| b.keys().cloned()).collect();
+        names.sort();
+        names.dedup();
+        names
     }
 }
 
@@ -130,4 +450,83 @@ mod tests {
 
         assert!(matches!(result, Err(VeritasError::VariableNotFound(_))));
     }
+
+    #[test]
+    fn test_scopes_shadow() {
+        let mut ctx = Context::new();
+        ctx.bind("x", 1);
+        ctx.push_scope();
+        ctx.bind("x", 2);
+        ctx.bind("y", 3);
+        assert_eq!(ctx.get_scalar("x").unwrap(), Scalar::from(2));
+        assert_eq!(ctx.variables(), vec!["x".to_string(), "y".to_string()]);
+
+        ctx.pop_scope().unwrap();
+        assert_eq!(ctx.get_scalar("x").unwrap(), Scalar::from(1));
+        assert!(!ctx.contains("y"));
+        assert!(ctx.pop_scope().is_err());
+    }
+
+    #[test]
+    fn test_expression_bindings() {
+        let (a, b, x) = (Expr::var("a"), Expr::var("b"), Expr::var("x"));
+        let mut ctx = Context::new();
+        let line = Expr::add(Expr::mul(Expr::number(3), x.clone()), Expr::number(1));
+        ctx.bind_expr("a", line.clone()).unwrap();
+        ctx.bind_expr("b", Expr::mul(a.clone(), Expr::number(2))).unwrap();
+        assert!(ctx.get("a").is_err());
+
+        // a = 7 is 3x + 1 = 7, ready to solve
+        let equation = Expr::equals(a.clone(), Expr::number(7));
+        assert_eq!(ctx.expand(&equation), Expr::equals(line, Expr::number(7)));
+
+        // x = b would close the loop x → b → a → x; so would a = a + 1
+        assert!(ctx.bind_expr("x", b.clone()).is_err());
+        assert!(ctx.bind_expr("a", Expr::add(a.clone(), Expr::number(1))).is_err());
+
+        // Through a function body as well
+        ctx.define("f", vec!["t".to_string()], Expr::add(Expr::var("t"), b)).unwrap();
+        let call = Expr::Function("f".to_string(), vec![Expr::number(1)]);
+        assert!(ctx.bind_expr("x", call.clone()).is_err());
+
+        // An inner x is a different name from the x in a
+        ctx.push_scope();
+        ctx.bind_expr("x", call).unwrap();
+
+        // Binding first and defining the function after closes the same loop
+        let mut ctx = Context::new();
+        let g = Expr::Function("g".to_string(), vec![Expr::number(1)]);
+        ctx.bind_expr("c", g).unwrap();
+        let body = Expr::add(Expr::var("t"), Expr::var("c"));
+        assert!(ctx.define("g", vec!["t".to_string()], body).is_err());
+        assert!(ctx.function("g").is_none());
+        assert!(Expr::var("c").evaluate(&ctx).is_err());
+    }
+
+    #[test]
+    fn test_assumptions() {
+        let mut ctx = Context::new();
+        ctx.assume("x", Assumption::Positive);
+
+        assert!(ctx.assumptions().has("x", Assumption::Nonnegative));
+        assert!(!ctx.contains("x"));
+    }
+
+    #[test]
+    fn test_define_function() {
+        let mut ctx = Context::new();
+        let params = vec!["x".to_string(), "y".to_string()];
+        let body = Expr::add(Expr::var("x"), Expr::var("y"));
+        ctx.define("f", params.clone(), body.clone()).unwrap();
+
+        // No redefinition, no calls to undefined functions
+        assert!(ctx.define("f", params.clone(), body).is_err());
+        let calls_g = Expr::Function("g".to_string(), vec![Expr::var("x")]);
+        assert!(ctx.define("h", params, calls_g).is_err());
+
+        // f(y, 1) = y + 1, not 1 + 1
+        let def = ctx.function("f").unwrap();
+        let applied = def.apply("f", &[Expr::var("y"), Expr::number(1)]).unwrap();
+        assert_eq!(applied, Expr::add(Expr::var("y"), Expr::number(1)));
+    }
 }
@@ -0,0 +1,780 @@
+//! Hash-consed expressions
+//!
+//! An `ExprArena` stores every distinct subexpression once, as a node
+//! whose children are `ExprId`s. Interning the same structure twice gives
+//! the same id, so equality is a comparison of ids and a subterm shared
+//! by many parents is one node, however deep the sharing goes. Numbers
+//! are compared bit for bit, so equal ids mean identical expressions.
+//!
+//! Results are memoized per node. `simplify` works bottom-up: a node's
+//! children are simplified first, each shared child once, and the rules
+//! then run on the node rebuilt from them, so a deeply shared expression
+//! costs one step per node rather than one per path. What each node
+//! simplified to is remembered for the life of the arena. `evaluate`
+//! computes each node once per call, and keeps the values of nodes with
+//! no variables or function calls across calls, since no binding can
+//! change them. Values and errors are the same as `Evaluate` on the tree.
+
+use super::budget::Meter;
+use super::context::{is_builtin, Value};
+use super::eval::{
+    add, as_bool, as_scalar, bound, builtin, check_base, constant, div, equal, exp, matmul,
+    mul, neg, order, pow, range, sqrt, sub, tan, unknown_function, variable,
+};
+use super::linalg::Array;
+use super::units::{Quantity, Unit};
+use super::{Context, Evaluate, Expr, Simplify};
+use crate::error::{Result, VeritasError};
+use crate::numeric::{Circle, Scalar};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+/// Deepest expression simplified, as `Simplify` allows
+const MAX_DEPTH: usize = 1000;
+
+/// Largest subterm written out in full for the simplifier's rules
+const LOCAL_SIZE: u64 = 32;
+
+/// Levels of a larger subterm written out before the rest becomes a hole
+const LOCAL_DEPTH: usize = 3;
+
+/// Leading character of a hole's name, one no parsed name can start with
+///
+/// A hole stands for an already simplified node the rules at a parent
+/// need not look inside: a call with no arguments, named after the node's
+/// id, so it is opaque, of unknown shape and not known to be defined.
+const HOLE: char = '#';
+
+/// Handle to an expression interned in an `ExprArena`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(u32);
+
+impl ExprId {
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// What a node is, apart from its children
+#[derive(Debug, Clone)]
+enum Op {
+    Number(Scalar),
+    Complex(Circle),
+    Variable(String),
+    Constant(String),
+    Quantity(Scalar, Unit),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Neg,
+    Sqrt,
+    Ln,
+    Exp,
+    Sin,
+    Cos,
+    Tan,
+    Function(String),
+    Vector,
+    /// Length of each row; the entries are the children, row by row
+    Matrix(Vec<usize>),
+    MatMul,
+    Bool(bool),
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    Implies,
+    /// Children are condition, value, condition, value, ..., fallback
+    Piecewise,
+    /// Children are lower bound, upper bound, body
+    Sum(String),
+    Product(String),
+    /// Children are value, body
+    Let(String),
+}
+
+impl PartialEq for Op {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Op::Number(a), Op::Number(b)) => bits(*a) == bits(*b),
+            (Op::Complex(a), Op::Complex(b)) => parts(*a) == parts(*b),
+            (Op::Quantity(a, u), Op::Quantity(b, v)) => bits(*a) == bits(*b) && u == v,
+            (Op::Variable(a), Op::Variable(b))
+            | (Op::Constant(a), Op::Constant(b))
+            | (Op::Function(a), Op::Function(b))
+            | (Op::Sum(a), Op::Sum(b))
+            | (Op::Product(a), Op::Product(b))
+            | (Op::Let(a), Op::Let(b)) => a == b,
+            (Op::Matrix(a), Op::Matrix(b)) => a == b,
+            (Op::Bool(a), Op::Bool(b)) => a == b,
+            // Every variant with data is matched above
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Op {}
+
+impl Hash for Op {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Op::Number(n) => bits(*n).hash(state),
+            Op::Complex(c) => parts(*c).hash(state),
+            Op::Quantity(n, unit) => {
+                bits(*n).hash(state);
+                unit.symbol().hash(state);
+            }
+            Op::Variable(name)
+            | Op::Constant(name)
+            | Op::Function(name)
+            | Op::Sum(name)
+            | Op::Product(name)
+            | Op::Let(name) => name.hash(state),
+            Op::Matrix(widths) => widths.hash(state),
+            Op::Bool(b) => b.hash(state),
+            _ => {}
+        }
+    }
+}
+
+/// Fraction and exponent of a number
+fn bits(n: Scalar) -> (i64, i32) {
+    (n.0.fraction, n.0.exponent)
+}
+
+fn parts(c: Circle) -> ((i64, i32), (i64, i32)) {
+    (bits(c.real()), bits(c.imag()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Node {
+    op: Op,
+    args: Vec<ExprId>,
+}
+
+/// Store of interned expressions
+#[derive(Debug, Clone, Default)]
+pub struct ExprArena {
+    nodes: Vec<Node>,
+    index: HashMap<Node, ExprId>,
+    /// Whether each node's value is independent of the context
+    closed: Vec<bool>,
+    /// Number of nodes in each node's tree, saturating
+    size: Vec<u64>,
+    /// Depth of each node's tree, as `Expr::depth` counts it
+    depth: Vec<usize>,
+    /// Simplified form of each node simplified so far
+    simplified: HashMap<ExprId, ExprId>,
+    /// Values of closed nodes evaluated so far
+    values: HashMap<ExprId, Value>,
+}
+
+impl ExprArena {
+    pub fn new() -> Self {
+        ExprArena::default()
+    }
+
+    /// Number of distinct subexpressions
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Children of a node, in the order `Expr::children` lists them
+    pub fn children(&self, id: ExprId) -> &[ExprId] {
+        &self.nodes[id.index()].args
+    }
+
+    /// Intern an expression and all its subexpressions
+    pub fn intern(&mut self, expr: &Expr) -> ExprId {
+        self.add(expr, false, holes)
+    }
+
+    /// Intern an expression, taking each hole to the node it stands for
+    /// if `holes` is set
+    fn add(&mut self, expr: &Expr, holes: bool) -> ExprId {
+        let (op, args) = match expr {
+            Expr::Number(n) => (Op::Number(*n), Vec::new()),
+            Expr::Complex(c) => (Op::Complex(*c), Vec::new()),
+            Expr::Variable(name) => (Op::Variable(name.clone()), Vec::new()),
+            Expr::Constant(name) => (Op::Constant(name.clone()), Vec::new()),
+            Expr::Quantity(n, unit) => (Op::Quantity(*n, unit.clone()), Vec::new()),
+            Expr::Bool(b) => (Op::Bool(*b), Vec::new()),
+
+            Expr::Add(a, b) => (Op::Add, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Sub(a, b) => (Op::Sub, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Mul(a, b) => (Op::Mul, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Div(a, b) => (Op::Div, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Pow(a, b) => (Op::Pow, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::MatMul(a, b) => (Op::MatMul, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Eq(a, b) => (Op::Eq, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Ne(a, b) => (Op::Ne, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Lt(a, b) => (Op::Lt, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Le(a, b) => (Op::Le, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Gt(a, b) => (Op::Gt, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Ge(a, b) => (Op::Ge, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::And(a, b) => (Op::And, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Or(a, b) => (Op::Or, vec![self.add(a, holes), self.add(b, holes)]),
+            Expr::Implies(a, b) => (Op::Implies, vec![self.add(a, holes), self.add(b, holes)]),
+
+            Expr::Neg(a) => (Op::Neg, vec![self.add(a, holes)]),
+            Expr::Sqrt(a) => (Op::Sqrt, vec![self.add(a, holes)]),
+            Expr::Ln(a) => (Op::Ln, vec![self.add(a, holes)]),
+            Expr::Exp(a) => (Op::Exp, vec![self.add(a, holes)]),
+            Expr::Sin(a) => (Op::Sin, vec![self.add(a, holes)]),
+            Expr::Cos(a) => (Op::Cos, vec![self.add(a, holes)]),
+            Expr::Tan(a) => (Op::Tan, vec![self.add(a, holes)]),
+            Expr::Not(a) => (Op::Not, vec![self.add(a, holes)]),
+
+            Expr::Function(name, args) => match self.hole(name, args, holes) {
+                Some(id) => return id,
+                None => (Op::Function(name.clone()), self.add_all(args, holes)),
+            },
+            Expr::Vector(entries) => (Op::Vector, self.add_all(entries, holes)),
+            Expr::Matrix(rows) => (
+                Op::Matrix(rows.iter().map(Vec::len).collect()),
+                self.add_all(rows.iter().flatten(), holes),
+            ),
+
+            Expr::Piecewise(branches, otherwise) => {
+                let pairs = branches.iter().flat_map(|(condition, value)| [condition, value]);
+                let args = self.add_all(pairs.chain([&**otherwise]), holes);
+                (Op::Piecewise, args)
+            }
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                let args = vec![
+                    self.add(lower, holes),
+                    self.add(upper, holes),
+                    self.add(body, holes),
+                ];
+                match expr {
+                    Expr::Sum(..) => (Op::Sum(index.clone()), args),
+                    _ => (Op::Product(index.clone()), args),
+                }
+            }
+            Expr::Let(name, value, body) => (
+                Op::Let(name.clone()),
+                vec![self.add(value, holes), self.add(body, holes)],
+            ),
+        };
+        self.insert(Node { op, args })
+    }
+
+    fn add_all<'e>(
+        &mut self,
+        exprs: impl IntoIterator<Item = &'e Expr>,
+        holes: bool,
+    ) -> Vec<ExprId> {
+        exprs.into_iter().map(|e| self.add(e, holes)).collect()
+    }
+
+    /// The node a hole stands for
+    fn hole(&self, name: &str, args: &[Expr], holes: bool) -> Option<ExprId> {
+        let index = name.strip_prefix(HOLE)?.parse::<usize>().ok()?;
+        (holes && args.is_empty() && index < self.nodes.len()).then_some(ExprId(index as u32))
+    }
+
+    fn insert(&mut self, node: Node) -> ExprId {
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+        let closed = match node.op {
+            // Bound by the context, or binding a name of their own
+            Op::Variable(_) | Op::Function(_) | Op::Sum(_) | Op::Product(_) | Op::Let(_) => false,
+            _ => node.args.iter().all(|arg| self.closed[arg.index()]),
+        };
+        let size = node
+            .args
+            .iter()
+            .fold(1, |n: u64, arg| n.saturating_add(self.size[arg.index()]));
+        let depth = 1 + node.args.iter().map(|arg| self.depth[arg.index()]).max().unwrap_or(0);
+        let id = ExprId(u32::try_from(self.nodes.len()).expect("expression arena is full"));
+        self.nodes.push(node.clone());
+        self.closed.push(closed);
+        self.size.push(size);
+        self.depth.push(depth);
+        self.index.insert(node, id);
+        id
+    }
+
+    /// The expression a node stands for
+    pub fn to_expr(&self, id: ExprId) -> Expr {
+        let node = &self.nodes[id.index()];
+        let args = node.args.iter().map(|&arg| self.to_expr(arg)).collect();
+        assemble(&node.op, args)
+    }
+
+    /// Simplified form of a node, as `Simplify::simplify` gives it
+    ///
+    /// Each node is simplified at most once; asking again is a lookup.
+    /// Children are simplified first and the rules run on the node rebuilt
+    /// from them, with large children written out only a few levels deep.
+    /// Sums, products and lets are simplified whole, since their bodies
+    /// depend on the name they bind.
+    pub fn simplify(&mut self, id: ExprId) -> Result<ExprId> {
+        if let Some(&simplified) = self.simplified.get(&id) {
+            return Ok(simplified);
+        }
+        let depth = self.depth[id.index()];
+        if depth > MAX_DEPTH {
+            return Err(VeritasError::ComplexityLimit(depth));
+        }
+
+        let node = self.nodes[id.index()].clone();
+        let simplified = if binds(&node.op) {
+            let simplified = self.to_expr(id).simplify()?;
+            self.intern(&simplified)
+        } else {
+            let args: Result<Vec<ExprId>> =
+                node.args.iter().map(|&arg| self.simplify(arg)).collect();
+            let rebuilt = self.insert(Node { op: node.op, args: args? });
+            let simplified = self.local_expr(rebuilt, 0).simplify()?;
+            self.add(&simplified, true)
+        };
+        self.simplified.insert(id, simplified);
+        Ok(simplified)
+    }
+
+    /// A node written out for the simplifier's rules
+    ///
+    /// Subterms of up to `LOCAL_SIZE` nodes are written in full. A larger
+    /// one is written a level at a time to `LOCAL_DEPTH`, and below that,
+    /// or if it binds a name, it becomes a hole.
+    fn local_expr(&self, id: ExprId, depth: usize) -> Expr {
+        let node = &self.nodes[id.index()];
+        if self.size[id.index()] <= LOCAL_SIZE {
+            return self.to_expr(id);
+        }
+        if depth == LOCAL_DEPTH || binds(&node.op) {
+            return Expr::Function(format!("{}{}", HOLE, id.0), Vec::new());
+        }
+        let args = node.args.iter().map(|&arg| self.local_expr(arg, depth + 1)).collect();
+        assemble(&node.op, args)
+    }
+
+    /// Value of a node, with each shared subterm evaluated once
+    pub fn evaluate(&mut self, id: ExprId, ctx: &Context) -> Result<Value> {
+        let mut evaluator = Evaluator::new(self, ctx);
+        let value = evaluator.value(id);
+        let computed = evaluator.memo;
+        for (id, known) in computed {
+            if self.closed[id.index()] {
+                self.values.insert(id, known);
+            }
+        }
+        value
+    }
+
+    /// Evaluate to a scalar (error if the result is not real)
+    pub fn evaluate_scalar(&mut self, id: ExprId, ctx: &Context) -> Result<Scalar> {
+        as_scalar(self.evaluate(id, ctx)?)
+    }
+}
+
+/// Whether a node binds a name in some of its children
+fn binds(op: &Op) -> bool {
+    matches!(op, Op::Sum(_) | Op::Product(_) | Op::Let(_))
+}
+
+/// The expression `op` makes of `args`, given in the order of its node's
+/// children
+fn assemble(op: &Op, args: Vec<Expr>) -> Expr {
+    let mut args = args.into_iter();
+    let mut arg = || args.next().expect("node has fewer children than its operation takes");
+    match op {
+        Op::Number(n) => Expr::Number(*n),
+        Op::Complex(c) => Expr::Complex(*c),
+        Op::Variable(name) => Expr::Variable(name.clone()),
+        Op::Constant(name) => Expr::Constant(name.clone()),
+        Op::Quantity(n, unit) => Expr::Quantity(*n, unit.clone()),
+        Op::Bool(b) => Expr::Bool(*b),
+
+        Op::Add => Expr::add(arg(), arg()),
+        Op::Sub => Expr::sub(arg(), arg()),
+        Op::Mul => Expr::mul(arg(), arg()),
+        Op::Div => Expr::div(arg(), arg()),
+        Op::Pow => Expr::pow(arg(), arg()),
+        Op::MatMul => Expr::MatMul(Box::new(arg()), Box::new(arg())),
+        Op::Eq => Expr::equals(arg(), arg()),
+        Op::Ne => Expr::not_equals(arg(), arg()),
+        Op::Lt => Expr::less(arg(), arg()),
+        Op::Le => Expr::less_eq(arg(), arg()),
+        Op::Gt => Expr::greater(arg(), arg()),
+        Op::Ge => Expr::greater_eq(arg(), arg()),
+        Op::And => Expr::and(arg(), arg()),
+        Op::Or => Expr::or(arg(), arg()),
+        Op::Implies => Expr::implies(arg(), arg()),
+
+        Op::Neg => Expr::neg(arg()),
+        Op::Sqrt => Expr::sqrt(arg()),
+        Op::Ln => Expr::ln(arg()),
+        Op::Exp => Expr::exp(arg()),
+        Op::Sin => Expr::sin(arg()),
+        Op::Cos => Expr::cos(arg()),
+        Op::Tan => Expr::tan(arg()),
+        Op::Not => Expr::not(arg()),
+
+        Op::Sum(index) => Expr::sum(index.clone(), arg(), arg(), arg()),
+        Op::Product(index) => Expr::product(index.clone(), arg(), arg(), arg()),
+        Op::Let(name) => Expr::let_in(name.clone(), arg(), arg()),
+
+        Op::Function(name) => Expr::Function(name.clone(), args.collect()),
+        Op::Vector => Expr::Vector(args.collect()),
+        Op::Matrix(widths) => {
+            Expr::Matrix(widths.iter().map(|&width| args.by_ref().take(width).collect()).collect())
+        }
+        Op::Piecewise => {
+            let mut rest: Vec<Expr> = args.collect();
+            let otherwise = rest.pop().expect("piecewise node has a fallback");
+            let mut rest = rest.into_iter();
+            let mut branches = Vec::new();
+            while let (Some(condition), Some(value)) = (rest.next(), rest.next()) {
+                branches.push((condition, value));
+            }
+            Expr::piecewise(branches, otherwise)
+        }
+    }
+}
+
+/// Entries of each row of a matrix node
+fn rows<'n>(widths: &'n [usize], mut args: &'n [ExprId]) -> impl Iterator<Item = &'n [ExprId]> {
+    widths.iter().map(move |&width| {
+        let (row, rest) = args.split_at(width);
+        args = rest;
+        row
+    })
+}
+
+/// One evaluation of an arena under one context
+///
+/// Operands are evaluated in the order `Evaluate` uses, through the same
+/// operations, so laziness and errors carry over unchanged.
+struct Evaluator<'a> {
+    arena: &'a ExprArena,
+    ctx: &'a Context,
+    /// Values of the nodes computed in this evaluation
+    memo: HashMap<ExprId, Value>,
+    /// Expressions written out for 0/0 limits and calls, each node once
+    trees: HashMap<ExprId, Expr>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(arena: &'a ExprArena, ctx: &'a Context) -> Self {
+        Evaluator {
+            arena,
+            ctx,
+            memo: HashMap::new(),
+            trees: HashMap::new(),
+        }
+    }
+
+    /// The expression a node stands for, built from its children's
+    fn tree(&mut self, id: ExprId) -> Expr {
+        if let Some(tree) = self.trees.get(&id) {
+            return tree.clone();
+        }
+        let arena = self.arena;
+        let node = &arena.nodes[id.index()];
+        let tree = assemble(&node.op, node.args.iter().map(|&arg| self.tree(arg)).collect());
+        self.trees.insert(id, tree.clone());
+        tree
+    }
+
+    fn value(&mut self, id: ExprId) -> Result<Value> {
+        if let Some(known) = self.arena.values.get(&id).or_else(|| self.memo.get(&id)) {
+            return Ok(known.clone());
+        }
+        let value = self.compute(id)?;
+        self.memo.insert(id, value.clone());
+        Ok(value)
+    }
+
+    fn scalar(&mut self, id: ExprId) -> Result<Scalar> {
+        as_scalar(self.value(id)?)
+    }
+
+    fn truth(&mut self, id: ExprId) -> Result<bool> {
+        as_bool(self.value(id)?)
+    }
+
+    fn scalars(&mut self, ids: &[ExprId]) -> Result<Vec<Scalar>> {
+        ids.iter().map(|&id| self.scalar(id)).collect()
+    }
+
+    fn compute(&mut self, id: ExprId) -> Result<Value> {
+        let arena = self.arena;
+        let ctx = self.ctx;
+        let node = &arena.nodes[id.index()];
+        let args = node.args.as_slice();
+        match &node.op {
+            Op::Number(n) => Ok(Value::Scalar(*n)),
+            Op::Complex(c) => Ok(Value::Circle(*c)),
+            Op::Quantity(n, unit) => Ok(Quantity::new(*n, unit)?.into()),
+            Op::Bool(b) => Ok(Value::Bool(*b)),
+
+            Op::Vector => Ok(Value::Array(Array::vector(self.scalars(args)?))),
+            Op::Matrix(widths) => {
+                let rows: Result<Vec<Vec<Scalar>>> =
+                    rows(widths, args).map(|row| self.scalars(row)).collect();
+                Ok(Value::Array(Array::from_rows(rows?)?))
+            }
+
+            Op::Variable(name) => variable(name, ctx, &mut Meter::default()),
+            Op::Constant(name) => constant(name),
+
+            Op::Add => add(self.value(args[0])?, self.value(args[1])?),
+            Op::Sub => sub(self.value(args[0])?, self.value(args[1])?),
+            Op::Mul => mul(self.value(args[0])?, self.value(args[1])?),
+            Op::Div => {
+                let (a, b) = (self.value(args[0])?, self.value(args[1])?);
+                div(a, b, || self.tree(id), ctx, &mut Meter::default())
+            }
+
+            Op::Pow => {
+                let base = self.value(args[0])?;
+                check_base(&base)?;
+                pow(base, self.scalar(args[1])?)
+            }
+
+            Op::MatMul => matmul(self.value(args[0])?, self.value(args[1])?),
+
+            Op::Neg => neg(self.value(args[0])?),
+            Op::Sqrt => sqrt(self.value(args[0])?),
+            Op::Ln => Ok(Value::Scalar(self.scalar(args[0])?.ln()?)),
+            Op::Exp => exp(self.value(args[0])?),
+            Op::Sin => Ok(Value::Scalar(self.scalar(args[0])?.sin()?)),
+            Op::Cos => Ok(Value::Scalar(self.scalar(args[0])?.cos()?)),
+            Op::Tan => Ok(Value::Scalar(tan(self.scalar(args[0])?)?)),
+
+            Op::Function(name) => match ctx.function(name) {
+                Some(def) => {
+                    let args: Vec<Expr> = args.iter().map(|&a| self.tree(a)).collect();
+                    def.apply(name, &args)?.evaluate(ctx)
+                }
+                None if is_builtin(name) => {
+                    let values: Result<Vec<Value>> = args.iter().map(|&a| self.value(a)).collect();
+                    builtin(name, &values?)
+                }
+                None => Err(unknown_function(name)),
+            },
+
+            Op::Eq => Ok(Value::Bool(equal(&self.value(args[0])?, &self.value(args[1])?)?)),
+            Op::Ne => Ok(Value::Bool(!equal(&self.value(args[0])?, &self.value(args[1])?)?)),
+
+            Op::Lt => Ok(Value::Bool(self.compare(args)?.is_lt())),
+            Op::Le => Ok(Value::Bool(self.compare(args)?.is_le())),
+            Op::Gt => Ok(Value::Bool(self.compare(args)?.is_gt())),
+            Op::Ge => Ok(Value::Bool(self.compare(args)?.is_ge())),
+
+            Op::And => Ok(Value::Bool(self.truth(args[0])? && self.truth(args[1])?)),
+            Op::Or => Ok(Value::Bool(self.truth(args[0])? || self.truth(args[1])?)),
+            Op::Not => Ok(Value::Bool(!self.truth(args[0])?)),
+            Op::Implies => Ok(Value::Bool(!self.truth(args[0])? || self.truth(args[1])?)),
+
+            Op::Piecewise => {
+                let (pairs, otherwise) = args.split_at(args.len() - 1);
+                for pair in pairs.chunks(2) {
+                    if self.truth(pair[0])? {
+                        return self.value(pair[1]);
+                    }
+                }
+                self.value(otherwise[0])
+            }
+
+            // The body depends on the index, so each term is a fresh evaluation
+            Op::Sum(index) | Op::Product(index) => {
+                let product = matches!(node.op, Op::Product(_));
+                let lower = bound(self.value(args[0])?)?;
+                let upper = bound(self.value(args[1])?)?;
+                let mut inner = ctx.clone();
+                inner.push_scope();
+                let mut total = Value::Scalar(if product { Scalar::ONE } else { Scalar::ZERO });
+                for k in range(lower, upper)? {
+                    inner.bind(index.clone(), Scalar::from_i64(k));
+                    let term = Evaluator::new(arena, &inner).value(args[2])?;
+                    total = if product { mul(total, term)? } else { add(total, term)? };
+                }
+                Ok(total)
+            }
+            Op::Let(name) => {
+                let mut inner = ctx.clone();
+                inner.push_scope();
+                inner.bind(name.clone(), self.value(args[0])?);
+                Evaluator::new(arena, &inner).value(args[1])
+            }
+        }
+    }
+
+    fn compare(&mut self, args: &[ExprId]) -> Result<Ordering> {
+        order(self.value(args[0])?, self.value(args[1])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolic::constants;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    #[test]
+    fn test_round_trip_and_sharing() {
+        let u = Expr::add(x(), Expr::number(1));
+        let exprs = [
+            Expr::mul(Expr::pow(u.clone(), Expr::number(2)), Expr::sin(u.clone())),
+            Expr::piecewise(
+                vec![(Expr::greater(x(), Expr::number(0)), Expr::sqrt(x()))],
+                Expr::neg(x()),
+            ),
+            Expr::sum("k", Expr::number(1), x(), Expr::div(Expr::number(1), Expr::var("k"))),
+            Expr::Matrix(vec![vec![x(), u.clone()], vec![constants::pi(), Expr::number(2)]]),
+            Expr::Function("max".to_string(), vec![u.clone(), Expr::Bool(true)]),
+            Expr::complex(Scalar::ONE, Scalar::TWO),
+            Expr::quantity(500, "g").unwrap(),
+        ];
+
+        let mut arena = ExprArena::new();
+        for expr in &exprs {
+            let id = arena.intern(expr);
+            assert_eq!(&arena.to_expr(id), expr);
+            assert_eq!(arena.intern(&expr.clone()), id);
+        }
+
+        // x + 1 is one node, and x is its first child
+        let shared = arena.intern(&u);
+        let before = arena.len();
+        assert_eq!(arena.intern(&Expr::add(x(), Expr::number(1))), shared);
+        assert_eq!(arena.children(shared)[0], arena.intern(&x()));
+        assert_eq!(arena.len(), before);
+        assert_ne!(arena.intern(&Expr::add(Expr::number(1), x())), shared);
+    }
+
+    #[test]
+    fn test_evaluate_matches_tree() {
+        let mut ctx = Context::new();
+        ctx.define("f", vec!["t".to_string()], Expr::mul(Expr::var("t"), Expr::number(3)))
+            .unwrap();
+        let recip = Expr::div(Expr::number(1), x());
+        let exprs = [
+            Expr::add(
+                Expr::Function("f".to_string(), vec![x()]),
+                Expr::pow(x(), Expr::number(3)),
+            ),
+            Expr::piecewise(
+                vec![(
+                    Expr::and(
+                        Expr::not_equals(x(), Expr::number(0)),
+                        Expr::greater(recip.clone(), Expr::number(2)),
+                    ),
+                    recip,
+                )],
+                Expr::clamp(Expr::mul(x(), x()), Expr::number(0), Expr::number(3)),
+            ),
+            Expr::product("k", Expr::number(1), x(), Expr::add(Expr::var("k"), x())),
+            // Removable 0/0 at x = 0
+            Expr::div(Expr::sin(x()), x()),
+            Expr::ln(Expr::sub(x(), Expr::number(5))),
+            Expr::let_in("x", Expr::add(x(), Expr::number(1)), Expr::mul(x(), x())),
+        ];
+
+        let mut arena = ExprArena::new();
+        for value in [0, 2, 4] {
+            ctx.bind("x", value);
+            for expr in &exprs {
+                let id = arena.intern(expr);
+                assert_eq!(arena.evaluate(id, &ctx).ok(), expr.evaluate(&ctx).ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_sharing() {
+        // x doubled 60 times: 61 nodes for a tree of 2⁶¹ - 1
+        let mut arena = ExprArena::new();
+        let mut id = arena.intern(&x());
+        for _ in 0..60 {
+            let node = Node {
+                op: Op::Add,
+                args: vec![id, id],
+            };
+            id = arena.insert(node);
+        }
+        assert_eq!(arena.len(), 61);
+
+        let mut ctx = Context::new();
+        ctx.bind("x", 3);
+        let expected = Scalar::from(3) * Scalar::from_i64(1 << 60);
+        assert_eq!(arena.evaluate_scalar(id, &ctx).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_simplify_memo() {
+        let expr = Expr::add(
+            Expr::mul(Expr::number(1), x()),
+            Expr::sub(Expr::var("y"), Expr::var("y")),
+        );
+        let mut arena = ExprArena::new();
+        let id = arena.intern(&expr);
+        let simplified = arena.simplify(id).unwrap();
+        assert_eq!(arena.to_expr(simplified), expr.simplify().unwrap());
+        assert_eq!(simplified, arena.intern(&x()));
+
+        let size = arena.len();
+        assert_eq!(arena.simplify(id).unwrap(), simplified);
+        assert_eq!(arena.len(), size);
+    }
+
+    #[test]
+    fn test_simplify_deep_sharing() {
+        // xₙ₊₁ = xₙ + xₙ to depth 60, over 1·x and over x
+        let doubled = |arena: &mut ExprArena, mut id: ExprId| {
+            for _ in 0..60 {
+                id = arena.insert(Node {
+                    op: Op::Add,
+                    args: vec![id, id],
+                });
+            }
+            id
+        };
+        let mut arena = ExprArena::new();
+        let start = arena.intern(&Expr::mul(Expr::number(1), x()));
+        let chain = doubled(&mut arena, start);
+        let start = arena.intern(&x());
+        let expected = doubled(&mut arena, start);
+
+        // One step per node: the tree has 2⁶¹ - 1
+        let size = arena.len();
+        assert_eq!(arena.simplify(chain).unwrap(), expected);
+        assert_eq!(arena.len(), size);
+        assert_eq!(arena.simplified.len(), 63);
+    }
+
+    #[test]
+    fn test_closed_values_kept() {
+        let mut arena = ExprArena::new();
+        let closed = arena.intern(&Expr::sqrt(Expr::number(2)));
+        let open = arena.intern(&Expr::mul(Expr::sqrt(Expr::number(2)), x()));
+
+        let mut ctx = Context::new();
+        ctx.bind("x", 2);
+        let expected = Scalar::from(2).sqrt().unwrap() * Scalar::from(2);
+        assert_eq!(arena.evaluate_scalar(open, &ctx).unwrap(), expected);
+        assert!(arena.values.contains_key(&closed));
+        assert!(!arena.values.contains_key(&open));
+    }
+}
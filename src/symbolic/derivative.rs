@@ -0,0 +1,272 @@
+//! Symbolic differentiation
+//!
+//! Structural application of the derivative rules. Subexpressions that
+//! do not depend on the variable are treated as constants, which keeps
+//! the derivatives of simple forms small before simplification.
+
+use super::units::Unit;
+use super::{Expr, Simplify};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+
+/// Trait for differentiating expressions
+pub trait Differentiate {
+    /// Derivative with respect to `var`, simplified
+    fn differentiate(&self, var: &str) -> Result<Expr>;
+}
+
+impl Differentiate for Expr {
+    fn differentiate(&self, var: &str) -> Result<Expr> {
+        derive(self, var)?.simplify()
+    }
+}
+
+/// Raw (unsimplified) derivative
+fn derive(expr: &Expr, var: &str) -> Result<Expr> {
+    if !expr.contains_variable(var) {
+        return Ok(Expr::number(0));
+    }
+
+    let derivative = match expr {
+        Expr::Number(_) | Expr::Complex(_) | Expr::Constant(_) => Expr::number(0),
+
+        // Zero with the same dimension, in SI so affine units stay exact
+        Expr::Quantity(_, unit) => Expr::Quantity(Scalar::ZERO, Unit::si(unit.dimension())),
+
+        Expr::Variable(name) => {
+            if name == var {
+                Expr::number(1)
+            } else {
+                Expr::number(0)
+            }
+        }
+
+        Expr::Add(a, b) => Expr::add(derive(a, var)?, derive(b, var)?),
+        Expr::Sub(a, b) => Expr::sub(derive(a, var)?, derive(b, var)?),
+
+        // Product rule, skipping the half that vanishes for constant factors
+        Expr::Mul(a, b) => {
+            if !a.contains_variable(var) {
+                Expr::mul((**a).clone(), derive(b, var)?)
+            } else if !b.contains_variable(var) {
+                Expr::mul(derive(a, var)?, (**b).clone())
+            } else {
+                Expr::add(
+                    Expr::mul(derive(a, var)?, (**b).clone()),
+                    Expr::mul((**a).clone(), derive(b, var)?),
+                )
+            }
+        }
+
+        // Quotient rule: (a'b - ab') / b²
+        Expr::Div(a, b) => {
+            if !b.contains_variable(var) {
+                Expr::div(derive(a, var)?, (**b).clone())
+            } else {
+                Expr::div(
+                    Expr::sub(
+                        Expr::mul(derive(a, var)?, (**b).clone()),
+                        Expr::mul((**a).clone(), derive(b, var)?),
+                    ),
+                    Expr::pow((**b).clone(), Expr::number(2)),
+                )
+            }
+        }
+
+        Expr::Pow(base, exp) => {
+            if !exp.contains_variable(var) {
+                // d(uⁿ) = n·uⁿ⁻¹·u'
+                Expr::mul(
+                    Expr::mul(
+                        (**exp).clone(),
+                        Expr::pow((**base).clone(), Expr::sub((**exp).clone(), Expr::number(1))),
+                    ),
+                    derive(base, var)?,
+                )
+            } else if !base.contains_variable(var) {
+                // d(aᵛ) = aᵛ·ln(a)·v'
+                Expr::mul(
+                    Expr::mul(expr.clone(), Expr::ln((**base).clone())),
+                    derive(exp, var)?,
+                )
+            } else {
+                // d(uᵛ) = uᵛ·(v'·ln(u) + v·u'/u)
+                Expr::mul(
+                    expr.clone(),
+                    Expr::add(
+                        Expr::mul(derive(exp, var)?, Expr::ln((**base).clone())),
+                        Expr::div(
+                            Expr::mul((**exp).clone(), derive(base, var)?),
+                            (**base).clone(),
+                        ),
+                    ),
+                )
+            }
+        }
+
+        Expr::Neg(a) => Expr::neg(derive(a, var)?),
+
+        // d√u = u' / (2√u)
+        Expr::Sqrt(a) => Expr::div(
+            derive(a, var)?,
+            Expr::mul(Expr::number(2), Expr::sqrt((**a).clone())),
+        ),
+
+        Expr::Ln(a) => Expr::div(derive(a, var)?, (**a).clone()),
+        Expr::Exp(a) => Expr::mul(expr.clone(), derive(a, var)?),
+
+        Expr::Sin(a) => Expr::mul(Expr::cos((**a).clone()), derive(a, var)?),
+        Expr::Cos(a) => Expr::neg(Expr::mul(Expr::sin((**a).clone()), derive(a, var)?)),
+        // d tan(u) = u' / cos²(u)
+        Expr::Tan(a) => Expr::div(
+            derive(a, var)?,
+            Expr::pow(Expr::cos((**a).clone()), Expr::number(2)),
+        ),
+
+        // Entry by entry
+        Expr::Vector(_) | Expr::Matrix(_) => expr.try_map_children(|entry| derive(entry, var))?,
+
+        // Product rule, keeping the factors in order
+        Expr::MatMul(a, b) => {
+            if !a.contains_variable(var) {
+                Expr::matmul((**a).clone(), derive(b, var)?)
+            } else if !b.contains_variable(var) {
+                Expr::matmul(derive(a, var)?, (**b).clone())
+            } else {
+                Expr::add(
+                    Expr::matmul(derive(a, var)?, (**b).clone()),
+                    Expr::matmul((**a).clone(), derive(b, var)?),
+                )
+            }
+        }
+
+        // Transpose and trace are linear
+        Expr::Function(name, args) if args.len() == 1 && (name == "transpose" || name == "trace") => {
+            Expr::Function(name.clone(), vec![derive(&args[0], var)?])
+        }
+
+        Expr::Function(name, args) if name == "dot" && args.len() == 2 => {
+            let (a, b) = (&args[0], &args[1]);
+            if !a.contains_variable(var) {
+                Expr::dot(a.clone(), derive(b, var)?)
+            } else if !b.contains_variable(var) {
+                Expr::dot(derive(a, var)?, b.clone())
+            } else {
+                Expr::add(
+                    Expr::dot(derive(a, var)?, b.clone()),
+                    Expr::dot(a.clone(), derive(b, var)?),
+                )
+            }
+        }
+
+        Expr::Function(name, _) => {
+            return Err(VeritasError::SimplificationError(format!(
+                "Cannot differentiate unknown function: {}",
+                name
+            )))
+        }
+
+        // Branch by branch; the boundaries themselves are not differentiable,
+        // and (as in `relu_backward`) they take the derivative of the branch
+        // that evaluation picks there
+        Expr::Piecewise(branches, otherwise) => {
+            let mut derived = Vec::with_capacity(branches.len());
+            for (condition, value) in branches {
+                derived.push((condition.clone(), derive(value, var)?));
+            }
+            Expr::piecewise(derived, derive(otherwise, var)?)
+        }
+
+        // Term by term over a fixed range
+        Expr::Sum(index, lower, upper, body) => {
+            if lower.contains_variable(var) || upper.contains_variable(var) {
+                return Err(VeritasError::SimplificationError(format!(
+                    "Cannot differentiate a sum whose range depends on {}: {}",
+                    var, expr
+                )));
+            }
+            Expr::sum(
+                index.clone(),
+                (**lower).clone(),
+                (**upper).clone(),
+                derive(body, var)?,
+            )
+        }
+
+        Expr::Product(..) => {
+            return Err(VeritasError::SimplificationError(format!(
+                "Cannot differentiate product over a range: {}",
+                expr
+            )))
+        }
+
+        // The body depends on var through the value too
+        Expr::Let(name, value, body) => derive(&body.substitute(name, value), var)?,
+
+        Expr::Bool(_)
+        | Expr::Eq(..)
+        | Expr::Ne(..)
+        | Expr::Lt(..)
+        | Expr::Le(..)
+        | Expr::Gt(..)
+        | Expr::Ge(..)
+        | Expr::And(..)
+        | Expr::Or(..)
+        | Expr::Not(_)
+        | Expr::Implies(..) => {
+            return Err(VeritasError::SimplificationError(format!(
+                "Cannot differentiate predicate: {}",
+                expr
+            )))
+        }
+    };
+
+    Ok(derivative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivative_of_constant() {
+        let expr = Expr::add(Expr::number(3), Expr::var("y"));
+        assert_eq!(expr.differentiate("x").unwrap(), Expr::number(0));
+    }
+
+    #[test]
+    fn test_power_rule() {
+        // d/dx x³ = 3·x²
+        let expr = Expr::pow(Expr::var("x"), Expr::number(3));
+        let expected = Expr::mul(
+            Expr::number(3),
+            Expr::pow(Expr::var("x"), Expr::number(2)),
+        );
+        assert_eq!(expr.differentiate("x").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_chain_rule() {
+        // d/dx sin(x²) = cos(x²)·(2·x)
+        let x2 = Expr::pow(Expr::var("x"), Expr::number(2));
+        let expr = Expr::sin(x2.clone());
+        let expected = Expr::mul(
+            Expr::cos(x2),
+            Expr::mul(Expr::number(2), Expr::var("x")),
+        );
+        assert_eq!(expr.differentiate("x").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unknown_function() {
+        let expr = Expr::Function("f".to_string(), vec![Expr::var("x")]);
+        assert!(expr.differentiate("x").is_err());
+    }
+
+    #[test]
+    fn test_piecewise_derivative() {
+        // d/dx relu(x) = step(x)
+        let x = Expr::var("x");
+        assert_eq!(Expr::relu(x.clone()).differentiate("x").unwrap(), Expr::step(x));
+    }
+}
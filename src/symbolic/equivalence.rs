@@ -0,0 +1,568 @@
+//! Equivalence checking via a canonical sum-of-products form
+//!
+//! Expressions are flattened into a sum of terms, each a numeric
+//! coefficient times a product of powers of opaque factors. Like terms
+//! are combined, so `x*x + x*x` and `2*x^2` land in the same shape.
+//!
+//! Numeric coefficients are compared with a tight relative tolerance:
+//! Spirix rounding can leave `(1/3)*3` a hair away from one, and that
+//! must not be mistaken for a different expression.
+
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::collections::BTreeMap;
+
+/// Largest number of terms a normal form may grow to
+const MAX_TERMS: usize = 4096;
+
+/// Largest integer power of a sum that gets expanded
+const MAX_EXPANSION_POWER: i64 = 8;
+
+/// A base raised to a numeric power
+#[derive(Debug, Clone, PartialEq)]
+pub struct Factor {
+    pub base: Expr,
+    pub exponent: Scalar,
+}
+
+/// Numeric coefficient times a product of factors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub coefficient: Scalar,
+    /// Factors with distinct bases, sorted by rendered base
+    pub factors: Vec<Factor>,
+}
+
+/// Canonical sum of terms
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalForm {
+    terms: BTreeMap<String, Term>,
+}
+
+impl Factor {
+    pub fn new(base: Expr, exponent: Scalar) -> Self {
+        Factor { base, exponent }
+    }
+
+    /// Rebuild as an expression
+    pub fn to_expr(&self) -> Expr {
+        if self.exponent == Scalar::ONE {
+            self.base.clone()
+        } else {
+            Expr::pow(self.base.clone(), Expr::Number(self.exponent))
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{}", self.base)
+    }
+}
+
+impl Term {
+    /// Term with no factors
+    pub fn constant(coefficient: Scalar) -> Self {
+        Term {
+            coefficient,
+            factors: Vec::new(),
+        }
+    }
+
+    /// Key identifying like terms (factors and exponents, not coefficient)
+    fn key(&self) -> String {
+        self.factors
+            .iter()
+            .map(|f| format!("{}^{}", f.key(), f.exponent))
+            .collect::<Vec<_>>()
+            .join("*")
+    }
+
+    /// Check that no factor depends on `var`
+    pub fn is_constant_in(&self, var: &str) -> bool {
+        self.factors.iter().all(|f| !f.base.contains_variable(var))
+    }
+
+    /// Product of two terms, merging powers of equal bases
+    pub fn mul(&self, other: &Term) -> Result<Term> {
+        let coefficient = self.coefficient.checked_mul(other.coefficient)?;
+
+        let mut merged: BTreeMap<String, Factor> = BTreeMap::new();
+        for factor in self.factors.iter().chain(other.factors.iter()) {
+            match merged.get_mut(&factor.key()) {
+                Some(existing) => {
+                    existing.exponent = existing.exponent.checked_add(factor.exponent)?;
+                }
+                None => {
+                    merged.insert(factor.key(), factor.clone());
+                }
+            }
+        }
+
+        let factors = merged
+            .into_values()
+            .filter(|f| !f.exponent.is_zero())
+            .collect();
+
+        Ok(Term {
+            coefficient,
+            factors,
+        })
+    }
+
+    /// Multiplicative inverse (error on zero coefficient)
+    pub fn inverse(&self) -> Result<Term> {
+        Ok(Term {
+            coefficient: Scalar::ONE.checked_div(self.coefficient)?,
+            factors: self
+                .factors
+                .iter()
+                .map(|f| Factor::new(f.base.clone(), -f.exponent))
+                .collect(),
+        })
+    }
+
+    /// Integer power of a single term
+    fn powi(&self, n: i64) -> Result<Term> {
+        let mut coefficient = Scalar::ONE;
+        for _ in 0..n.unsigned_abs() {
+            coefficient = coefficient.checked_mul(self.coefficient)?;
+        }
+        if n < 0 {
+            coefficient = Scalar::ONE.checked_div(coefficient)?;
+        }
+
+        let scale = Scalar::from_i64(n);
+        let mut factors = Vec::with_capacity(self.factors.len());
+        for f in &self.factors {
+            factors.push(Factor::new(f.base.clone(), f.exponent.checked_mul(scale)?));
+        }
+
+        Ok(Term {
+            coefficient,
+            factors,
+        })
+    }
+
+    /// Rebuild as an expression
+    pub fn to_expr(&self) -> Expr {
+        let product = self
+            .factors
+            .iter()
+            .map(Factor::to_expr)
+            .reduce(Expr::mul);
+
+        match product {
+            None => Expr::Number(self.coefficient),
+            Some(p) if self.coefficient == Scalar::ONE => p,
+            Some(p) if self.coefficient == -Scalar::ONE => Expr::neg(p),
+            Some(p) => Expr::mul(Expr::Number(self.coefficient), p),
+        }
+    }
+}
+
+impl NormalForm {
+    /// The empty sum
+    pub fn zero() -> Self {
+        NormalForm {
+            terms: BTreeMap::new(),
+        }
+    }
+
+    /// A numeric constant
+    pub fn constant(value: Scalar) -> Result<Self> {
+        let mut nf = NormalForm::zero();
+        nf.add_term(Term::constant(value))?;
+        Ok(nf)
+    }
+
+    /// A single opaque factor
+    fn atom(base: Expr) -> Self {
+        NormalForm::from_term(Term {
+            coefficient: Scalar::ONE,
+            factors: vec![Factor::new(base, Scalar::ONE)],
+        })
+    }
+
+    fn from_term(term: Term) -> Self {
+        let mut terms = BTreeMap::new();
+        if !term.coefficient.is_zero() {
+            terms.insert(term.key(), term);
+        }
+        NormalForm { terms }
+    }
+
+    /// Canonicalize an expression
+    pub fn from_expr(expr: &Expr) -> Result<Self> {
+        match expr {
+            Expr::Number(n) => NormalForm::constant(*n),
+            Expr::Complex(c) => {
+                if c.imag().is_zero() {
+                    NormalForm::constant(c.real())
+                } else {
+                    Ok(NormalForm::atom(expr.clone()))
+                }
+            }
+            Expr::Variable(_) | Expr::Constant(_) => Ok(NormalForm::atom(expr.clone())),
+
+            Expr::Add(a, b) => NormalForm::from_expr(a)?.add(&NormalForm::from_expr(b)?),
+            Expr::Sub(a, b) => {
+                let b = NormalForm::from_expr(b)?.scale(-Scalar::ONE)?;
+                NormalForm::from_expr(a)?.add(&b)
+            }
+            Expr::Mul(a, b) => NormalForm::from_expr(a)?.mul(&NormalForm::from_expr(b)?),
+            Expr::Div(a, b) => {
+                let b = NormalForm::from_expr(b)?.power(-Scalar::ONE)?;
+                NormalForm::from_expr(a)?.mul(&b)
+            }
+            Expr::Neg(a) => NormalForm::from_expr(a)?.scale(-Scalar::ONE),
+
+            Expr::Pow(base, exp) => {
+                let base = NormalForm::from_expr(base)?;
+                let exp = NormalForm::from_expr(exp)?;
+                match exp.as_constant() {
+                    Some(p) => base.power(p),
+                    None => Ok(NormalForm::atom(Expr::pow(base.to_expr(), exp.to_expr()))),
+                }
+            }
+            Expr::Sqrt(a) => NormalForm::from_expr(a)?.power(Scalar::ONE / Scalar::TWO),
+
+            // Opaque atoms with canonical arguments
+            Expr::Ln(a) => Ok(NormalForm::atom(Expr::ln(canonical(a)?))),
+            Expr::Exp(a) => Ok(NormalForm::atom(Expr::exp(canonical(a)?))),
+            Expr::Sin(a) => Ok(NormalForm::atom(Expr::sin(canonical(a)?))),
+            Expr::Cos(a) => Ok(NormalForm::atom(Expr::cos(canonical(a)?))),
+            Expr::Tan(a) => Ok(NormalForm::atom(Expr::tan(canonical(a)?))),
+            Expr::Function(name, args) => {
+                let args: Result<Vec<_>> = args.iter().map(canonical).collect();
+                Ok(NormalForm::atom(Expr::Function(name.clone(), args?)))
+            }
+
+            // Quantities, arrays, sums and truth-valued nodes are opaque atoms too
+            Expr::Quantity(..) | Expr::Bool(_) => Ok(NormalForm::atom(expr.clone())),
+            Expr::Vector(_)
+            | Expr::Matrix(_)
+            | Expr::MatMul(..)
+            | Expr::Sum(..)
+            | Expr::Product(..) => Ok(NormalForm::atom(expr.try_map_children(canonical)?)),
+            Expr::Let(name, value, body) => canonical(&body.substitute(name, value)),
+            Expr::Eq(a, b) => Ok(NormalForm::atom(Expr::equals(canonical(a)?, canonical(b)?))),
+            Expr::Ne(a, b) => Ok(NormalForm::atom(Expr::not_equals(canonical(a)?, canonical(b)?))),
+            Expr::Lt(a, b) => Ok(NormalForm::atom(Expr::less(canonical(a)?, canonical(b)?))),
+            Expr::Le(a, b) => Ok(NormalForm::atom(Expr::less_eq(canonical(a)?, canonical(b)?))),
+            Expr::Gt(a, b) => Ok(NormalForm::atom(Expr::greater(canonical(a)?, canonical(b)?))),
+            Expr::Ge(a, b) => Ok(NormalForm::atom(Expr::greater_eq(canonical(a)?, canonical(b)?))),
+            Expr::And(a, b) => Ok(NormalForm::atom(Expr::and(canonical(a)?, canonical(b)?))),
+            Expr::Or(a, b) => Ok(NormalForm::atom(Expr::or(canonical(a)?, canonical(b)?))),
+            Expr::Not(a) => Ok(NormalForm::atom(Expr::not(canonical(a)?))),
+            Expr::Implies(a, b) => Ok(NormalForm::atom(Expr::implies(canonical(a)?, canonical(b)?))),
+            Expr::Piecewise(branches, otherwise) => {
+                let mut parts = Vec::with_capacity(branches.len());
+                for (condition, value) in branches {
+                    parts.push((canonical(condition)?, canonical(value)?));
+                }
+                Ok(NormalForm::atom(Expr::piecewise(parts, canonical(otherwise)?)))
+            }
+        }
+    }
+
+    /// Rebuild as an expression
+    pub fn to_expr(&self) -> Expr {
+        let mut result: Option<Expr> = None;
+        for term in self.terms.values() {
+            result = Some(match result {
+                None => term.to_expr(),
+                Some(acc) if term.coefficient.is_negative() => {
+                    let positive = Term {
+                        coefficient: -term.coefficient,
+                        factors: term.factors.clone(),
+                    };
+                    Expr::sub(acc, positive.to_expr())
+                }
+                Some(acc) => Expr::add(acc, term.to_expr()),
+            });
+        }
+        result.unwrap_or(Expr::Number(Scalar::ZERO))
+    }
+
+    /// Iterate over terms in canonical order
+    pub fn terms(&self) -> impl Iterator<Item = &Term> {
+        self.terms.values()
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Check for the empty sum
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The value, if this is a plain number
+    pub fn as_constant(&self) -> Option<Scalar> {
+        match self.terms.len() {
+            0 => Some(Scalar::ZERO),
+            1 => {
+                let term = self.terms.values().next()?;
+                if term.factors.is_empty() {
+                    Some(term.coefficient)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The only term, if there is exactly one
+    pub fn as_single_term(&self) -> Option<&Term> {
+        if self.terms.len() == 1 {
+            self.terms.values().next()
+        } else {
+            None
+        }
+    }
+
+    fn add_term(&mut self, term: Term) -> Result<()> {
+        if term.coefficient.is_zero() {
+            return Ok(());
+        }
+
+        let key = term.key();
+        match self.terms.get_mut(&key) {
+            Some(existing) => {
+                let sum = existing.coefficient.checked_add(term.coefficient)?;
+                if sum.is_zero() {
+                    self.terms.remove(&key);
+                } else {
+                    existing.coefficient = sum;
+                }
+            }
+            None => {
+                if self.terms.len() >= MAX_TERMS {
+                    return Err(VeritasError::ComplexityLimit(self.terms.len() + 1));
+                }
+                self.terms.insert(key, term);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sum of two normal forms
+    pub fn add(&self, other: &NormalForm) -> Result<Self> {
+        let mut sum = self.clone();
+        for term in other.terms.values() {
+            sum.add_term(term.clone())?;
+        }
+        Ok(sum)
+    }
+
+    /// Multiply every coefficient by `factor`
+    pub fn scale(&self, factor: Scalar) -> Result<Self> {
+        let mut scaled = NormalForm::zero();
+        for term in self.terms.values() {
+            scaled.add_term(Term {
+                coefficient: term.coefficient.checked_mul(factor)?,
+                factors: term.factors.clone(),
+            })?;
+        }
+        Ok(scaled)
+    }
+
+    /// Multiply by a single term
+    pub fn mul_term(&self, term: &Term) -> Result<Self> {
+        let mut product = NormalForm::zero();
+        for t in self.terms.values() {
+            product.add_term(t.mul(term)?)?;
+        }
+        Ok(product)
+    }
+
+    /// Product of two normal forms (fully distributed)
+    pub fn mul(&self, other: &NormalForm) -> Result<Self> {
+        let mut product = NormalForm::zero();
+        for a in self.terms.values() {
+            for b in other.terms.values() {
+                product.add_term(a.mul(b)?)?;
+            }
+        }
+        Ok(product)
+    }
+
+    /// Raise to a numeric power
+    ///
+    /// Integer powers distribute over single terms and expand small
+    /// powers of sums. Fractional powers only distribute where that is
+    /// an identity (a bare factor, or a positive coefficient); anything
+    /// else becomes an opaque factor, since (x²)^½ is |x|, not x.
+    pub fn power(&self, p: Scalar) -> Result<Self> {
+        if p.is_zero() {
+            return NormalForm::constant(Scalar::ONE);
+        }
+
+        if let Some(n) = p.to_i64() {
+            if self.is_zero() {
+                return if n > 0 {
+                    Ok(NormalForm::zero())
+                } else {
+                    Err(VeritasError::DivisionByZero)
+                };
+            }
+            if let Some(term) = self.as_single_term() {
+                return Ok(NormalForm::from_term(term.powi(n)?));
+            }
+            if n > 0 && n <= MAX_EXPANSION_POWER {
+                let mut result = self.clone();
+                for _ in 1..n {
+                    result = result.mul(self)?;
+                }
+                return Ok(result);
+            }
+        } else if let Some(term) = self.as_single_term() {
+            if term.factors.len() == 1
+                && term.factors[0].exponent == Scalar::ONE
+                && !term.coefficient.is_negative()
+            {
+                let coefficient = if term.coefficient == Scalar::ONE {
+                    Scalar::ONE
+                } else {
+                    term.coefficient.pow(p)?
+                };
+                return Ok(NormalForm::from_term(Term {
+                    coefficient,
+                    factors: vec![Factor::new(term.factors[0].base.clone(), p)],
+                }));
+            }
+        }
+
+        Ok(NormalForm::from_term(Term {
+            coefficient: Scalar::ONE,
+            factors: vec![Factor::new(self.to_expr(), p)],
+        }))
+    }
+
+    /// Find a `var`-free term `c` such that `self = c * other`
+    pub fn constant_ratio(&self, other: &NormalForm, var: &str) -> Result<Option<Term>> {
+        let lead = match other.terms.values().next() {
+            Some(t) => t.inverse()?,
+            None => return Ok(None),
+        };
+
+        for term in self.terms.values() {
+            let candidate = term.mul(&lead)?;
+            if candidate.is_constant_in(var) && self.approx_eq(&other.mul_term(&candidate)?) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Term-by-term comparison with a relative coefficient tolerance
+    pub fn approx_eq(&self, other: &NormalForm) -> bool {
+        let keys = self.terms.keys().chain(other.terms.keys());
+        for key in keys {
+            let a = self.terms.get(key).map_or(Scalar::ZERO, |t| t.coefficient);
+            let b = other.terms.get(key).map_or(Scalar::ZERO, |t| t.coefficient);
+            if !coefficients_close(a, b) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Canonical rendering of a subexpression (used inside opaque atoms)
+fn canonical(expr: &Expr) -> Result<Expr> {
+    Ok(NormalForm::from_expr(expr)?.to_expr())
+}
+
+/// Relative tolerance for coefficient comparison (2⁻⁴⁰)
+fn tolerance() -> Scalar {
+    let two_20 = Scalar::from(1 << 20);
+    Scalar::ONE / (two_20 * two_20)
+}
+
+fn coefficients_close(a: Scalar, b: Scalar) -> bool {
+    let diff = (a - b).abs();
+    let mut scale = Scalar::ONE;
+    for c in [a.abs(), b.abs()] {
+        if c.inner() > scale.inner() {
+            scale = c;
+        }
+    }
+    diff.inner() <= (scale * tolerance()).inner()
+}
+
+/// Check that two expressions are algebraically equivalent
+///
+/// Sound but incomplete: `true` means both sides reduce to the same
+/// canonical form; `false` means this normal form cannot show it.
+pub fn equivalent(a: &Expr, b: &Expr) -> Result<bool> {
+    let lhs = NormalForm::from_expr(a)?;
+    let rhs = NormalForm::from_expr(b)?;
+    Ok(lhs.approx_eq(&rhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_terms_combine() {
+        // x*x + x*x ≡ 2*x^2
+        let x = Expr::var("x");
+        let lhs = Expr::add(Expr::mul(x.clone(), x.clone()), Expr::mul(x.clone(), x.clone()));
+        let rhs = Expr::mul(Expr::number(2), Expr::pow(x, Expr::number(2)));
+        assert!(equivalent(&lhs, &rhs).unwrap());
+    }
+
+    #[test]
+    fn test_binomial_expansion() {
+        // (x + 1)^2 ≡ x^2 + 2x + 1
+        let x = Expr::var("x");
+        let lhs = Expr::pow(Expr::add(x.clone(), Expr::number(1)), Expr::number(2));
+        let rhs = Expr::add(
+            Expr::add(
+                Expr::pow(x.clone(), Expr::number(2)),
+                Expr::mul(Expr::number(2), x),
+            ),
+            Expr::number(1),
+        );
+        assert!(equivalent(&lhs, &rhs).unwrap());
+    }
+
+    #[test]
+    fn test_rounded_coefficients() {
+        // (3 * x^2) / 3 ≡ x^2 despite 1/3 not being exact
+        let x2 = Expr::pow(Expr::var("x"), Expr::number(2));
+        let lhs = Expr::div(Expr::mul(Expr::number(3), x2.clone()), Expr::number(3));
+        assert!(equivalent(&lhs, &x2).unwrap());
+    }
+
+    #[test]
+    fn test_not_equivalent() {
+        let x = Expr::var("x");
+        let lhs = Expr::sqrt(Expr::pow(x.clone(), Expr::number(2)));
+        assert!(!equivalent(&lhs, &x).unwrap());
+        assert!(!equivalent(&Expr::sin(x.clone()), &Expr::cos(x)).unwrap());
+    }
+
+    #[test]
+    fn test_constant_ratio() {
+        // 6x + 3 = 3 * (2x + 1)
+        let x = Expr::var("x");
+        let a = NormalForm::from_expr(&Expr::add(
+            Expr::mul(Expr::number(6), x.clone()),
+            Expr::number(3),
+        ))
+        .unwrap();
+        let b = NormalForm::from_expr(&Expr::add(
+            Expr::mul(Expr::number(2), x),
+            Expr::number(1),
+        ))
+        .unwrap();
+        let ratio = a.constant_ratio(&b, "x").unwrap().unwrap();
+        assert_eq!(ratio.to_expr(), Expr::number(3));
+    }
+}
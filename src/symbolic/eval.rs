@@ -1,178 +1,572 @@
 //! Expression evaluation
 //!
 //! Evaluates symbolic expressions to numeric values using Spirix.
-
-use super::context::Value;
+//! Comparisons and logical connectives evaluate to `Value::Bool`;
+//! ordering follows `Scalar::compare`, so vanished, exploded and
+//! undefined operands are ordered where that is sound and errors
+//! otherwise. Quantities carry their dimension through arithmetic;
+//! adding or comparing unlike dimensions is a `DimensionMismatch`.
+//! Vectors and matrices evaluate to `Value::Array`, and combining arrays
+//! of the wrong shapes is a `ShapeMismatch`. Sums and products run over
+//! their integer range term by term, and a let evaluates its value before
+//! binding it in a new scope for the body. A quotient that comes out
+//! 0/0 in a single variable takes its limit at that point when the
+//! singularity is removable. `evaluate_within` charges every node to an
+//! `EvalBudget` and stops as soon as any of its limits is passed.
+
+use super::budget::{EvalBudget, Meter};
+use super::complex;
+use super::context::{is_builtin, Binding, Value};
+use super::linalg::{self, shape_mismatch, Array};
+use super::series::{limit_within, Direction, Limit};
+use crate::autograd::Shape;
+use super::units::{mismatch, Dimension, Quantity};
 use super::{Context, Expr};
 use crate::error::{Result, VeritasError};
 use crate::numeric::{Circle, Scalar};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+/// Most terms a sum or product is evaluated over
+pub const MAX_TERMS: i128 = 1 << 20;
 
 /// Trait for evaluating expressions
 pub trait Evaluate {
     /// Evaluate expression in given context
     fn evaluate(&self, ctx: &Context) -> Result<Value>;
 
+    /// Evaluate, failing with `BudgetExceeded` once `budget` is spent
+    fn evaluate_within(&self, ctx: &Context, budget: &EvalBudget) -> Result<Value>;
+
     /// Evaluate to scalar (error if result is complex)
     fn evaluate_scalar(&self, ctx: &Context) -> Result<Scalar>;
 
     /// Evaluate to circle (converts scalar if needed)
     fn evaluate_circle(&self, ctx: &Context) -> Result<Circle>;
+
+    /// Evaluate to truth value (error if result is numeric)
+    fn evaluate_bool(&self, ctx: &Context) -> Result<bool>;
 }
 
 impl Evaluate for Expr {
     fn evaluate(&self, ctx: &Context) -> Result<Value> {
+        self.eval(ctx, &mut Meter::default())
+    }
+
+    fn evaluate_within(&self, ctx: &Context, budget: &EvalBudget) -> Result<Value> {
+        self.eval(ctx, &mut Meter::new(budget))
+    }
+
+    fn evaluate_scalar(&self, ctx: &Context) -> Result<Scalar> {
+        as_scalar(self.evaluate(ctx)?)
+    }
+
+    fn evaluate_circle(&self, ctx: &Context) -> Result<Circle> {
+        as_circle(self.evaluate(ctx)?)
+    }
+
+    fn evaluate_bool(&self, ctx: &Context) -> Result<bool> {
+        as_bool(self.evaluate(ctx)?)
+    }
+}
+
+impl Expr {
+    /// Evaluate, charging every node and the value it produces to `meter`
+    pub(crate) fn eval(&self, ctx: &Context, meter: &mut Meter) -> Result<Value> {
+        meter.enter()?;
+        let value = self.eval_node(ctx, meter);
+        meter.leave();
+        let value = value?;
+        meter.check(&value)?;
+        Ok(value)
+    }
+
+    fn eval_scalar(&self, ctx: &Context, meter: &mut Meter) -> Result<Scalar> {
+        as_scalar(self.eval(ctx, meter)?)
+    }
+
+    fn eval_bool(&self, ctx: &Context, meter: &mut Meter) -> Result<bool> {
+        as_bool(self.eval(ctx, meter)?)
+    }
+
+    fn eval_node(&self, ctx: &Context, meter: &mut Meter) -> Result<Value> {
         match self {
             // Atomic values
             Expr::Number(n) => Ok(Value::Scalar(*n)),
             Expr::Complex(c) => Ok(Value::Circle(*c)),
+            Expr::Quantity(n, unit) => Ok(Quantity::new(*n, unit)?.into()),
 
-            Expr::Variable(name) => Ok(ctx.get(name)?.clone()),
+            Expr::Vector(entries) => {
+                let entries: Result<Vec<Scalar>> =
+                    entries.iter().map(|e| e.eval_scalar(ctx, meter)).collect();
+                Ok(Value::Array(Array::vector(entries?)))
+            }
+            Expr::Matrix(rows) => {
+                let rows: Result<Vec<Vec<Scalar>>> = rows
+                    .iter()
+                    .map(|row| row.iter().map(|e| e.eval_scalar(ctx, meter)).collect())
+                    .collect();
+                Ok(Value::Array(Array::from_rows(rows?)?))
+            }
 
-            Expr::Constant(name) => match name.as_str() {
-                "π" | "pi" => Ok(Value::Scalar(Scalar::PI)),
-                "e" => Ok(Value::Scalar(Scalar::E)),
-                "i" => Ok(Value::Circle(Circle::I)),
-                _ => Err(VeritasError::VariableNotFound(format!(
-                    "Unknown constant: {}",
-                    name
-                ))),
-            },
+            Expr::Variable(name) => variable(name, ctx, meter),
+            Expr::Constant(name) => constant(name),
 
             // Binary operations - try scalar first, fallback to circle
-            Expr::Add(a, b) => {
-                let a_val = a.evaluate(ctx)?;
-                let b_val = b.evaluate(ctx)?;
-
-                match (a_val, b_val) {
-                    (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_add(b)?)),
-                    (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_add(b)?)),
-                    (Value::Scalar(a), Value::Circle(b)) | (Value::Circle(b), Value::Scalar(a)) => {
-                        Ok(Value::Circle(Circle::from(a).checked_add(b)?))
-                    }
-                }
+            Expr::Add(a, b) => add(a.eval(ctx, meter)?, b.eval(ctx, meter)?),
+            Expr::Sub(a, b) => sub(a.eval(ctx, meter)?, b.eval(ctx, meter)?),
+            Expr::Mul(a, b) => mul(a.eval(ctx, meter)?, b.eval(ctx, meter)?),
+            Expr::Div(a, b) => {
+                let (a, b) = (a.eval(ctx, meter)?, b.eval(ctx, meter)?);
+                div(a, b, || self, ctx, meter)
             }
 
-            Expr::Sub(a, b) => {
-                let a_val = a.evaluate(ctx)?;
-                let b_val = b.evaluate(ctx)?;
+            // The exponent is only evaluated for a base that can be raised
+            Expr::Pow(base, exp) => {
+                let base = base.eval(ctx, meter)?;
+                check_base(&base)?;
+                pow(base, exp.eval_scalar(ctx, meter)?)
+            }
 
-                match (a_val, b_val) {
-                    (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_sub(b)?)),
-                    (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_sub(b)?)),
-                    (Value::Scalar(a), Value::Circle(b)) => {
-                        Ok(Value::Circle(Circle::from(a).checked_sub(b)?))
-                    }
-                    (Value::Circle(a), Value::Scalar(b)) => {
-                        Ok(Value::Circle(a.checked_sub(Circle::from(b))?))
-                    }
+            Expr::MatMul(a, b) => matmul(a.eval(ctx, meter)?, b.eval(ctx, meter)?),
+
+            // Unary operations
+            Expr::Neg(a) => neg(a.eval(ctx, meter)?),
+            Expr::Sqrt(a) => sqrt(a.eval(ctx, meter)?),
+            Expr::Ln(a) => Ok(Value::Scalar(a.eval_scalar(ctx, meter)?.ln()?)),
+            Expr::Exp(a) => exp(a.eval(ctx, meter)?),
+            Expr::Sin(a) => Ok(Value::Scalar(a.eval_scalar(ctx, meter)?.sin()?)),
+            Expr::Cos(a) => Ok(Value::Scalar(a.eval_scalar(ctx, meter)?.cos()?)),
+            Expr::Tan(a) => Ok(Value::Scalar(tan(a.eval_scalar(ctx, meter)?)?)),
+
+            Expr::Function(name, args) => match ctx.function(name) {
+                Some(def) => def.apply(name, args)?.eval(ctx, meter),
+                None if is_builtin(name) => {
+                    let values: Result<Vec<Value>> =
+                        args.iter().map(|a| a.eval(ctx, meter)).collect();
+                    builtin(name, &values?)
                 }
+                None => Err(unknown_function(name)),
+            },
+
+            // Predicates
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+
+            Expr::Eq(a, b) | Expr::Ne(a, b) => {
+                let same = equal(&a.eval(ctx, meter)?, &b.eval(ctx, meter)?)?;
+                Ok(Value::Bool(same == matches!(self, Expr::Eq(..))))
+            }
+
+            Expr::Lt(a, b) | Expr::Le(a, b) | Expr::Gt(a, b) | Expr::Ge(a, b) => {
+                let ordering = order(a.eval(ctx, meter)?, b.eval(ctx, meter)?)?;
+                Ok(Value::Bool(match self {
+                    Expr::Lt(..) => ordering.is_lt(),
+                    Expr::Le(..) => ordering.is_le(),
+                    Expr::Gt(..) => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }))
             }
 
-            Expr::Mul(a, b) => {
-                let a_val = a.evaluate(ctx)?;
-                let b_val = b.evaluate(ctx)?;
+            // Connectives short-circuit, so guards like x ≠ 0 ∧ 1/x > 2 are safe
+            Expr::And(a, b) => Ok(Value::Bool(
+                a.eval_bool(ctx, meter)? && b.eval_bool(ctx, meter)?,
+            )),
+            Expr::Or(a, b) => Ok(Value::Bool(
+                a.eval_bool(ctx, meter)? || b.eval_bool(ctx, meter)?,
+            )),
+            Expr::Not(a) => Ok(Value::Bool(!a.eval_bool(ctx, meter)?)),
+            Expr::Implies(a, b) => Ok(Value::Bool(
+                !a.eval_bool(ctx, meter)? || b.eval_bool(ctx, meter)?,
+            )),
 
-                match (a_val, b_val) {
-                    (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_mul(b)?)),
-                    (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_mul(b)?)),
-                    (Value::Scalar(a), Value::Circle(b)) | (Value::Circle(b), Value::Scalar(a)) => {
-                        Ok(Value::Circle(Circle::from(a).checked_mul(b)?))
+            // Conditions are tried in order; later ones are never evaluated
+            Expr::Piecewise(branches, otherwise) => {
+                for (condition, value) in branches {
+                    if condition.eval_bool(ctx, meter)? {
+                        return value.eval(ctx, meter);
                     }
                 }
+                otherwise.eval(ctx, meter)
             }
 
-            Expr::Div(a, b) => {
-                let a_val = a.evaluate(ctx)?;
-                let b_val = b.evaluate(ctx)?;
-
-                match (a_val, b_val) {
-                    (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_div(b)?)),
-                    (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_div(b)?)),
-                    (Value::Scalar(a), Value::Circle(b)) => {
-                        Ok(Value::Circle(Circle::from(a).checked_div(b)?))
-                    }
-                    (Value::Circle(a), Value::Scalar(b)) => {
-                        Ok(Value::Circle(a.checked_div(Circle::from(b))?))
-                    }
+            // Term by term in index order, the index shadowing any outer value
+            Expr::Sum(index, lower, upper, body) => {
+                let mut inner = ctx.clone();
+                inner.push_scope();
+                let mut total = Value::Scalar(Scalar::ZERO);
+                let lower = bound(lower.eval(ctx, meter)?)?;
+                for k in range(lower, bound(upper.eval(ctx, meter)?)?)? {
+                    inner.bind(index.clone(), Scalar::from_i64(k));
+                    total = add(total, body.eval(&inner, meter)?)?;
                 }
+                Ok(total)
+            }
+            Expr::Product(index, lower, upper, body) => {
+                let mut inner = ctx.clone();
+                inner.push_scope();
+                let mut total = Value::Scalar(Scalar::ONE);
+                let lower = bound(lower.eval(ctx, meter)?)?;
+                for k in range(lower, bound(upper.eval(ctx, meter)?)?)? {
+                    inner.bind(index.clone(), Scalar::from_i64(k));
+                    total = mul(total, body.eval(&inner, meter)?)?;
+                }
+                Ok(total)
             }
 
-            Expr::Pow(base, exp) => {
-                let base_val = base.evaluate_scalar(ctx)?;
-                let exp_val = exp.evaluate_scalar(ctx)?;
-                Ok(Value::Scalar(base_val.pow(exp_val)?))
+            // The value is evaluated outside the new scope: let x = x + 1 sees the outer x
+            Expr::Let(name, value, body) => {
+                let value = value.eval(ctx, meter)?;
+                let mut inner = ctx.clone();
+                inner.push_scope();
+                inner.bind(name.clone(), value);
+                body.eval(&inner, meter)
             }
+        }
+    }
+}
 
-            // Unary operations
-            Expr::Neg(a) => match a.evaluate(ctx)? {
-                Value::Scalar(s) => Ok(Value::Scalar(-s)),
-                Value::Circle(c) => Ok(Value::Circle(-c)),
-            },
+// Each operation on already evaluated operands, shared with the compiled
+// form in `bytecode` so the two agree bit for bit
+
+/// Value of a bound variable, checked against what is assumed about it
+///
+/// A variable bound to an expression takes that expression's value in the
+/// scope it was bound in.
+pub(crate) fn variable(name: &str, ctx: &Context, meter: &mut Meter) -> Result<Value> {
+    let value = match ctx.resolve(name)? {
+        (_, Binding::Value(value)) => value.clone(),
+        (scope, Binding::Expr(expr)) => expr.eval(&ctx.enclosing(scope), meter)?,
+    };
+    ctx.assumptions().check(name, &value)?;
+    Ok(value)
+}
 
-            Expr::Sqrt(a) => match a.evaluate(ctx)? {
-                Value::Scalar(s) => {
-                    // sqrt of negative returns complex
-                    if s.inner() < spirix::ScalarF6E5::ZERO {
-                        let c = Circle::from(s);
-                        Ok(Value::Circle(c.sqrt()?))
-                    } else {
-                        Ok(Value::Scalar(s.sqrt()?))
-                    }
-                }
-                Value::Circle(c) => Ok(Value::Circle(c.sqrt()?)),
-            },
+pub(crate) fn constant(name: &str) -> Result<Value> {
+    match name {
+        "π" | "pi" => Ok(Value::Scalar(Scalar::PI)),
+        "e" => Ok(Value::Scalar(Scalar::E)),
+        "i" => Ok(Value::Circle(Circle::I)),
+        _ => Err(VeritasError::VariableNotFound(format!(
+            "Unknown constant: {}",
+            name
+        ))),
+    }
+}
 
-            Expr::Ln(a) => {
-                let val = a.evaluate_scalar(ctx)?;
-                Ok(Value::Scalar(val.ln()?))
-            }
+pub(crate) fn as_scalar(value: Value) -> Result<Scalar> {
+    match value {
+        Value::Scalar(s) => Ok(s),
+        Value::Circle(_) => Err(VeritasError::SimplificationError(
+            "Expression evaluates to complex number, not scalar".to_string(),
+        )),
+        Value::Bool(_) => Err(VeritasError::SimplificationError(
+            "Expression evaluates to boolean, not scalar".to_string(),
+        )),
+        Value::Quantity(q) => Err(mismatch(Dimension::NONE, q.dimension())),
+        Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
+    }
+}
 
-            Expr::Exp(a) => match a.evaluate(ctx)? {
-                Value::Scalar(s) => Ok(Value::Scalar(s.exp()?)),
-                Value::Circle(c) => Ok(Value::Circle(c.exp()?)),
-            },
+pub(crate) fn as_circle(value: Value) -> Result<Circle> {
+    match value {
+        Value::Scalar(s) => Ok(Circle::from(s)),
+        Value::Circle(c) => Ok(c),
+        Value::Bool(_) => Err(VeritasError::SimplificationError(
+            "Expression evaluates to boolean, not complex".to_string(),
+        )),
+        Value::Quantity(q) => Err(mismatch(Dimension::NONE, q.dimension())),
+        Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
+    }
+}
 
-            Expr::Sin(a) => {
-                let val = a.evaluate_scalar(ctx)?;
-                Ok(Value::Scalar(val.sin()?))
-            }
+pub(crate) fn as_bool(value: Value) -> Result<bool> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        _ => Err(VeritasError::SimplificationError(
+            "Expression evaluates to a number, not boolean".to_string(),
+        )),
+    }
+}
 
-            Expr::Cos(a) => {
-                let val = a.evaluate_scalar(ctx)?;
-                Ok(Value::Scalar(val.cos()?))
-            }
+pub(crate) fn unknown_function(name: &str) -> VeritasError {
+    VeritasError::SimplificationError(format!("Unknown function: {}", name))
+}
 
-            Expr::Tan(a) => {
-                let val = a.evaluate_scalar(ctx)?;
-                let sin = val.sin()?;
-                let cos = val.cos()?;
-                Ok(Value::Scalar(sin.checked_div(cos)?))
+/// Built-in function applied to evaluated arguments
+pub(crate) fn builtin(name: &str, args: &[Value]) -> Result<Value> {
+    if linalg::is_builtin(name) {
+        linalg::apply_builtin(name, args)
+    } else {
+        complex::apply_builtin(name, args)
+    }
+}
+
+/// Value of a 0/0 quotient of one variable, as its limit at the bound point
+///
+/// sin(x)/x at x = 0 is 1. Anything the limit does not settle stays a
+/// division by zero. The expansions and the limit's value are charged to
+/// `meter` like the rest of the evaluation.
+fn removable(quotient: &Expr, ctx: &Context, meter: &mut Meter) -> Result<Value> {
+    if let [var] = quotient.variables().as_slice() {
+        if let Value::Scalar(at) = variable(var, ctx, meter)? {
+            let point = Expr::Number(at);
+            match limit_within(quotient, var, &point, Direction::Both, meter) {
+                Ok(Limit::Value(value)) => return value.eval(ctx, meter),
+                Err(e @ VeritasError::BudgetExceeded { .. }) => return Err(e),
+                _ => {}
             }
+        }
+    }
+    Err(VeritasError::DivisionByZero)
+}
 
-            Expr::Function(name, args) => Err(VeritasError::SimplificationError(format!(
-                "Unknown function: {}",
-                name
-            ))),
+pub(crate) fn add(a: Value, b: Value) -> Result<Value> {
+    match (a, b) {
+        (a, b) if has_array(&a, &b) => elementwise(&a, &b, "+", Scalar::checked_add),
+        (a, b) if has_quantity(&a, &b) => dimensional(&a, &b, "+", Quantity::checked_add),
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_add(b)?)),
+        (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_add(b)?)),
+        (Value::Scalar(a), Value::Circle(b)) | (Value::Circle(b), Value::Scalar(a)) => {
+            Ok(Value::Circle(Circle::from(a).checked_add(b)?))
         }
+        _ => Err(not_a_number("+")),
     }
+}
 
-    fn evaluate_scalar(&self, ctx: &Context) -> Result<Scalar> {
-        match self.evaluate(ctx)? {
-            Value::Scalar(s) => Ok(s),
-            Value::Circle(_) => Err(VeritasError::SimplificationError(
-                "Expression evaluates to complex number, not scalar".to_string(),
-            )),
+pub(crate) fn mul(a: Value, b: Value) -> Result<Value> {
+    match (a, b) {
+        (a, b) if has_array(&a, &b) => elementwise(&a, &b, "*", Scalar::checked_mul),
+        (a, b) if has_quantity(&a, &b) => dimensional(&a, &b, "*", Quantity::checked_mul),
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_mul(b)?)),
+        (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_mul(b)?)),
+        (Value::Scalar(a), Value::Circle(b)) | (Value::Circle(b), Value::Scalar(a)) => {
+            Ok(Value::Circle(Circle::from(a).checked_mul(b)?))
         }
+        _ => Err(not_a_number("*")),
     }
+}
 
-    fn evaluate_circle(&self, ctx: &Context) -> Result<Circle> {
-        match self.evaluate(ctx)? {
-            Value::Scalar(s) => Ok(Circle::from(s)),
-            Value::Circle(c) => Ok(c),
+pub(crate) fn sub(a: Value, b: Value) -> Result<Value> {
+    match (a, b) {
+        (a, b) if has_array(&a, &b) => elementwise(&a, &b, "-", Scalar::checked_sub),
+        (a, b) if has_quantity(&a, &b) => dimensional(&a, &b, "-", Quantity::checked_sub),
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_sub(b)?)),
+        (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_sub(b)?)),
+        (Value::Scalar(a), Value::Circle(b)) => Ok(Value::Circle(Circle::from(a).checked_sub(b)?)),
+        (Value::Circle(a), Value::Scalar(b)) => Ok(Value::Circle(a.checked_sub(Circle::from(b))?)),
+        _ => Err(not_a_number("-")),
+    }
+}
+
+/// Quotient of `a` and `b`, where `quotient` gives a/b itself for 0/0
+pub(crate) fn div<Q: Borrow<Expr>>(
+    a: Value,
+    b: Value,
+    quotient: impl FnOnce() -> Q,
+    ctx: &Context,
+    meter: &mut Meter,
+) -> Result<Value> {
+    match (a, b) {
+        (a, b) if has_array(&a, &b) => elementwise(&a, &b, "/", Scalar::checked_div),
+        (a, b) if has_quantity(&a, &b) => dimensional(&a, &b, "/", Quantity::checked_div),
+        (Value::Scalar(a), Value::Scalar(b)) if a.is_zero() && b.is_zero() => {
+            removable(quotient().borrow(), ctx, meter)
         }
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(a.checked_div(b)?)),
+        (Value::Circle(a), Value::Circle(b)) => Ok(Value::Circle(a.checked_div(b)?)),
+        (Value::Scalar(a), Value::Circle(b)) => Ok(Value::Circle(Circle::from(a).checked_div(b)?)),
+        (Value::Circle(a), Value::Scalar(b)) => Ok(Value::Circle(a.checked_div(Circle::from(b))?)),
+        _ => Err(not_a_number("/")),
+    }
+}
+
+/// Check that a base can be raised to a real power
+pub(crate) fn check_base(base: &Value) -> Result<()> {
+    match base {
+        Value::Scalar(_) | Value::Quantity(_) => Ok(()),
+        other => Err(not_raisable(other)),
+    }
+}
+
+pub(crate) fn pow(base: Value, exp: Scalar) -> Result<Value> {
+    match base {
+        Value::Scalar(b) => Ok(Value::Scalar(b.pow(exp)?)),
+        Value::Quantity(q) => Ok(q.checked_pow(exp)?.into()),
+        other => Err(not_raisable(&other)),
     }
 }
 
+fn not_raisable(base: &Value) -> VeritasError {
+    match base {
+        Value::Circle(_) => VeritasError::SimplificationError(
+            "Expression evaluates to complex number, not scalar".to_string(),
+        ),
+        Value::Array(a) => shape_mismatch("scalar", a.shape()),
+        _ => not_a_number("^"),
+    }
+}
+
+pub(crate) fn matmul(a: Value, b: Value) -> Result<Value> {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => Ok(Value::Array(a.matmul(&b)?)),
+        _ => Err(shape_mismatch("a vector or matrix", &Shape::scalar())),
+    }
+}
+
+pub(crate) fn neg(a: Value) -> Result<Value> {
+    match a {
+        Value::Scalar(s) => Ok(Value::Scalar(-s)),
+        Value::Circle(c) => Ok(Value::Circle(-c)),
+        Value::Quantity(q) => Ok(Value::Quantity(-q)),
+        Value::Array(a) => Ok(Value::Array(a.map(|x| Ok(-x))?)),
+        Value::Bool(_) => Err(not_a_number("-")),
+    }
+}
+
+/// Square root, complex for a negative number
+pub(crate) fn sqrt(a: Value) -> Result<Value> {
+    match a {
+        Value::Scalar(s) if s.inner() < spirix::ScalarF6E5::ZERO => {
+            Ok(Value::Circle(Circle::from(s).sqrt()?))
+        }
+        Value::Scalar(s) => Ok(Value::Scalar(s.sqrt()?)),
+        Value::Circle(c) => Ok(Value::Circle(c.sqrt()?)),
+        Value::Quantity(q) => Ok(q.sqrt()?.into()),
+        Value::Bool(_) => Err(not_a_number("√")),
+        Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
+    }
+}
+
+pub(crate) fn exp(a: Value) -> Result<Value> {
+    match a {
+        Value::Scalar(s) => Ok(Value::Scalar(s.exp()?)),
+        Value::Circle(c) => Ok(Value::Circle(c.exp()?)),
+        Value::Quantity(q) => Err(mismatch(Dimension::NONE, q.dimension())),
+        Value::Bool(_) => Err(not_a_number("exp")),
+        Value::Array(a) => Err(shape_mismatch("scalar", a.shape())),
+    }
+}
+
+pub(crate) fn tan(x: Scalar) -> Result<Scalar> {
+    x.sin()?.checked_div(x.cos()?)
+}
+
+/// Bound of a sum or product, which must be an integer
+pub(crate) fn bound(value: Value) -> Result<i64> {
+    let value = as_scalar(value)?;
+    value.to_i64().ok_or_else(|| {
+        VeritasError::InvalidInput(format!("Range bound {} is not an integer", value))
+    })
+}
+
+/// Integer range of a sum or product, empty when upper < lower
+pub(crate) fn range(lo: i64, hi: i64) -> Result<RangeInclusive<i64>> {
+    let count = i128::from(hi) - i128::from(lo) + 1;
+    if count > MAX_TERMS {
+        return Err(VeritasError::ComplexityLimit(count as usize));
+    }
+    Ok(lo..=hi)
+}
+
+/// Error for a truth value used where a number is needed
+fn not_a_number(op: &str) -> VeritasError {
+    VeritasError::SimplificationError(format!("Cannot apply {} to a boolean", op))
+}
+
+/// Real number or quantity as a quantity (numbers are dimensionless)
+fn as_quantity(value: &Value) -> Option<Quantity> {
+    match value {
+        Value::Scalar(s) => Some(Quantity::dimensionless(*s)),
+        Value::Quantity(q) => Some(*q),
+        Value::Circle(_) | Value::Bool(_) | Value::Array(_) => None,
+    }
+}
+
+fn has_array(a: &Value, b: &Value) -> bool {
+    matches!(a, Value::Array(_)) || matches!(b, Value::Array(_))
+}
+
+/// Element-wise arithmetic, broadcasting a real number over an array
+fn elementwise(
+    a: &Value,
+    b: &Value,
+    op: &str,
+    f: fn(&Scalar, Scalar) -> Result<Scalar>,
+) -> Result<Value> {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => Ok(Value::Array(a.zip(b, f)?)),
+        (Value::Array(a), Value::Scalar(s)) => Ok(Value::Array(a.map(|x| f(&x, *s))?)),
+        (Value::Scalar(s), Value::Array(b)) => Ok(Value::Array(b.map(|y| f(s, y))?)),
+        _ => Err(VeritasError::SimplificationError(format!(
+            "Cannot apply {} to an array and a non-real value",
+            op
+        ))),
+    }
+}
+
+fn has_quantity(a: &Value, b: &Value) -> bool {
+    matches!(a, Value::Quantity(_)) || matches!(b, Value::Quantity(_))
+}
+
+/// Arithmetic with units on at least one side
+fn dimensional(
+    a: &Value,
+    b: &Value,
+    op: &str,
+    f: fn(&Quantity, &Quantity) -> Result<Quantity>,
+) -> Result<Value> {
+    match (as_quantity(a), as_quantity(b)) {
+        (Some(a), Some(b)) => Ok(f(&a, &b)?.into()),
+        _ => Err(VeritasError::SimplificationError(format!(
+            "Cannot apply {} to a quantity and a non-real value",
+            op
+        ))),
+    }
+}
+
+/// Equality of two values (complex values compare componentwise)
+pub(crate) fn equal(a: &Value, b: &Value) -> Result<bool> {
+    let circle = |v: &Value| match v {
+        Value::Scalar(s) => Some(Circle::from(*s)),
+        Value::Circle(c) => Some(*c),
+        Value::Bool(_) | Value::Quantity(_) | Value::Array(_) => None,
+    };
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => Ok(a == b),
+        (Value::Array(a), Value::Array(b)) => a.equals(b),
+        (Value::Array(a), _) | (_, Value::Array(a)) => Err(shape_mismatch("scalar", a.shape())),
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(a.compare(b)? == Ordering::Equal),
+        (a, b) if has_quantity(a, b) => match (as_quantity(a), as_quantity(b)) {
+            (Some(a), Some(b)) => Ok(a.compare(&b)? == Ordering::Equal),
+            _ => Err(VeritasError::SimplificationError(
+                "Cannot compare a quantity with a non-real value".to_string(),
+            )),
+        },
+        _ => match (circle(a), circle(b)) {
+            (Some(a), Some(b)) => Ok(a.real().compare(&b.real())? == Ordering::Equal
+                && a.imag().compare(&b.imag())? == Ordering::Equal),
+            _ => Err(VeritasError::SimplificationError(
+                "Cannot compare a boolean with a number".to_string(),
+            )),
+        },
+    }
+}
+
+/// Order two real operands or quantities (complex numbers have no order)
+pub(crate) fn order(a: Value, b: Value) -> Result<Ordering> {
+    if has_quantity(&a, &b) {
+        return match (as_quantity(&a), as_quantity(&b)) {
+            (Some(a), Some(b)) => a.compare(&b),
+            _ => Err(VeritasError::SimplificationError(
+                "Cannot order a quantity and a non-real value".to_string(),
+            )),
+        };
+    }
+    as_scalar(a)?.compare(&as_scalar(b)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +605,21 @@ mod tests {
         assert_eq!(result, Scalar::from(21)); // (5 + 2) * 3 = 21
     }
 
+    #[test]
+    fn test_eval_checks_assumptions() {
+        use crate::symbolic::assumptions::Assumption;
+
+        let expr = Expr::sqrt(Expr::var("x"));
+        let mut ctx = Context::new();
+        ctx.assume("x", Assumption::Nonnegative);
+        ctx.bind("x", -4);
+
+        assert!(matches!(
+            expr.evaluate(&ctx),
+            Err(VeritasError::Contradiction(_))
+        ));
+    }
+
     #[test]
     fn test_eval_sqrt_negative() {
         // sqrt(-1) should return complex
@@ -226,4 +635,307 @@ mod tests {
             _ => panic!("Expected complex result"),
         }
     }
+
+    #[test]
+    fn test_eval_comparison() {
+        // 3 < π
+        let expr = Expr::less(Expr::number(3), crate::symbolic::constants::pi());
+        assert_eq!(expr.evaluate(&Context::new()).unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_eval_connectives_short_circuit() {
+        // x ≠ 0 ∧ 1/x > 2 is false (not an error) at x = 0
+        let x = Expr::var("x");
+        let expr = Expr::and(
+            Expr::not_equals(x.clone(), Expr::number(0)),
+            Expr::greater(Expr::div(Expr::number(1), x), Expr::number(2)),
+        );
+
+        let mut ctx = Context::new();
+        ctx.bind("x", 0);
+        assert!(!expr.evaluate_bool(&ctx).unwrap());
+
+        ctx.bind("x", 0.25);
+        assert!(expr.evaluate_bool(&ctx).unwrap());
+    }
+
+    #[test]
+    fn test_eval_removable_singularity() {
+        // sin(x)/x and (x² - 1)/(x - 1) are 0/0 at the point, but have limits
+        let x = Expr::var("x");
+        let sinc = Expr::div(Expr::sin(x.clone()), x.clone());
+        let mut ctx = Context::new();
+        ctx.bind("x", 0);
+        assert_eq!(sinc.evaluate_scalar(&ctx).unwrap(), Scalar::ONE);
+
+        let quotient = Expr::div(
+            Expr::sub(Expr::pow(x.clone(), Expr::number(2)), Expr::number(1)),
+            Expr::sub(x.clone(), Expr::number(1)),
+        );
+        ctx.bind("x", 1);
+        assert_eq!(quotient.evaluate_scalar(&ctx).unwrap(), Scalar::TWO);
+
+        // 1/x at 0 is not 0/0
+        ctx.bind("x", 0);
+        assert!(matches!(
+            Expr::div(Expr::number(1), x).evaluate(&ctx),
+            Err(VeritasError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_eval_bool_is_not_a_number() {
+        let expr = Expr::add(Expr::Bool(true), Expr::number(1));
+        assert!(expr.evaluate(&Context::new()).is_err());
+    }
+
+    #[test]
+    fn test_eval_piecewise() {
+        let expr = Expr::clamp(Expr::var("x"), Expr::number(0), Expr::number(1));
+        let mut ctx = Context::new();
+
+        for (x, expected) in [(-2, 0), (0, 0), (1, 1), (5, 1)] {
+            ctx.bind("x", x);
+            assert_eq!(expr.evaluate_scalar(&ctx).unwrap(), Scalar::from(expected));
+        }
+    }
+
+    #[test]
+    fn test_eval_sum_and_product() {
+        // Σ_{k=1}^{n} k² and n! at n = 5; the outer k does not leak in
+        let k = Expr::var("k");
+        let squares = Expr::sum(
+            "k",
+            Expr::number(1),
+            Expr::var("n"),
+            Expr::pow(k.clone(), Expr::number(2)),
+        );
+        let factorial = Expr::product("k", Expr::number(1), Expr::var("n"), k);
+        let mut ctx = Context::new();
+        ctx.bind("n", 5);
+        ctx.bind("k", 100);
+
+        assert_eq!(squares.evaluate_scalar(&ctx).unwrap(), Scalar::from(55));
+        assert_eq!(factorial.evaluate_scalar(&ctx).unwrap(), Scalar::from(120));
+
+        // Empty ranges are 0 and 1; fractional bounds are an error
+        ctx.bind("n", 0);
+        assert_eq!(squares.evaluate_scalar(&ctx).unwrap(), Scalar::ZERO);
+        assert_eq!(factorial.evaluate_scalar(&ctx).unwrap(), Scalar::ONE);
+        ctx.bind("n", 2.5);
+        assert!(matches!(squares.evaluate(&ctx), Err(VeritasError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_eval_let_and_scopes() {
+        // let x = x + 1 in 2x at x = 3: the value sees the outer x
+        let x = Expr::var("x");
+        let expr = Expr::let_in(
+            "x",
+            Expr::add(x.clone(), Expr::number(1)),
+            Expr::mul(Expr::number(2), x.clone()),
+        );
+        let mut ctx = Context::new();
+        ctx.bind("x", 3);
+        assert_eq!(expr.evaluate_scalar(&ctx).unwrap(), Scalar::from(8));
+
+        // a = 3x + 1 follows x in its own scope, not one opened later
+        let mut ctx = Context::new();
+        let a = Expr::var("a");
+        ctx.bind_expr("a", Expr::add(Expr::mul(Expr::number(3), x), Expr::number(1)))
+            .unwrap();
+        ctx.push_scope();
+        ctx.bind("x", 2);
+        assert!(matches!(a.evaluate(&ctx), Err(VeritasError::VariableNotFound(_))));
+        ctx.pop_scope().unwrap();
+        ctx.bind("x", 2);
+        assert_eq!(a.evaluate_scalar(&ctx).unwrap(), Scalar::from(7));
+
+        // A sum index does not capture the x in a
+        let sum = Expr::sum("x", Expr::number(1), Expr::number(3), a);
+        assert_eq!(sum.evaluate_scalar(&ctx).unwrap(), Scalar::from(21));
+    }
+
+    #[test]
+    fn test_eval_quantities() {
+        // 2 cup + 125 ml is a volume; 500 g + 1 cup is a mismatch
+        let cups = Expr::quantity(2, "cup").unwrap();
+        let ml = Expr::quantity(125, "ml").unwrap();
+        let grams = Expr::quantity(500, "g").unwrap();
+        let ctx = Context::new();
+
+        let volume = Expr::add(cups.clone(), ml).evaluate(&ctx).unwrap();
+        assert!(matches!(volume, Value::Quantity(q) if q.dimension() == Dimension::VOLUME));
+        assert!(matches!(
+            Expr::add(grams.clone(), cups).evaluate(&ctx),
+            Err(VeritasError::DimensionMismatch { .. })
+        ));
+
+        // 1 kg / 500 g is the plain number 2, and 1 kg > 500 g
+        let kg = Expr::quantity(1, "kg").unwrap();
+        assert_eq!(Expr::div(kg.clone(), grams.clone()).evaluate_scalar(&ctx).unwrap(), Scalar::TWO);
+        assert!(Expr::greater(kg, grams).evaluate_bool(&ctx).unwrap());
+    }
+
+    #[test]
+    fn test_eval_layer() {
+        let numbers = |rows: &[&[f64]]| {
+            Array::from_rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|&x| Scalar::from(x)).collect())
+                    .collect(),
+            )
+            .unwrap()
+        };
+        let mut ctx = Context::new();
+        ctx.bind("W", numbers(&[&[1.0, 2.0], &[3.0, 4.0]]));
+        ctx.bind("x", Array::vector(vec![Scalar::ONE, Scalar::ONE]));
+        ctx.bind("b", Array::vector(vec![Scalar::from(0.5), Scalar::from(-1)]));
+
+        // W·x + b = [3.5, 6]
+        let layer = Expr::add(Expr::matmul(Expr::var("W"), Expr::var("x")), Expr::var("b"));
+        assert_eq!(
+            layer.evaluate(&ctx).unwrap(),
+            Value::Array(Array::vector(vec![Scalar::from(3.5), Scalar::from(6)]))
+        );
+        assert_eq!(Expr::det(Expr::var("W")).evaluate_scalar(&ctx).unwrap(), Scalar::from(-2));
+        assert_eq!(
+            Expr::dot(Expr::var("x"), Expr::var("b")).evaluate_scalar(&ctx).unwrap(),
+            Scalar::from(-0.5)
+        );
+
+        // A vector of 3 fits neither W nor x
+        ctx.bind("y", Array::vector(vec![Scalar::ONE; 3]));
+        assert!(matches!(
+            Expr::matmul(Expr::var("W"), Expr::var("y")).evaluate(&ctx),
+            Err(VeritasError::ShapeMismatch { .. })
+        ));
+        assert!(matches!(
+            Expr::add(Expr::var("x"), Expr::var("y")).evaluate(&ctx),
+            Err(VeritasError::ShapeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_relu_matches_autograd() {
+        use crate::autograd::{relu, Shape, Tensor};
+        use spirix::ScalarF4E4;
+
+        let inputs: Vec<i32> = (-3..=3).collect();
+        let tensor = Tensor::from_scalars(
+            inputs.iter().map(|&i| ScalarF4E4::from(i)).collect(),
+            Shape::vector(inputs.len()),
+        )
+        .unwrap();
+        let activated = relu(&tensor).unwrap();
+        let outputs = activated.as_scalars().unwrap();
+
+        let expr = Expr::relu(Expr::var("x"));
+        let mut ctx = Context::new();
+        for (&x, &y) in inputs.iter().zip(outputs) {
+            ctx.bind("x", x);
+            let symbolic = expr.evaluate_scalar(&ctx).unwrap();
+            assert_eq!(ScalarF4E4::from(symbolic.to_i64().unwrap() as i32), y);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_within_budget() {
+        use crate::symbolic::{EvalBudget, Resource, Usage};
+
+        let mut ctx = Context::new();
+        let budget = EvalBudget::default();
+
+        // f₀(t) = t + t and fₖ(t) = fₖ₋₁(fₖ₋₁(t)): f₃₀ is 2³⁰ additions
+        ctx.define("f0", vec!["t".to_string()], Expr::add(Expr::var("t"), Expr::var("t")))
+            .unwrap();
+        for k in 1..=30 {
+            let call = |arg| Expr::Function(format!("f{}", k - 1), vec![arg]);
+            ctx.define(format!("f{}", k), vec!["t".to_string()], call(call(Expr::var("t"))))
+                .unwrap();
+        }
+        let expensive = Expr::Function("f30".to_string(), vec![Expr::number(1)]);
+        let tight = EvalBudget {
+            max_operations: 10_000,
+            ..budget
+        };
+        for result in [
+            expensive.evaluate_within(&ctx, &tight),
+            expensive.compile().evaluate_within(&ctx, &tight),
+        ] {
+            assert!(matches!(
+                result,
+                Err(VeritasError::BudgetExceeded {
+                    resource: Resource::Operations,
+                    used: Usage { operations: 10_001, .. },
+                })
+            ));
+        }
+
+        // 10²⁰⁰⁰ is fine as a scalar, but has too many digits to write out
+        let huge = Expr::pow(Expr::number(10), Expr::number(2000));
+        assert!(huge.evaluate(&ctx).is_ok());
+        assert!(matches!(
+            huge.evaluate_within(&ctx, &budget),
+            Err(VeritasError::BudgetExceeded { resource: Resource::Digits, used })
+                if used.digits >= 2000
+        ));
+
+        let mut deep = Expr::number(1);
+        for _ in 0..50 {
+            deep = Expr::neg(deep);
+        }
+        let shallow = EvalBudget {
+            max_depth: 20,
+            ..budget
+        };
+        assert!(matches!(
+            deep.evaluate_within(&ctx, &shallow),
+            Err(VeritasError::BudgetExceeded {
+                resource: Resource::Depth,
+                used: Usage { depth: 21, .. },
+            })
+        ));
+        assert_eq!(deep.evaluate_within(&ctx, &budget).unwrap(), Value::Scalar(Scalar::ONE));
+
+        // sin(x)/x at 0 is a handful of nodes, but its limit takes expansions
+        let sinc = Expr::div(Expr::sin(Expr::var("x")), Expr::var("x"));
+        ctx.bind("x", 0);
+        let small = EvalBudget {
+            max_operations: 20,
+            ..budget
+        };
+        assert!(sinc.evaluate_within(&ctx, &budget).is_ok());
+        for result in [
+            sinc.evaluate_within(&ctx, &small),
+            sinc.compile().evaluate_within(&ctx, &small),
+        ] {
+            assert!(matches!(
+                result,
+                Err(VeritasError::BudgetExceeded {
+                    resource: Resource::Operations,
+                    ..
+                })
+            ));
+        }
+
+        // x + (10²⁰⁰⁰ - 10²⁰⁰⁰) compiles to x + 0, but a budget still pays for 10²⁰⁰⁰
+        let cancelled = Expr::add(Expr::var("x"), Expr::sub(huge.clone(), huge));
+        let program = cancelled.compile();
+        assert_eq!(program.len(), 2);
+        assert!(program.evaluate(&ctx).is_ok());
+        for result in [
+            cancelled.evaluate_within(&ctx, &budget),
+            program.evaluate_within(&ctx, &budget),
+        ] {
+            assert!(matches!(
+                result,
+                Err(VeritasError::BudgetExceeded {
+                    resource: Resource::Digits,
+                    ..
+                })
+            ));
+        }
+    }
 }
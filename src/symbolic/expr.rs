@@ -6,6 +6,7 @@
 //! - Differentiated (symbolically)
 //! - Compared (structurally)
 
+use super::units::Unit;
 use crate::error::{Result, VeritasError};
 use crate::numeric::{Circle, Scalar};
 use std::fmt;
@@ -29,6 +30,9 @@ pub enum Expr {
     /// Named constant (e.g., "π", "e")
     Constant(String),
 
+    /// Physical quantity: a magnitude in a unit (e.g., 500 g)
+    Quantity(Scalar, Unit),
+
     // Binary operations
     /// Addition: a + b
     Add(Box<Expr>, Box<Expr>),
@@ -71,6 +75,68 @@ pub enum Expr {
     // Function application (general)
     /// Function call: f(args...)
     Function(String, Vec<Expr>),
+
+    // Linear algebra
+    /// Column vector: [a, b, ...]
+    Vector(Vec<Expr>),
+
+    /// Matrix, as a list of rows
+    Matrix(Vec<Vec<Expr>>),
+
+    /// Matrix product: A·B
+    MatMul(Box<Expr>, Box<Expr>),
+
+    // Predicates
+    /// Truth value
+    Bool(bool),
+
+    /// Equality: a = b
+    Eq(Box<Expr>, Box<Expr>),
+
+    /// Inequality: a ≠ b
+    Ne(Box<Expr>, Box<Expr>),
+
+    /// Less than: a < b
+    Lt(Box<Expr>, Box<Expr>),
+
+    /// Less or equal: a ≤ b
+    Le(Box<Expr>, Box<Expr>),
+
+    /// Greater than: a > b
+    Gt(Box<Expr>, Box<Expr>),
+
+    /// Greater or equal: a ≥ b
+    Ge(Box<Expr>, Box<Expr>),
+
+    /// Conjunction: a ∧ b
+    And(Box<Expr>, Box<Expr>),
+
+    /// Disjunction: a ∨ b
+    Or(Box<Expr>, Box<Expr>),
+
+    /// Negation: ¬a
+    Not(Box<Expr>),
+
+    /// Implication: a ⇒ b
+    Implies(Box<Expr>, Box<Expr>),
+
+    // Conditional
+    /// Piecewise: the value of the first branch whose condition holds,
+    /// else the fallback
+    Piecewise(Vec<(Expr, Expr)>, Box<Expr>),
+
+    // Big operators
+    /// Sum over an integer range: Σ_{k=a}^{b} f, as (k, a, b, f).
+    /// The index is bound in the body; an empty range sums to 0
+    Sum(String, Box<Expr>, Box<Expr>, Box<Expr>),
+
+    /// Product over an integer range: Π_{k=a}^{b} f; an empty range is 1
+    Product(String, Box<Expr>, Box<Expr>, Box<Expr>),
+
+    // Binding
+    /// Local definition: let x = a in b. The name is bound in the body
+    /// only, where it shadows any outer x; the value sees the outer scope
+    Let(String, Box<Expr>, Box<Expr>),
 }
 
 impl Expr {
@@ -91,6 +157,11 @@ impl Expr {
         Expr::Variable(name.into())
     }
 
+    /// Create quantity expression from a unit symbol ("g", "cup", "°C", ...)
+    pub fn quantity<T: Into<Scalar>>(value: T, unit: &str) -> Result<Self> {
+        Ok(Expr::Quantity(value.into(), Unit::parse(unit)?))
+    }
+
     /// Create addition
     pub fn add(lhs: Expr, rhs: Expr) -> Self {
         Expr::Add(Box::new(lhs), Box::new(rhs))
@@ -126,26 +197,202 @@ impl Expr {
         Expr::Sqrt(Box::new(expr))
     }
 
+    /// Create natural logarithm
+    pub fn ln(expr: Expr) -> Self {
+        Expr::Ln(Box::new(expr))
+    }
+
+    /// Create exponential
+    pub fn exp(expr: Expr) -> Self {
+        Expr::Exp(Box::new(expr))
+    }
+
+    /// Create sine
+    pub fn sin(expr: Expr) -> Self {
+        Expr::Sin(Box::new(expr))
+    }
+
+    /// Create cosine
+    pub fn cos(expr: Expr) -> Self {
+        Expr::Cos(Box::new(expr))
+    }
+
+    /// Create tangent
+    pub fn tan(expr: Expr) -> Self {
+        Expr::Tan(Box::new(expr))
+    }
+
+    /// Create equality a = b
+    pub fn equals(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Eq(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create inequality a ≠ b
+    pub fn not_equals(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Ne(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create comparison a < b
+    pub fn less(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Lt(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create comparison a ≤ b
+    pub fn less_eq(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Le(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create comparison a > b
+    pub fn greater(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Gt(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create comparison a ≥ b
+    pub fn greater_eq(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Ge(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create conjunction
+    pub fn and(lhs: Expr, rhs: Expr) -> Self {
+        Expr::And(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create disjunction
+    pub fn or(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Or(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create logical negation
+    pub fn not(expr: Expr) -> Self {
+        Expr::Not(Box::new(expr))
+    }
+
+    /// Create implication
+    pub fn implies(lhs: Expr, rhs: Expr) -> Self {
+        Expr::Implies(Box::new(lhs), Box::new(rhs))
+    }
+
+    /// Create piecewise expression from (condition, value) branches
+    pub fn piecewise(branches: Vec<(Expr, Expr)>, otherwise: Expr) -> Self {
+        Expr::Piecewise(branches, Box::new(otherwise))
+    }
+
+    /// Create sum Σ_{index=lower}^{upper} body
+    pub fn sum(index: impl Into<String>, lower: Expr, upper: Expr, body: Expr) -> Self {
+        Expr::Sum(index.into(), Box::new(lower), Box::new(upper), Box::new(body))
+    }
+
+    /// Create product Π_{index=lower}^{upper} body
+    pub fn product(index: impl Into<String>, lower: Expr, upper: Expr, body: Expr) -> Self {
+        Expr::Product(index.into(), Box::new(lower), Box::new(upper), Box::new(body))
+    }
+
+    /// Create local definition: let name = value in body
+    pub fn let_in(name: impl Into<String>, value: Expr, body: Expr) -> Self {
+        Expr::Let(name.into(), Box::new(value), Box::new(body))
+    }
+
+    /// Absolute value: -x if x < 0, else x
+    pub fn abs(expr: Expr) -> Self {
+        Expr::piecewise(
+            vec![(Expr::less(expr.clone(), Expr::number(0)), Expr::neg(expr.clone()))],
+            expr,
+        )
+    }
+
+    /// ReLU: x if x > 0, else 0 (as `autograd::ops::relu`)
+    pub fn relu(expr: Expr) -> Self {
+        Expr::piecewise(
+            vec![(Expr::greater(expr.clone(), Expr::number(0)), expr)],
+            Expr::number(0),
+        )
+    }
+
+    /// Unit step: 1 if x > 0, else 0 (the derivative `relu_backward` uses)
+    pub fn step(expr: Expr) -> Self {
+        Expr::piecewise(
+            vec![(Expr::greater(expr, Expr::number(0)), Expr::number(1))],
+            Expr::number(0),
+        )
+    }
+
+    /// Clamp x to [lo, hi]
+    pub fn clamp(expr: Expr, lo: Expr, hi: Expr) -> Self {
+        Expr::piecewise(
+            vec![
+                (Expr::less(expr.clone(), lo.clone()), lo),
+                (Expr::greater(expr.clone(), hi.clone()), hi),
+            ],
+            expr,
+        )
+    }
+
     // Query methods
 
+    /// Check if expression is a predicate (evaluates to a truth value)
+    pub fn is_predicate(&self) -> bool {
+        matches!(
+            self,
+            Expr::Bool(_)
+                | Expr::Eq(..)
+                | Expr::Ne(..)
+                | Expr::Lt(..)
+                | Expr::Le(..)
+                | Expr::Gt(..)
+                | Expr::Ge(..)
+                | Expr::And(..)
+                | Expr::Or(..)
+                | Expr::Not(_)
+                | Expr::Implies(..)
+        )
+    }
+
     /// Check if expression is a constant (no variables)
     pub fn is_constant(&self) -> bool {
         match self {
-            Expr::Number(_) | Expr::Complex(_) | Expr::Constant(_) => true,
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..)
+            | Expr::Bool(_) => true,
             Expr::Variable(_) => false,
             Expr::Add(a, b)
             | Expr::Sub(a, b)
             | Expr::Mul(a, b)
             | Expr::Div(a, b)
-            | Expr::Pow(a, b) => a.is_constant() && b.is_constant(),
+            | Expr::Pow(a, b)
+            | Expr::MatMul(a, b)
+            | Expr::Eq(a, b)
+            | Expr::Ne(a, b)
+            | Expr::Lt(a, b)
+            | Expr::Le(a, b)
+            | Expr::Gt(a, b)
+            | Expr::Ge(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b) => a.is_constant() && b.is_constant(),
             Expr::Neg(a)
+            | Expr::Not(a)
             | Expr::Sqrt(a)
             | Expr::Ln(a)
             | Expr::Exp(a)
             | Expr::Sin(a)
             | Expr::Cos(a)
             | Expr::Tan(a) => a.is_constant(),
-            Expr::Function(_, args) => args.iter().all(|arg| arg.is_constant()),
+            Expr::Function(_, args) | Expr::Vector(args) => args.iter().all(|arg| arg.is_constant()),
+            Expr::Matrix(rows) => rows.iter().flatten().all(|entry| entry.is_constant()),
+            Expr::Piecewise(branches, otherwise) => {
+                branches.iter().all(|(c, v)| c.is_constant() && v.is_constant())
+                    && otherwise.is_constant()
+            }
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                lower.is_constant()
+                    && upper.is_constant()
+                    && body.variables().iter().all(|v| v == index)
+            }
+            Expr::Let(name, value, body) => {
+                value.is_constant() && body.variables().iter().all(|v| v == name)
+            }
         }
     }
 
@@ -158,6 +405,11 @@ impl Expr {
         vars
     }
 
+    /// Check if a variable occurs anywhere in the expression
+    pub fn contains_variable(&self, name: &str) -> bool {
+        self.variables().iter().any(|v| v == name)
+    }
+
     fn collect_variables(&self, vars: &mut Vec<String>) {
         match self {
             Expr::Variable(name) => vars.push(name.clone()),
@@ -165,11 +417,22 @@ impl Expr {
             | Expr::Sub(a, b)
             | Expr::Mul(a, b)
             | Expr::Div(a, b)
-            | Expr::Pow(a, b) => {
+            | Expr::Pow(a, b)
+            | Expr::MatMul(a, b)
+            | Expr::Eq(a, b)
+            | Expr::Ne(a, b)
+            | Expr::Lt(a, b)
+            | Expr::Le(a, b)
+            | Expr::Gt(a, b)
+            | Expr::Ge(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b) => {
                 a.collect_variables(vars);
                 b.collect_variables(vars);
             }
             Expr::Neg(a)
+            | Expr::Not(a)
             | Expr::Sqrt(a)
             | Expr::Ln(a)
             | Expr::Exp(a)
@@ -178,11 +441,33 @@ impl Expr {
             | Expr::Tan(a) => {
                 a.collect_variables(vars);
             }
-            Expr::Function(_, args) => {
+            Expr::Function(_, args) | Expr::Vector(args) => {
                 for arg in args {
                     arg.collect_variables(vars);
                 }
             }
+            Expr::Matrix(rows) => {
+                for entry in rows.iter().flatten() {
+                    entry.collect_variables(vars);
+                }
+            }
+            Expr::Piecewise(branches, otherwise) => {
+                for (condition, value) in branches {
+                    condition.collect_variables(vars);
+                    value.collect_variables(vars);
+                }
+                otherwise.collect_variables(vars);
+            }
+            // The index is bound, so only free occurrences count
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                lower.collect_variables(vars);
+                upper.collect_variables(vars);
+                vars.extend(body.variables().into_iter().filter(|v| v != index));
+            }
+            Expr::Let(name, value, body) => {
+                value.collect_variables(vars);
+                vars.extend(body.variables().into_iter().filter(|v| v != name));
+            }
             _ => {}
         }
     }
@@ -190,21 +475,198 @@ impl Expr {
     /// Calculate depth of expression tree
     pub fn depth(&self) -> usize {
         match self {
-            Expr::Number(_) | Expr::Complex(_) | Expr::Variable(_) | Expr::Constant(_) => 1,
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Variable(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..)
+            | Expr::Bool(_) => 1,
             Expr::Add(a, b)
             | Expr::Sub(a, b)
             | Expr::Mul(a, b)
             | Expr::Div(a, b)
-            | Expr::Pow(a, b) => 1 + a.depth().max(b.depth()),
+            | Expr::Pow(a, b)
+            | Expr::MatMul(a, b)
+            | Expr::Eq(a, b)
+            | Expr::Ne(a, b)
+            | Expr::Lt(a, b)
+            | Expr::Le(a, b)
+            | Expr::Gt(a, b)
+            | Expr::Ge(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b) => 1 + a.depth().max(b.depth()),
             Expr::Neg(a)
+            | Expr::Not(a)
             | Expr::Sqrt(a)
             | Expr::Ln(a)
             | Expr::Exp(a)
             | Expr::Sin(a)
             | Expr::Cos(a)
             | Expr::Tan(a) => 1 + a.depth(),
-            Expr::Function(_, args) => 1 + args.iter().map(|arg| arg.depth()).max().unwrap_or(0),
+            Expr::Function(_, args) | Expr::Vector(args) => {
+                1 + args.iter().map(|arg| arg.depth()).max().unwrap_or(0)
+            }
+            Expr::Matrix(rows) => {
+                1 + rows.iter().flatten().map(|entry| entry.depth()).max().unwrap_or(0)
+            }
+            Expr::Piecewise(branches, otherwise) => {
+                let deepest = branches
+                    .iter()
+                    .map(|(c, v)| c.depth().max(v.depth()))
+                    .max()
+                    .unwrap_or(0);
+                1 + deepest.max(otherwise.depth())
+            }
+            Expr::Sum(_, lower, upper, body) | Expr::Product(_, lower, upper, body) => {
+                1 + lower.depth().max(upper.depth()).max(body.depth())
+            }
+            Expr::Let(_, value, body) => 1 + value.depth().max(body.depth()),
+        }
+    }
+
+    /// Immediate subexpressions, in order
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Variable(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..)
+            | Expr::Bool(_) => Vec::new(),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Pow(a, b)
+            | Expr::MatMul(a, b)
+            | Expr::Eq(a, b)
+            | Expr::Ne(a, b)
+            | Expr::Lt(a, b)
+            | Expr::Le(a, b)
+            | Expr::Gt(a, b)
+            | Expr::Ge(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b) => vec![&**a, &**b],
+            Expr::Neg(a)
+            | Expr::Not(a)
+            | Expr::Sqrt(a)
+            | Expr::Ln(a)
+            | Expr::Exp(a)
+            | Expr::Sin(a)
+            | Expr::Cos(a)
+            | Expr::Tan(a) => vec![&**a],
+            Expr::Function(_, args) | Expr::Vector(args) => args.iter().collect(),
+            Expr::Matrix(rows) => rows.iter().flatten().collect(),
+            Expr::Piecewise(branches, otherwise) => {
+                let mut children = Vec::with_capacity(2 * branches.len() + 1);
+                for (condition, value) in branches {
+                    children.push(condition);
+                    children.push(value);
+                }
+                children.push(&**otherwise);
+                children
+            }
+            Expr::Sum(_, lower, upper, body) | Expr::Product(_, lower, upper, body) => {
+                vec![&**lower, &**upper, &**body]
+            }
+            Expr::Let(_, value, body) => vec![&**value, &**body],
+        }
+    }
+
+    /// Rebuild this node with each immediate subexpression replaced by `f(child)`
+    pub fn try_map_children<E>(
+        &self,
+        mut f: impl FnMut(&Expr) -> std::result::Result<Expr, E>,
+    ) -> std::result::Result<Expr, E> {
+        let mut g = |e: &Expr| f(e).map(Box::new);
+
+        Ok(match self {
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Variable(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..)
+            | Expr::Bool(_) => self.clone(),
+            Expr::Add(a, b) => Expr::Add(g(a)?, g(b)?),
+            Expr::Sub(a, b) => Expr::Sub(g(a)?, g(b)?),
+            Expr::Mul(a, b) => Expr::Mul(g(a)?, g(b)?),
+            Expr::Div(a, b) => Expr::Div(g(a)?, g(b)?),
+            Expr::Pow(a, b) => Expr::Pow(g(a)?, g(b)?),
+            Expr::MatMul(a, b) => Expr::MatMul(g(a)?, g(b)?),
+            Expr::Eq(a, b) => Expr::Eq(g(a)?, g(b)?),
+            Expr::Ne(a, b) => Expr::Ne(g(a)?, g(b)?),
+            Expr::Lt(a, b) => Expr::Lt(g(a)?, g(b)?),
+            Expr::Le(a, b) => Expr::Le(g(a)?, g(b)?),
+            Expr::Gt(a, b) => Expr::Gt(g(a)?, g(b)?),
+            Expr::Ge(a, b) => Expr::Ge(g(a)?, g(b)?),
+            Expr::And(a, b) => Expr::And(g(a)?, g(b)?),
+            Expr::Or(a, b) => Expr::Or(g(a)?, g(b)?),
+            Expr::Implies(a, b) => Expr::Implies(g(a)?, g(b)?),
+            Expr::Neg(a) => Expr::Neg(g(a)?),
+            Expr::Not(a) => Expr::Not(g(a)?),
+            Expr::Sqrt(a) => Expr::Sqrt(g(a)?),
+            Expr::Ln(a) => Expr::Ln(g(a)?),
+            Expr::Exp(a) => Expr::Exp(g(a)?),
+            Expr::Sin(a) => Expr::Sin(g(a)?),
+            Expr::Cos(a) => Expr::Cos(g(a)?),
+            Expr::Tan(a) => Expr::Tan(g(a)?),
+            Expr::Function(name, args) => {
+                let mut mapped = Vec::with_capacity(args.len());
+                for arg in args {
+                    mapped.push(*g(arg)?);
+                }
+                Expr::Function(name.clone(), mapped)
+            }
+            Expr::Vector(entries) => {
+                let mut mapped = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    mapped.push(*g(entry)?);
+                }
+                Expr::Vector(mapped)
+            }
+            Expr::Matrix(rows) => {
+                let mut mapped = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let mut mapped_row = Vec::with_capacity(row.len());
+                    for entry in row {
+                        mapped_row.push(*g(entry)?);
+                    }
+                    mapped.push(mapped_row);
+                }
+                Expr::Matrix(mapped)
+            }
+            Expr::Piecewise(branches, otherwise) => {
+                let mut mapped = Vec::with_capacity(branches.len());
+                for (condition, value) in branches {
+                    mapped.push((*g(condition)?, *g(value)?));
+                }
+                Expr::Piecewise(mapped, g(otherwise)?)
+            }
+            Expr::Sum(index, lower, upper, body) => {
+                Expr::Sum(index.clone(), g(lower)?, g(upper)?, g(body)?)
+            }
+            Expr::Product(index, lower, upper, body) => {
+                Expr::Product(index.clone(), g(lower)?, g(upper)?, g(body)?)
+            }
+            Expr::Let(name, value, body) => Expr::Let(name.clone(), g(value)?, g(body)?),
+        })
+    }
+
+    /// Names of all functions called in the expression
+    pub fn functions(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            if let Expr::Function(name, _) = expr {
+                names.push(name.clone());
+            }
+            stack.extend(expr.children());
         }
+        names.sort();
+        names.dedup();
+        names
     }
 
     /// Check complexity limit
@@ -226,6 +688,7 @@ impl fmt::Display for Expr {
             Expr::Complex(c) => write!(f, "{}", c),
             Expr::Variable(v) => write!(f, "{}", v),
             Expr::Constant(c) => write!(f, "{}", c),
+            Expr::Quantity(n, unit) => write!(f, "{} {}", n, unit),
 
             Expr::Add(a, b) => write!(f, "({} + {})", a, b),
             Expr::Sub(a, b) => write!(f, "({} - {})", a, b),
@@ -251,10 +714,63 @@ impl fmt::Display for Expr {
                 }
                 write!(f, ")")
             }
+
+            Expr::Vector(entries) => write_list(f, entries),
+            Expr::Matrix(rows) => {
+                write!(f, "[")?;
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_list(f, row)?;
+                }
+                write!(f, "]")
+            }
+            Expr::MatMul(a, b) => write!(f, "({} · {})", a, b),
+
+            Expr::Bool(b) => write!(f, "{}", b),
+            Expr::Eq(a, b) => write!(f, "({} = {})", a, b),
+            Expr::Ne(a, b) => write!(f, "({} ≠ {})", a, b),
+            Expr::Lt(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Le(a, b) => write!(f, "({} ≤ {})", a, b),
+            Expr::Gt(a, b) => write!(f, "({} > {})", a, b),
+            Expr::Ge(a, b) => write!(f, "({} ≥ {})", a, b),
+            Expr::And(a, b) => write!(f, "({} ∧ {})", a, b),
+            Expr::Or(a, b) => write!(f, "({} ∨ {})", a, b),
+            Expr::Not(a) => write!(f, "(¬{})", a),
+            Expr::Implies(a, b) => write!(f, "({} ⇒ {})", a, b),
+
+            Expr::Piecewise(branches, otherwise) => {
+                write!(f, "{{")?;
+                for (condition, value) in branches {
+                    write!(f, "{} if {}; ", value, condition)?;
+                }
+                write!(f, "{} otherwise}}", otherwise)
+            }
+
+            Expr::Sum(index, lower, upper, body) => {
+                write!(f, "Σ({}={}..{}, {})", index, lower, upper, body)
+            }
+            Expr::Product(index, lower, upper, body) => {
+                write!(f, "Π({}={}..{}, {})", index, lower, upper, body)
+            }
+            Expr::Let(name, value, body) => write!(f, "(let {} = {} in {})", name, value, body),
         }
     }
 }
 
+/// [a, b, ...]
+fn write_list(f: &mut fmt::Formatter<'_>, entries: &[Expr]) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", entry)?;
+    }
+    write!(f, "]")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +806,60 @@ mod tests {
         assert!(!expr.is_constant());
     }
 
+    #[test]
+    fn test_predicate_display() {
+        // x > 0 ∧ x < 2
+        let x = Expr::var("x");
+        let expr = Expr::and(
+            Expr::greater(x.clone(), Expr::number(0)),
+            Expr::less(x, Expr::number(2)),
+        );
+
+        assert!(expr.is_predicate());
+        assert_eq!(format!("{}", expr), "((x > 0) ∧ (x < 2))");
+    }
+
+    #[test]
+    fn test_piecewise_display() {
+        let expr = Expr::relu(Expr::var("x"));
+        assert_eq!(format!("{}", expr), "{x if (x > 0); 0 otherwise}");
+        assert_eq!(expr.variables(), vec!["x".to_string()]);
+    }
+
+    #[test]
+    fn test_sum_binds_index() {
+        // Σ_{k=1}^{n} a·k has free variables a and n only
+        let expr = Expr::sum(
+            "k",
+            Expr::number(1),
+            Expr::var("n"),
+            Expr::mul(Expr::var("a"), Expr::var("k")),
+        );
+        assert_eq!(expr.variables(), vec!["a".to_string(), "n".to_string()]);
+        assert!(!expr.contains_variable("k"));
+        assert_eq!(format!("{}", expr), "Σ(k=1..n, (a * k))");
+
+        let closed = Expr::product("k", Expr::number(1), Expr::number(4), Expr::var("k"));
+        assert!(closed.is_constant());
+    }
+
+    #[test]
+    fn test_let_binds_name() {
+        // let x = x + y in x·z: the value's x is free, the body's is not
+        let expr = Expr::let_in(
+            "x",
+            Expr::add(Expr::var("x"), Expr::var("y")),
+            Expr::mul(Expr::var("x"), Expr::var("z")),
+        );
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(expr.variables(), names(&["x", "y", "z"]));
+        assert_eq!(format!("{}", expr), "(let x = (x + y) in (x * z))");
+
+        let closed = Expr::let_in("a", Expr::number(3), Expr::mul(Expr::var("a"), Expr::var("a")));
+        assert!(closed.is_constant());
+        assert_eq!(closed.variables(), Vec::<String>::new());
+    }
+
     #[test]
     fn test_depth() {
         let x = Expr::var("x");
@@ -0,0 +1,753 @@
+//! Rule-based symbolic integration
+//!
+//! Antiderivatives for the elementary forms the generators produce:
+//! polynomials, exp, ln, sin/cos/tan of linear arguments, u-substitution
+//! and integration by parts against powers of the variable.
+//!
+//! Nothing is trusted: every antiderivative is differentiated back and
+//! compared to the integrand in canonical form. Only then is it marked
+//! verified, so the results double as verified training problems.
+//!
+//! The check holds where the antiderivative is defined, which can be less
+//! than where the integrand is: ln(x) is an antiderivative of 1/x only for
+//! x > 0. Such conditions are kept in `Antiderivative::domain` and stated
+//! in the claim.
+
+use super::derivative::Differentiate;
+use super::equivalence::{equivalent, Factor, NormalForm, Term};
+use super::{Expr, Simplify};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use crate::verification::{Claim, Proof, VerificationState};
+
+/// Recursion limit for by-parts and substitution
+const MAX_DEPTH: usize = 16;
+
+/// Largest power of the variable reduced by integration by parts
+const MAX_BY_PARTS_POWER: i64 = 8;
+
+/// Integration rule applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrationRule {
+    /// ∫ c dx = c·x
+    Constant,
+    /// ∫ xⁿ dx = xⁿ⁺¹/(n+1)
+    Power,
+    /// ∫ 1/x dx = ln(x), for x > 0
+    Logarithm,
+    /// ∫ e^(ax+b) dx = e^(ax+b)/a
+    Exponential,
+    /// ∫ sin, cos, tan of linear arguments
+    Trigonometric,
+    /// ∫ f(g(x))·g'(x) dx = F(g(x))
+    Substitution,
+    /// ∫ u dv = uv - ∫ v du
+    ByParts,
+    /// ∫ (f + g) = ∫ f + ∫ g
+    Linearity,
+}
+
+impl IntegrationRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegrationRule::Constant => "constant rule",
+            IntegrationRule::Power => "power rule",
+            IntegrationRule::Logarithm => "logarithm rule",
+            IntegrationRule::Exponential => "exponential rule",
+            IntegrationRule::Trigonometric => "trigonometric rule",
+            IntegrationRule::Substitution => "u-substitution",
+            IntegrationRule::ByParts => "integration by parts",
+            IntegrationRule::Linearity => "linearity",
+        }
+    }
+}
+
+/// An antiderivative together with its verification
+#[derive(Debug, Clone)]
+pub struct Antiderivative {
+    pub integrand: Expr,
+    pub var: String,
+    /// Antiderivative (constant of integration omitted)
+    pub result: Expr,
+    /// Conditions on `var` under which `result` holds, u > 0 for each
+    /// ln(u) in it; empty when it holds wherever it is defined
+    pub domain: Vec<Expr>,
+    /// Rules applied, in order
+    pub rules: Vec<IntegrationRule>,
+    /// Differentiation check
+    pub proof: Proof,
+    pub state: VerificationState,
+}
+
+impl Antiderivative {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+}
+
+/// Trait for integrating expressions
+pub trait Integrate {
+    /// Antiderivative with respect to `var`, checked by differentiation
+    fn integrate(&self, var: &str) -> Result<Antiderivative>;
+}
+
+impl Integrate for Expr {
+    fn integrate(&self, var: &str) -> Result<Antiderivative> {
+        let mut rules = Vec::new();
+        let result = integrate_expr(self, var, &mut rules, 0)?.simplify()?;
+        let domain = logarithm_domain(&result, var);
+
+        let mut statement = format!("∫ {} d{} = {}", self, var, result);
+        if !domain.is_empty() {
+            let conditions: Vec<String> = domain.iter().map(|c| c.to_string()).collect();
+            statement.push_str(&format!(" for {}", conditions.join(", ")));
+        }
+        let claim = Claim::new(statement).with_symbolic(result.clone());
+        let mut proof = Proof::new(claim);
+        for rule in &rules {
+            proof.add_step(rule.name(), "integration table");
+        }
+        for condition in &domain {
+            proof.add_step(condition.to_string(), "domain of ln");
+        }
+
+        let derivative = result.differentiate(var)?;
+        proof.add_step(
+            format!("d/d{} {} = {}", var, result, derivative),
+            "symbolic differentiation",
+        );
+
+        let state = if equivalent(&derivative, self)? {
+            proof.add_step(
+                format!("{} ≡ {}", derivative, self),
+                "canonical normal form",
+            );
+            proof.verified = true;
+            VerificationState::Verified {
+                proof_id: proof.id(),
+            }
+        } else {
+            VerificationState::Uncertain {
+                reason: format!(
+                    "derivative {} could not be shown equal to {}",
+                    derivative, self
+                ),
+            }
+        };
+
+        Ok(Antiderivative {
+            integrand: self.clone(),
+            var: var.to_string(),
+            result,
+            domain,
+            rules,
+            proof,
+            state,
+        })
+    }
+}
+
+/// u > 0 for every ln(u) in `result` whose argument depends on `var`
+fn logarithm_domain(result: &Expr, var: &str) -> Vec<Expr> {
+    let mut domain = Vec::new();
+    let mut stack = vec![result];
+    while let Some(expr) = stack.pop() {
+        if let Expr::Ln(u) = expr {
+            let condition = Expr::greater((**u).clone(), Expr::number(0));
+            if u.contains_variable(var) && !domain.contains(&condition) {
+                domain.push(condition);
+            }
+        }
+        stack.extend(expr.children());
+    }
+    domain
+}
+
+/// Integrate term by term, falling back to whole-expression substitution
+fn integrate_expr(
+    expr: &Expr,
+    var: &str,
+    rules: &mut Vec<IntegrationRule>,
+    depth: usize,
+) -> Result<Expr> {
+    if depth > MAX_DEPTH {
+        return Err(VeritasError::ComplexityLimit(depth));
+    }
+
+    let normal = NormalForm::from_expr(expr)?;
+    let mut applied = Vec::new();
+    let mut parts = Vec::new();
+    let mut failure = None;
+
+    for term in normal.terms() {
+        match integrate_term(term, var, &mut applied, depth) {
+            Ok(part) => parts.push(part),
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+
+    match failure {
+        None => {
+            if parts.len() > 1 {
+                applied.push(IntegrationRule::Linearity);
+            }
+            rules.extend(applied);
+            Ok(parts.into_iter().reduce(Expr::add).unwrap_or(Expr::number(0)))
+        }
+        Some(err) => {
+            let factors = flatten_product(expr);
+            match substitution(&factors, var, rules, depth)? {
+                Some(result) => Ok(result),
+                None => Err(err),
+            }
+        }
+    }
+}
+
+/// Integrate one canonical term: pull out the constant part, then
+/// match the product of varying factors
+fn integrate_term(
+    term: &Term,
+    var: &str,
+    rules: &mut Vec<IntegrationRule>,
+    depth: usize,
+) -> Result<Expr> {
+    let (constant, varying): (Vec<Factor>, Vec<Factor>) = term
+        .factors
+        .iter()
+        .cloned()
+        .partition(|f| !f.base.contains_variable(var));
+
+    let k = Term {
+        coefficient: term.coefficient,
+        factors: constant,
+    }
+    .to_expr();
+
+    let body = match varying.as_slice() {
+        [] => {
+            rules.push(IntegrationRule::Constant);
+            Some(Expr::var(var))
+        }
+        [f] => match integrate_factor(f, var, rules)? {
+            Some(result) => Some(result),
+            None => substitution(&varying, var, rules, depth)?,
+        },
+        [a, b] => match by_parts(a, b, var, rules, depth)? {
+            Some(result) => Some(result),
+            None => substitution(&varying, var, rules, depth)?,
+        },
+        _ => substitution(&varying, var, rules, depth)?,
+    };
+
+    match body {
+        Some(body) if k == Expr::number(1) => Ok(body),
+        Some(body) => Ok(Expr::mul(k, body)),
+        None => Err(VeritasError::UnverifiableClaim(format!(
+            "No integration rule matches {}",
+            term.to_expr()
+        ))),
+    }
+}
+
+/// Slope `a` if `u` is linear in `var` (u' constant and non-zero)
+fn linear_slope(u: &Expr, var: &str) -> Result<Option<Expr>> {
+    let du = u.differentiate(var)?;
+    if du.contains_variable(var) || NormalForm::from_expr(&du)?.is_zero() {
+        Ok(None)
+    } else {
+        Ok(Some(du))
+    }
+}
+
+/// Table lookup for a single factor bᵖ
+fn integrate_factor(
+    f: &Factor,
+    var: &str,
+    rules: &mut Vec<IntegrationRule>,
+) -> Result<Option<Expr>> {
+    let n = f.exponent;
+    let minus_one = -Scalar::ONE;
+    let next = n.checked_add(Scalar::ONE)?;
+
+    // xⁿ and (ax+b)ⁿ
+    if let Some(a) = linear_slope(&f.base, var)? {
+        let u = f.base.clone();
+        let result = if n == minus_one {
+            rules.push(IntegrationRule::Logarithm);
+            Expr::div(Expr::ln(u), a)
+        } else {
+            rules.push(IntegrationRule::Power);
+            Expr::div(
+                Expr::pow(u, Expr::Number(next)),
+                Expr::mul(a, Expr::Number(next)),
+            )
+        };
+        return Ok(Some(result));
+    }
+
+    // e^(nu) = (e^u)ⁿ for linear u
+    if let Expr::Exp(u) = &f.base {
+        if let Some(a) = linear_slope(u, var)? {
+            rules.push(IntegrationRule::Exponential);
+            return Ok(Some(Expr::div(
+                f.to_expr(),
+                Expr::mul(Expr::Number(n), a),
+            )));
+        }
+    }
+
+    if n != Scalar::ONE {
+        return Ok(None);
+    }
+
+    let result = match &f.base {
+        Expr::Sin(u) => linear_slope(u, var)?.map(|a| {
+            rules.push(IntegrationRule::Trigonometric);
+            Expr::neg(Expr::div(Expr::cos((**u).clone()), a))
+        }),
+        Expr::Cos(u) => linear_slope(u, var)?.map(|a| {
+            rules.push(IntegrationRule::Trigonometric);
+            Expr::div(Expr::sin((**u).clone()), a)
+        }),
+        Expr::Tan(u) => linear_slope(u, var)?.map(|a| {
+            rules.push(IntegrationRule::Trigonometric);
+            Expr::neg(Expr::div(Expr::ln(Expr::cos((**u).clone())), a))
+        }),
+        // ∫ ln(u) = u·ln(u) - u (by parts with dv = dx)
+        Expr::Ln(u) => linear_slope(u, var)?.map(|a| {
+            rules.push(IntegrationRule::ByParts);
+            Expr::div(
+                Expr::sub(Expr::mul((**u).clone(), Expr::ln((**u).clone())), (**u).clone()),
+                a,
+            )
+        }),
+        // ∫ cᵘ = cᵘ / (ln(c)·a) for constant c
+        Expr::Pow(c, u) if !c.contains_variable(var) => linear_slope(u, var)?.map(|a| {
+            rules.push(IntegrationRule::Exponential);
+            Expr::div(f.base.clone(), Expr::mul(Expr::ln((**c).clone()), a))
+        }),
+        _ => None,
+    };
+
+    Ok(result)
+}
+
+/// Integration by parts for xᵐ·g(x)
+///
+/// - g = ln(x): closed form for any m ≠ -1
+/// - g = exp/sin/cos of a linear argument: reduce m by one and recurse
+fn by_parts(
+    a: &Factor,
+    b: &Factor,
+    var: &str,
+    rules: &mut Vec<IntegrationRule>,
+    depth: usize,
+) -> Result<Option<Expr>> {
+    let x = Expr::var(var);
+    let (power, other) = if a.base == x {
+        (a, b)
+    } else if b.base == x {
+        (b, a)
+    } else {
+        return Ok(None);
+    };
+
+    let m = power.exponent;
+    if other.exponent != Scalar::ONE {
+        return Ok(None);
+    }
+
+    // ∫ xᵐ ln(x) = xᵐ⁺¹ ln(x)/(m+1) - xᵐ⁺¹/(m+1)²
+    if other.base == Expr::ln(x.clone()) {
+        if m == -Scalar::ONE {
+            return Ok(None);
+        }
+        let next = Expr::Number(m.checked_add(Scalar::ONE)?);
+        let x_next = Expr::pow(x.clone(), next.clone());
+        rules.push(IntegrationRule::ByParts);
+        return Ok(Some(Expr::sub(
+            Expr::div(Expr::mul(x_next.clone(), Expr::ln(x)), next.clone()),
+            Expr::div(x_next, Expr::pow(next, Expr::number(2))),
+        )));
+    }
+
+    let m_int = match m.to_i64() {
+        Some(m) if (1..=MAX_BY_PARTS_POWER).contains(&m) => m,
+        _ => return Ok(None),
+    };
+
+    let mut inner_rules = Vec::new();
+    let g_integral = match &other.base {
+        Expr::Exp(_) | Expr::Sin(_) | Expr::Cos(_) => {
+            match integrate_factor(other, var, &mut inner_rules)? {
+                Some(g) => g,
+                None => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    // ∫ xᵐ g = xᵐ G - m ∫ xᵐ⁻¹ G
+    let reduced = Expr::mul(
+        Expr::pow(x.clone(), Expr::number(m_int as i32 - 1)),
+        g_integral.clone(),
+    );
+    let rest = match integrate_expr(&reduced, var, &mut inner_rules, depth + 1) {
+        Ok(r) => r,
+        Err(_) => return Ok(None),
+    };
+
+    rules.push(IntegrationRule::ByParts);
+    rules.extend(inner_rules);
+    Ok(Some(Expr::sub(
+        Expr::mul(power.to_expr(), g_integral),
+        Expr::mul(Expr::number(m_int as i32), rest),
+    )))
+}
+
+/// Candidate inner functions u with outer antiderivatives F(u)
+fn substitution_candidates(f: &Factor) -> Result<Vec<(Expr, Expr)>> {
+    let mut candidates = Vec::new();
+
+    if f.exponent == Scalar::ONE {
+        match &f.base {
+            Expr::Exp(v) => candidates.push(((**v).clone(), f.base.clone())),
+            Expr::Sin(v) => candidates.push(((**v).clone(), Expr::neg(Expr::cos((**v).clone())))),
+            Expr::Cos(v) => candidates.push(((**v).clone(), Expr::sin((**v).clone()))),
+            Expr::Tan(v) => candidates.push((
+                (**v).clone(),
+                Expr::neg(Expr::ln(Expr::cos((**v).clone()))),
+            )),
+            _ => {}
+        }
+    }
+
+    // The whole base as u, with the power rule outside
+    let u = f.base.clone();
+    if f.exponent == -Scalar::ONE {
+        candidates.push((u.clone(), Expr::ln(u)));
+    } else {
+        let next = Expr::Number(f.exponent.checked_add(Scalar::ONE)?);
+        candidates.push((u.clone(), Expr::div(Expr::pow(u, next.clone()), next)));
+    }
+
+    Ok(candidates)
+}
+
+/// u-substitution: find a factor F'(u) whose companions are a constant
+/// multiple of u'
+fn substitution(
+    factors: &[Factor],
+    var: &str,
+    rules: &mut Vec<IntegrationRule>,
+    depth: usize,
+) -> Result<Option<Expr>> {
+    if depth > MAX_DEPTH {
+        return Ok(None);
+    }
+
+    for (i, f) in factors.iter().enumerate() {
+        if !f.base.contains_variable(var) {
+            continue;
+        }
+
+        let rest = factors
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, g)| g.to_expr())
+            .reduce(Expr::mul)
+            .unwrap_or(Expr::number(1));
+        let rest = NormalForm::from_expr(&rest)?;
+
+        for (u, outer) in substitution_candidates(f)? {
+            let du = NormalForm::from_expr(&u.differentiate(var)?)?;
+            if du.is_zero() {
+                continue;
+            }
+            if let Some(c) = rest.constant_ratio(&du, var)? {
+                rules.push(IntegrationRule::Substitution);
+                let c = c.to_expr();
+                return Ok(Some(if c == Expr::number(1) {
+                    outer
+                } else {
+                    Expr::mul(c, outer)
+                }));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Split an expression into multiplicative factors without expanding
+fn flatten_product(expr: &Expr) -> Vec<Factor> {
+    match expr {
+        Expr::Mul(a, b) => {
+            let mut factors = flatten_product(a);
+            factors.extend(flatten_product(b));
+            factors
+        }
+        Expr::Div(a, b) => {
+            let mut factors = flatten_product(a);
+            factors.extend(
+                flatten_product(b)
+                    .into_iter()
+                    .map(|f| Factor::new(f.base, -f.exponent)),
+            );
+            factors
+        }
+        Expr::Neg(a) => {
+            let mut factors = vec![Factor::new(Expr::number(-1), Scalar::ONE)];
+            factors.extend(flatten_product(a));
+            factors
+        }
+        Expr::Pow(base, exp) => match &**exp {
+            Expr::Number(n) => vec![Factor::new((**base).clone(), *n)],
+            _ => vec![Factor::new(expr.clone(), Scalar::ONE)],
+        },
+        Expr::Sqrt(a) => vec![Factor::new((**a).clone(), Scalar::ONE / Scalar::TWO)],
+        _ => vec![Factor::new(expr.clone(), Scalar::ONE)],
+    }
+}
+
+/// Family of integrands for generated problems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrandFamily {
+    /// a·xⁿ + b
+    Polynomial,
+    /// a·e^(bx)
+    Exponential,
+    /// a·sin(bx) or a·cos(bx)
+    Trigonometric,
+    /// a·x·e^(x²)
+    Substitution,
+    /// x·e^(bx), x·sin(bx), ln(x)
+    ByParts,
+}
+
+/// Generator of verified integration problems for training
+pub struct IntegrationGenerator {
+    max_coefficient: u32,
+}
+
+impl IntegrationGenerator {
+    pub fn new(max_coefficient: u32) -> Self {
+        Self {
+            max_coefficient: max_coefficient.max(1),
+        }
+    }
+
+    /// Random coefficient in 1..=max
+    fn coefficient(&self) -> i32 {
+        let r = rand::random::<u32>();
+        (r - r / self.max_coefficient * self.max_coefficient) as i32 + 1
+    }
+
+    /// Generate a random integrand from a family
+    pub fn integrand(&self, family: IntegrandFamily) -> Expr {
+        let (a, b) = (self.coefficient(), self.coefficient());
+        Self::instance(family, a, b, rand::random::<u32>())
+    }
+
+    /// Integrand of a family with coefficients `a`, `b`
+    ///
+    /// `shape` picks the power of x, sin or cos, or the by-parts form; any
+    /// value is valid, so a random draw needs no range.
+    fn instance(family: IntegrandFamily, a: i32, b: i32, shape: u32) -> Expr {
+        let x = Expr::var("x");
+        let (a, b) = (Expr::number(a), Expr::number(b));
+
+        match family {
+            IntegrandFamily::Polynomial => {
+                let n = Expr::number((shape - shape / 5 * 5) as i32);
+                Expr::add(Expr::mul(a, Expr::pow(x, n)), b)
+            }
+            IntegrandFamily::Exponential => Expr::mul(a, Expr::exp(Expr::mul(b, x))),
+            IntegrandFamily::Trigonometric => {
+                if shape / 2 * 2 == shape {
+                    Expr::mul(a, Expr::sin(Expr::mul(b, x)))
+                } else {
+                    Expr::mul(a, Expr::cos(Expr::mul(b, x)))
+                }
+            }
+            IntegrandFamily::Substitution => Expr::mul(
+                Expr::mul(a, x.clone()),
+                Expr::exp(Expr::pow(x, Expr::number(2))),
+            ),
+            IntegrandFamily::ByParts => match shape - shape / 3 * 3 {
+                0 => Expr::mul(x.clone(), Expr::exp(Expr::mul(b, x))),
+                1 => Expr::mul(x.clone(), Expr::sin(Expr::mul(b, x))),
+                _ => Expr::ln(x),
+            },
+        }
+    }
+
+    /// Generate a verified antiderivative problem
+    pub fn generate(&self, family: IntegrandFamily) -> Result<Antiderivative> {
+        let integrand = self.integrand(family);
+        let antiderivative = integrand.integrate("x")?;
+
+        if antiderivative.is_verified() {
+            Ok(antiderivative)
+        } else {
+            Err(VeritasError::GenerationFailed(format!(
+                "antiderivative of {} failed verification",
+                integrand
+            )))
+        }
+    }
+
+    /// Generate a batch of verified problems, cycling thru families
+    pub fn generate_batch(&self, count: usize) -> Vec<Antiderivative> {
+        let families = [
+            IntegrandFamily::Polynomial,
+            IntegrandFamily::Exponential,
+            IntegrandFamily::Trigonometric,
+            IntegrandFamily::Substitution,
+            IntegrandFamily::ByParts,
+        ];
+
+        let mut problems = Vec::new();
+        for &family in families.iter().cycle().take(count) {
+            if let Ok(problem) = self.generate(family) {
+                problems.push(problem);
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolic::context::Value;
+    use crate::symbolic::{Context, Evaluate};
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    #[test]
+    fn test_polynomial() {
+        // ∫ 3x² + 2 dx = x³ + 2x
+        let expr = Expr::add(
+            Expr::mul(Expr::number(3), Expr::pow(x(), Expr::number(2))),
+            Expr::number(2),
+        );
+        let result = expr.integrate("x").unwrap();
+        assert!(result.is_verified());
+        assert!(result.rules.contains(&IntegrationRule::Power));
+        assert!(result.rules.contains(&IntegrationRule::Linearity));
+    }
+
+    #[test]
+    fn test_reciprocal() {
+        let expr = Expr::div(Expr::number(1), x());
+        let result = expr.integrate("x").unwrap();
+        assert!(result.is_verified());
+        assert_eq!(result.result, Expr::ln(x()));
+
+        // 1/x is defined at x = -1, but ln(x) is not an antiderivative there
+        let positive = Expr::greater(x(), Expr::number(0));
+        assert_eq!(result.domain, vec![positive.clone()]);
+        assert!(result.proof.claim.statement.ends_with(&format!("for {}", positive)));
+        let mut ctx = Context::new();
+        ctx.bind("x", -1);
+        assert!(expr.evaluate(&ctx).is_ok());
+        assert_eq!(positive.evaluate(&ctx).unwrap(), Value::Bool(false));
+
+        // Polynomials hold everywhere
+        let square = Expr::pow(x(), Expr::number(2));
+        assert!(square.integrate("x").unwrap().domain.is_empty());
+    }
+
+    #[test]
+    fn test_exponential_and_trig() {
+        for expr in [
+            Expr::exp(Expr::mul(Expr::number(2), x())),
+            Expr::sin(Expr::mul(Expr::number(3), x())),
+            Expr::cos(x()),
+        ] {
+            let result = expr.integrate("x").unwrap();
+            assert!(result.is_verified(), "{} not verified", expr);
+        }
+    }
+
+    #[test]
+    fn test_substitution() {
+        // ∫ 2x·e^(x²) dx = e^(x²)
+        let expr = Expr::mul(
+            Expr::mul(Expr::number(2), x()),
+            Expr::exp(Expr::pow(x(), Expr::number(2))),
+        );
+        let result = expr.integrate("x").unwrap();
+        assert!(result.is_verified());
+        assert!(result.rules.contains(&IntegrationRule::Substitution));
+    }
+
+    #[test]
+    fn test_substitution_with_sum_derivative() {
+        // ∫ (2x+1)·e^(x²+x) dx = e^(x²+x)
+        let u = Expr::add(Expr::pow(x(), Expr::number(2)), x());
+        let expr = Expr::mul(
+            Expr::add(Expr::mul(Expr::number(2), x()), Expr::number(1)),
+            Expr::exp(u),
+        );
+        let result = expr.integrate("x").unwrap();
+        assert!(result.is_verified());
+    }
+
+    #[test]
+    fn test_by_parts() {
+        for expr in [
+            Expr::mul(x(), Expr::exp(x())),
+            Expr::mul(Expr::pow(x(), Expr::number(2)), Expr::sin(x())),
+            Expr::ln(x()),
+            Expr::mul(x(), Expr::ln(x())),
+        ] {
+            let result = expr.integrate("x").unwrap();
+            assert!(result.is_verified(), "{} not verified", expr);
+            assert!(result.rules.contains(&IntegrationRule::ByParts));
+        }
+    }
+
+    #[test]
+    fn test_no_rule() {
+        // e^(x²) has no elementary antiderivative
+        let expr = Expr::exp(Expr::pow(x(), Expr::number(2)));
+        assert!(matches!(
+            expr.integrate("x"),
+            Err(VeritasError::UnverifiableClaim(_))
+        ));
+    }
+
+    #[test]
+    fn test_generator() {
+        // Every integrand new(5) can draw, so no random batch can fail
+        for family in [
+            IntegrandFamily::Polynomial,
+            IntegrandFamily::Exponential,
+            IntegrandFamily::Trigonometric,
+            IntegrandFamily::Substitution,
+            IntegrandFamily::ByParts,
+        ] {
+            for a in 1..=5 {
+                for b in 1..=5 {
+                    for shape in 0..5 {
+                        let expr = IntegrationGenerator::instance(family, a, b, shape);
+                        let result = expr.integrate("x").unwrap();
+                        assert!(result.is_verified(), "{} not verified", expr);
+                    }
+                }
+            }
+        }
+
+        let gen = IntegrationGenerator::new(5);
+        assert!(gen.generate_batch(10).iter().all(|p| p.is_verified()));
+    }
+}
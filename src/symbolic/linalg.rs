@@ -0,0 +1,925 @@
+//! Vectors and matrices
+//!
+//! `Expr::Vector` is a column vector and `Expr::Matrix` a list of rows.
+//! Arithmetic between arrays of the same shape acts element by element,
+//! with a scalar operand broadcast to every entry; `Expr::MatMul` is the
+//! matrix product. Transpose, determinant, trace, inverse and dot product
+//! are built-in functions. Shapes are checked before simplifying and again
+//! while evaluating, so W·x + b with mismatched sizes is a `ShapeMismatch`
+//! instead of a wrong answer.
+//!
+//! Large numeric products go through `autograd::matmul` when every entry
+//! is an integer that F4E4 holds exactly and no partial sum can round;
+//! everything else is computed in `Scalar` arithmetic.
+
+use super::complex;
+use super::context::Value;
+use super::Expr;
+use crate::autograd::{self, Shape, Tensor};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use spirix::ScalarF4E4;
+use std::cmp::Ordering;
+
+/// Functions available without a definition
+pub const BUILTINS: [&str; 5] = ["transpose", "det", "trace", "inv", "dot"];
+
+/// Check whether `name` is a built-in linear algebra function
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.contains(&name)
+}
+
+/// Products with at least this many multiply-adds use `autograd::matmul`
+const TENSOR_THRESHOLD: usize = 4096;
+
+/// Integers below this magnitude are exact in F4E4, partial sums included
+const TENSOR_EXACT: i64 = 1 << 14;
+
+impl Expr {
+    /// Column vector literal
+    pub fn vector(entries: Vec<Expr>) -> Self {
+        Expr::Vector(entries)
+    }
+
+    /// Matrix literal from its rows
+    pub fn matrix(rows: Vec<Vec<Expr>>) -> Self {
+        Expr::Matrix(rows)
+    }
+
+    /// Matrix product: A·B
+    pub fn matmul(a: Expr, b: Expr) -> Self {
+        Expr::MatMul(Box::new(a), Box::new(b))
+    }
+
+    /// Transpose: Aᵀ (a vector is its own transpose)
+    pub fn transpose(a: Expr) -> Self {
+        Expr::Function("transpose".to_string(), vec![a])
+    }
+
+    /// Determinant of a square matrix
+    pub fn det(a: Expr) -> Self {
+        Expr::Function("det".to_string(), vec![a])
+    }
+
+    /// Trace: sum of the diagonal
+    pub fn trace(a: Expr) -> Self {
+        Expr::Function("trace".to_string(), vec![a])
+    }
+
+    /// Inverse of a square matrix: A⁻¹
+    pub fn inverse(a: Expr) -> Self {
+        Expr::Function("inv".to_string(), vec![a])
+    }
+
+    /// Dot product of two vectors
+    pub fn dot(a: Expr, b: Expr) -> Self {
+        Expr::Function("dot".to_string(), vec![a, b])
+    }
+
+    /// Shape of the value, as far as it can be told without a context
+    ///
+    /// `None` means unknown, since a variable may be bound to an array.
+    /// A combination that no binding could make valid is a `ShapeMismatch`.
+    pub fn shape(&self) -> Result<Option<Shape>> {
+        let scalar = Ok(Some(Shape::scalar()));
+
+        match self {
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..)
+            | Expr::Bool(_) => scalar,
+            Expr::Variable(_) => Ok(None),
+
+            Expr::Vector(entries) => {
+                if entries.is_empty() {
+                    return Err(VeritasError::InvalidInput("Empty vector".to_string()));
+                }
+                for entry in entries {
+                    expect_scalar(entry)?;
+                }
+                Ok(Some(Shape::vector(entries.len())))
+            }
+            Expr::Matrix(rows) => {
+                let cols = rows.first().map_or(0, Vec::len);
+                if cols == 0 {
+                    return Err(VeritasError::InvalidInput("Empty matrix".to_string()));
+                }
+                for row in rows {
+                    if row.len() != cols {
+                        return Err(VeritasError::ShapeMismatch {
+                            expected: format!("rows of {}", cols),
+                            actual: format!("a row of {}", row.len()),
+                        });
+                    }
+                    for entry in row {
+                        expect_scalar(entry)?;
+                    }
+                }
+                Ok(Some(Shape::matrix(rows.len(), cols)))
+            }
+
+            // Element-wise; an unknown operand may be a scalar
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) => {
+                match (a.shape()?, b.shape()?) {
+                    (Some(a), Some(b)) => broadcast(&a, &b).map(Some),
+                    (Some(s), None) | (None, Some(s)) if s.rank() > 0 => Ok(Some(s)),
+                    _ => Ok(None),
+                }
+            }
+            Expr::MatMul(a, b) => match (a.shape()?, b.shape()?) {
+                (Some(a), Some(b)) => product_shape(&a, &b).map(Some),
+                (Some(s), None) | (None, Some(s)) if s.rank() == 0 => {
+                    Err(shape_mismatch("a vector or matrix", &s))
+                }
+                _ => Ok(None),
+            },
+            Expr::Neg(a) => a.shape(),
+
+            Expr::Pow(a, b)
+            | Expr::Lt(a, b)
+            | Expr::Le(a, b)
+            | Expr::Gt(a, b)
+            | Expr::Ge(a, b)
+            | Expr::And(a, b)
+            | Expr::Or(a, b)
+            | Expr::Implies(a, b) => {
+                expect_scalar(a)?;
+                expect_scalar(b)?;
+                scalar
+            }
+            Expr::Sqrt(a)
+            | Expr::Ln(a)
+            | Expr::Exp(a)
+            | Expr::Sin(a)
+            | Expr::Cos(a)
+            | Expr::Tan(a)
+            | Expr::Not(a) => {
+                expect_scalar(a)?;
+                scalar
+            }
+
+            // Arrays of the same shape can be compared for equality
+            Expr::Eq(a, b) | Expr::Ne(a, b) => {
+                if let (Some(s), Some(t)) = (a.shape()?, b.shape()?) {
+                    if s != t {
+                        return Err(shape_mismatch(&describe(&s), &t));
+                    }
+                }
+                scalar
+            }
+
+            Expr::Function(name, args) => {
+                let shapes: Result<Vec<_>> = args.iter().map(Expr::shape).collect();
+                let shapes = shapes?;
+                if is_builtin(name) {
+                    builtin_shape(name, &shapes)
+                } else if complex::is_builtin(name) {
+                    for arg in args {
+                        expect_scalar(arg)?;
+                    }
+                    scalar
+                } else {
+                    Ok(None)
+                }
+            }
+
+            // Every branch must have the same shape
+            Expr::Piecewise(branches, otherwise) => {
+                let mut shape = otherwise.shape()?;
+                for (condition, value) in branches {
+                    expect_scalar(condition)?;
+                    shape = match (shape, value.shape()?) {
+                        (Some(s), Some(t)) if s != t => {
+                            return Err(shape_mismatch(&describe(&s), &t))
+                        }
+                        (known @ Some(_), _) | (None, known) => known,
+                    };
+                }
+                Ok(shape)
+            }
+
+            // Adding up or multiplying terms keeps their shape
+            Expr::Sum(_, lower, upper, body) | Expr::Product(_, lower, upper, body) => {
+                expect_scalar(lower)?;
+                expect_scalar(upper)?;
+                body.shape()
+            }
+
+            // The bound name takes the shape of its value
+            Expr::Let(name, value, body) => {
+                value.shape()?;
+                body.substitute(name, value).shape()
+            }
+        }
+    }
+}
+
+/// "scalar", "vector of 3" or "2×3 matrix"
+pub fn describe(shape: &Shape) -> String {
+    match shape.dims() {
+        [] => "scalar".to_string(),
+        [n] => format!("vector of {}", n),
+        [rows, cols] => format!("{}×{} matrix", rows, cols),
+        dims => format!("tensor of shape {:?}", dims),
+    }
+}
+
+/// Error for an operand of the wrong shape
+pub(crate) fn shape_mismatch(expected: &str, actual: &Shape) -> VeritasError {
+    VeritasError::ShapeMismatch {
+        expected: expected.to_string(),
+        actual: describe(actual),
+    }
+}
+
+fn expect_scalar(expr: &Expr) -> Result<()> {
+    match expr.shape()? {
+        Some(shape) if shape.rank() > 0 => Err(shape_mismatch("scalar", &shape)),
+        _ => Ok(()),
+    }
+}
+
+/// Shape of an element-wise result: equal shapes, or a scalar and anything
+fn broadcast(a: &Shape, b: &Shape) -> Result<Shape> {
+    if a == b || b.rank() == 0 {
+        Ok(a.clone())
+    } else if a.rank() == 0 {
+        Ok(b.clone())
+    } else {
+        Err(shape_mismatch(&describe(a), b))
+    }
+}
+
+/// (rows, cols) of a left factor; a vector there is a row
+fn left_dims(shape: &Shape) -> (usize, usize) {
+    match shape.dims() {
+        [rows, cols] => (*rows, *cols),
+        [n] => (1, *n),
+        _ => (1, 1),
+    }
+}
+
+/// (rows, cols) of a right factor; a vector there is a column
+fn right_dims(shape: &Shape) -> (usize, usize) {
+    match shape.dims() {
+        [rows, cols] => (*rows, *cols),
+        [n] => (*n, 1),
+        _ => (1, 1),
+    }
+}
+
+/// Shape of A·B: matrix·matrix, matrix·vector or vector·matrix
+fn product_shape(a: &Shape, b: &Shape) -> Result<Shape> {
+    for side in [a, b] {
+        if side.rank() == 0 {
+            return Err(shape_mismatch("a vector or matrix", side));
+        }
+    }
+    if a.rank() == 1 && b.rank() == 1 {
+        return Err(shape_mismatch("a matrix (dot multiplies two vectors)", b));
+    }
+
+    let ((m, k), (rows, n)) = (left_dims(a), right_dims(b));
+    if k != rows {
+        return Err(VeritasError::ShapeMismatch {
+            expected: format!("{} rows", k),
+            actual: describe(b),
+        });
+    }
+    Ok(match (a.rank(), b.rank()) {
+        (2, 2) => Shape::matrix(m, n),
+        (2, _) => Shape::vector(m),
+        _ => Shape::vector(n),
+    })
+}
+
+/// Side of a square matrix
+fn square_side(shape: &Shape) -> Result<usize> {
+    match shape.dims() {
+        [rows, cols] if rows == cols => Ok(*rows),
+        _ => Err(shape_mismatch("a square matrix", shape)),
+    }
+}
+
+fn builtin_shape(name: &str, args: &[Option<Shape>]) -> Result<Option<Shape>> {
+    check_arity(name, args.len())?;
+
+    let Some(a) = &args[0] else {
+        // Scalar-valued whatever the argument turns out to be
+        return Ok(matches!(name, "det" | "trace" | "dot").then(Shape::scalar));
+    };
+    match name {
+        "transpose" => match a.dims() {
+            [rows, cols] => Ok(Some(Shape::matrix(*cols, *rows))),
+            [_] => Ok(Some(a.clone())),
+            _ => Err(shape_mismatch("a vector or matrix", a)),
+        },
+        "det" | "trace" => square_side(a).map(|_| Some(Shape::scalar())),
+        "inv" => square_side(a).map(|_| Some(a.clone())),
+        _ => {
+            if a.rank() != 1 {
+                return Err(shape_mismatch("a vector", a));
+            }
+            match &args[1] {
+                Some(b) if b != a => Err(shape_mismatch(&describe(a), b)),
+                _ => Ok(Some(Shape::scalar())),
+            }
+        }
+    }
+}
+
+fn check_arity(name: &str, count: usize) -> Result<()> {
+    let arity = if name == "dot" { 2 } else { 1 };
+    if count == arity {
+        Ok(())
+    } else {
+        Err(VeritasError::InvalidInput(format!(
+            "{} takes {} argument{}, got {}",
+            name,
+            arity,
+            if arity == 1 { "" } else { "s" },
+            count
+        )))
+    }
+}
+
+/// Evaluate a built-in linear algebra function
+pub fn apply_builtin(name: &str, args: &[Value]) -> Result<Value> {
+    check_arity(name, args.len())?;
+    let arrays: Result<Vec<&Array>> = args
+        .iter()
+        .map(|arg| match arg {
+            Value::Array(a) => Ok(a),
+            _ => Err(shape_mismatch("a vector or matrix", &Shape::scalar())),
+        })
+        .collect();
+    let arrays = arrays?;
+
+    Ok(match name {
+        "transpose" => Value::Array(arrays[0].transpose()),
+        "det" => Value::Scalar(arrays[0].det()?),
+        "trace" => Value::Scalar(arrays[0].trace()?),
+        "inv" => Value::Array(arrays[0].inverse()?),
+        "dot" => Value::Scalar(arrays[0].dot(arrays[1])?),
+        _ => {
+            return Err(VeritasError::SimplificationError(format!(
+                "Unknown function: {}",
+                name
+            )))
+        }
+    })
+}
+
+// ============================================================================
+// NUMERIC ARRAYS
+// ============================================================================
+
+/// Vector or matrix of real numbers, stored row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    shape: Shape,
+    entries: Vec<Scalar>,
+}
+
+impl Array {
+    /// Column vector
+    pub fn vector(entries: Vec<Scalar>) -> Self {
+        Array {
+            shape: Shape::vector(entries.len()),
+            entries,
+        }
+    }
+
+    /// Matrix from its rows (which must all have the same length)
+    pub fn from_rows(rows: Vec<Vec<Scalar>>) -> Result<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().find(|row| row.len() != cols) {
+            return Err(VeritasError::ShapeMismatch {
+                expected: format!("rows of {}", cols),
+                actual: format!("a row of {}", row.len()),
+            });
+        }
+        Ok(Array {
+            shape: Shape::matrix(rows.len(), cols),
+            entries: rows.concat(),
+        })
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    /// Entries in row-major order
+    pub fn entries(&self) -> &[Scalar] {
+        &self.entries
+    }
+
+    /// Apply `f` to every entry
+    pub fn map(&self, f: impl Fn(Scalar) -> Result<Scalar>) -> Result<Array> {
+        let entries: Result<Vec<Scalar>> = self.entries.iter().map(|&x| f(x)).collect();
+        Ok(Array {
+            shape: self.shape.clone(),
+            entries: entries?,
+        })
+    }
+
+    /// Combine two arrays of the same shape entry by entry
+    pub fn zip(&self, other: &Array, f: fn(&Scalar, Scalar) -> Result<Scalar>) -> Result<Array> {
+        if self.shape != other.shape {
+            return Err(shape_mismatch(&describe(&self.shape), &other.shape));
+        }
+        let entries: Result<Vec<Scalar>> = self
+            .entries
+            .iter()
+            .zip(&other.entries)
+            .map(|(x, &y)| f(x, y))
+            .collect();
+        Ok(Array {
+            shape: self.shape.clone(),
+            entries: entries?,
+        })
+    }
+
+    /// Entry-by-entry equality of two arrays of the same shape
+    pub fn equals(&self, other: &Array) -> Result<bool> {
+        if self.shape != other.shape {
+            return Err(shape_mismatch(&describe(&self.shape), &other.shape));
+        }
+        for (x, y) in self.entries.iter().zip(&other.entries) {
+            if x.compare(y)? != Ordering::Equal {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Matrix product
+    pub fn matmul(&self, other: &Array) -> Result<Array> {
+        let shape = product_shape(&self.shape, &other.shape)?;
+        let ((m, k), (_, n)) = (left_dims(&self.shape), right_dims(&other.shape));
+
+        if m * k * n >= TENSOR_THRESHOLD {
+            if let Some(entries) = tensor_product(self, other, (m, k, n))? {
+                return Ok(Array { shape, entries });
+            }
+        }
+
+        let mut entries = Vec::with_capacity(m * n);
+        for i in 0..m {
+            for j in 0..n {
+                let mut sum = Scalar::ZERO;
+                for l in 0..k {
+                    let term = self.entries[i * k + l].checked_mul(other.entries[l * n + j])?;
+                    sum = sum.checked_add(term)?;
+                }
+                entries.push(sum);
+            }
+        }
+        Ok(Array { shape, entries })
+    }
+
+    /// Transpose (a vector is returned unchanged)
+    pub fn transpose(&self) -> Array {
+        let [rows, cols] = self.shape.dims() else {
+            return self.clone();
+        };
+        let (rows, cols) = (*rows, *cols);
+        let entries = (0..cols)
+            .flat_map(|j| (0..rows).map(move |i| (i, j)))
+            .map(|(i, j)| self.entries[i * cols + j])
+            .collect();
+        Array {
+            shape: Shape::matrix(cols, rows),
+            entries,
+        }
+    }
+
+    /// Sum of the diagonal of a square matrix
+    pub fn trace(&self) -> Result<Scalar> {
+        let n = square_side(&self.shape)?;
+        (0..n).try_fold(Scalar::ZERO, |sum, i| sum.checked_add(self.entries[i * n + i]))
+    }
+
+    /// Determinant, by fraction-free (Bareiss) elimination
+    ///
+    /// Every division is exact, so integer matrices get exact determinants.
+    pub fn det(&self) -> Result<Scalar> {
+        let n = square_side(&self.shape)?;
+        let mut m = self.entries.clone();
+        let mut previous = Scalar::ONE;
+        let mut negate = false;
+
+        for k in 0..n {
+            let Some(pivot) = pivot_row(&m, n, k)? else {
+                return Ok(Scalar::ZERO);
+            };
+            if pivot != k {
+                swap_rows(&mut m, n, pivot, k);
+                negate = !negate;
+            }
+            let p = m[k * n + k];
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let cross = m[i * n + k].checked_mul(m[k * n + j])?;
+                    let v = m[i * n + j].checked_mul(p)?.checked_sub(cross)?;
+                    m[i * n + j] = v.checked_div(previous)?;
+                }
+            }
+            previous = p;
+        }
+
+        let det = m[n * n - 1];
+        Ok(if negate { -det } else { det })
+    }
+
+    /// Inverse, by Gauss-Jordan elimination on [A | I]
+    pub fn inverse(&self) -> Result<Array> {
+        let n = square_side(&self.shape)?;
+        let width = 2 * n;
+        let mut m = vec![Scalar::ZERO; n * width];
+        for i in 0..n {
+            m[i * width..i * width + n].copy_from_slice(&self.entries[i * n..(i + 1) * n]);
+            m[i * width + n + i] = Scalar::ONE;
+        }
+
+        for col in 0..n {
+            let pivot = pivot_row(&m, width, col)?.ok_or_else(|| {
+                VeritasError::UndefinedOperation("Inverse of a singular matrix".to_string())
+            })?;
+            swap_rows(&mut m, width, pivot, col);
+
+            let p = m[col * width + col];
+            for j in 0..width {
+                m[col * width + j] = m[col * width + j].checked_div(p)?;
+            }
+            for row in (0..n).filter(|&row| row != col) {
+                let factor = m[row * width + col];
+                for j in 0..width {
+                    let delta = factor.checked_mul(m[col * width + j])?;
+                    m[row * width + j] = m[row * width + j].checked_sub(delta)?;
+                }
+            }
+        }
+
+        let entries = (0..n)
+            .flat_map(|i| m[i * width + n..(i + 1) * width].to_vec())
+            .collect();
+        Ok(Array {
+            shape: self.shape.clone(),
+            entries,
+        })
+    }
+
+    /// Dot product of two vectors of the same length
+    pub fn dot(&self, other: &Array) -> Result<Scalar> {
+        if self.shape.rank() != 1 {
+            return Err(shape_mismatch("a vector", &self.shape));
+        }
+        if self.shape != other.shape {
+            return Err(shape_mismatch(&describe(&self.shape), &other.shape));
+        }
+        self.entries
+            .iter()
+            .zip(&other.entries)
+            .try_fold(Scalar::ZERO, |sum, (x, &y)| sum.checked_add(x.checked_mul(y)?))
+    }
+
+    /// Entries as integers small enough to be exact in F4E4
+    fn small_integers(&self) -> Option<Vec<i64>> {
+        self.entries
+            .iter()
+            .map(|x| x.to_i64().filter(|i| i.abs() < TENSOR_EXACT))
+            .collect()
+    }
+}
+
+/// Row (from `col` down) with the largest entry in column `col`, if nonzero
+fn pivot_row(m: &[Scalar], width: usize, col: usize) -> Result<Option<usize>> {
+    let rows = m.len() / width;
+    let mut best: Option<usize> = None;
+    for row in col..rows {
+        let candidate = m[row * width + col].abs();
+        if candidate.is_zero() {
+            continue;
+        }
+        best = match best {
+            Some(b) if m[b * width + col].abs().compare(&candidate)? != Ordering::Less => Some(b),
+            _ => Some(row),
+        };
+    }
+    Ok(best)
+}
+
+fn swap_rows(m: &mut [Scalar], width: usize, a: usize, b: usize) {
+    if a != b {
+        for j in 0..width {
+            m.swap(a * width + j, b * width + j);
+        }
+    }
+}
+
+/// Product through `autograd::matmul`, when F4E4 computes it exactly
+fn tensor_product(
+    a: &Array,
+    b: &Array,
+    (m, k, n): (usize, usize, usize),
+) -> Result<Option<Vec<Scalar>>> {
+    let (Some(xs), Some(ys)) = (a.small_integers(), b.small_integers()) else {
+        return Ok(None);
+    };
+
+    // Every partial sum is at most k·max|a|·max|b| in magnitude
+    let largest = |v: &[i64]| v.iter().map(|x| x.abs()).max().unwrap_or(0);
+    let exact = (k as i64)
+        .checked_mul(largest(&xs))
+        .and_then(|p| p.checked_mul(largest(&ys)))
+        .is_some_and(|bound| bound < TENSOR_EXACT);
+    if !exact {
+        return Ok(None);
+    }
+
+    let tensor = |v: &[i64], rows, cols| {
+        let data = v.iter().map(|&x| ScalarF4E4::from(x as i32)).collect();
+        Tensor::from_scalars(data, Shape::matrix(rows, cols))
+    };
+    let product = autograd::matmul(&tensor(&xs, m, k)?, &tensor(&ys, k, n)?)?;
+    let data = product
+        .as_scalars()
+        .ok_or_else(|| VeritasError::InvalidInput("Expected scalar data".to_string()))?;
+
+    Ok(data
+        .iter()
+        .map(|&v| f4e4_integer(v).map(Scalar::from_i64))
+        .collect())
+}
+
+/// The integer an F4E4 value holds exactly, found by bisection
+fn f4e4_integer(value: ScalarF4E4) -> Option<i64> {
+    let f4e4 = |n: i64| ScalarF4E4::from(n as i32);
+    let (mut lo, mut hi) = (-TENSOR_EXACT, TENSOR_EXACT);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if f4e4(mid) < value {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    (f4e4(lo) == value).then_some(lo)
+}
+
+// ============================================================================
+// SYMBOLIC LITERALS
+// ============================================================================
+
+/// Shape and row-major entries of a vector or matrix literal
+pub(crate) fn literal(expr: &Expr) -> Option<(Shape, Vec<Expr>)> {
+    match expr {
+        Expr::Vector(entries) => Some((Shape::vector(entries.len()), entries.clone())),
+        Expr::Matrix(rows) => {
+            let cols = rows.first().map_or(0, Vec::len);
+            if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+                return None;
+            }
+            Some((Shape::matrix(rows.len(), cols), rows.concat()))
+        }
+        _ => None,
+    }
+}
+
+/// Literal of the given shape from row-major entries
+pub(crate) fn from_entries(shape: &Shape, entries: Vec<Expr>) -> Expr {
+    match shape.dims() {
+        [_, cols] if *cols > 0 => Expr::Matrix(entries.chunks(*cols).map(<[Expr]>::to_vec).collect()),
+        _ => Expr::Vector(entries),
+    }
+}
+
+/// Element-wise `op` on literals, broadcasting an operand known to be scalar
+pub(crate) fn zip_literals(a: &Expr, b: &Expr, op: fn(Expr, Expr) -> Expr) -> Option<Expr> {
+    let scalar = |e: &Expr| matches!(e.shape(), Ok(Some(s)) if s.rank() == 0);
+
+    let (shape, entries): (Shape, Vec<Expr>) = match (literal(a), literal(b)) {
+        (Some((s, xs)), Some((t, ys))) if s == t => {
+            (s, xs.into_iter().zip(ys).map(|(x, y)| op(x, y)).collect())
+        }
+        (Some((s, xs)), None) if scalar(b) => {
+            (s, xs.into_iter().map(|x| op(x, b.clone())).collect())
+        }
+        (None, Some((t, ys))) if scalar(a) => {
+            (t, ys.into_iter().map(|y| op(a.clone(), y)).collect())
+        }
+        _ => return None,
+    };
+    Some(from_entries(&shape, entries))
+}
+
+/// `op` applied to each entry of a literal
+pub(crate) fn map_literal(a: &Expr, op: fn(Expr) -> Expr) -> Option<Expr> {
+    let (shape, entries) = literal(a)?;
+    Some(from_entries(&shape, entries.into_iter().map(op).collect()))
+}
+
+/// Σ aᵢbᵢ, unsimplified
+fn sum_of_products(pairs: impl Iterator<Item = (Expr, Expr)>) -> Option<Expr> {
+    pairs.map(|(x, y)| Expr::mul(x, y)).reduce(Expr::add)
+}
+
+/// (AB)ᵢⱼ = Σ aᵢₖbₖⱼ on two literals
+pub(crate) fn matmul_literals(a: &Expr, b: &Expr) -> Option<Expr> {
+    let ((s, xs), (t, ys)) = (literal(a)?, literal(b)?);
+    let shape = product_shape(&s, &t).ok()?;
+    let ((m, k), (_, n)) = (left_dims(&s), right_dims(&t));
+
+    let mut entries = Vec::with_capacity(m * n);
+    for i in 0..m {
+        for j in 0..n {
+            let pairs = (0..k).map(|l| (xs[i * k + l].clone(), ys[l * n + j].clone()));
+            entries.push(sum_of_products(pairs)?);
+        }
+    }
+    Some(from_entries(&shape, entries))
+}
+
+/// Rewrite a built-in applied to literals into the entries it computes
+pub(crate) fn expand_builtin(name: &str, args: &[Expr]) -> Option<Expr> {
+    match (name, args) {
+        ("transpose", [a]) => {
+            let (shape, xs) = literal(a)?;
+            let [rows, cols] = shape.dims() else {
+                return Some(a.clone());
+            };
+            let (rows, cols) = (*rows, *cols);
+            let entries = (0..cols)
+                .flat_map(|j| (0..rows).map(move |i| (i, j)))
+                .map(|(i, j)| xs[i * cols + j].clone())
+                .collect();
+            Some(from_entries(&Shape::matrix(cols, rows), entries))
+        }
+        ("trace", [a]) => {
+            let (shape, xs) = literal(a)?;
+            let n = square_side(&shape).ok()?;
+            (0..n).map(|i| xs[i * n + i].clone()).reduce(Expr::add)
+        }
+        ("det", [a]) => {
+            let (shape, xs) = literal(a)?;
+            let n = square_side(&shape).ok()?;
+            match numeric(&shape, &xs) {
+                Some(array) => array.det().ok().map(Expr::Number),
+                // Cofactor expansion grows as n!, so stop at 4×4
+                None if n <= 4 => Some(laplace(&xs, n)),
+                None => None,
+            }
+        }
+        ("inv", [a]) => {
+            let (shape, xs) = literal(a)?;
+            let n = square_side(&shape).ok()?;
+            match numeric(&shape, &xs) {
+                // A singular matrix stays for evaluation to report
+                Some(array) => {
+                    let inverse = array.inverse().ok()?;
+                    let entries = inverse.entries.into_iter().map(Expr::Number).collect();
+                    Some(from_entries(&shape, entries))
+                }
+                // A⁻¹ = adj(A) / det(A)
+                None if n == 2 => {
+                    let det = laplace(&xs, 2);
+                    let entries = [
+                        xs[3].clone(),
+                        Expr::neg(xs[1].clone()),
+                        Expr::neg(xs[2].clone()),
+                        xs[0].clone(),
+                    ]
+                    .into_iter()
+                    .map(|x| Expr::div(x, det.clone()))
+                    .collect();
+                    Some(from_entries(&shape, entries))
+                }
+                None => None,
+            }
+        }
+        ("dot", [a, b]) => {
+            let ((s, xs), (t, ys)) = (literal(a)?, literal(b)?);
+            if s.rank() != 1 || s != t {
+                return None;
+            }
+            sum_of_products(xs.into_iter().zip(ys))
+        }
+        _ => None,
+    }
+}
+
+/// The literal as a numeric array, if every entry is a number
+fn numeric(shape: &Shape, entries: &[Expr]) -> Option<Array> {
+    let values: Option<Vec<Scalar>> = entries
+        .iter()
+        .map(|e| match e {
+            Expr::Number(n) => Some(*n),
+            _ => None,
+        })
+        .collect();
+    Some(Array {
+        shape: shape.clone(),
+        entries: values?,
+    })
+}
+
+/// Determinant by cofactor expansion along the first row
+fn laplace(entries: &[Expr], n: usize) -> Expr {
+    if n == 1 {
+        return entries[0].clone();
+    }
+
+    let mut det: Option<Expr> = None;
+    for j in 0..n {
+        let minor: Vec<Expr> = (1..n)
+            .flat_map(|i| (0..n).filter(move |&c| c != j).map(move |c| (i, c)))
+            .map(|(i, c)| entries[i * n + c].clone())
+            .collect();
+        let term = Expr::mul(entries[j].clone(), laplace(&minor, n - 1));
+        det = Some(match det {
+            None => term,
+            Some(acc) if j % 2 == 1 => Expr::sub(acc, term),
+            Some(acc) => Expr::add(acc, term),
+        });
+    }
+    det.unwrap_or_else(|| Expr::number(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(rows: &[&[i32]]) -> Array {
+        Array::from_rows(
+            rows.iter()
+                .map(|row| row.iter().map(|&x| Scalar::from(x)).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_shape_checking() {
+        let w = Expr::matrix(vec![
+            vec![Expr::var("a"), Expr::var("b"), Expr::var("c")],
+            vec![Expr::var("d"), Expr::var("e"), Expr::var("f")],
+        ]);
+        let x3 = Expr::vector(vec![Expr::var("x"), Expr::var("y"), Expr::var("z")]);
+        let b2 = Expr::vector(vec![Expr::number(1), Expr::number(2)]);
+
+        // W·x + b: 2×3 times 3 plus 2
+        let layer = Expr::add(Expr::matmul(w.clone(), x3.clone()), b2.clone());
+        assert_eq!(layer.shape().unwrap(), Some(Shape::vector(2)));
+
+        // W·b has the wrong inner size, and a vector plus a 2×3 matrix is no sum
+        assert!(matches!(
+            Expr::matmul(w.clone(), b2).shape(),
+            Err(VeritasError::ShapeMismatch { .. })
+        ));
+        assert!(Expr::add(x3, w.clone()).shape().is_err());
+        assert!(Expr::det(w).shape().is_err());
+
+        // A ragged matrix is rejected
+        let ragged = Expr::matrix(vec![vec![Expr::number(1)], vec![Expr::number(2), Expr::number(3)]]);
+        assert!(ragged.shape().is_err());
+    }
+
+    #[test]
+    fn test_numeric_operations() {
+        let a = numbers(&[&[2, 1], &[1, 3]]);
+        assert_eq!(a.det().unwrap(), Scalar::from(5));
+        assert_eq!(a.trace().unwrap(), Scalar::from(5));
+        assert_eq!(a.transpose(), a);
+
+        // A·A⁻¹ = I
+        let identity = a.matmul(&a.inverse().unwrap()).unwrap();
+        let expected = numbers(&[&[1, 0], &[0, 1]]);
+        for (x, y) in identity.entries().iter().zip(expected.entries()) {
+            let diff = x.checked_sub(*y).unwrap().abs();
+            assert_eq!(diff.compare(&Scalar::from(1e-12)).unwrap(), Ordering::Less);
+        }
+
+        assert!(numbers(&[&[1, 2], &[2, 4]]).inverse().is_err());
+        assert_eq!(numbers(&[&[1, 2], &[2, 4]]).det().unwrap(), Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_large_product_matches_tensor_path() {
+        // 16×16 by 16×16 is over the threshold, with entries small enough
+        // that F4E4 computes every sum exactly
+        let rows: Vec<Vec<Scalar>> = (0..16)
+            .map(|i| (0..16).map(|j| Scalar::from((i * j) % 7 - 3)).collect())
+            .collect();
+        let a = Array::from_rows(rows).unwrap();
+        let product = a.matmul(&a.transpose()).unwrap();
+
+        // Row i against itself is Σⱼ ((ij mod 7) - 3)²
+        for i in 0..16i64 {
+            let expected: i64 = (0..16).map(|j| ((i * j) % 7 - 3).pow(2)).sum();
+            let diagonal = product.entries()[(i * 16 + i) as usize];
+            assert_eq!(diagonal.to_i64(), Some(expected));
+        }
+    }
+}
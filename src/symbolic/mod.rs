@@ -5,8 +5,24 @@
 //!
 //! Key types:
 //! - `Expr`: Symbolic expression tree
-//! - `Context`: Variable bindings
+//! - `Context`: Scoped bindings to values or expressions, and function definitions
+//! - `Assumptions`: Known facts about variables (x > 0, n ∈ ℤ, ...)
 //! - `Simplify`: Expression simplification
+//! - `EvalBudget`: Limits on the work one evaluation or simplification may do
+//! - `PartialEvaluate`: Fold what is bound, keep the rest symbolic
+//! - `Program`: Compiled form of an `Expr` for repeated evaluation
+//! - `Batch`: One expression over the rows of a tensor, with a per-row mask
+//! - `ExprArena`: Hash-consed expressions with memoized simplify and evaluate
+//! - `Differentiate` / `Integrate`: Calculus, checked against each other
+//! - `series` / `limit`: Taylor expansion and limits, through removable 0/0
+//! - `Summation`: Closed forms for Σ and Π over integer ranges
+//! - `Polynomial`: Exact expansion and factoring over the rationals
+//! - `OdeSystem`: Adaptive Dormand–Prince integration with event detection
+//! - `prove_identity`: Equational proofs by searching rewrites of both sides
+//! - Complex built-ins (conj, re, im, mag, arg) with Euler's formula rules
+//! - `Render`: LaTeX, MathML and 2-D ASCII output, numbers in any base
+//! - `Quantity`: Magnitudes with units, checked by dimensional analysis
+//! - Vectors and matrices, with shapes checked before they are combined
 //!
 //! Design principles:
 //! - Every expression can be simplified
@@ -14,19 +30,58 @@
 //! - Simplification preserves mathematical equivalence
 //! - Evaluation returns Spirix types (traceable errors)
 
+pub mod assumptions;
+pub mod batch;
+pub mod budget;
 pub mod context;
 pub mod eval;
 pub mod expr;
 pub mod simplify;
 pub mod arithmetic;
 pub mod bitwise;
+pub mod bytecode;
+pub mod complex;
+pub mod dag;
+pub mod derivative;
+pub mod equivalence;
+pub mod integrate;
+pub mod linalg;
+pub mod ode;
+pub mod polynomial;
+pub mod prover;
+pub mod rational;
+pub mod render;
+pub mod series;
+pub mod substitute;
+pub mod summation;
+pub mod units;
 
-pub use context::Context;
+pub use assumptions::{Assumption, Assumptions};
+pub use batch::{Batch, Element};
+pub use budget::{EvalBudget, Resource, Usage};
+pub use context::{Binding, Context, FunctionDef};
 pub use eval::Evaluate;
 pub use expr::Expr;
 pub use simplify::Simplify;
 pub use arithmetic::{ArithOp, ArithProblem, ArithResult, ArithGenerator};
-pub use bitwise::{BitwiseOp, BitwiseProblem, BitwiseResult, BitwiseGenerator};
+pub use bytecode::Program;
+pub use bitwise::{BitExpr, BitRelation, BitwiseOp, BitwiseProblem, BitwiseResult, BitwiseGenerator};
+pub use dag::{ExprArena, ExprId};
+pub use derivative::Differentiate;
+pub use equivalence::{equivalent, NormalForm};
+pub use integrate::{Antiderivative, Integrate, IntegrationGenerator, IntegrationRule, IntegrandFamily};
+pub use linalg::Array;
+pub use ode::{
+    Crossing, Event, EventHit, OdeOptions, OdeSolution, OdeSystem, Step, Termination,
+};
+pub use polynomial::{expand, factor, Factorization, Polynomial};
+pub use prover::{prove_identity, IdentityProof, Rewrite, RuleFamily, SearchBudget};
+pub use rational::Rational;
+pub use render::{Render, RenderOptions};
+pub use series::{limit, series, Direction, Limit, Series};
+pub use substitute::PartialEvaluate;
+pub use summation::{ClosedForm, Summation, SummationRule};
+pub use units::{convert, scale_to_total, Dimension, Quantity, Unit};
 
 use crate::error::{Result, VeritasError};
 use crate::numeric::{Circle, Scalar};
@@ -0,0 +1,826 @@
+//! Ordinary differential equations
+//!
+//! `OdeSystem` holds an initial value problem dy/dt = f(t, y), y(t₀) = y₀,
+//! with each right-hand side an `Expr` in the time variable and the state
+//! variables. `solve` integrates it with the Dormand–Prince 5(4) pair:
+//! seven stages give a fifth-order step and an embedded fourth-order one,
+//! and their difference estimates the local error. A step is accepted
+//! when that estimate is within `rtol·|y| + atol` in every component, and
+//! the next step size follows from how far inside or outside it fell.
+//!
+//! Components are `Scalar` or `Circle`, whichever the initial value is.
+//! Every stage value is checked: as soon as Spirix reports one vanished,
+//! exploded or undefined the run stops with `IntegrationStopped`, naming
+//! the time and step, rather than carrying a value that has lost its
+//! meaning into the next step.
+//!
+//! Events are zero crossings of an `Expr` in the same variables, located
+//! by bisection on the cubic Hermite interpolant of each accepted step.
+
+use super::context::Value;
+use super::{Context, Evaluate, Expr};
+use crate::error::{Result, VeritasError};
+use crate::numeric::{Circle, Scalar};
+
+/// Stage times cᵢ of the Dormand–Prince tableau
+const NODES: [(i32, i32); 7] = [(0, 1), (1, 5), (3, 10), (4, 5), (8, 9), (1, 1), (1, 1)];
+
+/// Stage coefficients; the last row is also the fifth-order solution
+const COEFFICIENTS: [&[(i32, i32)]; 6] = [
+    &[(1, 5)],
+    &[(3, 40), (9, 40)],
+    &[(44, 45), (-56, 15), (32, 9)],
+    &[(19372, 6561), (-25360, 2187), (64448, 6561), (-212, 729)],
+    &[(9017, 3168), (-355, 33), (46732, 5247), (49, 176), (-5103, 18656)],
+    &[(35, 384), (0, 1), (500, 1113), (125, 192), (-2187, 6784), (11, 84)],
+];
+
+/// Fifth-order weights minus fourth-order weights
+const ERROR_WEIGHTS: [(i32, i32); 7] = [
+    (71, 57600),
+    (0, 1),
+    (-71, 16695),
+    (71, 1920),
+    (-17253, 339200),
+    (22, 525),
+    (-1, 40),
+];
+
+/// Bisection steps locating an event within a step
+const EVENT_BISECTIONS: usize = 48;
+
+/// Initial steps per unit of the interval, when none is given
+const INITIAL_DIVISIONS: i32 = 64;
+
+fn fraction((num, den): (i32, i32)) -> Scalar {
+    Scalar::from(num) / Scalar::from(den)
+}
+
+/// 2⁻ⁿ for n ≤ 60
+fn power_of_half(n: u32) -> Scalar {
+    let mut result = Scalar::ONE;
+    for _ in 0..n / 20 {
+        result = result / Scalar::from(1 << 20);
+    }
+    result / Scalar::from(1 << (n - n / 20 * 20))
+}
+
+fn less(a: Scalar, b: Scalar) -> bool {
+    a.inner() < b.inner()
+}
+
+/// Tolerances and limits for `OdeSystem::solve`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OdeOptions {
+    /// Relative tolerance on each component
+    pub rtol: Scalar,
+    /// Absolute tolerance on each component
+    pub atol: Scalar,
+    /// First step size; by default the interval over 64
+    pub initial_step: Option<Scalar>,
+    /// Largest step size
+    pub max_step: Option<Scalar>,
+    /// Smallest step size before giving up
+    pub min_step: Scalar,
+    /// Most accepted steps
+    pub max_steps: usize,
+}
+
+impl Default for OdeOptions {
+    fn default() -> Self {
+        OdeOptions {
+            rtol: power_of_half(20),
+            atol: power_of_half(30),
+            initial_step: None,
+            max_step: None,
+            min_step: power_of_half(40),
+            max_steps: 100_000,
+        }
+    }
+}
+
+/// Direction of a zero crossing that triggers an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossing {
+    /// From negative to zero or positive
+    Rising,
+    /// From positive to zero or negative
+    Falling,
+    Either,
+}
+
+/// Zero crossing of a condition to watch for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub condition: Expr,
+    pub crossing: Crossing,
+    /// Stop the integration when it occurs
+    pub terminal: bool,
+}
+
+impl Event {
+    /// Crossing of zero in either direction, not stopping the run
+    pub fn new(name: impl Into<String>, condition: Expr) -> Self {
+        Event {
+            name: name.into(),
+            condition,
+            crossing: Crossing::Either,
+            terminal: false,
+        }
+    }
+
+    pub fn with_crossing(mut self, crossing: Crossing) -> Self {
+        self.crossing = crossing;
+        self
+    }
+
+    pub fn stopping(mut self) -> Self {
+        self.terminal = true;
+        self
+    }
+}
+
+/// An event as it occurred
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventHit {
+    pub name: String,
+    pub t: Scalar,
+    /// State at `t`, interpolated within the step
+    pub state: Vec<Value>,
+}
+
+/// One accepted step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    /// Start of the step
+    pub t: Scalar,
+    pub h: Scalar,
+    /// Estimated local error relative to the tolerance, at most one
+    pub error: Scalar,
+}
+
+/// Why the integration ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum Termination {
+    /// Reached the end of the interval
+    Completed,
+    /// A terminal event occurred
+    Event(String),
+    /// Took `max_steps` steps first
+    StepLimit,
+    /// The error estimate needed a step below `min_step`
+    StepSizeUnderflow,
+}
+
+/// Trajectory of an integration
+#[derive(Debug, Clone)]
+pub struct OdeSolution {
+    pub variables: Vec<String>,
+    /// Times of the accepted steps, from t₀
+    pub times: Vec<Scalar>,
+    /// State at each time, in the order of `variables`
+    pub states: Vec<Vec<Value>>,
+    pub steps: Vec<Step>,
+    /// Steps retried with a smaller size
+    pub rejected: usize,
+    pub events: Vec<EventHit>,
+    /// Evaluations of the right-hand sides
+    pub evaluations: usize,
+    pub termination: Termination,
+}
+
+impl OdeSolution {
+    /// Time the integration ended at
+    pub fn final_time(&self) -> Scalar {
+        *self.times.last().expect("a solution starts at t₀")
+    }
+
+    /// Value of `variable` where the integration ended
+    pub fn final_value(&self, variable: &str) -> Option<&Value> {
+        let index = self.variables.iter().position(|v| v == variable)?;
+        self.states.last()?.get(index)
+    }
+
+    /// Largest local error estimate relative to the tolerance
+    pub fn max_error(&self) -> Scalar {
+        self.steps
+            .iter()
+            .map(|step| step.error)
+            .fold(Scalar::ZERO, |max, e| if less(max, e) { e } else { max })
+    }
+}
+
+/// Initial value problem dy/dt = f(t, y)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OdeSystem {
+    time: String,
+    start: Scalar,
+    /// State variable, its derivative and its value at the start
+    equations: Vec<(String, Expr, Value)>,
+    events: Vec<Event>,
+}
+
+impl OdeSystem {
+    /// System in the time variable `time`, starting at `start`
+    pub fn new(time: impl Into<String>, start: Scalar) -> Self {
+        OdeSystem {
+            time: time.into(),
+            start,
+            equations: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Add d`variable`/dt = `derivative` with `variable`(t₀) = `initial`
+    pub fn equation(
+        mut self,
+        variable: impl Into<String>,
+        derivative: Expr,
+        initial: impl Into<Value>,
+    ) -> Self {
+        self.equations.push((variable.into(), derivative, initial.into()));
+        self
+    }
+
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn variables(&self) -> Vec<&str> {
+        self.equations.iter().map(|(v, _, _)| v.as_str()).collect()
+    }
+
+    /// Integrate from the start to `end`
+    ///
+    /// `ctx` supplies parameters and functions the right-hand sides use;
+    /// the time and state variables are bound over it.
+    pub fn solve(&self, ctx: &Context, end: Scalar, options: &OdeOptions) -> Result<OdeSolution> {
+        self.validate(end)?;
+        let mut run = Run::new(self, ctx);
+        let mut y = Vec::with_capacity(self.equations.len());
+        for (variable, _, initial) in &self.equations {
+            y.push(Component::from_value(variable, initial.clone())?);
+        }
+        run.integrate(y, end, options)
+    }
+
+    fn validate(&self, end: Scalar) -> Result<()> {
+        if self.equations.is_empty() {
+            return Err(VeritasError::InvalidInput("no equations to integrate".to_string()));
+        }
+        let mut names = vec![self.time.as_str()];
+        for variable in self.variables() {
+            if names.contains(&variable) {
+                return Err(VeritasError::InvalidInput(format!(
+                    "{} is named twice among the time and state variables",
+                    variable
+                )));
+            }
+            names.push(variable);
+        }
+        if !less(self.start, end) {
+            return Err(VeritasError::InvalidInput(format!(
+                "cannot integrate from {} to {}: the end must come later",
+                self.start, end
+            )));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// State components
+// ============================================================================
+
+/// One state component, real or complex for the whole run
+#[derive(Debug, Clone, Copy)]
+enum Component {
+    Real(Scalar),
+    Complex(Circle),
+}
+
+impl Component {
+    fn from_value(variable: &str, value: Value) -> Result<Self> {
+        match value {
+            Value::Scalar(s) => Ok(Component::Real(s)),
+            Value::Circle(c) => Ok(Component::Complex(c)),
+            _ => Err(VeritasError::InvalidInput(format!(
+                "{} must start at a real or complex number",
+                variable
+            ))),
+        }
+    }
+
+    /// Derivative of a component of this kind
+    fn derivative(self, variable: &str, value: Value) -> Result<Self> {
+        match (self, value) {
+            (Component::Real(_), Value::Scalar(s)) => Ok(Component::Real(s)),
+            (Component::Complex(_), Value::Scalar(s)) => Ok(Component::Complex(Circle::from(s))),
+            (Component::Complex(_), Value::Circle(c)) => Ok(Component::Complex(c)),
+            (Component::Real(_), Value::Circle(_)) => Err(VeritasError::InvalidInput(format!(
+                "d{}/dt is complex but {} is real; start it at a complex value",
+                variable, variable
+            ))),
+            _ => Err(VeritasError::InvalidInput(format!(
+                "d{}/dt is not a number",
+                variable
+            ))),
+        }
+    }
+
+    fn to_value(self) -> Value {
+        match self {
+            Component::Real(s) => Value::Scalar(s),
+            Component::Complex(c) => Value::Circle(c),
+        }
+    }
+
+    /// self + factor·other, other of the same kind
+    fn add_scaled(self, factor: Scalar, other: Component) -> Component {
+        match (self, other) {
+            (Component::Real(a), Component::Real(b)) => Component::Real(a + factor * b),
+            (Component::Complex(a), Component::Complex(b)) => {
+                Component::Complex(a + Circle::from(factor) * b)
+            }
+            (Component::Real(a), Component::Complex(b)) => {
+                Component::Complex(Circle::from(a) + Circle::from(factor) * b)
+            }
+            (Component::Complex(a), Component::Real(b)) => {
+                Component::Complex(a + Circle::from(factor * b))
+            }
+        }
+    }
+
+    fn scaled(self, factor: Scalar) -> Component {
+        match self {
+            Component::Real(a) => Component::Real(factor * a),
+            Component::Complex(a) => Component::Complex(Circle::from(factor) * a),
+        }
+    }
+
+    fn magnitude(self) -> Scalar {
+        match self {
+            Component::Real(a) => a.abs(),
+            Component::Complex(a) => a.magnitude(),
+        }
+    }
+
+    /// Fail if Spirix can no longer represent the value faithfully
+    fn check(self) -> Result<Self> {
+        match self {
+            Component::Real(a) => a.representable()?,
+            Component::Complex(a) => {
+                a.real().representable()?;
+                a.imag().representable()?
+            }
+        };
+        Ok(self)
+    }
+}
+
+// ============================================================================
+// Integration
+// ============================================================================
+
+/// Working state of one call to `solve`
+struct Run<'a> {
+    system: &'a OdeSystem,
+    /// The caller's context with a scope for the time and state variables
+    ctx: Context,
+    /// Accepted steps so far
+    step: usize,
+    evaluations: usize,
+}
+
+impl<'a> Run<'a> {
+    fn new(system: &'a OdeSystem, ctx: &Context) -> Self {
+        let mut ctx = ctx.clone();
+        ctx.push_scope();
+        Run {
+            system,
+            ctx,
+            step: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Numeric failures stop the run where they happened; anything else
+    /// (an unbound variable, a shape error) passes through unchanged
+    fn stopped(&self, t: Scalar, cause: VeritasError) -> VeritasError {
+        if cause.is_mathematical() {
+            VeritasError::IntegrationStopped {
+                t: t.to_string(),
+                step: self.step,
+                cause: Box::new(cause),
+            }
+        } else {
+            cause
+        }
+    }
+
+    fn bind(&mut self, t: Scalar, y: &[Component]) {
+        self.ctx.bind(self.system.time.as_str(), t);
+        for ((variable, _, _), component) in self.system.equations.iter().zip(y) {
+            self.ctx.bind(variable.as_str(), component.to_value());
+        }
+    }
+
+    /// f(t, y), every component checked
+    fn derivative(&mut self, t: Scalar, y: &[Component]) -> Result<Vec<Component>> {
+        for component in y {
+            component.check().map_err(|e| self.stopped(t, e))?;
+        }
+        self.bind(t, y);
+        self.evaluations += 1;
+        let mut slopes = Vec::with_capacity(y.len());
+        for ((variable, derivative, _), component) in self.system.equations.iter().zip(y) {
+            let slope = derivative
+                .evaluate(&self.ctx)
+                .and_then(|value| component.derivative(variable, value))
+                .and_then(Component::check)
+                .map_err(|e| self.stopped(t, e))?;
+            slopes.push(slope);
+        }
+        Ok(slopes)
+    }
+
+    /// Value of each event condition at (t, y)
+    fn conditions(&mut self, t: Scalar, y: &[Component]) -> Result<Vec<Scalar>> {
+        self.bind(t, y);
+        let mut values = Vec::with_capacity(self.system.events.len());
+        for event in &self.system.events {
+            let value = event
+                .condition
+                .evaluate_scalar(&self.ctx)
+                .and_then(|s| s.representable())
+                .map_err(|e| self.stopped(t, e))?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn integrate(
+        &mut self,
+        mut y: Vec<Component>,
+        end: Scalar,
+        options: &OdeOptions,
+    ) -> Result<OdeSolution> {
+        let nodes = NODES.map(fraction);
+        let weights = ERROR_WEIGHTS.map(fraction);
+        let coefficients: Vec<Vec<Scalar>> = COEFFICIENTS
+            .iter()
+            .map(|row| row.iter().copied().map(fraction).collect())
+            .collect();
+        let (safety, min_factor, max_factor) =
+            (fraction((9, 10)), fraction((1, 5)), fraction((5, 1)));
+        let exponent = fraction((-1, 5));
+
+        let mut t = self.system.start;
+        let mut h = options
+            .initial_step
+            .unwrap_or((end - t) / Scalar::from(INITIAL_DIVISIONS));
+        if let Some(max) = options.max_step {
+            h = if less(max, h) { max } else { h };
+        }
+
+        let mut solution = OdeSolution {
+            variables: self.system.variables().iter().map(|v| v.to_string()).collect(),
+            times: vec![t],
+            states: vec![y.iter().map(|c| c.to_value()).collect()],
+            steps: Vec::new(),
+            rejected: 0,
+            events: Vec::new(),
+            evaluations: 0,
+            termination: Termination::Completed,
+        };
+        let mut slope = self.derivative(t, &y)?;
+        let mut conditions = self.conditions(t, &y)?;
+
+        while less(t, end) {
+            if self.step >= options.max_steps {
+                solution.termination = Termination::StepLimit;
+                break;
+            }
+            let last = !less(t + h, end);
+            if last {
+                h = end - t;
+            }
+
+            // Stages k₁..k₇; the seventh is evaluated at the new solution
+            let mut stages = vec![slope.clone()];
+            for (i, row) in coefficients.iter().enumerate() {
+                let state: Vec<Component> = (0..y.len())
+                    .map(|j| {
+                        row.iter()
+                            .zip(&stages)
+                            .fold(y[j], |acc, (&a, k)| acc.add_scaled(h * a, k[j]))
+                    })
+                    .collect();
+                let k = self.derivative(t + nodes[i + 1] * h, &state)?;
+                stages.push(k);
+            }
+            let next: Vec<Component> = (0..y.len())
+                .map(|j| {
+                    coefficients[5]
+                        .iter()
+                        .zip(&stages)
+                        .fold(y[j], |acc, (&a, k)| acc.add_scaled(h * a, k[j]))
+                })
+                .collect();
+
+            // Largest error component relative to its tolerance
+            let mut error = Scalar::ZERO;
+            for j in 0..y.len() {
+                let estimate = weights
+                    .iter()
+                    .zip(&stages)
+                    .fold(Component::Real(Scalar::ZERO), |acc, (&w, k)| {
+                        acc.add_scaled(h * w, k[j])
+                    })
+                    .magnitude();
+                let (before, after) = (y[j].magnitude(), next[j].magnitude());
+                let scale = if less(before, after) { after } else { before };
+                let ratio = estimate / (options.atol + options.rtol * scale);
+                let ratio = ratio.representable().map_err(|e| self.stopped(t, e))?;
+                if less(error, ratio) {
+                    error = ratio;
+                }
+            }
+
+            let accepted = !less(Scalar::ONE, error);
+            if accepted {
+                let t_next = if last { end } else { t + h };
+                let slope_next = stages.pop().expect("seven stages");
+                let conditions_next = self.conditions(t_next, &next)?;
+                solution.steps.push(Step { t, h, error });
+                self.step += 1;
+
+                let interval = Interval {
+                    t,
+                    h,
+                    start: &y,
+                    end: &next,
+                    slope_start: &slope,
+                    slope_end: &slope_next,
+                };
+                if let Some(hit) =
+                    self.events(&interval, &conditions, &conditions_next, &mut solution)?
+                {
+                    solution.times.push(hit.t);
+                    solution.states.push(hit.state.clone());
+                    solution.termination = Termination::Event(hit.name);
+                    break;
+                }
+
+                t = t_next;
+                y = next;
+                slope = slope_next;
+                conditions = conditions_next;
+                solution.times.push(t);
+                solution.states.push(y.iter().map(|c| c.to_value()).collect());
+            } else {
+                solution.rejected += 1;
+            }
+
+            // h·0.9·error^(-1/5), kept within a factor of five either way
+            let mut factor = if error.is_zero() {
+                max_factor
+            } else {
+                safety * error.pow(exponent).map_err(|e| self.stopped(t, e))?
+            };
+            factor = if less(factor, min_factor) { min_factor } else { factor };
+            factor = if less(max_factor, factor) { max_factor } else { factor };
+            if !accepted && less(Scalar::ONE, factor) {
+                factor = Scalar::ONE;
+            }
+            h = (h * factor).representable().map_err(|e| self.stopped(t, e))?;
+            if let Some(max) = options.max_step {
+                h = if less(max, h) { max } else { h };
+            }
+            if less(t, end) && less(h, options.min_step) {
+                solution.termination = Termination::StepSizeUnderflow;
+                break;
+            }
+        }
+
+        solution.evaluations = self.evaluations;
+        Ok(solution)
+    }
+
+    /// Record the events of one step, in order; the first terminal one
+    /// ends the search and is returned
+    fn events(
+        &mut self,
+        interval: &Interval,
+        before: &[Scalar],
+        after: &[Scalar],
+        solution: &mut OdeSolution,
+    ) -> Result<Option<EventHit>> {
+        let mut hits = Vec::new();
+        for (index, event) in self.system.events.iter().enumerate() {
+            if crosses(event.crossing, before[index], after[index]) {
+                hits.push(index);
+            }
+        }
+        let mut located = Vec::with_capacity(hits.len());
+        for index in hits {
+            let theta = self.locate(index, interval, before[index])?;
+            located.push((theta, index));
+        }
+        located.sort_by(|a, b| a.0.inner().partial_cmp(&b.0.inner()).expect("θ is normal"));
+
+        for (theta, index) in located {
+            let event = &self.system.events[index];
+            let hit = EventHit {
+                name: event.name.clone(),
+                t: interval.t + theta * interval.h,
+                state: interval.at(theta).iter().map(|c| c.to_value()).collect(),
+            };
+            solution.events.push(hit.clone());
+            if event.terminal {
+                return Ok(Some(hit));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fraction θ of the step where event `index` first changes sign
+    fn locate(&mut self, index: usize, interval: &Interval, before: Scalar) -> Result<Scalar> {
+        let half = fraction((1, 2));
+        let (mut low, mut high) = (Scalar::ZERO, Scalar::ONE);
+        for _ in 0..EVENT_BISECTIONS {
+            let middle = (low + high) * half;
+            let t = interval.t + middle * interval.h;
+            let value = self.conditions(t, &interval.at(middle))?[index];
+            if value.is_zero() || value.is_negative() != before.is_negative() {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        Ok(high)
+    }
+}
+
+/// Whether a condition going from `before` to `after` is a crossing
+fn crosses(crossing: Crossing, before: Scalar, after: Scalar) -> bool {
+    let positive = |s: Scalar| !s.is_zero() && !s.is_negative();
+    let rising = before.is_negative() && !after.is_negative();
+    let falling = positive(before) && !positive(after);
+    match crossing {
+        Crossing::Rising => rising,
+        Crossing::Falling => falling,
+        Crossing::Either => rising || falling,
+    }
+}
+
+/// One accepted step, for interpolation within it
+struct Interval<'a> {
+    t: Scalar,
+    h: Scalar,
+    start: &'a [Component],
+    end: &'a [Component],
+    slope_start: &'a [Component],
+    slope_end: &'a [Component],
+}
+
+impl Interval<'_> {
+    /// Cubic Hermite interpolant at t + θ·h, matching the values and
+    /// slopes at both ends
+    fn at(&self, theta: Scalar) -> Vec<Component> {
+        let (two, three) = (Scalar::from(2), Scalar::from(3));
+        let theta2 = theta * theta;
+        let theta3 = theta2 * theta;
+        let h00 = two * theta3 - three * theta2 + Scalar::ONE;
+        let h10 = theta3 - two * theta2 + theta;
+        let h01 = three * theta2 - two * theta3;
+        let h11 = theta3 - theta2;
+        (0..self.start.len())
+            .map(|j| {
+                self.start[j]
+                    .scaled(h00)
+                    .add_scaled(h10 * self.h, self.slope_start[j])
+                    .add_scaled(h01, self.end[j])
+                    .add_scaled(h11 * self.h, self.slope_end[j])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolic::constants;
+
+    fn close(a: Scalar, b: Scalar, tolerance: Scalar) -> bool {
+        less((a - b).abs(), tolerance)
+    }
+
+    fn scalar(value: &Value) -> Scalar {
+        match value {
+            Value::Scalar(s) => *s,
+            other => panic!("{:?} is not real", other),
+        }
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        // y' = -y, y(0) = 1: y(1) = 1/e
+        let system = OdeSystem::new("t", Scalar::ZERO).equation(
+            "y",
+            Expr::neg(Expr::var("y")),
+            Scalar::ONE,
+        );
+        let solution = system.solve(&Context::new(), Scalar::ONE, &OdeOptions::default()).unwrap();
+        assert_eq!(solution.termination, Termination::Completed);
+        assert_eq!(solution.final_time(), Scalar::ONE);
+        let expected = Scalar::from(-1).exp().unwrap();
+        let y = scalar(solution.final_value("y").unwrap());
+        assert!(close(y, expected, power_of_half(18)), "{} vs {}", y, expected);
+        assert!(!less(Scalar::ONE, solution.max_error()));
+        assert_eq!(solution.steps.len() + 1, solution.times.len());
+    }
+
+    #[test]
+    fn test_oscillator_with_events() {
+        // x' = v, v' = -x from (1, 0): x = cos t crosses zero falling at π/2
+        // and rising at 3π/2; the rising crossing stops the run
+        let system = OdeSystem::new("t", Scalar::ZERO)
+            .equation("x", Expr::var("v"), Scalar::ONE)
+            .equation("v", Expr::neg(Expr::var("x")), Scalar::ZERO)
+            .event(Event::new("down", Expr::var("x")).with_crossing(Crossing::Falling))
+            .event(
+                Event::new("up", Expr::var("x"))
+                    .with_crossing(Crossing::Rising)
+                    .stopping(),
+            );
+        let end = Scalar::from(10);
+        let solution = system.solve(&Context::new(), end, &OdeOptions::default()).unwrap();
+
+        assert_eq!(solution.termination, Termination::Event("up".to_string()));
+        let names: Vec<&str> = solution.events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["down", "up"]);
+        let half_pi = Scalar::PI / Scalar::from(2);
+        let tolerance = power_of_half(16);
+        assert!(close(solution.events[0].t, half_pi, tolerance));
+        assert!(close(solution.final_time(), Scalar::from(3) * half_pi, tolerance));
+        let v = scalar(solution.final_value("v").unwrap());
+        assert!(close(v, Scalar::ONE, tolerance));
+    }
+
+    #[test]
+    fn test_complex_rotation() {
+        // z' = i·z, z(0) = 1: z(π) = -1
+        let system = OdeSystem::new("t", Scalar::ZERO).equation(
+            "z",
+            Expr::mul(constants::i(), Expr::var("z")),
+            Circle::ONE,
+        );
+        let solution = system.solve(&Context::new(), Scalar::PI, &OdeOptions::default()).unwrap();
+        let Some(Value::Circle(z)) = solution.final_value("z") else {
+            panic!("z should stay complex");
+        };
+        let tolerance = power_of_half(16);
+        assert!(close(z.real(), Scalar::from(-1), tolerance));
+        assert!(close(z.imag(), Scalar::ZERO, tolerance));
+
+        // A real variable with a complex derivative is rejected
+        let real = OdeSystem::new("t", Scalar::ZERO).equation(
+            "z",
+            Expr::mul(constants::i(), Expr::var("z")),
+            Scalar::ONE,
+        );
+        assert!(real.solve(&Context::new(), Scalar::ONE, &OdeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_numeric_failure_stops_run() {
+        // y' = y·2^(2^40) explodes on the first evaluation
+        let huge = Expr::pow(
+            Expr::number(2),
+            Expr::pow(Expr::number(2), Expr::number(40)),
+        );
+        let system = OdeSystem::new("t", Scalar::ZERO).equation(
+            "y",
+            Expr::mul(Expr::var("y"), huge),
+            Scalar::ONE,
+        );
+        let error = system
+            .solve(&Context::new(), Scalar::ONE, &OdeOptions::default())
+            .unwrap_err();
+        let VeritasError::IntegrationStopped { step, cause, .. } = error else {
+            panic!("expected IntegrationStopped, got {:?}", error);
+        };
+        assert_eq!(step, 0);
+        assert!(cause.is_mathematical());
+
+        // Unbound parameters are reported as they are
+        let unbound = OdeSystem::new("t", Scalar::ZERO).equation("y", Expr::var("k"), Scalar::ONE);
+        let error = unbound
+            .solve(&Context::new(), Scalar::ONE, &OdeOptions::default())
+            .unwrap_err();
+        assert!(matches!(error, VeritasError::VariableNotFound(_)));
+    }
+}
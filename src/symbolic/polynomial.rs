@@ -0,0 +1,797 @@
+//! Polynomials with exact rational coefficients
+//!
+//! `Polynomial` is a sparse sum of monomials over named atoms. An atom
+//! is a variable, a constant, or any subexpression that is not itself
+//! polynomial (sin(x), √y, 1/(x+1), ...), so every expression has a
+//! polynomial reading and `expand` applies everywhere. The univariate
+//! algorithms behind `factor` work on the dense coefficient vector of a
+//! polynomial in a single atom.
+//!
+//! `factor` pulls out the rational content, splits the primitive part
+//! into square-free factors (Yun's algorithm) and peels a linear factor
+//! off each rational root. The factors are multiplied back together and
+//! compared exactly with the input before the result is returned.
+
+use super::rational::{gcd, Rational};
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use std::collections::BTreeMap;
+
+/// Largest number of terms a polynomial may grow to
+const MAX_TERMS: usize = 4096;
+
+/// Largest integer power that gets expanded
+const MAX_POWER: i64 = 64;
+
+/// Largest |coefficient| whose divisors are searched for rational roots
+const MAX_ROOT_SEARCH: i128 = 1_000_000_000_000;
+
+/// Product of atoms raised to positive powers, keyed by rendered atom
+pub type Monomial = BTreeMap<String, u32>;
+
+/// Sparse polynomial over atoms with rational coefficients
+#[derive(Debug, Clone)]
+pub struct Polynomial {
+    terms: BTreeMap<Monomial, Rational>,
+    atoms: BTreeMap<String, Expr>,
+}
+
+impl PartialEq for Polynomial {
+    // Atoms are keyed by their rendering, so equal terms mean equal polynomials
+    fn eq(&self, other: &Self) -> bool {
+        self.terms == other.terms
+    }
+}
+
+impl Polynomial {
+    /// The zero polynomial
+    pub fn zero() -> Self {
+        Polynomial {
+            terms: BTreeMap::new(),
+            atoms: BTreeMap::new(),
+        }
+    }
+
+    /// A constant
+    pub fn constant(value: Rational) -> Self {
+        let mut poly = Polynomial::zero();
+        if !value.is_zero() {
+            poly.terms.insert(Monomial::new(), value);
+        }
+        poly
+    }
+
+    /// A single atom to the first power
+    pub fn atom(expr: Expr) -> Self {
+        let key = format!("{}", expr);
+        let mut poly = Polynomial::zero();
+        poly.terms.insert(Monomial::from([(key.clone(), 1)]), Rational::ONE);
+        poly.atoms.insert(key, expr);
+        poly
+    }
+
+    /// Read an expression as a polynomial over its atoms
+    pub fn from_expr(expr: &Expr) -> Result<Self> {
+        match expr {
+            Expr::Number(n) => Ok(match Rational::from_scalar(*n) {
+                Some(r) => Polynomial::constant(r),
+                None => Polynomial::atom(expr.clone()),
+            }),
+            Expr::Complex(c) => {
+                if c.imag().is_zero() {
+                    Polynomial::from_expr(&Expr::Number(c.real()))
+                } else {
+                    Ok(Polynomial::atom(expr.clone()))
+                }
+            }
+            Expr::Variable(_) | Expr::Constant(_) => Ok(Polynomial::atom(expr.clone())),
+
+            Expr::Add(a, b) => Polynomial::from_expr(a)?.add(&Polynomial::from_expr(b)?),
+            Expr::Sub(a, b) => Polynomial::from_expr(a)?.sub(&Polynomial::from_expr(b)?),
+            Expr::Mul(a, b) => Polynomial::from_expr(a)?.mul(&Polynomial::from_expr(b)?),
+            Expr::Neg(a) => Polynomial::from_expr(a)?.scale(-Rational::ONE),
+
+            Expr::Div(a, b) => {
+                let numerator = Polynomial::from_expr(a)?;
+                let denominator = Polynomial::from_expr(b)?;
+                match denominator.as_constant() {
+                    Some(c) => numerator.scale(Rational::ONE.checked_div(c)?),
+                    None => Ok(Polynomial::atom(Expr::div(
+                        numerator.to_expr()?,
+                        denominator.to_expr()?,
+                    ))),
+                }
+            }
+
+            Expr::Pow(base, exp) => {
+                let base = Polynomial::from_expr(base)?;
+                let exp = Polynomial::from_expr(exp)?;
+                let power = exp
+                    .as_constant()
+                    .filter(|r| r.is_integer())
+                    .and_then(|r| u32::try_from(r.numer()).ok())
+                    .filter(|n| i64::from(*n) <= MAX_POWER);
+                match power {
+                    Some(n) => base.pow(n),
+                    None => Ok(Polynomial::atom(Expr::pow(base.to_expr()?, exp.to_expr()?))),
+                }
+            }
+
+            // Non-polynomial operations become atoms with expanded arguments
+            Expr::Sqrt(a) => Ok(Polynomial::atom(Expr::sqrt(expand(a)?))),
+            Expr::Ln(a) => Ok(Polynomial::atom(Expr::ln(expand(a)?))),
+            Expr::Exp(a) => Ok(Polynomial::atom(Expr::exp(expand(a)?))),
+            Expr::Sin(a) => Ok(Polynomial::atom(Expr::sin(expand(a)?))),
+            Expr::Cos(a) => Ok(Polynomial::atom(Expr::cos(expand(a)?))),
+            Expr::Tan(a) => Ok(Polynomial::atom(Expr::tan(expand(a)?))),
+            Expr::Function(name, args) => {
+                let args: Result<Vec<_>> = args.iter().map(expand).collect();
+                Ok(Polynomial::atom(Expr::Function(name.clone(), args?)))
+            }
+
+            Expr::Quantity(..) | Expr::Bool(_) => Ok(Polynomial::atom(expr.clone())),
+            Expr::Vector(_)
+            | Expr::Matrix(_)
+            | Expr::MatMul(..)
+            | Expr::Sum(..)
+            | Expr::Product(..) => Ok(Polynomial::atom(expr.try_map_children(expand)?)),
+            Expr::Let(name, value, body) => expand(&body.substitute(name, value)),
+            Expr::Eq(a, b) => Ok(Polynomial::atom(Expr::equals(expand(a)?, expand(b)?))),
+            Expr::Ne(a, b) => Ok(Polynomial::atom(Expr::not_equals(expand(a)?, expand(b)?))),
+            Expr::Lt(a, b) => Ok(Polynomial::atom(Expr::less(expand(a)?, expand(b)?))),
+            Expr::Le(a, b) => Ok(Polynomial::atom(Expr::less_eq(expand(a)?, expand(b)?))),
+            Expr::Gt(a, b) => Ok(Polynomial::atom(Expr::greater(expand(a)?, expand(b)?))),
+            Expr::Ge(a, b) => Ok(Polynomial::atom(Expr::greater_eq(expand(a)?, expand(b)?))),
+            Expr::And(a, b) => Ok(Polynomial::atom(Expr::and(expand(a)?, expand(b)?))),
+            Expr::Or(a, b) => Ok(Polynomial::atom(Expr::or(expand(a)?, expand(b)?))),
+            Expr::Not(a) => Ok(Polynomial::atom(Expr::not(expand(a)?))),
+            Expr::Implies(a, b) => Ok(Polynomial::atom(Expr::implies(expand(a)?, expand(b)?))),
+            Expr::Piecewise(branches, otherwise) => {
+                let mut parts = Vec::with_capacity(branches.len());
+                for (condition, value) in branches {
+                    parts.push((expand(condition)?, expand(value)?));
+                }
+                Ok(Polynomial::atom(Expr::piecewise(parts, expand(otherwise)?)))
+            }
+        }
+    }
+
+    /// Rebuild as an expression, highest degree first
+    pub fn to_expr(&self) -> Result<Expr> {
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by_key(|(monomial, _)| std::cmp::Reverse(degree(monomial)));
+
+        let mut result: Option<Expr> = None;
+        for (monomial, coefficient) in terms {
+            let magnitude = self.term_expr(monomial, coefficient.abs())?;
+            result = Some(match result {
+                None if coefficient.is_negative() => match magnitude {
+                    Expr::Number(n) => Expr::Number(-n),
+                    other => Expr::neg(other),
+                },
+                None => magnitude,
+                Some(acc) if coefficient.is_negative() => Expr::sub(acc, magnitude),
+                Some(acc) => Expr::add(acc, magnitude),
+            });
+        }
+        Ok(result.unwrap_or(Expr::number(0)))
+    }
+
+    /// Expression for a single (positive-coefficient) term
+    fn term_expr(&self, monomial: &Monomial, coefficient: Rational) -> Result<Expr> {
+        let product = monomial
+            .iter()
+            .map(|(key, &power)| {
+                let atom = self.atoms[key].clone();
+                if power == 1 {
+                    atom
+                } else {
+                    Expr::pow(atom, Expr::number(power as i32))
+                }
+            })
+            .reduce(Expr::mul);
+
+        Ok(match product {
+            None => coefficient.to_expr()?,
+            Some(p) if coefficient == Rational::ONE => p,
+            Some(p) => Expr::mul(coefficient.to_expr()?, p),
+        })
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Check for the zero polynomial
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The value, if this is a constant
+    pub fn as_constant(&self) -> Option<Rational> {
+        match self.terms.len() {
+            0 => Some(Rational::ZERO),
+            1 => self.terms.get(&Monomial::new()).copied(),
+            _ => None,
+        }
+    }
+
+    /// Highest total degree (zero for constants)
+    pub fn degree(&self) -> u32 {
+        self.terms.keys().map(degree).max().unwrap_or(0)
+    }
+
+    /// Atoms that occur in some term
+    pub fn atoms(&self) -> Vec<&Expr> {
+        let mut used: Vec<&String> = self.terms.keys().flat_map(|m| m.keys()).collect();
+        used.sort();
+        used.dedup();
+        used.into_iter().map(|key| &self.atoms[key]).collect()
+    }
+
+    /// Coefficients of the powers of `atom`, lowest first
+    ///
+    /// Each coefficient is a polynomial in the remaining atoms.
+    pub fn coefficients_in(&self, atom: &Expr) -> Vec<Polynomial> {
+        let key = format!("{}", atom);
+        let mut coeffs: Vec<Polynomial> = Vec::new();
+        for (monomial, coefficient) in &self.terms {
+            let power = monomial.get(&key).copied().unwrap_or(0) as usize;
+            while coeffs.len() <= power {
+                let mut zero = Polynomial::zero();
+                zero.merge_atoms(self);
+                coeffs.push(zero);
+            }
+            let mut rest = monomial.clone();
+            rest.remove(&key);
+            coeffs[power].terms.insert(rest, *coefficient);
+        }
+        coeffs
+    }
+
+    fn add_term(&mut self, monomial: Monomial, coefficient: Rational) -> Result<()> {
+        if coefficient.is_zero() {
+            return Ok(());
+        }
+
+        match self.terms.get_mut(&monomial) {
+            Some(existing) => {
+                let sum = existing.checked_add(coefficient)?;
+                if sum.is_zero() {
+                    self.terms.remove(&monomial);
+                } else {
+                    *existing = sum;
+                }
+            }
+            None => {
+                if self.terms.len() >= MAX_TERMS {
+                    return Err(VeritasError::ComplexityLimit(self.terms.len() + 1));
+                }
+                self.terms.insert(monomial, coefficient);
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_atoms(&mut self, other: &Polynomial) {
+        for (key, atom) in &other.atoms {
+            self.atoms.entry(key.clone()).or_insert_with(|| atom.clone());
+        }
+    }
+
+    /// Sum of two polynomials
+    pub fn add(&self, other: &Polynomial) -> Result<Self> {
+        let mut sum = self.clone();
+        sum.merge_atoms(other);
+        for (monomial, coefficient) in &other.terms {
+            sum.add_term(monomial.clone(), *coefficient)?;
+        }
+        Ok(sum)
+    }
+
+    /// Difference of two polynomials
+    pub fn sub(&self, other: &Polynomial) -> Result<Self> {
+        self.add(&other.scale(-Rational::ONE)?)
+    }
+
+    /// Multiply every coefficient by `factor`
+    pub fn scale(&self, factor: Rational) -> Result<Self> {
+        let mut scaled = Polynomial::zero();
+        scaled.merge_atoms(self);
+        for (monomial, coefficient) in &self.terms {
+            scaled.add_term(monomial.clone(), coefficient.checked_mul(factor)?)?;
+        }
+        Ok(scaled)
+    }
+
+    /// Product of two polynomials (fully distributed)
+    pub fn mul(&self, other: &Polynomial) -> Result<Self> {
+        let mut product = Polynomial::zero();
+        product.merge_atoms(self);
+        product.merge_atoms(other);
+        for (m1, c1) in &self.terms {
+            for (m2, c2) in &other.terms {
+                let mut monomial = m1.clone();
+                for (key, power) in m2 {
+                    *monomial.entry(key.clone()).or_insert(0) += power;
+                }
+                product.add_term(monomial, c1.checked_mul(*c2)?)?;
+            }
+        }
+        Ok(product)
+    }
+
+    /// Non-negative integer power
+    pub fn pow(&self, n: u32) -> Result<Self> {
+        let mut result = Polynomial::constant(Rational::ONE);
+        result.merge_atoms(self);
+        for _ in 0..n {
+            result = result.mul(self)?;
+        }
+        Ok(result)
+    }
+
+    /// Dense coefficients in the single atom, lowest degree first
+    ///
+    /// `None` if more than one atom occurs or a term mixes atoms.
+    fn dense(&self) -> Option<(Option<Expr>, Dense)> {
+        let atoms = self.atoms();
+        if atoms.len() > 1 {
+            return None;
+        }
+        let atom = atoms.first().map(|a| (*a).clone());
+
+        let mut coeffs = vec![Rational::ZERO; self.degree() as usize + 1];
+        for (monomial, coefficient) in &self.terms {
+            coeffs[degree(monomial) as usize] = *coefficient;
+        }
+        Some((atom, Dense::new(coeffs)))
+    }
+
+    /// Polynomial in `atom` from dense coefficients
+    fn from_dense(atom: &Expr, dense: &Dense) -> Self {
+        let key = format!("{}", atom);
+        let mut poly = Polynomial::zero();
+        poly.atoms.insert(key.clone(), atom.clone());
+        for (power, coefficient) in dense.0.iter().enumerate() {
+            if coefficient.is_zero() {
+                continue;
+            }
+            let monomial = if power == 0 {
+                Monomial::new()
+            } else {
+                Monomial::from([(key.clone(), power as u32)])
+            };
+            poly.terms.insert(monomial, *coefficient);
+        }
+        poly
+    }
+}
+
+fn degree(monomial: &Monomial) -> u32 {
+    monomial.values().sum()
+}
+
+/// Dense univariate coefficients, lowest degree first, no trailing zeros
+#[derive(Debug, Clone, PartialEq)]
+struct Dense(Vec<Rational>);
+
+impl Dense {
+    fn new(mut coeffs: Vec<Rational>) -> Self {
+        while coeffs.last().is_some_and(|c| c.is_zero()) {
+            coeffs.pop();
+        }
+        Dense(coeffs)
+    }
+
+    fn one() -> Self {
+        Dense(vec![Rational::ONE])
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Degree, with the zero polynomial treated as degree 0
+    fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    fn leading(&self) -> Rational {
+        self.0.last().copied().unwrap_or(Rational::ZERO)
+    }
+
+    fn scale(&self, factor: Rational) -> Result<Self> {
+        let coeffs: Result<Vec<_>> = self.0.iter().map(|c| c.checked_mul(factor)).collect();
+        Ok(Dense::new(coeffs?))
+    }
+
+    fn sub(&self, other: &Dense) -> Result<Self> {
+        let len = self.0.len().max(other.0.len());
+        let mut coeffs = Vec::with_capacity(len);
+        for i in 0..len {
+            let a = self.0.get(i).copied().unwrap_or(Rational::ZERO);
+            let b = other.0.get(i).copied().unwrap_or(Rational::ZERO);
+            coeffs.push(a.checked_sub(b)?);
+        }
+        Ok(Dense::new(coeffs))
+    }
+
+    fn mul(&self, other: &Dense) -> Result<Self> {
+        if self.is_zero() || other.is_zero() {
+            return Ok(Dense(Vec::new()));
+        }
+        let mut coeffs = vec![Rational::ZERO; self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j].checked_add(a.checked_mul(*b)?)?;
+            }
+        }
+        Ok(Dense::new(coeffs))
+    }
+
+    /// Quotient and remainder
+    fn div_rem(&self, divisor: &Dense) -> Result<(Dense, Dense)> {
+        if divisor.is_zero() {
+            return Err(VeritasError::DivisionByZero);
+        }
+
+        let mut remainder = self.clone();
+        let mut quotient = vec![Rational::ZERO; self.0.len().saturating_sub(divisor.degree())];
+        while !remainder.is_zero() && remainder.degree() >= divisor.degree() {
+            let shift = remainder.degree() - divisor.degree();
+            let factor = remainder.leading().checked_div(divisor.leading())?;
+            quotient[shift] = factor;
+
+            let mut coeffs = remainder.0.clone();
+            for (i, d) in divisor.0.iter().enumerate() {
+                coeffs[i + shift] = coeffs[i + shift].checked_sub(d.checked_mul(factor)?)?;
+            }
+            // The leading coefficient cancels exactly
+            coeffs.pop();
+            remainder = Dense::new(coeffs);
+        }
+        Ok((Dense::new(quotient), remainder))
+    }
+
+    /// Quotient of an exact division
+    fn exact_div(&self, divisor: &Dense) -> Result<Dense> {
+        let (quotient, remainder) = self.div_rem(divisor)?;
+        if !remainder.is_zero() {
+            return Err(VeritasError::SimplificationError(
+                "polynomial division left a remainder".to_string(),
+            ));
+        }
+        Ok(quotient)
+    }
+
+    fn monic(&self) -> Result<Dense> {
+        self.scale(self.leading().recip()?)
+    }
+
+    /// Monic greatest common divisor
+    fn gcd(&self, other: &Dense) -> Result<Dense> {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b)?;
+            a = b;
+            b = r;
+        }
+        a.monic()
+    }
+
+    fn derivative(&self) -> Result<Dense> {
+        let mut coeffs = Vec::with_capacity(self.0.len());
+        for (i, c) in self.0.iter().enumerate().skip(1) {
+            coeffs.push(c.checked_mul(Rational::from_i64(i as i64))?);
+        }
+        Ok(Dense::new(coeffs))
+    }
+
+    fn eval(&self, x: Rational) -> Result<Rational> {
+        let mut acc = Rational::ZERO;
+        for c in self.0.iter().rev() {
+            acc = acc.checked_mul(x)?.checked_add(*c)?;
+        }
+        Ok(acc)
+    }
+
+    /// Split into content and a primitive integer polynomial with
+    /// positive leading coefficient
+    fn primitive(&self) -> Result<(Rational, Dense)> {
+        let mut numerators = 0u128;
+        let mut denominators = 1i128;
+        for c in &self.0 {
+            numerators = gcd(numerators, c.numer().unsigned_abs());
+            let g = gcd(denominators as u128, c.denom() as u128) as i128;
+            denominators = (denominators / g)
+                .checked_mul(c.denom())
+                .ok_or(VeritasError::NumericOverflow)?;
+        }
+
+        let magnitude = i128::try_from(numerators).map_err(|_| VeritasError::NumericOverflow)?;
+        let mut content = Rational::new(magnitude, denominators)?;
+        if self.leading().is_negative() {
+            content = -content;
+        }
+        Ok((content, self.scale(content.recip()?)?))
+    }
+}
+
+/// Square-free decomposition of a primitive polynomial
+///
+/// Returns pairwise coprime square-free factors with their multiplicities.
+fn square_free(f: &Dense) -> Result<Vec<(Dense, u32)>> {
+    let mut factors = Vec::new();
+    let mut c = f.gcd(&f.derivative()?)?;
+    let mut w = f.exact_div(&c)?;
+    let mut multiplicity = 1;
+
+    while c.degree() > 0 {
+        let y = w.gcd(&c)?;
+        let z = w.exact_div(&y)?;
+        if z.degree() > 0 {
+            factors.push((z, multiplicity));
+        }
+        multiplicity += 1;
+        w = y;
+        c = c.exact_div(&w)?;
+    }
+    if w.degree() > 0 {
+        factors.push((w, multiplicity));
+    }
+    Ok(factors)
+}
+
+/// Positive divisors of |n|, if small enough to enumerate
+fn divisors(n: i128) -> Option<Vec<i128>> {
+    let n = n.abs();
+    if n == 0 || n > MAX_ROOT_SEARCH {
+        return None;
+    }
+
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            small.push(d);
+            if d * d != n {
+                large.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    small.extend(large.into_iter().rev());
+    Some(small)
+}
+
+/// Split a square-free primitive polynomial into linear factors for its
+/// rational roots and a remaining factor with no rational roots
+fn split_rational_roots(f: &Dense) -> Result<(Vec<Dense>, Dense)> {
+    let mut linear = Vec::new();
+    let mut rest = f.clone();
+
+    // Root at zero
+    if rest.0.first().is_some_and(|c| c.is_zero()) {
+        let x = Dense(vec![Rational::ZERO, Rational::ONE]);
+        rest = rest.exact_div(&x)?;
+        linear.push(x);
+    }
+
+    // Rational root theorem: p/q with p | a₀ and q | aₙ
+    let (Some(ps), Some(qs)) = (divisors(rest.0[0].numer()), divisors(rest.leading().numer())) else {
+        return Ok((linear, rest));
+    };
+    for &q in &qs {
+        for &p in &ps {
+            for p in [p, -p] {
+                if rest.degree() == 0 {
+                    return Ok((linear, rest));
+                }
+                let root = Rational::new(p, q)?;
+                // Skip non-reduced duplicates (2/2 was already tried as 1/1)
+                if root.denom() != q || !rest.eval(root)?.is_zero() {
+                    continue;
+                }
+                // q·x − p is primitive, so the quotient stays integral
+                let factor = Dense(vec![Rational::from_i64(-(p as i64)), Rational::from_i64(q as i64)]);
+                rest = rest.exact_div(&factor)?;
+                linear.push(factor);
+            }
+        }
+    }
+    Ok((linear, rest))
+}
+
+/// A polynomial written as content times powers of factors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Factorization {
+    /// Rational constant in front
+    pub content: Rational,
+    /// Primitive factors with multiplicities
+    pub factors: Vec<(Polynomial, u32)>,
+}
+
+impl Factorization {
+    /// Multiply the factors back out
+    pub fn expand(&self) -> Result<Polynomial> {
+        let mut product = Polynomial::constant(self.content);
+        for (factor, multiplicity) in &self.factors {
+            product = product.mul(&factor.pow(*multiplicity)?)?;
+        }
+        Ok(product)
+    }
+
+    /// Rebuild as a product expression
+    pub fn to_expr(&self) -> Result<Expr> {
+        let mut product: Option<Expr> = None;
+        for (factor, multiplicity) in &self.factors {
+            let mut e = factor.to_expr()?;
+            if *multiplicity > 1 {
+                e = Expr::pow(e, Expr::number(*multiplicity as i32));
+            }
+            product = Some(match product {
+                None => e,
+                Some(acc) => Expr::mul(acc, e),
+            });
+        }
+
+        Ok(match product {
+            None => self.content.to_expr()?,
+            Some(p) if self.content == Rational::ONE => p,
+            Some(p) if self.content == -Rational::ONE => Expr::neg(p),
+            Some(p) => Expr::mul(self.content.to_expr()?, p),
+        })
+    }
+}
+
+/// Distribute products and integer powers, collecting like terms
+pub fn expand(expr: &Expr) -> Result<Expr> {
+    Polynomial::from_expr(expr)?.to_expr()
+}
+
+/// Factor a polynomial in one variable over the rationals
+///
+/// The result is checked to multiply back to the input exactly.
+/// Factors of degree two or more have no rational roots but are not
+/// guaranteed irreducible.
+pub fn factor(expr: &Expr) -> Result<Factorization> {
+    let poly = Polynomial::from_expr(expr)?;
+    let (atom, dense) = poly.dense().ok_or_else(|| {
+        VeritasError::SimplificationError(format!(
+            "Cannot factor {}: not a polynomial in one variable",
+            expr
+        ))
+    })?;
+
+    let atom = match atom {
+        Some(atom) if dense.degree() > 0 => atom,
+        _ => {
+            return Ok(Factorization {
+                content: poly.as_constant().unwrap_or(Rational::ZERO),
+                factors: Vec::new(),
+            })
+        }
+    };
+
+    let (content, primitive) = dense.primitive()?;
+    let mut factors = Vec::new();
+    for (square_free_factor, multiplicity) in square_free(&primitive)? {
+        // Yun's factors are monic; restore integer coefficients
+        let (_, integral) = square_free_factor.primitive()?;
+        let (linear, rest) = split_rational_roots(&integral)?;
+        for f in linear {
+            factors.push((Polynomial::from_dense(&atom, &f), multiplicity));
+        }
+        if rest.degree() > 0 {
+            factors.push((Polynomial::from_dense(&atom, &rest), multiplicity));
+        }
+    }
+
+    // Gauss's lemma makes the product of primitive factors primitive,
+    // so the content is unchanged; check the whole thing regardless
+    let factorization = Factorization { content, factors };
+    let product = factorization.expand()?;
+    if product != poly {
+        return Err(VeritasError::VerificationFailed {
+            expected: format!("{}", poly.to_expr()?),
+            actual: format!("{}", product.to_expr()?),
+        });
+    }
+    Ok(factorization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    fn dense(coeffs: &[i64]) -> Dense {
+        Dense::new(coeffs.iter().map(|&c| Rational::from_i64(c)).collect())
+    }
+
+    #[test]
+    fn test_expand_binomial() {
+        // (x + 1)^2 = x^2 + 2x + 1
+        let expr = Expr::pow(Expr::add(x(), Expr::number(1)), Expr::number(2));
+        let expected = Expr::add(
+            Expr::add(
+                Expr::pow(x(), Expr::number(2)),
+                Expr::mul(Expr::number(2), x()),
+            ),
+            Expr::number(1),
+        );
+        assert_eq!(expand(&expr).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_expand_keeps_atoms() {
+        // (sin(x) + y)(sin(x) - y) = sin(x)^2 - y^2
+        let s = Expr::sin(x());
+        let y = Expr::var("y");
+        let expr = Expr::mul(Expr::add(s.clone(), y.clone()), Expr::sub(s, y));
+        let poly = Polynomial::from_expr(&expr).unwrap();
+        assert_eq!(poly.len(), 2);
+        assert_eq!(poly.degree(), 2);
+    }
+
+    #[test]
+    fn test_square_free() {
+        // x²(x+1)³
+        let f = dense(&[0, 0, 1, 3, 3, 1]);
+        let parts = square_free(&f).unwrap();
+        assert_eq!(
+            parts,
+            vec![(dense(&[0, 1]), 2), (dense(&[1, 1]), 3)]
+        );
+    }
+
+    #[test]
+    fn test_factor_rational_roots() {
+        // 2x² - x - 1 = (x - 1)(2x + 1)
+        let expr = Expr::sub(
+            Expr::sub(Expr::mul(Expr::number(2), Expr::pow(x(), Expr::number(2))), x()),
+            Expr::number(1),
+        );
+        let factorization = factor(&expr).unwrap();
+        assert_eq!(factorization.content, Rational::ONE);
+        assert_eq!(factorization.factors.len(), 2);
+        assert_eq!(factorization.expand().unwrap(), Polynomial::from_expr(&expr).unwrap());
+    }
+
+    #[test]
+    fn test_factor_content_and_multiplicity() {
+        // 3x³ - 6x² + 3x = 3·x·(x - 1)²
+        let expr = Expr::add(
+            Expr::sub(
+                Expr::mul(Expr::number(3), Expr::pow(x(), Expr::number(3))),
+                Expr::mul(Expr::number(6), Expr::pow(x(), Expr::number(2))),
+            ),
+            Expr::mul(Expr::number(3), x()),
+        );
+        let factorization = factor(&expr).unwrap();
+        assert_eq!(factorization.content, Rational::from_i64(3));
+        let multiplicities: Vec<u32> = factorization.factors.iter().map(|(_, m)| *m).collect();
+        assert_eq!(multiplicities, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_factor_irreducible_remainder() {
+        // x² + 1 has no rational roots
+        let expr = Expr::add(Expr::pow(x(), Expr::number(2)), Expr::number(1));
+        let factorization = factor(&expr).unwrap();
+        assert_eq!(factorization.factors.len(), 1);
+        assert_eq!(factorization.factors[0].1, 1);
+    }
+
+    #[test]
+    fn test_factor_rejects_multivariate() {
+        let expr = Expr::mul(x(), Expr::var("y"));
+        assert!(factor(&expr).is_err());
+    }
+}
@@ -0,0 +1,829 @@
+//! Equational proofs of algebraic identities
+//!
+//! Evaluating both sides at sample points can refute lhs = rhs but never
+//! prove it. `prove_identity` searches for a proof instead: it rewrites
+//! both sides with a library of oriented rules (ring axioms, exponent
+//! laws, trigonometric identities, integer arithmetic) and looks for an
+//! expression reachable from each. The search is bidirectional and
+//! best-first, smallest expressions first, with equal expressions shared
+//! through an `ExprArena` so each is expanded once per side.
+//!
+//! Every rule rewrites a subterm to one with the same value wherever the
+//! subterm is defined, and is defined at the same points: a rule that
+//! drops part of it (a·0 = 0, a + (-a) = 0, sin²a + cos²a = 1) only fires
+//! when that part is defined everywhere, so (1/x)·0 = 0 is not proved.
+//! A proof is the chain of rewrites from lhs to the
+//! common form followed by the chain from rhs read backwards; it is
+//! replayed rule by rule before it is returned. When the budget runs out
+//! first the identity is `Uncertain`, which says nothing about whether
+//! it holds.
+
+use super::dag::{ExprArena, ExprId};
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use crate::verification::{Claim, Proof, VerificationState};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::convert::Infallible;
+
+/// Bounds on the search for a proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchBudget {
+    /// Most distinct expressions reached from both sides together
+    pub max_terms: usize,
+    /// Longest chain of rewrites from either side
+    pub max_steps: usize,
+    /// Largest expression, in nodes, the search passes through; never
+    /// less than the larger side
+    pub max_size: usize,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        SearchBudget {
+            max_terms: 20_000,
+            max_steps: 12,
+            max_size: 40,
+        }
+    }
+}
+
+/// Family a rewrite rule belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleFamily {
+    /// Commutativity, associativity, distributivity, identities, inverses
+    Ring,
+    /// Products and powers of powers
+    Exponent,
+    /// Pythagorean, parity and angle-sum identities
+    Trigonometric,
+    /// Exact operations on integer literals
+    Arithmetic,
+}
+
+impl RuleFamily {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleFamily::Ring => "ring axiom",
+            RuleFamily::Exponent => "exponent law",
+            RuleFamily::Trigonometric => "trigonometric identity",
+            RuleFamily::Arithmetic => "integer arithmetic",
+        }
+    }
+}
+
+/// One rewrite of a proof
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    /// The rule, as the identity it applies
+    pub rule: &'static str,
+    pub family: RuleFamily,
+    /// Applied right to left: the rule rewrites `after` into `before`
+    pub reversed: bool,
+    /// Child indices from the root down to the rewritten subterm
+    pub path: Vec<usize>,
+    /// Whole expression before and after
+    pub before: Expr,
+    pub after: Expr,
+}
+
+/// Outcome of a proof search
+#[derive(Debug, Clone)]
+pub struct IdentityProof {
+    pub lhs: Expr,
+    pub rhs: Expr,
+    /// Rewrites taking lhs to rhs, empty unless proven
+    pub rewrites: Vec<Rewrite>,
+    pub proof: Proof,
+    pub state: VerificationState,
+}
+
+impl IdentityProof {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+}
+
+/// Search for an equational proof of `lhs = rhs`
+pub fn prove_identity(equation: &Expr, budget: &SearchBudget) -> Result<IdentityProof> {
+    let Expr::Eq(lhs, rhs) = equation else {
+        return Err(VeritasError::InvalidInput(format!(
+            "{} is not an equation",
+            equation
+        )));
+    };
+    let (lhs, rhs) = (&**lhs, &**rhs);
+    let claim = Claim::new(format!("{} = {}", lhs, rhs)).with_symbolic(equation.clone());
+    let mut proof = Proof::new(claim);
+
+    let found = search(lhs, rhs, budget);
+    let (rewrites, state) = match found {
+        Ok(rewrites) => {
+            check(lhs, rhs, &rewrites)?;
+            for rewrite in &rewrites {
+                let direction = if rewrite.reversed { ", right to left" } else { "" };
+                proof.add_step(
+                    format!("{} = {}", rewrite.before, rewrite.after),
+                    format!("{}: {}{}", rewrite.family.name(), rewrite.rule, direction),
+                );
+            }
+            proof.verified = true;
+            let state = VerificationState::Verified {
+                proof_id: proof.id(),
+            };
+            (rewrites, state)
+        }
+        Err(reason) => (Vec::new(), VerificationState::Uncertain { reason }),
+    };
+
+    Ok(IdentityProof {
+        lhs: lhs.clone(),
+        rhs: rhs.clone(),
+        rewrites,
+        proof,
+        state,
+    })
+}
+
+// ============================================================================
+// Search
+// ============================================================================
+
+/// An expression reached from one side
+struct Visit {
+    expr: Expr,
+    /// Rewrites from the start of the side
+    steps: usize,
+    /// The expression it was rewritten from, the rule and where
+    from: Option<(ExprId, &'static Rule, Vec<usize>)>,
+}
+
+/// Expressions reached from one side, and those still to expand
+#[derive(Default)]
+struct Side {
+    seen: HashMap<ExprId, Visit>,
+    /// Smallest size plus steps first, then first reached
+    queue: BinaryHeap<Reverse<(usize, u64, ExprId)>>,
+}
+
+impl Side {
+    fn reach(&mut self, id: ExprId, visit: Visit, size: usize, order: u64) {
+        self.queue.push(Reverse((size + visit.steps, order, id)));
+        self.seen.insert(id, visit);
+    }
+
+    /// Rewrites from the start of the side to `id`, in order
+    fn chain(&self, mut id: ExprId) -> Vec<Rewrite> {
+        let mut chain = Vec::new();
+        while let Some((parent, rule, path)) = &self.seen[&id].from {
+            chain.push(Rewrite {
+                rule: rule.name,
+                family: rule.family,
+                reversed: false,
+                path: path.clone(),
+                before: self.seen[parent].expr.clone(),
+                after: self.seen[&id].expr.clone(),
+            });
+            id = *parent;
+        }
+        chain.reverse();
+        chain
+    }
+}
+
+/// Rewrites from lhs to rhs, or why none were found
+fn search(
+    lhs: &Expr,
+    rhs: &Expr,
+    budget: &SearchBudget,
+) -> std::result::Result<Vec<Rewrite>, String> {
+    let max_size = budget.max_size.max(size(lhs)).max(size(rhs));
+    let mut arena = ExprArena::new();
+    let mut sides = [Side::default(), Side::default()];
+    let mut order = 0;
+    for (side, start) in sides.iter_mut().zip([lhs, rhs]) {
+        let id = arena.intern(start);
+        let visit = Visit {
+            expr: start.clone(),
+            steps: 0,
+            from: None,
+        };
+        side.reach(id, visit, size(start), order);
+        order += 1;
+    }
+
+    let mut meeting = arena.intern(lhs);
+    let mut terms = 2;
+    let mut turn = 0;
+    'search: while !sides[1].seen.contains_key(&meeting) {
+        // Alternate sides while both have something left to expand
+        let side = if sides[turn].queue.is_empty() { 1 - turn } else { turn };
+        turn = 1 - turn;
+        let Some(Reverse((_, _, id))) = sides[side].queue.pop() else {
+            return Err(format!(
+                "no common form within {} rewrites and {} nodes",
+                budget.max_steps, max_size
+            ));
+        };
+        let (expr, steps) = {
+            let visit = &sides[side].seen[&id];
+            (visit.expr.clone(), visit.steps)
+        };
+        if steps >= budget.max_steps {
+            continue;
+        }
+
+        for (path, rule, next) in rewrites(&expr) {
+            let next_size = size(&next);
+            if next_size > max_size {
+                continue;
+            }
+            let next_id = arena.intern(&next);
+            if sides[side].seen.contains_key(&next_id) {
+                continue;
+            }
+            let visit = Visit {
+                expr: next,
+                steps: steps + 1,
+                from: Some((id, rule, path)),
+            };
+            sides[side].reach(next_id, visit, next_size, order);
+            order += 1;
+            terms += 1;
+            if sides[1 - side].seen.contains_key(&next_id) {
+                meeting = next_id;
+                break 'search;
+            }
+            if terms >= budget.max_terms {
+                return Err(format!("no common form among {} expressions", terms));
+            }
+        }
+    }
+
+    // lhs to the meeting point, then back out to rhs
+    let mut rewrites = sides[0].chain(meeting);
+    let mut back = sides[1].chain(meeting);
+    back.reverse();
+    rewrites.extend(back.into_iter().map(|r| Rewrite {
+        reversed: true,
+        before: r.after,
+        after: r.before,
+        ..r
+    }));
+    Ok(rewrites)
+}
+
+/// Every single rewrite of `expr`, at the root or inside it
+fn rewrites(expr: &Expr) -> Vec<(Vec<usize>, &'static Rule, Expr)> {
+    let mut found: Vec<(Vec<usize>, &'static Rule, Expr)> = RULES
+        .iter()
+        .filter_map(|rule| (rule.apply)(expr).map(|after| (Vec::new(), rule, after)))
+        .collect();
+    for (i, child) in expr.children().into_iter().enumerate() {
+        for (mut path, rule, after) in rewrites(child) {
+            path.insert(0, i);
+            found.push((path, rule, replace_child(expr, i, &after)));
+        }
+    }
+    found
+}
+
+/// `rule` applied to the subterm at `path`
+fn apply_at(expr: &Expr, path: &[usize], rule: &Rule) -> Option<Expr> {
+    match path.split_first() {
+        None => (rule.apply)(expr),
+        Some((&i, rest)) => {
+            let child = *expr.children().get(i)?;
+            Some(replace_child(expr, i, &apply_at(child, rest, rule)?))
+        }
+    }
+}
+
+fn replace_child(expr: &Expr, index: usize, replacement: &Expr) -> Expr {
+    let mut i = 0;
+    let mapped: std::result::Result<Expr, Infallible> = expr.try_map_children(|child| {
+        let mapped = if i == index { replacement.clone() } else { child.clone() };
+        i += 1;
+        Ok(mapped)
+    });
+    match mapped {
+        Ok(expr) => expr,
+        Err(never) => match never {},
+    }
+}
+
+/// Number of nodes
+fn size(expr: &Expr) -> usize {
+    1 + expr.children().into_iter().map(size).sum::<usize>()
+}
+
+/// Replay a chain of rewrites from lhs to rhs
+fn check(lhs: &Expr, rhs: &Expr, rewrites: &[Rewrite]) -> Result<()> {
+    let invalid = |i: usize, what: String| {
+        VeritasError::ProofInvalid(format!("rewrite {}: {}", i + 1, what))
+    };
+    let mut current = lhs;
+    for (i, rewrite) in rewrites.iter().enumerate() {
+        if rewrite.before != *current {
+            return Err(invalid(i, format!("starts from {}, not {}", rewrite.before, current)));
+        }
+        let rule = RULES
+            .iter()
+            .find(|rule| rule.name == rewrite.rule)
+            .ok_or_else(|| invalid(i, format!("no rule {}", rewrite.rule)))?;
+        let (from, to) = if rewrite.reversed {
+            (&rewrite.after, &rewrite.before)
+        } else {
+            (&rewrite.before, &rewrite.after)
+        };
+        if apply_at(from, &rewrite.path, rule).as_ref() != Some(to) {
+            return Err(invalid(i, format!("{} does not rewrite {} to {}", rule.name, from, to)));
+        }
+        current = &rewrite.after;
+    }
+    if current != rhs {
+        return Err(VeritasError::ProofInvalid(format!("rewrites end at {}, not {}", current, rhs)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Oriented rewrite applied at the root of a subterm
+struct Rule {
+    name: &'static str,
+    family: RuleFamily,
+    apply: fn(&Expr) -> Option<Expr>,
+}
+
+const fn rule(name: &'static str, family: RuleFamily, apply: fn(&Expr) -> Option<Expr>) -> Rule {
+    Rule { name, family, apply }
+}
+
+/// Both orientations of a rule are listed where both are useful
+const RULES: &[Rule] = &[
+    rule("a + b = b + a", RuleFamily::Ring, add_commute),
+    rule("a·b = b·a", RuleFamily::Ring, mul_commute),
+    rule("(a + b) + c = a + (b + c)", RuleFamily::Ring, add_associate),
+    rule("(a·b)·c = a·(b·c)", RuleFamily::Ring, mul_associate),
+    rule("a·(b + c) = a·b + a·c", RuleFamily::Ring, distribute),
+    rule("a·b + a·c = a·(b + c)", RuleFamily::Ring, factor),
+    rule("a + 0 = a", RuleFamily::Ring, add_zero),
+    rule("a·1 = a", RuleFamily::Ring, mul_one),
+    rule("a·0 = 0", RuleFamily::Ring, mul_zero),
+    rule("a - b = a + (-b)", RuleFamily::Ring, subtract),
+    rule("-a = (-1)·a", RuleFamily::Ring, negate),
+    rule("a + (-a) = 0", RuleFamily::Ring, cancel),
+    rule("a + a = 2·a", RuleFamily::Ring, double),
+    rule("2·a = a + a", RuleFamily::Ring, halve),
+    rule("a·a = a²", RuleFamily::Exponent, square),
+    rule("a² = a·a", RuleFamily::Exponent, unsquare),
+    rule("aᵐ·aⁿ = aᵐ⁺ⁿ", RuleFamily::Exponent, add_exponents),
+    rule("aᵐ·a = aᵐ⁺¹", RuleFamily::Exponent, raise),
+    rule("(aᵐ)ⁿ = aᵐⁿ", RuleFamily::Exponent, power_of_power),
+    rule("(a·b)ⁿ = aⁿ·bⁿ", RuleFamily::Exponent, power_of_product),
+    rule("aⁿ·bⁿ = (a·b)ⁿ", RuleFamily::Exponent, product_of_powers),
+    rule("a¹ = a", RuleFamily::Exponent, first_power),
+    rule("eᵃ·eᵇ = eᵃ⁺ᵇ", RuleFamily::Exponent, exp_product),
+    rule("eᵃ⁺ᵇ = eᵃ·eᵇ", RuleFamily::Exponent, exp_sum),
+    rule("e⁰ = 1", RuleFamily::Exponent, exp_zero),
+    rule("sin²a + cos²a = 1", RuleFamily::Trigonometric, pythagoras),
+    rule("sin(-a) = -sin a", RuleFamily::Trigonometric, sin_odd),
+    rule("cos(-a) = cos a", RuleFamily::Trigonometric, cos_even),
+    rule("sin(a + b) = sin a·cos b + cos a·sin b", RuleFamily::Trigonometric, sin_sum),
+    rule("cos(a + b) = cos a·cos b - sin a·sin b", RuleFamily::Trigonometric, cos_sum),
+    rule("tan a = sin a / cos a", RuleFamily::Trigonometric, tangent),
+    rule("sin 0 = 0, cos 0 = 1", RuleFamily::Trigonometric, trig_zero),
+    rule("integer arithmetic", RuleFamily::Arithmetic, arithmetic),
+];
+
+/// Value of an integer literal
+fn integer(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Number(n) => n.to_i64(),
+        _ => None,
+    }
+}
+
+/// Integer literal ≥ 1
+fn positive(expr: &Expr) -> Option<i64> {
+    integer(expr).filter(|&n| n >= 1)
+}
+
+/// Literal for a result small enough to stay exact
+fn literal(n: i64) -> Option<Expr> {
+    i32::try_from(n).ok().map(Expr::number)
+}
+
+fn is_number(expr: &Expr, n: i64) -> bool {
+    integer(expr) == Some(n)
+}
+
+/// Defined for every value of its variables: built without quotients,
+/// roots, logarithms, tangents or powers other than positive integers
+fn defined(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(_) | Expr::Variable(_) | Expr::Constant(_) => true,
+        Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => defined(a) && defined(b),
+        Expr::Neg(a) | Expr::Exp(a) | Expr::Sin(a) | Expr::Cos(a) => defined(a),
+        Expr::Pow(a, n) => positive(n).is_some() && defined(a),
+        _ => false,
+    }
+}
+
+fn add_commute(e: &Expr) -> Option<Expr> {
+    let Expr::Add(a, b) = e else { return None };
+    Some(Expr::add((**b).clone(), (**a).clone()))
+}
+
+fn mul_commute(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(a, b) = e else { return None };
+    Some(Expr::mul((**b).clone(), (**a).clone()))
+}
+
+fn add_associate(e: &Expr) -> Option<Expr> {
+    let Expr::Add(ab, c) = e else { return None };
+    let Expr::Add(a, b) = &**ab else { return None };
+    Some(Expr::add((**a).clone(), Expr::add((**b).clone(), (**c).clone())))
+}
+
+fn mul_associate(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(ab, c) = e else { return None };
+    let Expr::Mul(a, b) = &**ab else { return None };
+    Some(Expr::mul((**a).clone(), Expr::mul((**b).clone(), (**c).clone())))
+}
+
+fn distribute(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(a, bc) = e else { return None };
+    let Expr::Add(b, c) = &**bc else { return None };
+    Some(Expr::add(
+        Expr::mul((**a).clone(), (**b).clone()),
+        Expr::mul((**a).clone(), (**c).clone()),
+    ))
+}
+
+fn factor(e: &Expr) -> Option<Expr> {
+    let Expr::Add(ab, cd) = e else { return None };
+    let (Expr::Mul(a, b), Expr::Mul(c, d)) = (&**ab, &**cd) else {
+        return None;
+    };
+    (a == c).then(|| Expr::mul((**a).clone(), Expr::add((**b).clone(), (**d).clone())))
+}
+
+fn add_zero(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Add(a, b) if is_number(b, 0) => Some((**a).clone()),
+        _ => None,
+    }
+}
+
+fn mul_one(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Mul(a, b) if is_number(b, 1) => Some((**a).clone()),
+        _ => None,
+    }
+}
+
+fn mul_zero(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Mul(a, b) if is_number(b, 0) && defined(a) => Some(Expr::number(0)),
+        _ => None,
+    }
+}
+
+fn subtract(e: &Expr) -> Option<Expr> {
+    let Expr::Sub(a, b) = e else { return None };
+    Some(Expr::add((**a).clone(), Expr::neg((**b).clone())))
+}
+
+fn negate(e: &Expr) -> Option<Expr> {
+    let Expr::Neg(a) = e else { return None };
+    Some(Expr::mul(Expr::number(-1), (**a).clone()))
+}
+
+fn cancel(e: &Expr) -> Option<Expr> {
+    let Expr::Add(a, b) = e else { return None };
+    let negated = match &**b {
+        Expr::Neg(b) => b,
+        Expr::Mul(minus_one, b) if is_number(minus_one, -1) => b,
+        _ => return None,
+    };
+    (a == negated && defined(a)).then(|| Expr::number(0))
+}
+
+fn double(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Add(a, b) if a == b => Some(Expr::mul(Expr::number(2), (**a).clone())),
+        _ => None,
+    }
+}
+
+fn halve(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Mul(two, a) if is_number(two, 2) => Some(Expr::add((**a).clone(), (**a).clone())),
+        _ => None,
+    }
+}
+
+fn square(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Mul(a, b) if a == b => Some(Expr::pow((**a).clone(), Expr::number(2))),
+        _ => None,
+    }
+}
+
+fn unsquare(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Pow(a, two) if is_number(two, 2) => Some(Expr::mul((**a).clone(), (**a).clone())),
+        _ => None,
+    }
+}
+
+/// Positive integer exponents only: a⁻¹·a¹ = a⁰ fails at a = 0
+fn add_exponents(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(left, right) = e else { return None };
+    let (Expr::Pow(a, m), Expr::Pow(b, n)) = (&**left, &**right) else {
+        return None;
+    };
+    if a != b {
+        return None;
+    }
+    let exponent = positive(m)?.checked_add(positive(n)?)?;
+    Some(Expr::pow((**a).clone(), literal(exponent)?))
+}
+
+fn raise(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(left, b) = e else { return None };
+    let Expr::Pow(a, m) = &**left else { return None };
+    if a != b {
+        return None;
+    }
+    Some(Expr::pow((**a).clone(), literal(positive(m)?.checked_add(1)?)?))
+}
+
+/// Positive integer exponents only: (x²)^(1/2) is |x|, not x
+fn power_of_power(e: &Expr) -> Option<Expr> {
+    let Expr::Pow(inner, n) = e else { return None };
+    let Expr::Pow(a, m) = &**inner else { return None };
+    let exponent = positive(m)?.checked_mul(positive(n)?)?;
+    Some(Expr::pow((**a).clone(), literal(exponent)?))
+}
+
+fn power_of_product(e: &Expr) -> Option<Expr> {
+    let Expr::Pow(ab, n) = e else { return None };
+    let Expr::Mul(a, b) = &**ab else { return None };
+    positive(n)?;
+    Some(Expr::mul(
+        Expr::pow((**a).clone(), (**n).clone()),
+        Expr::pow((**b).clone(), (**n).clone()),
+    ))
+}
+
+fn product_of_powers(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(left, right) = e else { return None };
+    let (Expr::Pow(a, m), Expr::Pow(b, n)) = (&**left, &**right) else {
+        return None;
+    };
+    if m != n {
+        return None;
+    }
+    positive(n)?;
+    Some(Expr::pow(Expr::mul((**a).clone(), (**b).clone()), (**n).clone()))
+}
+
+fn first_power(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Pow(a, one) if is_number(one, 1) => Some((**a).clone()),
+        _ => None,
+    }
+}
+
+fn exp_product(e: &Expr) -> Option<Expr> {
+    let Expr::Mul(left, right) = e else { return None };
+    let (Expr::Exp(a), Expr::Exp(b)) = (&**left, &**right) else {
+        return None;
+    };
+    Some(Expr::exp(Expr::add((**a).clone(), (**b).clone())))
+}
+
+fn exp_sum(e: &Expr) -> Option<Expr> {
+    let Expr::Exp(sum) = e else { return None };
+    let Expr::Add(a, b) = &**sum else { return None };
+    Some(Expr::mul(Expr::exp((**a).clone()), Expr::exp((**b).clone())))
+}
+
+fn exp_zero(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Exp(a) if is_number(a, 0) => Some(Expr::number(1)),
+        _ => None,
+    }
+}
+
+fn pythagoras(e: &Expr) -> Option<Expr> {
+    let Expr::Add(left, right) = e else { return None };
+    let (Expr::Pow(sin, m), Expr::Pow(cos, n)) = (&**left, &**right) else {
+        return None;
+    };
+    let (Expr::Sin(a), Expr::Cos(b)) = (&**sin, &**cos) else {
+        return None;
+    };
+    (a == b && is_number(m, 2) && is_number(n, 2) && defined(a)).then(|| Expr::number(1))
+}
+
+fn sin_odd(e: &Expr) -> Option<Expr> {
+    let Expr::Sin(arg) = e else { return None };
+    let Expr::Neg(a) = &**arg else { return None };
+    Some(Expr::neg(Expr::sin((**a).clone())))
+}
+
+fn cos_even(e: &Expr) -> Option<Expr> {
+    let Expr::Cos(arg) = e else { return None };
+    let Expr::Neg(a) = &**arg else { return None };
+    Some(Expr::cos((**a).clone()))
+}
+
+fn sin_sum(e: &Expr) -> Option<Expr> {
+    let Expr::Sin(arg) = e else { return None };
+    let Expr::Add(a, b) = &**arg else { return None };
+    let (a, b) = ((**a).clone(), (**b).clone());
+    Some(Expr::add(
+        Expr::mul(Expr::sin(a.clone()), Expr::cos(b.clone())),
+        Expr::mul(Expr::cos(a), Expr::sin(b)),
+    ))
+}
+
+fn cos_sum(e: &Expr) -> Option<Expr> {
+    let Expr::Cos(arg) = e else { return None };
+    let Expr::Add(a, b) = &**arg else { return None };
+    let (a, b) = ((**a).clone(), (**b).clone());
+    Some(Expr::sub(
+        Expr::mul(Expr::cos(a.clone()), Expr::cos(b.clone())),
+        Expr::mul(Expr::sin(a), Expr::sin(b)),
+    ))
+}
+
+/// tan a and sin a / cos a are undefined at the same points
+fn tangent(e: &Expr) -> Option<Expr> {
+    let Expr::Tan(a) = e else { return None };
+    Some(Expr::div(Expr::sin((**a).clone()), Expr::cos((**a).clone())))
+}
+
+fn trig_zero(e: &Expr) -> Option<Expr> {
+    match e {
+        Expr::Sin(a) if is_number(a, 0) => Some(Expr::number(0)),
+        Expr::Cos(a) if is_number(a, 0) => Some(Expr::number(1)),
+        _ => None,
+    }
+}
+
+/// Integer operations whose result is an integer, so nothing is rounded
+fn arithmetic(e: &Expr) -> Option<Expr> {
+    let value = match e {
+        Expr::Neg(a) => integer(a)?.checked_neg()?,
+        Expr::Add(a, b) => integer(a)?.checked_add(integer(b)?)?,
+        Expr::Sub(a, b) => integer(a)?.checked_sub(integer(b)?)?,
+        Expr::Mul(a, b) => integer(a)?.checked_mul(integer(b)?)?,
+        Expr::Div(a, b) => {
+            let (a, b) = (integer(a)?, integer(b)?);
+            if b == 0 || a.checked_rem(b)? != 0 {
+                return None;
+            }
+            a / b
+        }
+        // 0⁰ is left alone
+        Expr::Pow(a, b) => {
+            let (a, b) = (integer(a)?, integer(b)?);
+            if a == 0 && b == 0 {
+                return None;
+            }
+            a.checked_pow(u32::try_from(b).ok()?)?
+        }
+        _ => return None,
+    };
+    literal(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    fn y() -> Expr {
+        Expr::var("y")
+    }
+
+    fn prove(lhs: Expr, rhs: Expr) -> IdentityProof {
+        prove_identity(&Expr::equals(lhs, rhs), &SearchBudget::default()).unwrap()
+    }
+
+    #[test]
+    fn test_ring_axioms() {
+        // x·(y + z) = z·x + y·x
+        let z = Expr::var("z");
+        let lhs = Expr::mul(x(), Expr::add(y(), z.clone()));
+        let rhs = Expr::add(Expr::mul(z, x()), Expr::mul(y(), x()));
+        let result = prove(lhs.clone(), rhs.clone());
+        assert!(result.is_verified());
+        assert_eq!(result.rewrites.first().unwrap().before, lhs);
+        assert_eq!(result.rewrites.last().unwrap().after, rhs);
+        assert!(result.rewrites.iter().any(|r| r.rule == "a·(b + c) = a·b + a·c"));
+    }
+
+    #[test]
+    fn test_exponent_laws() {
+        // x²·x³ = x⁵ and eˣ·eʸ = eˣ⁺ʸ, one rewrite each
+        let lhs = Expr::mul(Expr::pow(x(), Expr::number(2)), Expr::pow(x(), Expr::number(3)));
+        let result = prove(lhs, Expr::pow(x(), Expr::number(5)));
+        assert!(result.is_verified());
+        assert_eq!(result.rewrites.len(), 1);
+        assert_eq!(result.rewrites[0].family, RuleFamily::Exponent);
+
+        let result = prove(
+            Expr::mul(Expr::exp(x()), Expr::exp(y())),
+            Expr::exp(Expr::add(x(), y())),
+        );
+        assert!(result.is_verified());
+    }
+
+    #[test]
+    fn test_trigonometric_identities() {
+        // sin²x + cos²x + y = y + 1
+        let pythagorean = Expr::add(
+            Expr::add(
+                Expr::pow(Expr::sin(x()), Expr::number(2)),
+                Expr::pow(Expr::cos(x()), Expr::number(2)),
+            ),
+            y(),
+        );
+        let result = prove(pythagorean, Expr::add(y(), Expr::number(1)));
+        assert!(result.is_verified());
+        assert_eq!(result.rewrites.len(), 2);
+
+        // sin 2x = 2·sin x·cos x meets in the middle: both sides are rewritten
+        let lhs = Expr::sin(Expr::mul(Expr::number(2), x()));
+        let rhs = Expr::mul(Expr::mul(Expr::number(2), Expr::sin(x())), Expr::cos(x()));
+        let result = prove(lhs, rhs);
+        assert!(result.is_verified());
+        assert!(result.rewrites.iter().any(|r| r.reversed));
+        assert!(result.rewrites.iter().any(|r| !r.reversed));
+    }
+
+    #[test]
+    fn test_proof_lists_every_rewrite() {
+        let lhs = Expr::sub(Expr::add(x(), y()), y());
+        let result = prove(lhs, x());
+        assert!(result.is_verified());
+        assert!(result.proof.verified);
+        assert_eq!(result.proof.steps.len(), result.rewrites.len());
+        for (step, rewrite) in result.proof.steps.iter().zip(&result.rewrites) {
+            assert_eq!(step.description, format!("{} = {}", rewrite.before, rewrite.after));
+            assert!(step.justification.contains(rewrite.rule));
+        }
+
+        // A tampered chain does not replay
+        let mut rewrites = result.rewrites.clone();
+        rewrites[0].after = y();
+        assert!(check(&result.lhs, &result.rhs, &rewrites).is_err());
+    }
+
+    #[test]
+    fn test_budget_runs_out() {
+        // x + 1 = x + 2 is false, so no proof exists to find
+        let budget = SearchBudget {
+            max_terms: 500,
+            ..SearchBudget::default()
+        };
+        let equation = Expr::equals(
+            Expr::add(x(), Expr::number(1)),
+            Expr::add(x(), Expr::number(2)),
+        );
+        let result = prove_identity(&equation, &budget).unwrap();
+        assert!(result.state.is_uncertain());
+        assert!(result.rewrites.is_empty());
+        assert!(!result.proof.verified);
+
+        assert!(prove_identity(&x(), &budget).is_err());
+    }
+
+    #[test]
+    fn test_undefined_subterms_are_kept() {
+        // (1/x)·0 and 1/x - 1/x are undefined at x = 0, where 0 is not
+        let reciprocal = Expr::div(Expr::number(1), x());
+        let result = prove(Expr::mul(reciprocal.clone(), Expr::number(0)), Expr::number(0));
+        assert!(!result.is_verified());
+        let budget = SearchBudget {
+            max_terms: 2000,
+            ..SearchBudget::default()
+        };
+        let equation = Expr::equals(Expr::sub(reciprocal.clone(), reciprocal), Expr::number(0));
+        assert!(!prove_identity(&equation, &budget).unwrap().is_verified());
+
+        // x·0 = 0 holds everywhere
+        assert!(prove(Expr::mul(x(), Expr::number(0)), Expr::number(0)).is_verified());
+    }
+}
@@ -0,0 +1,241 @@
+//! Exact rational numbers
+//!
+//! Polynomial algorithms (GCDs, root tests, factor checks) need exact
+//! arithmetic: a factorization is only accepted if it multiplies back to
+//! the original exactly. Scalars are binary floating point, so these
+//! coefficients are carried as reduced fractions of i128 instead, and
+//! overflow is reported rather than wrapped.
+
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::fmt;
+
+/// Reduced fraction with positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+/// Greatest common divisor of magnitudes
+pub(crate) fn gcd(a: u128, b: u128) -> u128 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn overflow<T>(value: Option<T>) -> Result<T> {
+    value.ok_or(VeritasError::NumericOverflow)
+}
+
+impl Rational {
+    pub const ZERO: Self = Rational { num: 0, den: 1 };
+    pub const ONE: Self = Rational { num: 1, den: 1 };
+
+    /// Build `num / den` in lowest terms
+    pub fn new(num: i128, den: i128) -> Result<Self> {
+        if den == 0 {
+            return Err(VeritasError::DivisionByZero);
+        }
+        // Keep i128::MIN out so magnitudes and negations never overflow
+        if num == i128::MIN || den == i128::MIN {
+            return Err(VeritasError::NumericOverflow);
+        }
+
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+        let (num, den) = (num / g, den / g);
+        Ok(if den < 0 {
+            Rational { num: -num, den: -den }
+        } else {
+            Rational { num, den }
+        })
+    }
+
+    /// Integer value
+    pub fn from_i64(n: i64) -> Self {
+        Rational {
+            num: n as i128,
+            den: 1,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    pub fn abs(&self) -> Self {
+        Rational {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn checked_add(&self, rhs: Self) -> Result<Self> {
+        let g = gcd(self.den as u128, rhs.den as u128) as i128;
+        let den = overflow((self.den / g).checked_mul(rhs.den))?;
+        let lhs_num = overflow(self.num.checked_mul(den / self.den))?;
+        let rhs_num = overflow(rhs.num.checked_mul(den / rhs.den))?;
+        Rational::new(overflow(lhs_num.checked_add(rhs_num))?, den)
+    }
+
+    pub fn checked_sub(&self, rhs: Self) -> Result<Self> {
+        self.checked_add(-rhs)
+    }
+
+    pub fn checked_mul(&self, rhs: Self) -> Result<Self> {
+        // Cross-reduce first so intermediate products stay small
+        let g1 = gcd(self.num.unsigned_abs(), rhs.den as u128) as i128;
+        let g2 = gcd(rhs.num.unsigned_abs(), self.den as u128) as i128;
+        let num = overflow((self.num / g1).checked_mul(rhs.num / g2))?;
+        let den = overflow((self.den / g2).checked_mul(rhs.den / g1))?;
+        Rational::new(num, den)
+    }
+
+    pub fn checked_div(&self, rhs: Self) -> Result<Self> {
+        self.checked_mul(rhs.recip()?)
+    }
+
+    /// Multiplicative inverse
+    pub fn recip(&self) -> Result<Self> {
+        Rational::new(self.den, self.num)
+    }
+
+    /// Non-negative integer power
+    pub fn checked_pow(&self, exp: u32) -> Result<Self> {
+        // Through `new`, since (-2)^127 is exactly i128::MIN
+        Rational::new(
+            overflow(self.num.checked_pow(exp))?,
+            overflow(self.den.checked_pow(exp))?,
+        )
+    }
+
+    /// Exact value of a scalar, if it is a fraction with a power-of-two
+    /// denominator that fits
+    pub fn from_scalar(value: Scalar) -> Option<Self> {
+        if value.is_undefined() {
+            return None;
+        }
+
+        let mut scaled = value;
+        for k in 0..=96 {
+            if let Some(n) = scaled.to_i64() {
+                return Rational::new(n as i128, 1i128 << k).ok();
+            }
+            scaled = scaled * Scalar::TWO;
+        }
+        None
+    }
+
+    /// Nearest scalar
+    pub fn to_scalar(&self) -> Result<Scalar> {
+        let num = overflow(i64::try_from(self.num).ok())?;
+        let den = overflow(i64::try_from(self.den).ok())?;
+        Scalar::from_i64(num).checked_div(Scalar::from_i64(den))
+    }
+
+    /// Expression for this value: a number, or a quotient of integers
+    pub fn to_expr(&self) -> Result<Expr> {
+        let num = overflow(i64::try_from(self.num).ok())?;
+        let den = overflow(i64::try_from(self.den).ok())?;
+        let abs = overflow(num.checked_abs())?;
+        let magnitude = if den == 1 {
+            Expr::Number(Scalar::from_i64(abs))
+        } else {
+            Expr::div(
+                Expr::Number(Scalar::from_i64(abs)),
+                Expr::Number(Scalar::from_i64(den)),
+            )
+        };
+
+        Ok(if num < 0 && den != 1 {
+            Expr::neg(magnitude)
+        } else if num < 0 {
+            Expr::Number(Scalar::from_i64(num))
+        } else {
+            magnitude
+        })
+    }
+}
+
+impl std::ops::Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced_form() {
+        let r = Rational::new(6, -4).unwrap();
+        assert_eq!(r.numer(), -3);
+        assert_eq!(r.denom(), 2);
+        assert!(Rational::new(1, 0).is_err());
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let third = Rational::new(1, 3).unwrap();
+        let sum = third.checked_add(third).unwrap().checked_add(third).unwrap();
+        assert_eq!(sum, Rational::ONE);
+
+        let product = Rational::new(2, 3).unwrap().checked_mul(Rational::new(9, 4).unwrap()).unwrap();
+        assert_eq!(product, Rational::new(3, 2).unwrap());
+    }
+
+    #[test]
+    fn test_from_scalar() {
+        assert_eq!(Rational::from_scalar(Scalar::from(3)), Some(Rational::from_i64(3)));
+        assert_eq!(
+            Rational::from_scalar(Scalar::from(0.75)),
+            Some(Rational::new(3, 4).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        let big = Rational::new(i128::MAX, 1).unwrap();
+        assert_eq!(big.checked_add(Rational::ONE), Err(VeritasError::NumericOverflow));
+
+        // -2^127 fits in an i128 but has no negation there
+        let minus_two = Rational::from_i64(-2);
+        assert_eq!(minus_two.checked_pow(127), Err(VeritasError::NumericOverflow));
+        assert_eq!(minus_two.checked_pow(3), Ok(Rational::from_i64(-8)));
+    }
+}
@@ -0,0 +1,1134 @@
+//! Renderers for `Expr`: LaTeX, presentation MathML and 2-D ASCII
+//!
+//! `Display` brackets every operation so the output is unambiguous.
+//! These renderers are for people instead: parentheses only where
+//! precedence and associativity require them, real fractions, radicals
+//! and raised exponents, and numbers in a chosen base (dozenal digits
+//! are 0-9, A, B).
+
+use super::rational::Rational;
+use super::units::Unit;
+use super::Expr;
+use crate::error::{Result, VeritasError};
+use crate::numeric::{Circle, Scalar};
+
+const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Rendering settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    base: u32,
+    /// Digits after the radix point for non-integers (rounded)
+    pub fraction_digits: usize,
+}
+
+impl RenderOptions {
+    pub fn decimal() -> Self {
+        RenderOptions {
+            base: 10,
+            fraction_digits: 12,
+        }
+    }
+
+    pub fn dozenal() -> Self {
+        RenderOptions {
+            base: 12,
+            ..RenderOptions::decimal()
+        }
+    }
+
+    /// Numbers in any base from 2 to 36
+    pub fn in_base(base: u32) -> Result<Self> {
+        if !(2..=36).contains(&base) {
+            return Err(VeritasError::InvalidInput(format!(
+                "Cannot render numbers in base {}",
+                base
+            )));
+        }
+        Ok(RenderOptions {
+            base,
+            ..RenderOptions::decimal()
+        })
+    }
+
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions::decimal()
+    }
+}
+
+/// Trait for rendering expressions for people
+pub trait Render {
+    /// LaTeX math-mode source
+    fn to_latex(&self, options: &RenderOptions) -> String;
+
+    /// Presentation MathML `<math>` element
+    fn to_mathml(&self, options: &RenderOptions) -> String;
+
+    /// Multi-line layout for terminals
+    fn to_ascii(&self, options: &RenderOptions) -> String;
+}
+
+impl Render for Expr {
+    fn to_latex(&self, options: &RenderOptions) -> String {
+        latex(self, options)
+    }
+
+    fn to_mathml(&self, options: &RenderOptions) -> String {
+        format!(
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+            mathml(self, options)
+        )
+    }
+
+    fn to_ascii(&self, options: &RenderOptions) -> String {
+        ascii(self, options)
+            .lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// ============================================================================
+// NUMBERS
+// ============================================================================
+
+/// Format a scalar in the configured base
+///
+/// Integers and binary fractions are converted exactly (then rounded to
+/// `fraction_digits`); vanished, exploded and undefined values use the
+/// Spirix notation from `Display`.
+pub fn format_number(n: Scalar, options: &RenderOptions) -> String {
+    match Rational::from_scalar(n) {
+        Some(r) => format_rational(r, options),
+        None => format!("{}", n),
+    }
+}
+
+fn format_unsigned(mut n: u128, base: u32) -> String {
+    if n == 0 {
+        return "0".to_string();
+    }
+
+    let mut digits = Vec::new();
+    while n > 0 {
+        let quotient = n / base as u128;
+        digits.push(DIGITS[(n - quotient * base as u128) as usize]);
+        n = quotient;
+    }
+    digits.reverse();
+    String::from_utf8_lossy(&digits).into_owned()
+}
+
+fn format_rational(r: Rational, options: &RenderOptions) -> String {
+    let base = options.base as u128;
+    let den = r.denom() as u128;
+    let mut int = r.numer().unsigned_abs() / den;
+    let mut rem = r.numer().unsigned_abs() - int * den;
+
+    // One digit past the limit, for rounding
+    let mut digits: Vec<u32> = Vec::new();
+    while rem != 0 && digits.len() <= options.fraction_digits {
+        rem *= base;
+        let digit = rem / den;
+        digits.push(digit as u32);
+        rem -= digit * den;
+    }
+
+    if digits.len() > options.fraction_digits {
+        let last = digits.pop().unwrap_or(0);
+        if 2 * last >= options.base {
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    int += 1;
+                    break;
+                }
+                i -= 1;
+                digits[i] += 1;
+                if digits[i] < options.base {
+                    break;
+                }
+                digits[i] = 0;
+            }
+        }
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+
+    let mut s = String::new();
+    if r.is_negative() && (int != 0 || !digits.is_empty()) {
+        s.push('-');
+    }
+    s.push_str(&format_unsigned(int, options.base));
+    if !digits.is_empty() {
+        s.push('.');
+        s.extend(digits.iter().map(|&d| DIGITS[d as usize] as char));
+    }
+    s
+}
+
+// ============================================================================
+// PRECEDENCE
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// A complex literal as the expression it is written as (a + bi)
+fn complex_expr(c: Circle) -> Expr {
+    let (re, im) = (c.real(), c.imag());
+    let i = Expr::Constant("i".to_string());
+    let imaginary = |im: Scalar| {
+        if im == Scalar::ONE {
+            i.clone()
+        } else {
+            Expr::mul(Expr::Number(im), i.clone())
+        }
+    };
+
+    if im.is_zero() {
+        Expr::Number(re)
+    } else if re.is_zero() && im.is_negative() {
+        Expr::neg(imaginary(-im))
+    } else if re.is_zero() {
+        imaginary(im)
+    } else if im.is_negative() {
+        Expr::sub(Expr::Number(re), imaginary(-im))
+    } else {
+        Expr::add(Expr::Number(re), imaginary(im))
+    }
+}
+
+/// Binding strength, loosest first
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        // The body extends as far right as it can
+        Expr::Let(..) => 0,
+        Expr::Implies(..) => 1,
+        Expr::Or(..) => 2,
+        Expr::And(..) => 3,
+        Expr::Not(_) => 4,
+        Expr::Eq(..) | Expr::Ne(..) | Expr::Lt(..) | Expr::Le(..) | Expr::Gt(..) | Expr::Ge(..) => 5,
+        // Σ and Π extend as far right as a sum does
+        Expr::Add(..) | Expr::Sub(..) | Expr::Sum(..) | Expr::Product(..) => 6,
+        Expr::Mul(..) | Expr::Div(..) | Expr::MatMul(..) => 7,
+        Expr::Quantity(n, _) if n.is_negative() => 8,
+        // 500 g is a product
+        Expr::Quantity(..) => 7,
+        Expr::Neg(_) => 8,
+        Expr::Number(n) if n.is_negative() => 8,
+        Expr::Complex(c) => precedence(&complex_expr(*c)),
+        Expr::Pow(..) | Expr::Exp(_) => 9,
+        // Aᵀ and A⁻¹ are written as powers
+        Expr::Function(name, args) if args.len() == 1 && (name == "transpose" || name == "inv") => 9,
+        _ => 10,
+    }
+}
+
+fn is_infix(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Add(..)
+            | Expr::Sub(..)
+            | Expr::Mul(..)
+            | Expr::MatMul(..)
+            | Expr::Eq(..)
+            | Expr::Ne(..)
+            | Expr::Lt(..)
+            | Expr::Le(..)
+            | Expr::Gt(..)
+            | Expr::Ge(..)
+            | Expr::And(..)
+            | Expr::Or(..)
+            | Expr::Implies(..)
+    )
+}
+
+/// Check whether the rendering begins with a minus sign
+fn starts_with_minus(expr: &Expr) -> bool {
+    match expr {
+        Expr::Neg(_) => true,
+        Expr::Number(n) | Expr::Quantity(n, _) => n.is_negative(),
+        Expr::Complex(c) => starts_with_minus(&complex_expr(*c)),
+        Expr::Add(a, _) | Expr::Sub(a, _) | Expr::Mul(a, _) | Expr::MatMul(a, _) => {
+            !needs_parens(expr, a, Side::Left) && starts_with_minus(a)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `child`, as the `side` operand of `parent`, needs parentheses
+fn needs_parens(parent: &Expr, child: &Expr, side: Side) -> bool {
+    // x + (-y), not x + -y
+    if side == Side::Right && is_infix(parent) && starts_with_minus(child) {
+        return true;
+    }
+    // (Σ k) + 1, not Σ k + 1
+    if side == Side::Left && is_infix(parent) && is_big_operator(child) {
+        return true;
+    }
+
+    let (p, c) = (precedence(parent), precedence(child));
+    if c != p {
+        return c < p;
+    }
+
+    match parent {
+        Expr::Sub(..) | Expr::Div(..) => side == Side::Right,
+        // Aᵀ and A⁻¹ are powers too
+        Expr::Pow(..) | Expr::Function(..) | Expr::Implies(..) => side == Side::Left,
+        // Comparisons do not chain
+        Expr::Eq(..) | Expr::Ne(..) | Expr::Lt(..) | Expr::Le(..) | Expr::Gt(..) | Expr::Ge(..) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Operand of a prefix minus
+fn negation_needs_parens(child: &Expr) -> bool {
+    // -a·b reads correctly, -a + b does not
+    precedence(child) < 7 || starts_with_minus(child)
+}
+
+fn is_big_operator(expr: &Expr) -> bool {
+    matches!(expr, Expr::Sum(..) | Expr::Product(..))
+}
+
+/// Body of Σ or Π: Σ k², Σ (k + 1), and Σ Σ without parentheses
+fn summand_needs_parens(body: &Expr) -> bool {
+    precedence(body) < 7 && !is_big_operator(body)
+}
+
+/// Operand of logical negation
+fn not_needs_parens(child: &Expr) -> bool {
+    precedence(child) < 4
+}
+
+/// Write 2x rather than 2·x (only where digits cannot be letters)
+fn juxtapose(a: &Expr, b: &Expr, options: &RenderOptions) -> bool {
+    fn starts_with_letter(e: &Expr) -> bool {
+        match e {
+            Expr::Variable(_)
+            | Expr::Constant(_)
+            | Expr::Function(..)
+            | Expr::Sqrt(_)
+            | Expr::Ln(_)
+            | Expr::Exp(_)
+            | Expr::Sin(_)
+            | Expr::Cos(_)
+            | Expr::Tan(_) => true,
+            Expr::Pow(base, _) => starts_with_letter(base),
+            _ => false,
+        }
+    }
+
+    options.base <= 10 && matches!(a, Expr::Number(_)) && starts_with_letter(b)
+}
+
+// ============================================================================
+// LATEX
+// ============================================================================
+
+fn latex_name(name: &str) -> String {
+    match name {
+        "π" | "pi" => "\\pi".to_string(),
+        _ if name.chars().count() == 1 => name.to_string(),
+        _ => format!("\\mathrm{{{}}}", name),
+    }
+}
+
+/// Unit symbol in upright type (°C, kg·m/s^2)
+fn latex_unit(unit: &Unit) -> String {
+    let symbol = unit.symbol().replace('°', "{}^{\\circ}").replace('·', "\\cdot ");
+    format!("\\mathrm{{{}}}", symbol)
+}
+
+fn latex(expr: &Expr, o: &RenderOptions) -> String {
+    let child = |c: &Expr, side: Side| {
+        let s = latex(c, o);
+        if needs_parens(expr, c, side) {
+            format!("\\left({}\\right)", s)
+        } else {
+            s
+        }
+    };
+    let infix = |a: &Expr, op: &str, b: &Expr| {
+        format!("{} {} {}", child(a, Side::Left), op, child(b, Side::Right))
+    };
+    let call = |name: &str, a: &Expr| format!("{}\\left({}\\right)", name, latex(a, o));
+
+    match expr {
+        Expr::Number(n) => format_number(*n, o),
+        Expr::Complex(c) => latex(&complex_expr(*c), o),
+        Expr::Variable(v) | Expr::Constant(v) => latex_name(v),
+        Expr::Quantity(n, unit) => format!("{}\\,{}", format_number(*n, o), latex_unit(unit)),
+        Expr::Bool(b) => format!("\\mathrm{{{}}}", b),
+
+        Expr::Add(a, b) => infix(a, "+", b),
+        Expr::Sub(a, b) => infix(a, "-", b),
+        Expr::Mul(a, b) if juxtapose(a, b, o) => {
+            format!("{}{}", child(a, Side::Left), child(b, Side::Right))
+        }
+        Expr::Mul(a, b) => infix(a, "\\cdot", b),
+        Expr::Div(a, b) => format!("\\frac{{{}}}{{{}}}", latex(a, o), latex(b, o)),
+        Expr::Pow(a, b) => format!("{}^{{{}}}", child(a, Side::Left), latex(b, o)),
+
+        Expr::Neg(a) if negation_needs_parens(a) => format!("-\\left({}\\right)", latex(a, o)),
+        Expr::Neg(a) => format!("-{}", latex(a, o)),
+        Expr::Sqrt(a) => format!("\\sqrt{{{}}}", latex(a, o)),
+        Expr::Ln(a) => call("\\ln", a),
+        Expr::Exp(a) => format!("e^{{{}}}", latex(a, o)),
+        Expr::Sin(a) => call("\\sin", a),
+        Expr::Cos(a) => call("\\cos", a),
+        Expr::Tan(a) => call("\\tan", a),
+        Expr::Function(name, args) if name == "mag" && args.len() == 1 => {
+            format!("\\left|{}\\right|", latex(&args[0], o))
+        }
+        Expr::Function(name, args) if name == "conj" && args.len() == 1 => {
+            format!("\\overline{{{}}}", latex(&args[0], o))
+        }
+        Expr::Function(name, args) if name == "transpose" && args.len() == 1 => {
+            format!("{}^{{\\mathsf{{T}}}}", child(&args[0], Side::Left))
+        }
+        Expr::Function(name, args) if name == "inv" && args.len() == 1 => {
+            format!("{}^{{-1}}", child(&args[0], Side::Left))
+        }
+        Expr::Function(name, args) if name == "det" && args.len() == 1 => call("\\det", &args[0]),
+        Expr::Function(name, args) if name == "trace" && args.len() == 1 => {
+            call("\\operatorname{tr}", &args[0])
+        }
+        Expr::Function(name, args) => {
+            let name = if name.chars().count() == 1 {
+                name.clone()
+            } else {
+                format!("\\operatorname{{{}}}", name)
+            };
+            let args: Vec<String> = args.iter().map(|a| latex(a, o)).collect();
+            format!("{}\\left({}\\right)", name, args.join(", "))
+        }
+
+        Expr::Vector(entries) => {
+            let rows: Vec<String> = entries.iter().map(|e| latex(e, o)).collect();
+            format!("\\begin{{pmatrix}} {} \\end{{pmatrix}}", rows.join(" \\\\ "))
+        }
+        Expr::Matrix(rows) => {
+            let rows: Vec<String> = rows
+                .iter()
+                .map(|row| row.iter().map(|e| latex(e, o)).collect::<Vec<_>>().join(" & "))
+                .collect();
+            format!("\\begin{{pmatrix}} {} \\end{{pmatrix}}", rows.join(" \\\\ "))
+        }
+        Expr::MatMul(a, b) => format!("{} {}", child(a, Side::Left), child(b, Side::Right)),
+
+        Expr::Eq(a, b) => infix(a, "=", b),
+        Expr::Ne(a, b) => infix(a, "\\neq", b),
+        Expr::Lt(a, b) => infix(a, "<", b),
+        Expr::Le(a, b) => infix(a, "\\leq", b),
+        Expr::Gt(a, b) => infix(a, ">", b),
+        Expr::Ge(a, b) => infix(a, "\\geq", b),
+        Expr::And(a, b) => infix(a, "\\land", b),
+        Expr::Or(a, b) => infix(a, "\\lor", b),
+        Expr::Not(a) if not_needs_parens(a) => format!("\\lnot \\left({}\\right)", latex(a, o)),
+        Expr::Not(a) => format!("\\lnot {}", latex(a, o)),
+        Expr::Implies(a, b) => infix(a, "\\Rightarrow", b),
+
+        Expr::Piecewise(branches, otherwise) => {
+            let mut rows: Vec<String> = branches
+                .iter()
+                .map(|(c, v)| format!("{} & \\text{{if }} {}", latex(v, o), latex(c, o)))
+                .collect();
+            rows.push(format!("{} & \\text{{otherwise}}", latex(otherwise, o)));
+            format!("\\begin{{cases}} {} \\end{{cases}}", rows.join(" \\\\ "))
+        }
+
+        Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+            let sign = if matches!(expr, Expr::Sum(..)) { "\\sum" } else { "\\prod" };
+            let body = if summand_needs_parens(body) {
+                format!("\\left({}\\right)", latex(body, o))
+            } else {
+                latex(body, o)
+            };
+            format!(
+                "{}_{{{} = {}}}^{{{}}} {}",
+                sign,
+                latex_name(index),
+                latex(lower, o),
+                latex(upper, o),
+                body
+            )
+        }
+
+        Expr::Let(name, value, body) => format!(
+            "\\text{{let }} {} = {} \\text{{ in }} {}",
+            latex_name(name),
+            latex(value, o),
+            latex(body, o)
+        ),
+    }
+}
+
+// ============================================================================
+// MATHML
+// ============================================================================
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn mo(op: &str) -> String {
+    format!("<mo>{}</mo>", escape(op))
+}
+
+fn mrow(parts: &[String]) -> String {
+    format!("<mrow>{}</mrow>", parts.concat())
+}
+
+fn fenced(inner: String) -> String {
+    mrow(&[mo("("), inner, mo(")")])
+}
+
+fn mathml(expr: &Expr, o: &RenderOptions) -> String {
+    let child = |c: &Expr, side: Side| {
+        let s = mathml(c, o);
+        if needs_parens(expr, c, side) {
+            fenced(s)
+        } else {
+            s
+        }
+    };
+    let infix = |a: &Expr, op: &str, b: &Expr| {
+        mrow(&[child(a, Side::Left), mo(op), child(b, Side::Right)])
+    };
+    // U+2061 FUNCTION APPLICATION
+    let call = |name: &str, args: Vec<String>| {
+        let mut inner = Vec::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            if i > 0 {
+                inner.push(mo(","));
+            }
+            inner.push(arg);
+        }
+        mrow(&[
+            format!("<mi>{}</mi>", escape(name)),
+            mo("\u{2061}"),
+            fenced(mrow(&inner)),
+        ])
+    };
+
+    match expr {
+        Expr::Number(n) if n.is_negative() => {
+            mrow(&[mo("\u{2212}"), format!("<mn>{}</mn>", format_number(-*n, o))])
+        }
+        Expr::Number(n) => format!("<mn>{}</mn>", format_number(*n, o)),
+        Expr::Complex(c) => mathml(&complex_expr(*c), o),
+        Expr::Variable(v) => format!("<mi>{}</mi>", escape(v)),
+        Expr::Constant(c) => format!("<mi>{}</mi>", escape(c)),
+        Expr::Quantity(n, unit) => mrow(&[
+            mathml(&Expr::Number(*n), o),
+            "<mspace width=\"0.167em\"/>".to_string(),
+            format!("<mi mathvariant=\"normal\">{}</mi>", escape(unit.symbol())),
+        ]),
+        Expr::Bool(b) => format!("<mtext>{}</mtext>", b),
+
+        Expr::Add(a, b) => infix(a, "+", b),
+        Expr::Sub(a, b) => infix(a, "\u{2212}", b),
+        // U+2062 INVISIBLE TIMES
+        Expr::Mul(a, b) if juxtapose(a, b, o) => infix(a, "\u{2062}", b),
+        Expr::Mul(a, b) => infix(a, "⋅", b),
+        Expr::Div(a, b) => format!("<mfrac>{}{}</mfrac>", mathml(a, o), mathml(b, o)),
+        Expr::Pow(a, b) => format!("<msup>{}{}</msup>", child(a, Side::Left), mathml(b, o)),
+
+        Expr::Neg(a) => {
+            let inner = mathml(a, o);
+            let inner = if negation_needs_parens(a) { fenced(inner) } else { inner };
+            mrow(&[mo("\u{2212}"), inner])
+        }
+        Expr::Sqrt(a) => format!("<msqrt>{}</msqrt>", mathml(a, o)),
+        Expr::Ln(a) => call("ln", vec![mathml(a, o)]),
+        Expr::Exp(a) => format!("<msup><mi>e</mi>{}</msup>", mathml(a, o)),
+        Expr::Sin(a) => call("sin", vec![mathml(a, o)]),
+        Expr::Cos(a) => call("cos", vec![mathml(a, o)]),
+        Expr::Tan(a) => call("tan", vec![mathml(a, o)]),
+        Expr::Function(name, args) if name == "mag" && args.len() == 1 => {
+            mrow(&[mo("|"), mathml(&args[0], o), mo("|")])
+        }
+        Expr::Function(name, args) if name == "conj" && args.len() == 1 => {
+            format!("<mover>{}<mo>¯</mo></mover>", mrow(&[mathml(&args[0], o)]))
+        }
+        Expr::Function(name, args) if name == "transpose" && args.len() == 1 => format!(
+            "<msup>{}<mi mathvariant=\"sans-serif\">T</mi></msup>",
+            child(&args[0], Side::Left)
+        ),
+        Expr::Function(name, args) if name == "inv" && args.len() == 1 => format!(
+            "<msup>{}{}</msup>",
+            child(&args[0], Side::Left),
+            mrow(&[mo("\u{2212}"), "<mn>1</mn>".to_string()])
+        ),
+        Expr::Function(name, args) if name == "trace" && args.len() == 1 => {
+            call("tr", vec![mathml(&args[0], o)])
+        }
+        Expr::Function(name, args) => call(name, args.iter().map(|a| mathml(a, o)).collect()),
+
+        Expr::Vector(entries) => {
+            let rows: Vec<String> = entries
+                .iter()
+                .map(|e| format!("<mtr><mtd>{}</mtd></mtr>", mathml(e, o)))
+                .collect();
+            fenced(format!("<mtable>{}</mtable>", rows.concat()))
+        }
+        Expr::Matrix(rows) => {
+            let rows: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    let cells: Vec<String> =
+                        row.iter().map(|e| format!("<mtd>{}</mtd>", mathml(e, o))).collect();
+                    format!("<mtr>{}</mtr>", cells.concat())
+                })
+                .collect();
+            fenced(format!("<mtable>{}</mtable>", rows.concat()))
+        }
+        Expr::MatMul(a, b) => infix(a, "\u{2062}", b),
+
+        Expr::Eq(a, b) => infix(a, "=", b),
+        Expr::Ne(a, b) => infix(a, "≠", b),
+        Expr::Lt(a, b) => infix(a, "<", b),
+        Expr::Le(a, b) => infix(a, "≤", b),
+        Expr::Gt(a, b) => infix(a, ">", b),
+        Expr::Ge(a, b) => infix(a, "≥", b),
+        Expr::And(a, b) => infix(a, "∧", b),
+        Expr::Or(a, b) => infix(a, "∨", b),
+        Expr::Not(a) => {
+            let inner = mathml(a, o);
+            let inner = if not_needs_parens(a) { fenced(inner) } else { inner };
+            mrow(&[mo("¬"), inner])
+        }
+        Expr::Implies(a, b) => infix(a, "⇒", b),
+
+        Expr::Piecewise(branches, otherwise) => {
+            let row = |value: String, condition: String| {
+                format!("<mtr><mtd>{}</mtd><mtd>{}</mtd></mtr>", value, condition)
+            };
+            let mut rows: Vec<String> = branches
+                .iter()
+                .map(|(c, v)| {
+                    row(mathml(v, o), mrow(&["<mtext>if&#xA0;</mtext>".to_string(), mathml(c, o)]))
+                })
+                .collect();
+            rows.push(row(mathml(otherwise, o), "<mtext>otherwise</mtext>".to_string()));
+            mrow(&[mo("{"), format!("<mtable>{}</mtable>", rows.concat())])
+        }
+
+        Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+            let sign = if matches!(expr, Expr::Sum(..)) { "∑" } else { "∏" };
+            let below = mrow(&[format!("<mi>{}</mi>", escape(index)), mo("="), mathml(lower, o)]);
+            let body = if summand_needs_parens(body) {
+                fenced(mathml(body, o))
+            } else {
+                mathml(body, o)
+            };
+            mrow(&[
+                format!("<munderover>{}{}{}</munderover>", mo(sign), below, mathml(upper, o)),
+                body,
+            ])
+        }
+
+        Expr::Let(name, value, body) => mrow(&[
+            "<mtext>let&#xA0;</mtext>".to_string(),
+            format!("<mi>{}</mi>", escape(name)),
+            mo("="),
+            mathml(value, o),
+            "<mtext>&#xA0;in&#xA0;</mtext>".to_string(),
+            mathml(body, o),
+        ]),
+    }
+}
+
+// ============================================================================
+// 2-D ASCII
+// ============================================================================
+
+/// Rectangle of text with a baseline row for alignment
+#[derive(Debug, Clone)]
+struct Block {
+    /// Lines, all padded to the same width
+    lines: Vec<String>,
+    baseline: usize,
+}
+
+impl Block {
+    fn text(s: &str) -> Block {
+        Block {
+            lines: vec![s.to_string()],
+            baseline: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.lines.first().map_or(0, |l| l.chars().count())
+    }
+
+    fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Side by side, aligned on baselines
+    fn row(blocks: Vec<Block>) -> Block {
+        let above = blocks.iter().map(|b| b.baseline).max().unwrap_or(0);
+        let below = blocks
+            .iter()
+            .map(|b| b.height() - b.baseline - 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![String::new(); above + below + 1];
+        for block in &blocks {
+            let blank = " ".repeat(block.width());
+            let top = above - block.baseline;
+            for (i, line) in lines.iter_mut().enumerate() {
+                match i.checked_sub(top).and_then(|j| block.lines.get(j)) {
+                    Some(l) => line.push_str(l),
+                    None => line.push_str(&blank),
+                }
+            }
+        }
+        Block {
+            lines,
+            baseline: above,
+        }
+    }
+
+    /// Pad every line to `width`, centered
+    fn centered(&self, width: usize) -> Vec<String> {
+        let left = (width - self.width()) / 2;
+        let right = width - self.width() - left;
+        self.lines
+            .iter()
+            .map(|l| format!("{}{}{}", " ".repeat(left), l, " ".repeat(right)))
+            .collect()
+    }
+
+    /// Numerator over denominator
+    fn fraction(num: Block, den: Block) -> Block {
+        let width = num.width().max(den.width()) + 2;
+        let mut lines = num.centered(width);
+        lines.push("-".repeat(width));
+        lines.extend(den.centered(width));
+        Block {
+            lines,
+            baseline: num.height(),
+        }
+    }
+
+    /// Exponent raised to the right of the base
+    fn power(base: Block, exp: Block) -> Block {
+        let mut lines: Vec<String> = exp
+            .lines
+            .iter()
+            .map(|l| format!("{}{}", " ".repeat(base.width()), l))
+            .collect();
+        lines.extend(
+            base.lines
+                .iter()
+                .map(|l| format!("{}{}", l, " ".repeat(exp.width()))),
+        );
+        Block {
+            lines,
+            baseline: exp.height() + base.baseline,
+        }
+    }
+
+    /// Radical sign with a bar over the radicand
+    fn radical(inner: Block) -> Block {
+        let last = inner.height() - 1;
+        let mut lines = vec![format!("  {}", "_".repeat(inner.width()))];
+        for (i, l) in inner.lines.iter().enumerate() {
+            let prefix = if i == last { "\\/" } else { " |" };
+            lines.push(format!("{}{}", prefix, l));
+        }
+        Block {
+            lines,
+            baseline: inner.baseline + 1,
+        }
+    }
+
+    /// Left column drawn from (top, middle, bottom, single) pieces
+    fn delimiter(height: usize, baseline: usize, pieces: [&str; 4]) -> Block {
+        let lines = if height == 1 {
+            vec![pieces[3].to_string()]
+        } else {
+            (0..height)
+                .map(|i| {
+                    let piece = if i == 0 {
+                        pieces[0]
+                    } else if i == height - 1 {
+                        pieces[2]
+                    } else {
+                        pieces[1]
+                    };
+                    piece.to_string()
+                })
+                .collect()
+        };
+        Block { lines, baseline }
+    }
+
+    fn parens(self) -> Block {
+        let (h, b) = (self.height(), self.baseline);
+        Block::row(vec![
+            Block::delimiter(h, b, ["/", "|", "\\", "("]),
+            self,
+            Block::delimiter(h, b, ["\\", "|", "/", ")"]),
+        ])
+    }
+
+    /// Square brackets around the block
+    fn bracketed(self) -> Block {
+        let (h, b) = (self.height(), self.baseline);
+        Block::row(vec![
+            Block::delimiter(h, b, ["[", "[", "[", "["]),
+            self,
+            Block::delimiter(h, b, ["]", "]", "]", "]"]),
+        ])
+    }
+
+    /// Cells in centered columns two spaces apart, one line of blocks per row
+    fn grid(rows: Vec<Vec<Block>>) -> Block {
+        let columns = rows.first().map_or(0, Vec::len);
+        let widths: Vec<usize> = (0..columns)
+            .map(|j| rows.iter().filter_map(|row| row.get(j)).map(Block::width).max().unwrap_or(0))
+            .collect();
+
+        let lines = rows
+            .into_iter()
+            .map(|row| {
+                let mut cells = Vec::with_capacity(2 * row.len());
+                for (j, cell) in row.into_iter().enumerate() {
+                    if j > 0 {
+                        cells.push(Block::text("  "));
+                    }
+                    let width = widths.get(j).copied().unwrap_or(cell.width());
+                    cells.push(Block {
+                        lines: cell.centered(width),
+                        baseline: cell.baseline,
+                    });
+                }
+                Block::row(cells)
+            })
+            .collect();
+        Block::stack(lines)
+    }
+
+    /// Σ or Π sign (three lines) with its limits centered above and below
+    fn big_operator(sign: [&str; 3], upper: Block, lower: Block) -> Block {
+        let sign = Block {
+            lines: sign.iter().map(|l| l.to_string()).collect(),
+            baseline: 1,
+        };
+        let width = upper.width().max(lower.width()).max(sign.width());
+        let mut lines = upper.centered(width);
+        lines.extend(sign.centered(width));
+        lines.extend(lower.centered(width));
+        Block {
+            lines,
+            baseline: upper.height() + 1,
+        }
+    }
+
+    /// Blocks stacked top to bottom, left aligned
+    fn stack(blocks: Vec<Block>) -> Block {
+        let width = blocks.iter().map(Block::width).max().unwrap_or(0);
+        let lines: Vec<String> = blocks
+            .iter()
+            .flat_map(|b| b.lines.iter())
+            .map(|l| format!("{}{}", l, " ".repeat(width - l.chars().count())))
+            .collect();
+        let baseline = lines.len() / 2;
+        Block { lines, baseline }
+    }
+}
+
+fn ascii(expr: &Expr, o: &RenderOptions) -> Block {
+    let child = |c: &Expr, side: Side| {
+        let b = ascii(c, o);
+        if needs_parens(expr, c, side) {
+            b.parens()
+        } else {
+            b
+        }
+    };
+    let infix = |a: &Expr, op: &str, b: &Expr| {
+        Block::row(vec![
+            child(a, Side::Left),
+            Block::text(&format!(" {} ", op)),
+            child(b, Side::Right),
+        ])
+    };
+    let call = |name: &str, args: Vec<Block>| {
+        let mut inner = Vec::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            if i > 0 {
+                inner.push(Block::text(", "));
+            }
+            inner.push(arg);
+        }
+        Block::row(vec![Block::text(name), Block::row(inner).parens()])
+    };
+
+    match expr {
+        Expr::Number(n) => Block::text(&format_number(*n, o)),
+        Expr::Complex(c) => ascii(&complex_expr(*c), o),
+        Expr::Variable(v) => Block::text(v),
+        Expr::Constant(c) if c == "π" => Block::text("pi"),
+        Expr::Constant(c) => Block::text(c),
+        Expr::Quantity(n, unit) => Block::text(&format!(
+            "{} {}",
+            format_number(*n, o),
+            unit.symbol().replace('°', "deg").replace('·', "*")
+        )),
+        Expr::Bool(b) => Block::text(&b.to_string()),
+
+        Expr::Add(a, b) => infix(a, "+", b),
+        Expr::Sub(a, b) => infix(a, "-", b),
+        Expr::Mul(a, b) if juxtapose(a, b, o) => {
+            Block::row(vec![child(a, Side::Left), child(b, Side::Right)])
+        }
+        Expr::Mul(a, b) => infix(a, "*", b),
+        Expr::Div(a, b) => Block::fraction(ascii(a, o), ascii(b, o)),
+        Expr::Pow(a, b) => Block::power(child(a, Side::Left), ascii(b, o)),
+
+        Expr::Neg(a) => {
+            let inner = ascii(a, o);
+            let inner = if negation_needs_parens(a) { inner.parens() } else { inner };
+            Block::row(vec![Block::text("-"), inner])
+        }
+        Expr::Sqrt(a) => Block::radical(ascii(a, o)),
+        Expr::Ln(a) => call("ln", vec![ascii(a, o)]),
+        Expr::Exp(a) => Block::power(Block::text("e"), ascii(a, o)),
+        Expr::Sin(a) => call("sin", vec![ascii(a, o)]),
+        Expr::Cos(a) => call("cos", vec![ascii(a, o)]),
+        Expr::Tan(a) => call("tan", vec![ascii(a, o)]),
+        Expr::Function(name, args) if name == "mag" && args.len() == 1 => {
+            let inner = ascii(&args[0], o);
+            let (h, b) = (inner.height(), inner.baseline);
+            let bar = || Block::delimiter(h, b, ["|", "|", "|", "|"]);
+            Block::row(vec![bar(), inner, bar()])
+        }
+        Expr::Function(name, args) if name == "transpose" && args.len() == 1 => {
+            Block::power(child(&args[0], Side::Left), Block::text("T"))
+        }
+        Expr::Function(name, args) if name == "inv" && args.len() == 1 => {
+            Block::power(child(&args[0], Side::Left), Block::text("-1"))
+        }
+        Expr::Function(name, args) => call(name, args.iter().map(|a| ascii(a, o)).collect()),
+
+        Expr::Vector(entries) => {
+            Block::grid(entries.iter().map(|e| vec![ascii(e, o)]).collect()).bracketed()
+        }
+        Expr::Matrix(rows) => Block::grid(
+            rows.iter()
+                .map(|row| row.iter().map(|e| ascii(e, o)).collect())
+                .collect(),
+        )
+        .bracketed(),
+        Expr::MatMul(a, b) => infix(a, "@", b),
+
+        Expr::Eq(a, b) => infix(a, "=", b),
+        Expr::Ne(a, b) => infix(a, "!=", b),
+        Expr::Lt(a, b) => infix(a, "<", b),
+        Expr::Le(a, b) => infix(a, "<=", b),
+        Expr::Gt(a, b) => infix(a, ">", b),
+        Expr::Ge(a, b) => infix(a, ">=", b),
+        Expr::And(a, b) => infix(a, "and", b),
+        Expr::Or(a, b) => infix(a, "or", b),
+        Expr::Not(a) => {
+            let inner = ascii(a, o);
+            let inner = if not_needs_parens(a) { inner.parens() } else { inner };
+            Block::row(vec![Block::text("not "), inner])
+        }
+        Expr::Implies(a, b) => infix(a, "=>", b),
+
+        Expr::Piecewise(branches, otherwise) => {
+            let mut rows: Vec<Block> = branches
+                .iter()
+                .map(|(c, v)| Block::row(vec![ascii(v, o), Block::text("  if "), ascii(c, o)]))
+                .collect();
+            rows.push(Block::row(vec![ascii(otherwise, o), Block::text("  otherwise")]));
+
+            let body = Block::stack(rows);
+            let (h, b) = (body.height(), body.baseline);
+            let mut brace = Block::delimiter(h, b, ["/ ", "| ", "\\ ", "{ "]);
+            if h > 2 {
+                brace.lines[b] = "< ".to_string();
+            }
+            Block::row(vec![brace, body])
+        }
+
+        Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+            let sign = if matches!(expr, Expr::Sum(..)) {
+                ["___", "\\  ", "/__"]
+            } else {
+                ["___", "| |", "| |"]
+            };
+            let below = Block::row(vec![Block::text(&format!("{}=", index)), ascii(lower, o)]);
+            let body = if summand_needs_parens(body) {
+                ascii(body, o).parens()
+            } else {
+                ascii(body, o)
+            };
+            Block::row(vec![
+                Block::big_operator(sign, ascii(upper, o), below),
+                Block::text(" "),
+                body,
+            ])
+        }
+
+        Expr::Let(name, value, body) => Block::row(vec![
+            Block::text(&format!("let {} = ", name)),
+            ascii(value, o),
+            Block::text(" in "),
+            ascii(body, o),
+        ]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    #[test]
+    fn test_minimal_parentheses() {
+        let o = RenderOptions::default();
+        let (a, b, c) = (Expr::var("a"), Expr::var("b"), Expr::var("c"));
+
+        // (a - b) - c needs none, a - (b - c) needs them
+        let left = Expr::sub(Expr::sub(a.clone(), b.clone()), c.clone());
+        let right = Expr::sub(a.clone(), Expr::sub(b.clone(), c.clone()));
+        assert_eq!(left.to_latex(&o), "a - b - c");
+        assert_eq!(right.to_latex(&o), "a - \\left(b - c\\right)");
+
+        // a·(b + c) and a + b·c
+        let product = Expr::mul(a.clone(), Expr::add(b.clone(), c.clone()));
+        assert_eq!(product.to_latex(&o), "a \\cdot \\left(b + c\\right)");
+        let sum = Expr::add(a, Expr::mul(b, c));
+        assert_eq!(sum.to_latex(&o), "a + b \\cdot c");
+    }
+
+    #[test]
+    fn test_latex_layout() {
+        let o = RenderOptions::default();
+        // (x + 1)/2 + 3x² + √x
+        let expr = Expr::add(
+            Expr::add(
+                Expr::div(Expr::add(x(), Expr::number(1)), Expr::number(2)),
+                Expr::mul(Expr::number(3), Expr::pow(x(), Expr::number(2))),
+            ),
+            Expr::sqrt(x()),
+        );
+        assert_eq!(expr.to_latex(&o), "\\frac{x + 1}{2} + 3x^{2} + \\sqrt{x}");
+
+        // (-x)² keeps its parentheses
+        let expr = Expr::pow(Expr::neg(x()), Expr::number(2));
+        assert_eq!(expr.to_latex(&o), "\\left(-x\\right)^{2}");
+    }
+
+    #[test]
+    fn test_mathml() {
+        let o = RenderOptions::default();
+        let expr = Expr::div(Expr::number(1), Expr::sqrt(x()));
+        assert_eq!(
+            expr.to_mathml(&o),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">\
+             <mfrac><mn>1</mn><msqrt><mi>x</mi></msqrt></mfrac></math>"
+        );
+    }
+
+    #[test]
+    fn test_ascii_layout() {
+        let o = RenderOptions::default();
+        let expr = Expr::div(Expr::add(x(), Expr::number(1)), Expr::number(2));
+        assert_eq!(expr.to_ascii(&o), " x + 1\n-------\n   2");
+
+        let expr = Expr::pow(x(), Expr::number(2));
+        assert_eq!(expr.to_ascii(&o), " 2\nx");
+    }
+
+    #[test]
+    fn test_big_operators() {
+        let o = RenderOptions::default();
+        let k = Expr::var("k");
+        let squares = Expr::sum(
+            "k",
+            Expr::number(1),
+            Expr::var("n"),
+            Expr::pow(k.clone(), Expr::number(2)),
+        );
+        assert_eq!(squares.to_latex(&o), "\\sum_{k = 1}^{n} k^{2}");
+        assert_eq!(squares.to_ascii(&o), " n\n___  2\n\\   k\n/__\nk=1");
+
+        // A sum as the body is bracketed, and so is Σ as a left operand
+        let shifted = Expr::product(
+            "k",
+            Expr::number(1),
+            Expr::var("n"),
+            Expr::add(k, Expr::number(1)),
+        );
+        assert_eq!(shifted.to_latex(&o), "\\prod_{k = 1}^{n} \\left(k + 1\\right)");
+        let total = Expr::add(squares, Expr::number(1));
+        assert_eq!(
+            total.to_latex(&o),
+            "\\left(\\sum_{k = 1}^{n} k^{2}\\right) + 1"
+        );
+    }
+
+    #[test]
+    fn test_let() {
+        let o = RenderOptions::default();
+        let a = Expr::var("a");
+        let expr = Expr::let_in("a", Expr::add(x(), Expr::number(1)), Expr::add(a.clone(), a));
+        assert_eq!(expr.to_latex(&o), "\\text{let } a = x + 1 \\text{ in } a + a");
+        assert_eq!(expr.to_ascii(&o), "let a = x + 1 in a + a");
+
+        // The body reaches to the end, so a let operand is bracketed
+        let doubled = Expr::mul(Expr::number(2), expr);
+        assert_eq!(
+            doubled.to_latex(&o),
+            "2 \\cdot \\left(\\text{let } a = x + 1 \\text{ in } a + a\\right)"
+        );
+    }
+
+    #[test]
+    fn test_number_bases() {
+        let dozenal = RenderOptions::dozenal();
+        assert_eq!(format_number(Scalar::from(144), &dozenal), "100");
+        assert_eq!(format_number(Scalar::from(-11), &dozenal), "-B");
+        assert_eq!(format_number(Scalar::from(0.5), &dozenal), "0.6");
+
+        let binary = RenderOptions::in_base(2).unwrap();
+        assert_eq!(format_number(Scalar::from(0.75), &binary), "0.11");
+        assert!(RenderOptions::in_base(37).is_err());
+
+        // Rounded to the digit limit, trailing zeros dropped
+        let third = Scalar::ONE / Scalar::from(3);
+        let decimal = RenderOptions::decimal();
+        assert_eq!(format_number(third, &decimal), "0.333333333333");
+    }
+}
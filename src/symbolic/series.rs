@@ -0,0 +1,905 @@
+//! Taylor series and limits
+//!
+//! `series` expands an expression in powers of t = x - a by combining the
+//! expansions of its parts: sums and products term by term, quotients
+//! through the reciprocal of the divisor's leading term, and elementary
+//! functions by composing their known series with the inner expansion.
+//! Coefficients stay exact rationals where they can and are simplified
+//! expressions otherwise. A divisor that starts at a higher power gives
+//! negative exponents, so sin(x)/x at 0 expands to 1 - x²/6 + ... instead
+//! of failing at 0/0.
+//!
+//! `limit` reads the answer off the leading term of an expansion. When
+//! there is no expansion it tries L'Hôpital's rule on quotients, and when
+//! neither settles the question the answer is `Limit::Uncertain`.
+
+use super::budget::Meter;
+use super::{Context, Differentiate, Evaluate, Expr, Rational, Simplify};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Extra terms computed beyond the requested order before giving up
+const MAX_EXTRA_TERMS: usize = 16;
+
+/// Most terms computed while looking for the leading term of a limit
+const MAX_LIMIT_TERMS: usize = 24;
+
+/// Deepest chain of L'Hôpital steps
+const MAX_LHOPITAL: usize = 4;
+
+/// Largest denominator of a folded number still treated as exact
+const EXACT_DENOMINATOR: i128 = 1 << 16;
+
+fn no_series(reason: impl fmt::Display) -> VeritasError {
+    VeritasError::SimplificationError(format!("No series expansion: {}", reason))
+}
+
+/// Failure that more terms might fix, the only kind worth a retry
+fn too_few_terms(reason: &str) -> VeritasError {
+    no_series(format!("too few terms: {}", reason))
+}
+
+fn is_too_few_terms(e: &VeritasError) -> bool {
+    matches!(e, VeritasError::SimplificationError(m) if m.contains("too few terms: "))
+}
+
+/// Series coefficient: exact where possible, else a simplified expression
+#[derive(Debug, Clone, PartialEq)]
+enum Coeff {
+    Exact(Rational),
+    Symbolic(Expr),
+}
+
+impl Coeff {
+    const ZERO: Coeff = Coeff::Exact(Rational::ZERO);
+    const ONE: Coeff = Coeff::Exact(Rational::ONE);
+
+    fn int(n: i64) -> Coeff {
+        Coeff::Exact(Rational::from_i64(n))
+    }
+
+    /// Simplified expression, exact if it folds to a short binary fraction
+    fn symbolic(expr: Expr) -> Result<Coeff> {
+        let expr = expr.simplify()?;
+        if let Expr::Number(n) = &expr {
+            if let Some(r) = Rational::from_scalar(*n).filter(|r| r.denom() <= EXACT_DENOMINATOR) {
+                return Ok(Coeff::Exact(r));
+            }
+        }
+        Ok(Coeff::Symbolic(expr))
+    }
+
+    fn to_expr(&self) -> Result<Expr> {
+        match self {
+            Coeff::Exact(r) => r.to_expr(),
+            Coeff::Symbolic(e) => Ok(e.clone()),
+        }
+    }
+
+    fn is_exactly(&self, value: Rational) -> bool {
+        *self == Coeff::Exact(value)
+    }
+
+    /// Exact arithmetic when both sides are exact and it does not
+    /// overflow, symbolic otherwise
+    fn combine(
+        &self,
+        other: &Coeff,
+        exact: fn(&Rational, Rational) -> Result<Rational>,
+        build: fn(Expr, Expr) -> Expr,
+    ) -> Result<Coeff> {
+        if let (Coeff::Exact(a), Coeff::Exact(b)) = (self, other) {
+            match exact(a, *b) {
+                Err(VeritasError::NumericOverflow) => {}
+                result => return result.map(Coeff::Exact),
+            }
+        }
+        Coeff::symbolic(build(self.to_expr()?, other.to_expr()?))
+    }
+
+    fn add(&self, other: &Coeff) -> Result<Coeff> {
+        if self.is_exactly(Rational::ZERO) {
+            return Ok(other.clone());
+        }
+        if other.is_exactly(Rational::ZERO) {
+            return Ok(self.clone());
+        }
+        self.combine(other, Rational::checked_add, Expr::add)
+    }
+
+    fn sub(&self, other: &Coeff) -> Result<Coeff> {
+        self.add(&other.neg()?)
+    }
+
+    fn mul(&self, other: &Coeff) -> Result<Coeff> {
+        if self.is_exactly(Rational::ZERO) || other.is_exactly(Rational::ZERO) {
+            return Ok(Coeff::ZERO);
+        }
+        self.combine(other, Rational::checked_mul, Expr::mul)
+    }
+
+    fn div(&self, other: &Coeff) -> Result<Coeff> {
+        self.combine(other, Rational::checked_div, Expr::div)
+    }
+
+    fn neg(&self) -> Result<Coeff> {
+        match self {
+            Coeff::Exact(r) => Ok(Coeff::Exact(-*r)),
+            Coeff::Symbolic(e) => Coeff::symbolic(Expr::neg(e.clone())),
+        }
+    }
+
+    /// Sign, if it can be settled
+    ///
+    /// Constant expressions are evaluated, but a value too small to tell
+    /// from rounding error (sin π computed numerically) is not trusted.
+    fn sign(&self) -> Option<Ordering> {
+        match self {
+            Coeff::Exact(r) => Some(r.numer().cmp(&0)),
+            Coeff::Symbolic(e) if e.is_constant() => {
+                let value = e.evaluate_scalar(&Context::new()).ok()?;
+                let tolerance = Scalar::ONE / Scalar::from(1 << 20);
+                match value.abs().compare(&tolerance).ok()? {
+                    Ordering::Greater => value.compare(&Scalar::ZERO).ok(),
+                    _ => None,
+                }
+            }
+            Coeff::Symbolic(_) => None,
+        }
+    }
+
+    /// `Some(true)` if provably zero, `Some(false)` if provably not
+    fn is_zero(&self) -> Option<bool> {
+        self.sign().map(|s| s == Ordering::Equal)
+    }
+
+    /// f(self), exact when `self` is `at` and f(at) is `exact`
+    fn apply(&self, f: fn(Expr) -> Expr, at: Rational, exact: Rational) -> Result<Coeff> {
+        if self.is_exactly(at) {
+            Ok(Coeff::Exact(exact))
+        } else {
+            Coeff::symbolic(f(self.to_expr()?))
+        }
+    }
+}
+
+/// Truncated Laurent series in t = x - a
+///
+/// `coeffs[i]` is the coefficient of t^(low + i). Every stored coefficient
+/// is known; nothing is known from t^high() on.
+#[derive(Debug, Clone)]
+struct Terms {
+    low: i32,
+    coeffs: Vec<Coeff>,
+}
+
+impl Terms {
+    /// c + 0·t + ... known up to t^high
+    fn constant(c: Coeff, high: i32) -> Terms {
+        let mut coeffs = vec![Coeff::ZERO; high.max(1) as usize];
+        coeffs[0] = c;
+        Terms { low: 0, coeffs }
+    }
+
+    fn high(&self) -> i32 {
+        self.low + self.coeffs.len() as i32
+    }
+
+    fn get(&self, exponent: i32) -> Coeff {
+        if exponent < self.low || exponent >= self.high() {
+            Coeff::ZERO
+        } else {
+            self.coeffs[(exponent - self.low) as usize].clone()
+        }
+    }
+
+    fn zip(&self, other: &Terms, f: fn(&Coeff, &Coeff) -> Result<Coeff>) -> Result<Terms> {
+        let low = self.low.min(other.low);
+        let high = self.high().min(other.high()).max(low);
+        let coeffs = (low..high)
+            .map(|e| f(&self.get(e), &other.get(e)))
+            .collect::<Result<_>>()?;
+        Ok(Terms { low, coeffs })
+    }
+
+    fn add(&self, other: &Terms) -> Result<Terms> {
+        self.zip(other, Coeff::add)
+    }
+
+    fn sub(&self, other: &Terms) -> Result<Terms> {
+        self.zip(other, Coeff::sub)
+    }
+
+    fn scale(&self, c: &Coeff) -> Result<Terms> {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|x| x.mul(c))
+            .collect::<Result<_>>()?;
+        Ok(Terms {
+            low: self.low,
+            coeffs,
+        })
+    }
+
+    /// Cauchy product, known as far as the less precise factor allows
+    ///
+    /// Leading zeros are dropped first; they would cost precision.
+    fn mul(&self, other: &Terms) -> Result<Terms> {
+        let (a, b) = (self.clone().normalize(), other.clone().normalize());
+        let len = a.coeffs.len().min(b.coeffs.len());
+        let mut coeffs = Vec::with_capacity(len);
+        for k in 0..len {
+            let mut sum = Coeff::ZERO;
+            for i in 0..=k {
+                sum = sum.add(&a.coeffs[i].mul(&b.coeffs[k - i])?)?;
+            }
+            coeffs.push(sum);
+        }
+        Ok(Terms {
+            low: a.low + b.low,
+            coeffs,
+        })
+    }
+
+    /// Drop leading coefficients that are provably zero
+    fn normalize(mut self) -> Terms {
+        let zeros = self
+            .coeffs
+            .iter()
+            .take_while(|c| c.is_zero() == Some(true))
+            .count();
+        self.coeffs.drain(..zeros);
+        self.low += zeros as i32;
+        self
+    }
+
+    /// 1/b by the recurrence b₀d_k = -(b₁d_(k-1) + ... + b_k d₀)
+    fn recip(&self) -> Result<Terms> {
+        let b = self.clone().normalize();
+        let b0 = match b.coeffs.first() {
+            Some(b0) => b0.clone(),
+            None => return Err(too_few_terms("every computed term of a divisor vanishes")),
+        };
+        if b0.is_zero() != Some(false) {
+            return Err(no_series(format!(
+                "cannot tell whether {} is zero",
+                b0.to_expr()?
+            )));
+        }
+
+        let mut d = vec![Coeff::ONE.div(&b0)?];
+        for k in 1..b.coeffs.len() {
+            let mut sum = Coeff::ZERO;
+            for j in 1..=k {
+                sum = sum.add(&b.coeffs[j].mul(&d[k - j])?)?;
+            }
+            d.push(sum.neg()?.div(&b0)?);
+        }
+        Ok(Terms {
+            low: -b.low,
+            coeffs: d,
+        })
+    }
+
+    /// Square-and-multiply
+    fn pow(&self, n: u64) -> Result<Terms> {
+        let mut result: Option<Terms> = None;
+        let mut base = self.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = Some(match result {
+                    None => base.clone(),
+                    Some(r) => r.mul(&base)?,
+                });
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base.mul(&base)?;
+            }
+        }
+        Ok(result.unwrap_or_else(|| Terms::constant(Coeff::ONE, self.high())))
+    }
+
+    /// Split u = u₀ + w, with w starting at t¹
+    fn split_constant(&self) -> Result<(Coeff, Terms)> {
+        let mut w = self.clone().normalize();
+        if w.low < 0 {
+            return Err(no_series("a function's argument has a pole"));
+        }
+        if w.coeffs.is_empty() && w.low == 0 {
+            return Err(too_few_terms("no constant term among them"));
+        }
+        let u0 = w.get(0);
+        if w.low == 0 {
+            w.coeffs.remove(0);
+            w.low = 1;
+        }
+        Ok((u0, w))
+    }
+
+    /// a₀ + a₁w + a₂w² + ... for w starting at t¹
+    ///
+    /// `coefficient(k)` is asked only for the powers that matter.
+    fn compose(w: &Terms, mut coefficient: impl FnMut(usize) -> Result<Coeff>) -> Result<Terms> {
+        let high = w.high();
+        let mut sum = Terms::constant(coefficient(0)?, high);
+        let mut power = Terms::constant(Coeff::ONE, high);
+        for k in 1..high.max(1) as usize {
+            power = power.mul(w)?;
+            if power.low >= high {
+                break;
+            }
+            sum = sum.add(&power.scale(&coefficient(k)?)?)?;
+        }
+        Ok(sum)
+    }
+}
+
+/// Expands subexpressions around one point, `terms` coefficients deep
+struct Expander<'a> {
+    var: &'a str,
+    point: Coeff,
+    terms: usize,
+    /// Charged a level per subexpression and an operation per coefficient
+    meter: &'a mut Meter,
+}
+
+impl Expander<'_> {
+    fn expand(&mut self, expr: &Expr) -> Result<Terms> {
+        self.meter.enter()?;
+        let expansion = self.expand_node(expr);
+        self.meter.leave();
+        let expansion = expansion?;
+        for _ in &expansion.coeffs {
+            self.meter.tick()?;
+        }
+        Ok(expansion)
+    }
+
+    fn expand_node(&mut self, expr: &Expr) -> Result<Terms> {
+        let high = self.terms as i32;
+        if !expr.contains_variable(self.var) {
+            return Ok(Terms::constant(Coeff::symbolic(expr.clone())?, high));
+        }
+
+        match expr {
+            // x = a + t
+            Expr::Variable(_) => {
+                let mut t = Terms::constant(self.point.clone(), high);
+                if high > 1 {
+                    t.coeffs[1] = Coeff::ONE;
+                }
+                Ok(t)
+            }
+            Expr::Add(a, b) => self.expand(a)?.add(&self.expand(b)?),
+            Expr::Sub(a, b) => self.expand(a)?.sub(&self.expand(b)?),
+            Expr::Mul(a, b) => self.expand(a)?.mul(&self.expand(b)?),
+            Expr::Div(a, b) => self.expand(a)?.mul(&self.expand(b)?.recip()?),
+            Expr::Neg(a) => self.expand(a)?.scale(&Coeff::int(-1)),
+            Expr::Pow(base, exp) if !exp.contains_variable(self.var) => {
+                let u = self.expand(base)?;
+                self.power(&u, Coeff::symbolic((**exp).clone())?)
+            }
+            // uᵛ = e^(v·ln u)
+            Expr::Pow(base, exp) => {
+                let log = self.expand(base)?;
+                let log = self.ln(&log)?;
+                let product = self.expand(exp)?.mul(&log)?;
+                self.exp(&product)
+            }
+            Expr::Sqrt(a) => {
+                let u = self.expand(a)?;
+                self.power(&u, Coeff::Exact(Rational::new(1, 2)?))
+            }
+            Expr::Ln(a) => {
+                let u = self.expand(a)?;
+                self.ln(&u)
+            }
+            Expr::Exp(a) => {
+                let u = self.expand(a)?;
+                self.exp(&u)
+            }
+            Expr::Sin(a) => {
+                let u = self.expand(a)?;
+                self.sin_cos(&u, false)
+            }
+            Expr::Cos(a) => {
+                let u = self.expand(a)?;
+                self.sin_cos(&u, true)
+            }
+            Expr::Tan(a) => {
+                let u = self.expand(a)?;
+                self.sin_cos(&u, false)?
+                    .mul(&self.sin_cos(&u, true)?.recip()?)
+            }
+            _ => Err(no_series(format!("no rule for {}", expr))),
+        }
+    }
+
+    /// e^(u₀ + w) = e^(u₀)·Σ wᵏ/k!
+    fn exp(&self, u: &Terms) -> Result<Terms> {
+        let (u0, w) = u.split_constant()?;
+        let mut a = u0.apply(Expr::exp, Rational::ZERO, Rational::ONE)?;
+        Terms::compose(&w, |k| {
+            if k > 0 {
+                a = a.div(&Coeff::int(k as i64))?;
+            }
+            Ok(a.clone())
+        })
+    }
+
+    /// sin or cos of u₀ + w, from the derivatives of sin at u₀
+    fn sin_cos(&self, u: &Terms, cosine: bool) -> Result<Terms> {
+        let (u0, w) = u.split_constant()?;
+        let sin = u0.apply(Expr::sin, Rational::ZERO, Rational::ZERO)?;
+        let cos = u0.apply(Expr::cos, Rational::ZERO, Rational::ONE)?;
+        let cycle = [sin.clone(), cos.clone(), sin.neg()?, cos.neg()?];
+        let shift = usize::from(cosine);
+
+        let mut factorial = Coeff::ONE;
+        Terms::compose(&w, |k| {
+            if k > 0 {
+                factorial = factorial.mul(&Coeff::int(k as i64))?;
+            }
+            let j = k + shift;
+            cycle[j - j / 4 * 4].div(&factorial)
+        })
+    }
+
+    /// ln(u₀ + w) = ln u₀ + Σ (-1)^(k+1) (w/u₀)ᵏ / k
+    fn ln(&self, u: &Terms) -> Result<Terms> {
+        let (u0, w) = u.split_constant()?;
+        if u0.is_zero() != Some(false) {
+            return Err(no_series(format!("ln at {}", u0.to_expr()?)));
+        }
+        let inverse = Coeff::ONE.div(&u0)?;
+        let mut power = Coeff::ONE;
+        Terms::compose(&w, |k| {
+            if k == 0 {
+                return u0.apply(Expr::ln, Rational::ONE, Rational::ZERO);
+            }
+            power = power.mul(&inverse)?;
+            let sign = if k / 2 * 2 == k { -1 } else { 1 };
+            power.div(&Coeff::int(sign * k as i64))
+        })
+    }
+
+    /// uᵉ: repeated products for integer e, else the binomial series
+    /// u₀ᵉ·Σ C(e, k) (w/u₀)ᵏ
+    fn power(&self, u: &Terms, e: Coeff) -> Result<Terms> {
+        if let Coeff::Exact(r) = &e {
+            if let (true, Ok(n)) = (r.is_integer(), i64::try_from(r.numer())) {
+                let positive = u.pow(n.unsigned_abs())?;
+                return if n < 0 {
+                    positive.recip()
+                } else {
+                    Ok(positive)
+                };
+            }
+        }
+
+        let (u0, w) = u.split_constant()?;
+        if u0.is_zero() != Some(false) {
+            return Err(no_series(format!(
+                "power {} at {}",
+                e.to_expr()?,
+                u0.to_expr()?
+            )));
+        }
+        let leading = Coeff::symbolic(Expr::pow(u0.to_expr()?, e.to_expr()?))?;
+        let inverse = Coeff::ONE.div(&u0)?;
+        let mut binomial = Coeff::ONE;
+        let mut power = Coeff::ONE;
+        Terms::compose(&w, |k| {
+            if k > 0 {
+                let falling = e.sub(&Coeff::int(k as i64 - 1))?;
+                binomial = binomial.mul(&falling)?.div(&Coeff::int(k as i64))?;
+                power = power.mul(&inverse)?;
+            }
+            leading.mul(&binomial)?.mul(&power)
+        })
+    }
+}
+
+/// Truncated series of an expression around a point
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub var: String,
+    pub point: Expr,
+    /// Highest power kept; the remainder is O((x - a)^(order + 1))
+    pub order: usize,
+    /// (exponent, coefficient) of every nonzero term, lowest first;
+    /// exponents are negative below a pole
+    pub terms: Vec<(i32, Expr)>,
+}
+
+impl Series {
+    /// Coefficient of (x - a)^k
+    pub fn coefficient(&self, k: i32) -> Expr {
+        self.terms
+            .iter()
+            .find(|(e, _)| *e == k)
+            .map_or_else(|| Expr::number(0), |(_, c)| c.clone())
+    }
+
+    /// x - a, or just x when a = 0
+    fn offset(&self) -> Expr {
+        match &self.point {
+            Expr::Number(n) if n.is_zero() => Expr::var(&self.var),
+            point => Expr::sub(Expr::var(&self.var), point.clone()),
+        }
+    }
+
+    /// Σ c_k (x - a)^k over the kept terms
+    pub fn polynomial(&self) -> Expr {
+        let t = self.offset();
+        let mut sum: Option<Expr> = None;
+        for (k, c) in &self.terms {
+            let (negative, magnitude) = match c {
+                Expr::Number(n) if n.is_negative() => (true, Expr::Number(-*n)),
+                Expr::Neg(inner) => (true, (**inner).clone()),
+                _ => (false, c.clone()),
+            };
+            let term = match (k, magnitude) {
+                (0, m) => m,
+                (1, m) if m == Expr::number(1) => t.clone(),
+                (_, m) if m == Expr::number(1) => Expr::pow(t.clone(), Expr::number(*k)),
+                (1, m) => Expr::mul(m, t.clone()),
+                (_, m) => Expr::mul(m, Expr::pow(t.clone(), Expr::number(*k))),
+            };
+            sum = Some(match (sum, negative) {
+                (None, false) => term,
+                (None, true) => Expr::neg(term),
+                (Some(s), false) => Expr::add(s, term),
+                (Some(s), true) => Expr::sub(s, term),
+            });
+        }
+        sum.unwrap_or_else(|| Expr::number(0))
+    }
+
+    /// O((x - a)^(order + 1))
+    pub fn remainder(&self) -> Expr {
+        let power = Expr::pow(self.offset(), Expr::number(self.order as i32 + 1));
+        Expr::Function("O".to_string(), vec![power])
+    }
+
+    /// Polynomial plus remainder term
+    pub fn to_expr(&self) -> Expr {
+        Expr::add(self.polynomial(), self.remainder())
+    }
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_expr())
+    }
+}
+
+/// Series of `expr` in powers of (`var` - `point`) up to `order`
+///
+/// Works through removable singularities: sin(x)/x at 0 gives
+/// 1 - x²/6 + x⁴/120 + O(x⁵) for order 4.
+pub fn series(expr: &Expr, var: &str, point: &Expr, order: usize) -> Result<Series> {
+    let point_coeff = Coeff::symbolic(point.clone())?;
+    let wanted = order as i32 + 1;
+
+    // Division by a series starting at tⁿ costs n terms of precision
+    let mut meter = Meter::default();
+    let mut terms = order + 2;
+    let most = order + 1 + MAX_EXTRA_TERMS;
+    let expansion = loop {
+        let mut expander = Expander {
+            var,
+            point: point_coeff.clone(),
+            terms,
+            meter: &mut meter,
+        };
+        match expander.expand(expr) {
+            Ok(t) if t.high() >= wanted => break t,
+            Err(e) if !is_too_few_terms(&e) => return Err(e),
+            _ if terms < most => terms = (2 * terms).min(most),
+            Ok(_) => {
+                return Err(no_series(format!(
+                    "{} loses too many terms to reach order {}",
+                    expr, order
+                )))
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    let mut kept = Vec::new();
+    for k in expansion.low..wanted {
+        let c = expansion.get(k);
+        if c.is_zero() != Some(true) {
+            kept.push((k, c.to_expr()?));
+        }
+    }
+
+    Ok(Series {
+        var: var.to_string(),
+        point: point.clone(),
+        order,
+        terms: kept,
+    })
+}
+
+/// Side from which a limit is approached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From below (x → a⁻)
+    Left,
+    /// From above (x → a⁺)
+    Right,
+    /// Both sides, which must agree
+    Both,
+}
+
+/// Answer to "what does the expression approach?"
+#[derive(Debug, Clone, PartialEq)]
+pub enum Limit {
+    Value(Expr),
+    PositiveInfinity,
+    NegativeInfinity,
+    /// The one-sided limits differ
+    DoesNotExist,
+    /// Neither series nor L'Hôpital's rule settled it
+    Uncertain {
+        reason: String,
+    },
+}
+
+/// Limit of `expr` as `var` approaches `point` from `direction`
+pub fn limit(expr: &Expr, var: &str, point: &Expr, direction: Direction) -> Result<Limit> {
+    limit_within(expr, var, point, direction, &mut Meter::default())
+}
+
+/// `limit`, charging the expansions it computes to `meter`
+pub(crate) fn limit_within(
+    expr: &Expr,
+    var: &str,
+    point: &Expr,
+    direction: Direction,
+    meter: &mut Meter,
+) -> Result<Limit> {
+    limit_at(expr, var, point, direction, 0, meter)
+}
+
+fn limit_at(
+    expr: &Expr,
+    var: &str,
+    point: &Expr,
+    direction: Direction,
+    depth: usize,
+    meter: &mut Meter,
+) -> Result<Limit> {
+    let reason = match leading_term(expr, var, point, meter) {
+        // Out of budget is not a reason to try L'Hôpital
+        Err(e @ VeritasError::BudgetExceeded { .. }) => return Err(e),
+        Ok((low, c)) => return from_leading_term(low, c, direction),
+        Err(e) => e,
+    };
+
+    // L'Hôpital: 0/0 and ∞/∞ have the limit of a'/b'
+    if let (Expr::Div(a, b), true) = (expr, depth < MAX_LHOPITAL) {
+        let top = limit_at(a, var, point, direction, depth + 1, meter)?;
+        let bottom = limit_at(b, var, point, direction, depth + 1, meter)?;
+        let vanishes = |l: &Limit| match l {
+            Limit::Value(v) => Coeff::symbolic(v.clone()).map(|c| c.is_zero() == Some(true)),
+            _ => Ok(false),
+        };
+        let infinite = |l: &Limit| matches!(l, Limit::PositiveInfinity | Limit::NegativeInfinity);
+
+        if (vanishes(&top)? && vanishes(&bottom)?) || (infinite(&top) && infinite(&bottom)) {
+            let quotient = Expr::div(a.differentiate(var)?, b.differentiate(var)?);
+            return limit_at(&quotient, var, point, direction, depth + 1, meter);
+        }
+        if let (Limit::Value(x), Limit::Value(y)) = (&top, &bottom) {
+            if Coeff::symbolic(y.clone())?.is_zero() == Some(false) {
+                return Ok(Limit::Value(Expr::div(x.clone(), y.clone()).simplify()?));
+            }
+        }
+    }
+
+    Ok(Limit::Uncertain {
+        reason: reason.to_string(),
+    })
+}
+
+/// Lowest exponent whose coefficient is not provably zero, and that coefficient
+fn leading_term(
+    expr: &Expr,
+    var: &str,
+    point: &Expr,
+    meter: &mut Meter,
+) -> Result<(i32, Coeff)> {
+    let point = Coeff::symbolic(point.clone())?;
+    let mut terms = 4;
+    loop {
+        let mut expander = Expander {
+            var,
+            point: point.clone(),
+            terms,
+            meter: &mut *meter,
+        };
+        match expander.expand(expr).map(Terms::normalize) {
+            // Nothing but zeros below a positive power still means the limit is 0
+            Ok(t) if !t.coeffs.is_empty() || t.low > 0 => return Ok((t.low, t.get(t.low))),
+            Err(e) if !is_too_few_terms(&e) => return Err(e),
+            _ if terms < MAX_LIMIT_TERMS => terms *= 2,
+            Ok(_) => return Err(no_series("every computed term vanishes")),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn from_leading_term(low: i32, c: Coeff, direction: Direction) -> Result<Limit> {
+    if low > 0 {
+        return Ok(Limit::Value(Expr::number(0)));
+    }
+    if low == 0 {
+        return Ok(Limit::Value(c.to_expr()?));
+    }
+
+    // c·t^low with low < 0: a pole whose sign flips across it when low is odd
+    let sign = match c.sign() {
+        Some(Ordering::Equal) | None => {
+            return Ok(Limit::Uncertain {
+                reason: format!("cannot tell the sign of {}", c.to_expr()?),
+            })
+        }
+        Some(sign) => sign,
+    };
+    let odd = low / 2 * 2 != low;
+    let positive = match direction {
+        Direction::Right => sign == Ordering::Greater,
+        Direction::Left => (sign == Ordering::Greater) != odd,
+        Direction::Both if odd => return Ok(Limit::DoesNotExist),
+        Direction::Both => sign == Ordering::Greater,
+    };
+    Ok(if positive {
+        Limit::PositiveInfinity
+    } else {
+        Limit::NegativeInfinity
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::var("x")
+    }
+
+    fn ratio(num: i128, den: i128) -> Expr {
+        Rational::new(num, den).unwrap().to_expr().unwrap()
+    }
+
+    #[test]
+    fn test_elementary_series() {
+        let exp = series(&Expr::exp(x()), "x", &Expr::number(0), 3).unwrap();
+        assert_eq!(exp.coefficient(0), ratio(1, 1));
+        assert_eq!(exp.coefficient(2), ratio(1, 2));
+        assert_eq!(exp.coefficient(3), ratio(1, 6));
+
+        // ln x = (x - 1) - (x - 1)²/2 + (x - 1)³/3 + O((x - 1)⁴)
+        let ln = series(&Expr::ln(x()), "x", &Expr::number(1), 3).unwrap();
+        let terms: Vec<i32> = ln.terms.iter().map(|(k, _)| *k).collect();
+        assert_eq!(terms, vec![1, 2, 3]);
+        assert_eq!(ln.coefficient(2), ratio(-1, 2));
+        assert_eq!(
+            ln.remainder(),
+            Expr::Function(
+                "O".to_string(),
+                vec![Expr::pow(Expr::sub(x(), Expr::number(1)), Expr::number(4))]
+            )
+        );
+    }
+
+    #[test]
+    fn test_removable_singularity_series() {
+        // sin(x)/x = 1 - x²/6 + x⁴/120 + O(x⁵)
+        let expr = Expr::div(Expr::sin(x()), x());
+        let s = series(&expr, "x", &Expr::number(0), 4).unwrap();
+        assert_eq!(
+            s.terms,
+            vec![(0, ratio(1, 1)), (2, ratio(-1, 6)), (4, ratio(1, 120))]
+        );
+        assert_eq!(
+            s.polynomial(),
+            Expr::add(
+                Expr::sub(
+                    ratio(1, 1),
+                    Expr::mul(ratio(1, 6), Expr::pow(x(), Expr::number(2)))
+                ),
+                Expr::mul(ratio(1, 120), Expr::pow(x(), Expr::number(4)))
+            )
+        );
+    }
+
+    #[test]
+    fn test_series_retries() {
+        // x³/(x - sin x): the divisor vanishes until the x³ term, so only a retry reaches it
+        let expr = Expr::div(
+            Expr::pow(x(), Expr::number(3)),
+            Expr::sub(x(), Expr::sin(x())),
+        );
+        let s = series(&expr, "x", &Expr::number(0), 0).unwrap();
+        assert_eq!(s.coefficient(0), ratio(6, 1));
+
+        // More terms cannot help |x|, so its failure comes back as it is
+        let err = series(&Expr::abs(x()), "x", &Expr::number(0), 2).unwrap_err();
+        assert!(!is_too_few_terms(&err));
+    }
+
+    #[test]
+    fn test_limits() {
+        let zero = Expr::number(0);
+        // (1 - cos x)/x² → 1/2
+        let expr = Expr::div(
+            Expr::sub(Expr::number(1), Expr::cos(x())),
+            Expr::pow(x(), Expr::number(2)),
+        );
+        assert_eq!(
+            limit(&expr, "x", &zero, Direction::Both).unwrap(),
+            Limit::Value(ratio(1, 2))
+        );
+
+        // (x² - 1)/(x - 1) → 2
+        let expr = Expr::div(
+            Expr::sub(Expr::pow(x(), Expr::number(2)), Expr::number(1)),
+            Expr::sub(x(), Expr::number(1)),
+        );
+        assert_eq!(
+            limit(&expr, "x", &Expr::number(1), Direction::Both).unwrap(),
+            Limit::Value(ratio(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_poles_and_directions() {
+        let zero = Expr::number(0);
+        let inverse = Expr::div(Expr::number(1), x());
+        assert_eq!(
+            limit(&inverse, "x", &zero, Direction::Right).unwrap(),
+            Limit::PositiveInfinity
+        );
+        assert_eq!(
+            limit(&inverse, "x", &zero, Direction::Left).unwrap(),
+            Limit::NegativeInfinity
+        );
+        assert_eq!(
+            limit(&inverse, "x", &zero, Direction::Both).unwrap(),
+            Limit::DoesNotExist
+        );
+
+        let inverse_square = Expr::div(Expr::number(-1), Expr::pow(x(), Expr::number(2)));
+        assert_eq!(
+            limit(&inverse_square, "x", &zero, Direction::Both).unwrap(),
+            Limit::NegativeInfinity
+        );
+    }
+
+    #[test]
+    fn test_lhopital_and_uncertain() {
+        // x/(a·x): the series stalls on whether a is zero, L'Hôpital gives 1/a
+        let a = Expr::var("a");
+        let expr = Expr::div(x(), Expr::mul(a.clone(), x()));
+        assert_eq!(
+            limit(&expr, "x", &Expr::number(0), Direction::Both).unwrap(),
+            Limit::Value(Expr::div(Expr::number(1), a))
+        );
+
+        // |x|/x has different one-sided limits, but no series to show it
+        let expr = Expr::div(Expr::abs(x()), x());
+        assert!(matches!(
+            limit(&expr, "x", &Expr::number(0), Direction::Both).unwrap(),
+            Limit::Uncertain { .. }
+        ));
+    }
+}
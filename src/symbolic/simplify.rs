@@ -1,31 +1,304 @@
 //! Expression simplification
 //!
 //! Algebraic simplification preserving mathematical equivalence.
+//!
+//! Rewrites that are only identities under conditions (√(x²) = x needs
+//! x ≥ 0) fire only when `Assumptions` prove the condition, and each one
+//! is recorded as a step justified by `ConditionalIdentity`. The
+//! complex-domain rules (i² = -1, Euler's formula, conjugates) are
+//! recorded too, justified by the `AlgebraicIdentity` they apply.
+//!
+//! Shapes are checked first. Arithmetic on vector and matrix literals,
+//! products of literals and built-ins applied to literals are worked out
+//! entry by entry, so W·x + b with symbolic entries becomes a vector of
+//! scalar expressions.
 
-use super::Expr;
+use super::assumptions::{Assumption, Assumptions};
+use super::budget::{EvalBudget, Meter};
+use super::complex::{
+    apply_builtin, complex_literal, is_builtin, is_complex_pair, literal, quarter_turns,
+    times_i, unit_at,
+};
+use super::context::Value;
+use super::linalg::{
+    expand_builtin, literal as array_literal, map_literal, matmul_literals, zip_literals,
+};
+use super::{constants, Expr};
+use crate::compositor::{ComputationStep, Justification, Transformation};
 use crate::error::Result;
-use crate::numeric::Scalar;
+use crate::numeric::{Circle, Scalar};
+use std::cmp::Ordering;
+use std::mem;
 
 /// Trait for simplifying expressions
 pub trait Simplify {
     /// Simplify expression algebraically
     fn simplify(&self) -> Result<Expr>;
+
+    /// Simplify using facts about the variables
+    fn simplify_with(&self, assumptions: &Assumptions) -> Result<Expr>;
+
+    /// Simplify, also returning the named-identity rewrites applied
+    fn simplify_traced(&self, assumptions: &Assumptions) -> Result<(Expr, Vec<ComputationStep>)>;
+
+    /// Simplify, failing with `BudgetExceeded` once `budget` is spent
+    fn simplify_within(&self, assumptions: &Assumptions, budget: &EvalBudget) -> Result<Expr>;
 }
 
 impl Simplify for Expr {
     fn simplify(&self) -> Result<Expr> {
-        self.check_complexity(1000)?;
+        self.simplify_with(&Assumptions::new())
+    }
 
-        let simplified = match self {
-            // Atomic expressions are already simple
-            Expr::Number(_) | Expr::Complex(_) | Expr::Variable(_) | Expr::Constant(_) => {
-                self.clone()
+    fn simplify_with(&self, assumptions: &Assumptions) -> Result<Expr> {
+        self.simplify_within(assumptions, &EvalBudget::unlimited())
+    }
+
+    fn simplify_traced(&self, assumptions: &Assumptions) -> Result<(Expr, Vec<ComputationStep>)> {
+        self.shape()?;
+        let mut simplifier = Simplifier::new(assumptions, Meter::default());
+        let result = simplifier.run(self)?;
+        Ok((result, simplifier.steps))
+    }
+
+    fn simplify_within(&self, assumptions: &Assumptions, budget: &EvalBudget) -> Result<Expr> {
+        self.shape()?;
+        Simplifier::new(assumptions, Meter::new(budget)).run(self)
+    }
+}
+
+/// Simplification pass over one expression
+struct Simplifier<'a> {
+    assumptions: &'a Assumptions,
+    /// Named-identity rewrites applied so far
+    steps: Vec<ComputationStep>,
+    /// Work done so far, each subexpression visited counting once
+    meter: Meter,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(assumptions: &'a Assumptions, meter: Meter) -> Self {
+        Simplifier {
+            assumptions,
+            steps: Vec::new(),
+            meter,
+        }
+    }
+
+    /// Apply a conditional rewrite if every condition is proven
+    fn conditional(
+        &mut self,
+        before: &Expr,
+        after: Expr,
+        rule: &str,
+        conditions: &[(&Expr, Assumption)],
+    ) -> Option<Expr> {
+        if !conditions
+            .iter()
+            .all(|(expr, fact)| self.assumptions.proves(expr, *fact))
+        {
+            return None;
+        }
+
+        self.steps.push(ComputationStep {
+            transformation: Transformation::Identity {
+                rule: rule.to_string(),
+            },
+            before: before.clone(),
+            after: after.clone(),
+            justification: Justification::ConditionalIdentity {
+                rule: rule.to_string(),
+                conditions: conditions
+                    .iter()
+                    .map(|(expr, fact)| fact.describe(expr))
+                    .collect(),
+            },
+        });
+        Some(after)
+    }
+
+    /// `truth` in place of `before`, which is decided once `operands` are
+    /// defined: outright if they always are, as a conditional rewrite if
+    /// the assumptions prove what they need
+    fn fold_defined(
+        &mut self,
+        before: &Expr,
+        operands: &[&Expr],
+        truth: bool,
+        rule: &str,
+    ) -> Option<Expr> {
+        let mut conditions = Vec::new();
+        if !operands.iter().all(|e| definedness(e, &mut conditions)) {
+            return None;
+        }
+        if conditions.is_empty() {
+            return Some(Expr::Bool(truth));
+        }
+        self.conditional(before, Expr::Bool(truth), rule, &conditions)
+    }
+
+    /// Apply an unconditional named identity
+    fn identity(&mut self, before: &Expr, after: Expr, rule: &str) -> Expr {
+        self.steps.push(ComputationStep {
+            transformation: Transformation::Identity {
+                rule: rule.to_string(),
+            },
+            before: before.clone(),
+            after: after.clone(),
+            justification: Justification::AlgebraicIdentity(rule.to_string()),
+        });
+        after
+    }
+
+    /// Fold a binary operation on complex literals into one literal
+    fn fold_complex(
+        &mut self,
+        before: Expr,
+        op: fn(&Circle, Circle) -> Result<Circle>,
+        rule: &str,
+    ) -> Expr {
+        let operands: Vec<Option<Circle>> = before.children().into_iter().map(literal).collect();
+        let folded = match operands[..] {
+            [Some(a), Some(b)] => op(&a, b).ok(),
+            _ => None,
+        };
+        match folded {
+            Some(z) => self.identity(&before, complex_literal(z), rule),
+            // Division by zero stays for evaluation to report
+            None => before,
+        }
+    }
+
+    /// Exact sin θ or cos θ at θ = kπ/2
+    fn exact_trig(&mut self, before: &Expr) -> Option<Expr> {
+        let (theta, pick_sin) = match before {
+            Expr::Sin(theta) => (theta, true),
+            Expr::Cos(theta) => (theta, false),
+            _ => return None,
+        };
+        let (cos, sin) = unit_at(quarter_turns(theta)?);
+        let value = Expr::Number(Scalar::from_i64(if pick_sin { sin } else { cos }));
+        let rule = format!("{} = {}", before, value);
+        Some(self.identity(before, value, &rule))
+    }
+
+    /// Rules for a built-in complex function applied to `z`
+    fn complex_function(&mut self, name: &str, z: &Expr) -> Option<Expr> {
+        let before = Expr::Function(name.to_string(), vec![z.clone()]);
+
+        if let Some(value) = literal(z) {
+            let rule = match name {
+                "conj" => "conj(a + bi) = a - bi",
+                "re" => "re(a + bi) = a",
+                "im" => "im(a + bi) = b",
+                "mag" => "|a + bi| = √(a² + b²)",
+                // The argument is only exact on the axes
+                _ => return self.exact_argument(&before, value),
+            };
+            let after = match apply_builtin(name, &[Value::Circle(value)]).ok()? {
+                Value::Scalar(s) => Expr::Number(s),
+                Value::Circle(c) => complex_literal(c),
+                _ => return None,
+            };
+            return Some(self.identity(&before, after, rule));
+        }
+
+        match (name, z) {
+            // conj(conj(z)) = z
+            ("conj", Expr::Function(inner, args)) if inner == "conj" && args.len() == 1 => {
+                Some(self.identity(&before, args[0].clone(), "conj(conj(z)) = z"))
+            }
+            // |e^(iθ)| = 1 for real θ
+            ("mag", Expr::Exp(exponent)) => {
+                let theta = times_i(exponent)?;
+                self.conditional(
+                    &before,
+                    Expr::Number(Scalar::ONE),
+                    "|e^(iθ)| = 1",
+                    &[(theta, Assumption::Real)],
+                )
             }
+            _ => None,
+        }
+    }
+
+    /// arg of a literal on the real or imaginary axis
+    fn exact_argument(&mut self, before: &Expr, z: Circle) -> Option<Expr> {
+        let (re, im) = (z.real(), z.imag());
+        let after = if im.is_zero() && !re.is_zero() && !re.is_negative() {
+            Expr::Number(Scalar::ZERO)
+        } else if im.is_zero() && re.is_negative() {
+            constants::pi()
+        } else if re.is_zero() && !im.is_zero() {
+            let half = Expr::div(constants::pi(), Expr::number(2));
+            if im.is_negative() {
+                Expr::neg(half)
+            } else {
+                half
+            }
+        } else {
+            return None;
+        };
+        Some(self.identity(before, after, "arg on the axes"))
+    }
+
+    /// Decide a comparison against zero from the sign facts of the other side
+    fn decide_sign(&mut self, comparison: Expr) -> Expr {
+        fn zero(e: &Expr) -> bool {
+            matches!(e, Expr::Number(n) if n.is_zero())
+        }
+
+        // Normalize to `x ⋈ 0`, remembering which sign fact settles it
+        let (x, fact, truth) = match &comparison {
+            Expr::Gt(a, b) if zero(b) => (a, Assumption::Positive, true),
+            Expr::Lt(a, b) if zero(a) => (b, Assumption::Positive, true),
+            Expr::Ge(a, b) if zero(b) => (a, Assumption::Nonnegative, true),
+            Expr::Le(a, b) if zero(a) => (b, Assumption::Nonnegative, true),
+            Expr::Ne(a, b) if zero(b) => (a, Assumption::Nonzero, true),
+            Expr::Ne(a, b) if zero(a) => (b, Assumption::Nonzero, true),
+            Expr::Eq(a, b) if zero(b) => (a, Assumption::Nonzero, false),
+            Expr::Eq(a, b) if zero(a) => (b, Assumption::Nonzero, false),
+            Expr::Lt(a, b) if zero(b) => (a, Assumption::Nonnegative, false),
+            Expr::Gt(a, b) if zero(a) => (b, Assumption::Nonnegative, false),
+            Expr::Le(a, b) if zero(b) => (a, Assumption::Positive, false),
+            Expr::Ge(a, b) if zero(a) => (b, Assumption::Positive, false),
+            _ => return comparison,
+        };
+
+        let x = (**x).clone();
+        self.conditional(&comparison, Expr::Bool(truth), "sign of a known quantity", &[(&x, fact)])
+            .unwrap_or(comparison)
+    }
+
+    fn run(&mut self, expr: &Expr) -> Result<Expr> {
+        self.meter.enter()?;
+        let simplified = self.rewrite(expr);
+        self.meter.leave();
+        let simplified = simplified?;
+        if let Expr::Number(n) = &simplified {
+            self.meter.check_scalar(*n)?;
+        }
+        Ok(simplified)
+    }
+
+    fn rewrite(&mut self, expr: &Expr) -> Result<Expr> {
+        expr.check_complexity(1000)?;
+
+        let simplified = match expr {
+            // Atomic expressions are already simple
+            Expr::Number(_)
+            | Expr::Complex(_)
+            | Expr::Variable(_)
+            | Expr::Constant(_)
+            | Expr::Quantity(..) => expr.clone(),
 
             // Addition simplification
             Expr::Add(a, b) => {
-                let a = a.simplify()?;
-                let b = b.simplify()?;
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                if let Some(entries) = zip_literals(&a, &b, Expr::add) {
+                    return self.run(&entries);
+                }
 
                 match (&a, &b) {
                     // 0 + x = x
@@ -34,14 +307,29 @@ impl Simplify for Expr {
                     (_, Expr::Number(n)) if n.is_zero() => a,
                     // Constant folding: n1 + n2
                     (Expr::Number(n1), Expr::Number(n2)) => Expr::Number((*n1 + *n2)),
+                    _ if is_complex_pair(&a, &b) => self.fold_complex(
+                        Expr::add(a.clone(), b.clone()),
+                        Circle::checked_add,
+                        "(a + bi) + (c + di) = (a + c) + (b + d)i",
+                    ),
+                    // cos θ + i sin θ = e^(iθ)
+                    (Expr::Cos(theta), Expr::Mul(..))
+                        if times_i(&b).is_some_and(|s| *s == Expr::sin((**theta).clone())) =>
+                    {
+                        let after = Expr::exp(Expr::mul(constants::i(), (**theta).clone()));
+                        self.identity(&Expr::add(a.clone(), b.clone()), after, "cos θ + i sin θ = e^(iθ)")
+                    }
                     _ => Expr::add(a, b),
                 }
             }
 
             // Subtraction simplification
             Expr::Sub(a, b) => {
-                let a = a.simplify()?;
-                let b = b.simplify()?;
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                if let Some(entries) = zip_literals(&a, &b, Expr::sub) {
+                    return self.run(&entries);
+                }
 
                 match (&a, &b) {
                     // x - 0 = x
@@ -50,14 +338,22 @@ impl Simplify for Expr {
                     _ if a == b => Expr::Number(Scalar::ZERO),
                     // Constant folding
                     (Expr::Number(n1), Expr::Number(n2)) => Expr::Number((*n1 - *n2)),
+                    _ if is_complex_pair(&a, &b) => self.fold_complex(
+                        Expr::sub(a.clone(), b.clone()),
+                        Circle::checked_sub,
+                        "(a + bi) - (c + di) = (a - c) + (b - d)i",
+                    ),
                     _ => Expr::sub(a, b),
                 }
             }
 
             // Multiplication simplification
             Expr::Mul(a, b) => {
-                let a = a.simplify()?;
-                let b = b.simplify()?;
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                if let Some(entries) = zip_literals(&a, &b, Expr::mul) {
+                    return self.run(&entries);
+                }
 
                 match (&a, &b) {
                     // 0 * x = 0
@@ -70,14 +366,30 @@ impl Simplify for Expr {
                     (_, Expr::Number(n)) if *n == Scalar::ONE => a,
                     // Constant folding
                     (Expr::Number(n1), Expr::Number(n2)) => Expr::Number((*n1 * *n2)),
+                    // Includes i·i = -1
+                    _ if is_complex_pair(&a, &b) => self.fold_complex(
+                        Expr::mul(a.clone(), b.clone()),
+                        Circle::checked_mul,
+                        "(a + bi)(c + di) = (ac - bd) + (ad + bc)i",
+                    ),
+                    // z·conj(z) = |z|²
+                    (z, Expr::Function(f, args)) | (Expr::Function(f, args), z)
+                        if f == "conj" && args.len() == 1 && args[0] == *z =>
+                    {
+                        let after = Expr::pow(Expr::mag(z.clone()), Expr::number(2));
+                        self.identity(&Expr::mul(a.clone(), b.clone()), after, "z·conj(z) = |z|²")
+                    }
                     _ => Expr::mul(a, b),
                 }
             }
 
             // Division simplification
             Expr::Div(a, b) => {
-                let a = a.simplify()?;
-                let b = b.simplify()?;
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                if let Some(entries) = zip_literals(&a, &b, Expr::div) {
+                    return self.run(&entries);
+                }
 
                 match (&a, &b) {
                     // 0 / x = 0 (x ≠ 0)
@@ -95,14 +407,19 @@ impl Simplify for Expr {
                             Expr::Number((*n1 / *n2))
                         }
                     }
+                    _ if is_complex_pair(&a, &b) => self.fold_complex(
+                        Expr::div(a.clone(), b.clone()),
+                        Circle::checked_div,
+                        "(a + bi)/(c + di) = (a + bi)(c - di)/(c² + d²)",
+                    ),
                     _ => Expr::div(a, b),
                 }
             }
 
             // Power simplification
             Expr::Pow(base, exp) => {
-                let base = base.simplify()?;
-                let exp = exp.simplify()?;
+                let base = self.run(base)?;
+                let exp = self.run(exp)?;
 
                 match (&base, &exp) {
                     // x ^ 0 = 1
@@ -122,26 +439,70 @@ impl Simplify for Expr {
                             Expr::pow(base, exp)
                         }
                     }
+                    // i⁴ = 1, so powers of i cycle
+                    (_, Expr::Number(n)) if base == constants::i() => match n.to_i64() {
+                        Some(k) => {
+                            let after = quarter_turn(k);
+                            self.identity(&Expr::pow(base.clone(), exp.clone()), after, "i⁴ = 1")
+                        }
+                        None => Expr::pow(base, exp),
+                    },
+                    // (√x)² = x for x ≥ 0
+                    (Expr::Sqrt(inner), Expr::Number(n)) if *n == Scalar::TWO => {
+                        let before = Expr::pow(base.clone(), exp.clone());
+                        self.conditional(
+                            &before,
+                            (**inner).clone(),
+                            "(√x)² = x",
+                            &[(&**inner, Assumption::Nonnegative)],
+                        )
+                        .unwrap_or(before)
+                    }
+                    // (xᵃ)ᵇ = xᵃᵇ for x > 0, checked before ab is simplified so
+                    // a rewrite that does not fire leaves no steps behind
+                    (Expr::Pow(inner, inner_exp), _)
+                        if self.assumptions.proves(inner, Assumption::Positive) =>
+                    {
+                        let before = Expr::pow(base.clone(), exp.clone());
+                        let product = self.run(&Expr::mul((**inner_exp).clone(), exp.clone()))?;
+                        self.conditional(
+                            &before,
+                            Expr::pow((**inner).clone(), product),
+                            "(xᵃ)ᵇ = xᵃᵇ",
+                            &[(&**inner, Assumption::Positive)],
+                        )
+                        .unwrap_or(before)
+                    }
                     _ => Expr::pow(base, exp),
                 }
             }
 
             // Negation simplification
             Expr::Neg(a) => {
-                let a = a.simplify()?;
+                let a = self.run(a)?;
+                if let Some(entries) = map_literal(&a, Expr::neg) {
+                    return self.run(&entries);
+                }
 
                 match &a {
                     // -(-x) = x
                     Expr::Neg(inner) => (**inner).clone(),
                     // -(n) = -n
                     Expr::Number(n) => Expr::Number(-*n),
-                    _ => Expr::neg(a),
+                    _ => match literal(&a) {
+                        Some(z) => self.identity(
+                            &Expr::neg(a.clone()),
+                            complex_literal(-z),
+                            "-(a + bi) = -a - bi",
+                        ),
+                        None => Expr::neg(a),
+                    },
                 }
             }
 
             // Square root simplification
             Expr::Sqrt(a) => {
-                let a = a.simplify()?;
+                let a = self.run(a)?;
 
                 match &a {
                     // sqrt(0) = 0
@@ -156,20 +517,323 @@ impl Simplify for Expr {
                             Expr::sqrt(a)
                         }
                     }
+                    // √(x²) = x for x ≥ 0
+                    Expr::Pow(inner, e) if **e == Expr::number(2) => {
+                        let before = Expr::sqrt(a.clone());
+                        self.conditional(
+                            &before,
+                            (**inner).clone(),
+                            "√(x²) = x",
+                            &[(&**inner, Assumption::Nonnegative)],
+                        )
+                        .unwrap_or(before)
+                    }
                     _ => Expr::sqrt(a),
                 }
             }
 
+            Expr::Ln(a) => {
+                let a = self.run(a)?;
+
+                match &a {
+                    // ln(eˣ) = x for real x
+                    Expr::Exp(inner) => {
+                        let before = Expr::ln(a.clone());
+                        self.conditional(
+                            &before,
+                            (**inner).clone(),
+                            "ln(eˣ) = x",
+                            &[(&**inner, Assumption::Real)],
+                        )
+                        .unwrap_or(before)
+                    }
+                    _ => Expr::ln(a),
+                }
+            }
+
+            Expr::Exp(a) => {
+                let a = self.run(a)?;
+
+                match &a {
+                    // e^ln(x) = x for x > 0
+                    Expr::Ln(inner) => {
+                        let before = Expr::exp(a.clone());
+                        self.conditional(
+                            &before,
+                            (**inner).clone(),
+                            "e^ln(x) = x",
+                            &[(&**inner, Assumption::Positive)],
+                        )
+                        .unwrap_or(before)
+                    }
+                    // e^(iθ) = cos θ + i sin θ, where both are exact
+                    Expr::Mul(..) => match times_i(&a).and_then(quarter_turns) {
+                        Some(k) => {
+                            let before = Expr::exp(a.clone());
+                            self.identity(&before, quarter_turn(k), "e^(iθ) = cos θ + i sin θ")
+                        }
+                        None => Expr::exp(a),
+                    },
+                    _ => Expr::exp(a),
+                }
+            }
+
+            Expr::Sin(a) => {
+                let a = self.run(a)?;
+                if let Some(exact) = self.exact_trig(&Expr::sin(a.clone())) {
+                    return Ok(exact);
+                }
+
+                match &a {
+                    // sin(nπ) = 0 for integer n
+                    Expr::Mul(n, c) if **c == constants::pi() => {
+                        let before = Expr::sin(a.clone());
+                        self.conditional(
+                            &before,
+                            Expr::Number(Scalar::ZERO),
+                            "sin(nπ) = 0",
+                            &[(&**n, Assumption::Integer)],
+                        )
+                        .unwrap_or(before)
+                    }
+                    _ => Expr::sin(a),
+                }
+            }
+
             // Other operations - just simplify children
-            Expr::Ln(a) => Expr::Ln(Box::new(a.simplify()?)),
-            Expr::Exp(a) => Expr::Exp(Box::new(a.simplify()?)),
-            Expr::Sin(a) => Expr::Sin(Box::new(a.simplify()?)),
-            Expr::Cos(a) => Expr::Cos(Box::new(a.simplify()?)),
-            Expr::Tan(a) => Expr::Tan(Box::new(a.simplify()?)),
+            Expr::Cos(a) => {
+                let a = self.run(a)?;
+                match self.exact_trig(&Expr::cos(a.clone())) {
+                    Some(exact) => exact,
+                    None => Expr::cos(a),
+                }
+            }
+            Expr::Tan(a) => Expr::Tan(Box::new(self.run(a)?)),
 
             Expr::Function(name, args) => {
-                let args: Result<Vec<_>> = args.iter().map(|arg| arg.simplify()).collect();
-                Expr::Function(name.clone(), args?)
+                let args: Result<Vec<_>> = args.iter().map(|arg| self.run(arg)).collect();
+                let args = args?;
+                match &args[..] {
+                    [z] if is_builtin(name) => self
+                        .complex_function(name, z)
+                        .unwrap_or_else(|| Expr::Function(name.clone(), args.clone())),
+                    // (Aᵀ)ᵀ = A
+                    [Expr::Function(inner, a)]
+                        if name == "transpose" && inner == "transpose" && a.len() == 1 =>
+                    {
+                        a[0].clone()
+                    }
+                    _ => match expand_builtin(name, &args) {
+                        Some(entries) => self.run(&entries)?,
+                        None => Expr::Function(name.clone(), args),
+                    },
+                }
+            }
+
+            Expr::Vector(entries) => {
+                let entries: Result<Vec<_>> = entries.iter().map(|e| self.run(e)).collect();
+                Expr::Vector(entries?)
+            }
+            Expr::Matrix(rows) => {
+                let mut simplified = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let row: Result<Vec<_>> = row.iter().map(|e| self.run(e)).collect();
+                    simplified.push(row?);
+                }
+                Expr::Matrix(simplified)
+            }
+            Expr::MatMul(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                match matmul_literals(&a, &b) {
+                    Some(entries) => self.run(&entries)?,
+                    None => Expr::matmul(a, b),
+                }
+            }
+
+            Expr::Bool(_) => expr.clone(),
+
+            // Comparisons: fold numbers, decide x ⋈ x, decide signs
+            Expr::Eq(a, b) | Expr::Le(a, b) | Expr::Ge(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                // Literals are equal when every entry is
+                if let (Expr::Eq(..), Some((s, xs)), Some((t, ys))) =
+                    (expr, array_literal(&a), array_literal(&b))
+                {
+                    if s == t {
+                        let entries = xs.into_iter().zip(ys).map(|(x, y)| Expr::equals(x, y));
+                        if let Some(all) = entries.reduce(Expr::and) {
+                            return self.run(&all);
+                        }
+                    }
+                }
+                match fold_comparison(expr, &a, &b) {
+                    Some(truth) => Expr::Bool(truth),
+                    None if a == b => {
+                        let before = rebuild_comparison(expr, a.clone(), b);
+                        let folded = self.fold_defined(&before, &[&a], true, "x = x");
+                        folded.unwrap_or_else(|| self.decide_sign(before))
+                    }
+                    None => self.decide_sign(rebuild_comparison(expr, a, b)),
+                }
+            }
+            Expr::Ne(a, b) | Expr::Lt(a, b) | Expr::Gt(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+                match fold_comparison(expr, &a, &b) {
+                    Some(truth) => Expr::Bool(truth),
+                    None if a == b => {
+                        let before = rebuild_comparison(expr, a.clone(), b);
+                        let folded = self.fold_defined(&before, &[&a], false, "x ≠ x is false");
+                        folded.unwrap_or_else(|| self.decide_sign(before))
+                    }
+                    None => self.decide_sign(rebuild_comparison(expr, a, b)),
+                }
+            }
+
+            Expr::And(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+
+                match (&a, &b) {
+                    // false ∧ x = false, true ∧ x = x; x ∧ false only for
+                    // a defined x, since x is evaluated first
+                    (Expr::Bool(false), _) => Expr::Bool(false),
+                    (_, Expr::Bool(false)) => {
+                        let before = Expr::and(a.clone(), b.clone());
+                        self.fold_defined(&before, &[&a], false, "x ∧ false = false")
+                            .unwrap_or(before)
+                    }
+                    (Expr::Bool(true), _) => b,
+                    (_, Expr::Bool(true)) => a,
+                    // x ∧ x = x
+                    _ if a == b => a,
+                    _ => Expr::and(a, b),
+                }
+            }
+
+            Expr::Or(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+
+                match (&a, &b) {
+                    // true ∨ x = true, false ∨ x = x; x ∨ true as for ∧
+                    (Expr::Bool(true), _) => Expr::Bool(true),
+                    (_, Expr::Bool(true)) => {
+                        let before = Expr::or(a.clone(), b.clone());
+                        self.fold_defined(&before, &[&a], true, "x ∨ true = true")
+                            .unwrap_or(before)
+                    }
+                    (Expr::Bool(false), _) => b,
+                    (_, Expr::Bool(false)) => a,
+                    // x ∨ x = x
+                    _ if a == b => a,
+                    _ => Expr::or(a, b),
+                }
+            }
+
+            Expr::Not(a) => {
+                let a = self.run(a)?;
+
+                match a {
+                    Expr::Bool(truth) => Expr::Bool(!truth),
+                    // ¬¬x = x
+                    Expr::Not(inner) => *inner,
+                    // ¬(a = b) = (a ≠ b) and back; order negations are not
+                    // flipped, since undefined operands compare with nothing
+                    Expr::Eq(x, y) => Expr::Ne(x, y),
+                    Expr::Ne(x, y) => Expr::Eq(x, y),
+                    other => Expr::not(other),
+                }
+            }
+
+            Expr::Implies(a, b) => {
+                let a = self.run(a)?;
+                let b = self.run(b)?;
+
+                match (&a, &b) {
+                    // false ⇒ x, x ⇒ true, x ⇒ x are all true, the last two
+                    // for a defined x
+                    (Expr::Bool(false), _) => Expr::Bool(true),
+                    (_, Expr::Bool(true)) => {
+                        let before = Expr::implies(a.clone(), b.clone());
+                        self.fold_defined(&before, &[&a], true, "x ⇒ true")
+                            .unwrap_or(before)
+                    }
+                    _ if a == b => {
+                        let before = Expr::implies(a.clone(), b.clone());
+                        self.fold_defined(&before, &[&a], true, "x ⇒ x").unwrap_or(before)
+                    }
+                    // true ⇒ x = x
+                    (Expr::Bool(true), _) => b,
+                    // x ⇒ false = ¬x
+                    (_, Expr::Bool(false)) => self.run(&Expr::not(a))?,
+                    _ => Expr::implies(a, b),
+                }
+            }
+
+            // Drop branches that cannot fire, stop at one that always does
+            Expr::Piecewise(branches, otherwise) => {
+                let mut kept = Vec::with_capacity(branches.len());
+                let mut fallback = None;
+                for (condition, value) in branches {
+                    match self.run(condition)? {
+                        Expr::Bool(false) => {}
+                        Expr::Bool(true) => {
+                            fallback = Some(self.run(value)?);
+                            break;
+                        }
+                        condition => kept.push((condition, self.run(value)?)),
+                    }
+                }
+                let otherwise = match fallback {
+                    Some(value) => value,
+                    None => self.run(otherwise)?,
+                };
+
+                // Branches agreeing with the fallback are redundant
+                if kept.iter().all(|(_, value)| *value == otherwise) {
+                    otherwise
+                } else {
+                    Expr::piecewise(kept, otherwise)
+                }
+            }
+
+            // Empty and single-term ranges unfold; closed forms live in `summation`
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                let lower = self.run(lower)?;
+                let upper = self.run(upper)?;
+                let scoped = self.assumptions.for_index(index);
+                let mut inner = Simplifier::new(&scoped, mem::take(&mut self.meter));
+                let body = inner.run(body);
+                self.meter = inner.meter;
+                let body = body?;
+                self.steps.append(&mut inner.steps);
+                let bounds = match (&lower, &upper) {
+                    (Expr::Number(a), Expr::Number(b)) => a.to_i64().zip(b.to_i64()),
+                    _ => None,
+                };
+                match bounds {
+                    Some((a, b)) if b < a => match expr {
+                        Expr::Sum(..) => Expr::number(0),
+                        _ => Expr::number(1),
+                    },
+                    Some((a, b)) if a == b => self.run(&body.substitute(index, &lower))?,
+                    _ => match expr {
+                        Expr::Sum(..) => Expr::sum(index.clone(), lower, upper, body),
+                        _ => Expr::product(index.clone(), lower, upper, body),
+                    },
+                }
+            }
+
+            // Inlined, so the body simplifies with the value in place
+            Expr::Let(name, value, body) => {
+                let value = self.run(value)?;
+                let before = Expr::let_in(name.clone(), value.clone(), (**body).clone());
+                let inlined = body.substitute(name, &value);
+                let inlined = self.identity(&before, inlined, "let x = a in b = b[x := a]");
+                self.run(&inlined)?
             }
         };
 
@@ -177,9 +841,110 @@ impl Simplify for Expr {
     }
 }
 
+/// e^(ikπ/2) = iᵏ as a literal
+fn quarter_turn(k: i64) -> Expr {
+    let (cos, sin) = unit_at(k);
+    complex_literal(Circle::from_parts(
+        Scalar::from_i64(cos),
+        Scalar::from_i64(sin),
+    ))
+}
+
+/// Whether `expr` is defined wherever `conditions` hold, adding what
+/// quotients, logarithms, roots and powers need to them; false if some
+/// part may be undefined in a way no `Assumption` rules out
+fn definedness<'e>(expr: &'e Expr, conditions: &mut Vec<(&'e Expr, Assumption)>) -> bool {
+    match expr {
+        // A literal is only as good as its comparisons, which vanished,
+        // exploded and undefined values fail
+        Expr::Number(n) | Expr::Quantity(n, _) => comparable(*n),
+        Expr::Complex(z) => comparable(z.real()) && comparable(z.imag()),
+        Expr::Variable(_) | Expr::Constant(_) | Expr::Bool(_) => true,
+        Expr::Div(a, b) => {
+            conditions.push((b, Assumption::Nonzero));
+            definedness(a, conditions) && definedness(b, conditions)
+        }
+        Expr::Ln(a) => {
+            conditions.push((a, Assumption::Positive));
+            definedness(a, conditions)
+        }
+        Expr::Sqrt(a) => {
+            conditions.push((a, Assumption::Nonnegative));
+            definedness(a, conditions)
+        }
+        // xⁿ needs nothing for n ≥ 0 an integer, x ≠ 0 for n < 0, and
+        // x > 0 otherwise
+        Expr::Pow(base, exp) => {
+            match &**exp {
+                Expr::Number(n) if n.to_i64().is_some_and(|n| n >= 0) => {}
+                Expr::Number(n) if n.to_i64().is_some() => {
+                    conditions.push((base, Assumption::Nonzero))
+                }
+                _ => conditions.push((base, Assumption::Positive)),
+            }
+            definedness(base, conditions) && definedness(exp, conditions)
+        }
+        Expr::Add(a, b)
+        | Expr::Sub(a, b)
+        | Expr::Mul(a, b)
+        | Expr::Eq(a, b)
+        | Expr::Ne(a, b)
+        | Expr::Lt(a, b)
+        | Expr::Le(a, b)
+        | Expr::Gt(a, b)
+        | Expr::Ge(a, b)
+        | Expr::And(a, b)
+        | Expr::Or(a, b)
+        | Expr::Implies(a, b) => definedness(a, conditions) && definedness(b, conditions),
+        Expr::Neg(a) | Expr::Exp(a) | Expr::Sin(a) | Expr::Cos(a) | Expr::Not(a) => {
+            definedness(a, conditions)
+        }
+        Expr::Vector(entries) => entries.iter().all(|e| definedness(e, conditions)),
+        Expr::Matrix(rows) => rows.iter().flatten().all(|e| definedness(e, conditions)),
+        // tan at its poles, calls, products of shapes and the rest
+        _ => false,
+    }
+}
+
+/// Whether a number compares equal to itself rather than failing
+fn comparable(n: Scalar) -> bool {
+    n.compare(&n).is_ok()
+}
+
+/// Decide a comparison between two numbers, if their states allow it
+fn fold_comparison(op: &Expr, a: &Expr, b: &Expr) -> Option<bool> {
+    let (Expr::Number(a), Expr::Number(b)) = (a, b) else {
+        return None;
+    };
+    let ordering = a.compare(b).ok()?;
+
+    match op {
+        Expr::Eq(..) => Some(ordering == Ordering::Equal),
+        Expr::Ne(..) => Some(ordering != Ordering::Equal),
+        Expr::Lt(..) => Some(ordering == Ordering::Less),
+        Expr::Le(..) => Some(ordering != Ordering::Greater),
+        Expr::Gt(..) => Some(ordering == Ordering::Greater),
+        Expr::Ge(..) => Some(ordering != Ordering::Less),
+        _ => None,
+    }
+}
+
+/// Rebuild a comparison node of the same kind as `op`
+fn rebuild_comparison(op: &Expr, a: Expr, b: Expr) -> Expr {
+    match op {
+        Expr::Eq(..) => Expr::equals(a, b),
+        Expr::Ne(..) => Expr::not_equals(a, b),
+        Expr::Lt(..) => Expr::less(a, b),
+        Expr::Le(..) => Expr::less_eq(a, b),
+        Expr::Gt(..) => Expr::greater(a, b),
+        _ => Expr::greater_eq(a, b),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::symbolic::{Context, Evaluate};
 
     #[test]
     fn test_simplify_add_zero() {
@@ -241,4 +1006,298 @@ mod tests {
         let simplified = expr.simplify().unwrap();
         assert_eq!(simplified, Expr::number(20));
     }
+
+    #[test]
+    fn test_conditional_rewrites_need_assumptions() {
+        let x = Expr::var("x");
+        let expr = Expr::sqrt(Expr::pow(x.clone(), Expr::number(2)));
+
+        // Without assumptions √(x²) is |x|, so it must stay put
+        assert_eq!(expr.simplify().unwrap(), expr);
+
+        let assumptions = Assumptions::new().with("x", Assumption::Positive);
+        assert_eq!(expr.simplify_with(&assumptions).unwrap(), x);
+    }
+
+    #[test]
+    fn test_conditional_rewrites_are_justified() {
+        let x = Expr::var("x");
+        let expr = Expr::ln(Expr::exp(x.clone()));
+        let assumptions = Assumptions::new().with("x", Assumption::Real);
+
+        let (result, steps) = expr.simplify_traced(&assumptions).unwrap();
+        assert_eq!(result, x);
+        assert_eq!(steps.len(), 1);
+        match &steps[0].justification {
+            Justification::ConditionalIdentity { conditions, .. } => {
+                assert_eq!(conditions, &vec!["x ∈ ℝ".to_string()]);
+            }
+            other => panic!("Expected conditional identity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unfired_rewrites_leave_no_steps() {
+        // (xⁱ)ⁱ would simplify i·i on the way to x⁻¹, but only for x > 0
+        let i = constants::i();
+        let expr = Expr::pow(Expr::pow(Expr::var("x"), i.clone()), i);
+        let (result, steps) = expr.simplify_traced(&Assumptions::new()).unwrap();
+        assert_eq!(result, expr);
+        assert!(steps.is_empty());
+
+        let positive = Assumptions::new().with("x", Assumption::Positive);
+        let (_, steps) = expr.simplify_traced(&positive).unwrap();
+        assert!(steps.iter().any(|step| matches!(
+            &step.justification,
+            Justification::ConditionalIdentity { rule, .. } if rule == "(xᵃ)ᵇ = xᵃᵇ"
+        )));
+    }
+
+    #[test]
+    fn test_sin_integer_multiple_of_pi() {
+        let expr = Expr::sin(Expr::mul(Expr::var("n"), constants::pi()));
+        let assumptions = Assumptions::new().with("n", Assumption::Integer);
+
+        assert_eq!(expr.simplify_with(&assumptions).unwrap(), Expr::number(0));
+    }
+
+    #[test]
+    fn test_simplify_comparisons() {
+        // 2 < 3 folds; x ≤ x holds for any x
+        let expr = Expr::less(Expr::number(2), Expr::number(3));
+        assert_eq!(expr.simplify().unwrap(), Expr::Bool(true));
+
+        let x = Expr::var("x");
+        let expr = Expr::less_eq(x.clone(), x.clone());
+        assert_eq!(expr.simplify().unwrap(), Expr::Bool(true));
+
+        // 1/x = 1/x is an error at x = 0, not true, unless x ≠ 0 is known
+        let reciprocal = Expr::div(Expr::number(1), x.clone());
+        let expr = Expr::equals(reciprocal.clone(), reciprocal.clone());
+        assert_ne!(expr.simplify().unwrap(), Expr::Bool(true));
+        let nonzero = Assumptions::new().with("x", Assumption::Nonzero);
+        assert_eq!(expr.simplify_with(&nonzero).unwrap(), Expr::Bool(true));
+
+        // Likewise (1/x > 0) ∧ false, whose left side is evaluated first
+        let guarded = Expr::and(Expr::greater(reciprocal, Expr::number(0)), Expr::Bool(false));
+        assert_ne!(guarded.simplify().unwrap(), Expr::Bool(false));
+        let expr = Expr::and(Expr::greater(x, Expr::number(0)), Expr::Bool(false));
+        assert_eq!(expr.simplify().unwrap(), Expr::Bool(false));
+
+        // Literals that fail to compare are not folded either
+        let exploded = Scalar::new(spirix::ScalarF6E5::MAX) * Scalar::TWO;
+        let undefined = Scalar::ONE / Scalar::ZERO;
+        for n in [exploded, undefined] {
+            let n = Expr::Number(n);
+            for expr in [Expr::equals(n.clone(), n.clone()), Expr::not_equals(n.clone(), n)] {
+                assert!(expr.evaluate(&Context::new()).is_err());
+                assert!(!matches!(expr.simplify().unwrap(), Expr::Bool(_)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_connectives() {
+        let p = Expr::greater(Expr::var("x"), Expr::number(0));
+
+        // (x > 0) ∧ (1 < 2) = x > 0
+        let expr = Expr::and(p.clone(), Expr::less(Expr::number(1), Expr::number(2)));
+        assert_eq!(expr.simplify().unwrap(), p);
+
+        // ¬¬(x > 0) = x > 0
+        let expr = Expr::not(Expr::not(p.clone()));
+        assert_eq!(expr.simplify().unwrap(), p);
+
+        // (x > 0) ⇒ false = ¬(x > 0)
+        let expr = Expr::implies(p.clone(), Expr::Bool(false));
+        assert_eq!(expr.simplify().unwrap(), Expr::not(p));
+    }
+
+    #[test]
+    fn test_simplify_let() {
+        // let y = x + 0 in y - x = 0, and the inlining is a recorded step
+        let x = Expr::var("x");
+        let expr = Expr::let_in(
+            "y",
+            Expr::add(x.clone(), Expr::number(0)),
+            Expr::sub(Expr::var("y"), x.clone()),
+        );
+        let (simplified, steps) = expr.simplify_traced(&Assumptions::new()).unwrap();
+        assert_eq!(simplified, Expr::number(0));
+        assert!(steps.iter().any(|step| step.before == Expr::let_in(
+            "y",
+            x.clone(),
+            Expr::sub(Expr::var("y"), x.clone())
+        )));
+    }
+
+    #[test]
+    fn test_simplify_piecewise() {
+        let x = Expr::var("x");
+
+        // Without facts about x, relu stays piecewise
+        let relu = Expr::relu(x.clone());
+        assert_eq!(relu.simplify().unwrap(), relu);
+
+        // For positive x the first branch always fires
+        let assumptions = Assumptions::new().with("x", Assumption::Positive);
+        assert_eq!(relu.simplify_with(&assumptions).unwrap(), x);
+
+        // |x| for nonnegative x is x
+        let assumptions = Assumptions::new().with("x", Assumption::Nonnegative);
+        assert_eq!(Expr::abs(x.clone()).simplify_with(&assumptions).unwrap(), x);
+
+        // Decidable conditions on constants fold away
+        assert_eq!(Expr::abs(Expr::number(-3)).simplify().unwrap(), Expr::number(3));
+    }
+
+    #[test]
+    fn test_simplify_ranges() {
+        let k = Expr::var("k");
+
+        // Σ_{k=3}^{2} k = 0, Π_{k=3}^{2} k = 1, Σ_{k=4}^{4} k² = 16
+        let empty_sum = Expr::sum("k", Expr::number(3), Expr::number(2), k.clone());
+        let empty_product = Expr::product("k", Expr::number(3), Expr::number(2), k.clone());
+        let single = Expr::sum(
+            "k",
+            Expr::number(4),
+            Expr::number(4),
+            Expr::pow(k.clone(), Expr::number(2)),
+        );
+        assert_eq!(empty_sum.simplify().unwrap(), Expr::number(0));
+        assert_eq!(empty_product.simplify().unwrap(), Expr::number(1));
+        assert_eq!(single.simplify().unwrap(), Expr::number(16));
+
+        // An outer fact about k says nothing about the index
+        let assumptions = Assumptions::new().with("k", Assumption::Positive);
+        let relus = Expr::sum("k", Expr::number(-2), Expr::var("n"), Expr::relu(k));
+        assert_eq!(relus.simplify_with(&assumptions).unwrap(), relus);
+    }
+
+    #[test]
+    fn test_simplify_complex_literals() {
+        let i = constants::i();
+
+        // i·i = -1, i³ = -i
+        assert_eq!(Expr::mul(i.clone(), i.clone()).simplify().unwrap(), Expr::number(-1));
+        assert_eq!(
+            Expr::pow(i.clone(), Expr::number(3)).simplify().unwrap(),
+            Expr::complex(Scalar::ZERO, Scalar::from(-1))
+        );
+
+        // 3 + 4i becomes one literal, and its magnitude folds
+        let z = Expr::add(Expr::number(3), Expr::mul(Expr::number(4), i));
+        let folded = Expr::complex(Scalar::from(3), Scalar::from(4));
+        assert_eq!(z.simplify().unwrap(), folded);
+        assert_eq!(Expr::mag(z).simplify().unwrap(), Expr::number(5));
+    }
+
+    #[test]
+    fn test_simplify_euler() {
+        let i = constants::i();
+
+        // e^(iπ) = -1, justified by Euler's formula
+        let expr = Expr::exp(Expr::mul(i.clone(), constants::pi()));
+        let (result, steps) = expr.simplify_traced(&Assumptions::new()).unwrap();
+        assert_eq!(result, Expr::number(-1));
+        assert!(matches!(
+            &steps[0].justification,
+            Justification::AlgebraicIdentity(rule) if rule == "e^(iθ) = cos θ + i sin θ"
+        ));
+
+        // cos θ + i sin θ = e^(iθ) for symbolic θ
+        let theta = Expr::var("θ");
+        let polar = Expr::add(
+            Expr::cos(theta.clone()),
+            Expr::mul(i.clone(), Expr::sin(theta.clone())),
+        );
+        assert_eq!(polar.simplify().unwrap(), Expr::exp(Expr::mul(i, theta)));
+    }
+
+    #[test]
+    fn test_simplify_conjugates() {
+        let z = Expr::var("z");
+
+        assert_eq!(Expr::conj(Expr::conj(z.clone())).simplify().unwrap(), z);
+        assert_eq!(
+            Expr::mul(z.clone(), Expr::conj(z.clone())).simplify().unwrap(),
+            Expr::pow(Expr::mag(z), Expr::number(2))
+        );
+
+        // |e^(iθ)| = 1 needs θ real
+        let unit = Expr::mag(Expr::exp(Expr::mul(constants::i(), Expr::var("t"))));
+        assert_eq!(unit.simplify().unwrap(), unit);
+        let assumptions = Assumptions::new().with("t", Assumption::Real);
+        assert_eq!(unit.simplify_with(&assumptions).unwrap(), Expr::number(1));
+    }
+
+    #[test]
+    fn test_simplify_layer_equation() {
+        let (x, y) = (Expr::var("x"), Expr::var("y"));
+        let w = Expr::matrix(vec![
+            vec![Expr::number(1), Expr::number(2)],
+            vec![Expr::number(3), Expr::number(4)],
+        ]);
+        let b = Expr::vector(vec![Expr::number(5), Expr::number(6)]);
+        let layer = Expr::add(Expr::matmul(w.clone(), Expr::vector(vec![x.clone(), y.clone()])), b);
+
+        // W·x + b = [x + 2y + 5, 3x + 4y + 6]
+        let expected = Expr::vector(vec![
+            Expr::add(Expr::add(x.clone(), Expr::mul(Expr::number(2), y.clone())), Expr::number(5)),
+            Expr::add(
+                Expr::add(Expr::mul(Expr::number(3), x), Expr::mul(Expr::number(4), y)),
+                Expr::number(6),
+            ),
+        ]);
+        assert_eq!(layer.simplify().unwrap(), expected);
+        assert_eq!(
+            Expr::equals(layer, expected).simplify().unwrap(),
+            Expr::Bool(true)
+        );
+
+        // Built-ins on literals: det, trace and a double transpose
+        assert_eq!(Expr::det(w.clone()).simplify().unwrap(), Expr::number(-2));
+        assert_eq!(Expr::trace(w).simplify().unwrap(), Expr::number(5));
+        let a = Expr::var("A");
+        assert_eq!(Expr::transpose(Expr::transpose(a.clone())).simplify().unwrap(), a);
+    }
+
+    #[test]
+    fn test_simplify_checks_shapes() {
+        use crate::error::VeritasError;
+
+        let w = Expr::matrix(vec![vec![Expr::var("a"), Expr::var("b")]]);
+        let x3 = Expr::vector(vec![Expr::number(1), Expr::number(2), Expr::number(3)]);
+        assert!(matches!(
+            Expr::matmul(w, x3).simplify(),
+            Err(VeritasError::ShapeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_simplify_within_budget() {
+        use crate::error::VeritasError;
+        use crate::symbolic::{EvalBudget, Resource};
+
+        let assumptions = Assumptions::new();
+        let budget = EvalBudget::default();
+        let expr = Expr::add(Expr::mul(Expr::number(1), Expr::var("x")), Expr::number(0));
+        assert_eq!(expr.simplify_within(&assumptions, &budget).unwrap(), Expr::var("x"));
+
+        let tight = EvalBudget {
+            max_operations: 3,
+            ..budget
+        };
+        assert!(matches!(
+            expr.simplify_within(&assumptions, &tight),
+            Err(VeritasError::BudgetExceeded { resource: Resource::Operations, .. })
+        ));
+
+        // Folding 10²⁰⁰⁰ passes the digit limit
+        let huge = Expr::pow(Expr::number(10), Expr::number(2000));
+        assert!(matches!(
+            huge.simplify_within(&assumptions, &budget),
+            Err(VeritasError::BudgetExceeded { resource: Resource::Digits, .. })
+        ));
+    }
 }
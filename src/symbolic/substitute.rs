@@ -0,0 +1,341 @@
+//! Substitution and partial evaluation
+//!
+//! `substitute` replaces a variable with an expression. `partial_evaluate`
+//! folds every subtree whose variables are bound in a `Context` and leaves
+//! the rest symbolic, so a formula can be specialized one variable at a
+//! time; `specialize` does that for a single variable and returns the
+//! step for a `ThoughtStructure`.
+
+use super::context::{is_builtin, Value};
+use super::linalg;
+use super::units::Unit;
+use super::{Context, Evaluate, Expr};
+use crate::compositor::{ComputationStep, Justification, Transformation};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+impl Expr {
+    /// Replace every occurrence of `var` with `replacement`
+    pub fn substitute(&self, var: &str, replacement: &Expr) -> Expr {
+        let mut replacements = HashMap::new();
+        replacements.insert(var.to_string(), replacement.clone());
+        self.substitute_all(&replacements)
+    }
+
+    /// Replace several variables at once
+    ///
+    /// Replacements are simultaneous: substituting {x ↦ y, y ↦ x} swaps
+    /// the two, and a replacement is never itself rewritten.
+    pub fn substitute_all(&self, replacements: &HashMap<String, Expr>) -> Expr {
+        match self {
+            Expr::Variable(name) => {
+                return replacements.get(name).cloned().unwrap_or_else(|| self.clone());
+            }
+            Expr::Sum(index, lower, upper, body) => {
+                let (index, body) = substitute_bound(index, body, replacements);
+                return Expr::sum(
+                    index,
+                    lower.substitute_all(replacements),
+                    upper.substitute_all(replacements),
+                    body,
+                );
+            }
+            Expr::Product(index, lower, upper, body) => {
+                let (index, body) = substitute_bound(index, body, replacements);
+                return Expr::product(
+                    index,
+                    lower.substitute_all(replacements),
+                    upper.substitute_all(replacements),
+                    body,
+                );
+            }
+            Expr::Let(name, value, body) => {
+                let (name, body) = substitute_bound(name, body, replacements);
+                return Expr::let_in(name, value.substitute_all(replacements), body);
+            }
+            _ => {}
+        }
+
+        let mapped: std::result::Result<Expr, Infallible> =
+            self.try_map_children(|child| Ok(child.substitute_all(replacements)));
+        match mapped {
+            Ok(expr) => expr,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// Substitute in the body of a sum, product or let
+///
+/// The index shadows a replacement of the same name. If a replacement
+/// mentions the index, the index is renamed first so it is not captured:
+/// Σ_{k} x·k with x ↦ k becomes Σ_{k′} k·k′.
+fn substitute_bound(
+    index: &str,
+    body: &Expr,
+    replacements: &HashMap<String, Expr>,
+) -> (String, Expr) {
+    let mut inner: HashMap<String, Expr> = replacements
+        .iter()
+        .filter(|(name, _)| name.as_str() != index && body.contains_variable(name))
+        .map(|(name, e)| (name.clone(), e.clone()))
+        .collect();
+
+    if !inner.values().any(|e| e.contains_variable(index)) {
+        return (index.to_string(), body.substitute_all(&inner));
+    }
+
+    let mut fresh = format!("{}′", index);
+    while body.contains_variable(&fresh) || inner.values().any(|e| e.contains_variable(&fresh)) {
+        fresh.push('′');
+    }
+    inner.insert(index.to_string(), Expr::var(fresh.clone()));
+    let body = body.substitute_all(&inner);
+    (fresh, body)
+}
+
+impl From<Value> for Expr {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Scalar(s) => Expr::Number(s),
+            Value::Circle(c) => Expr::Complex(c),
+            Value::Bool(b) => Expr::Bool(b),
+            Value::Quantity(q) => Expr::Quantity(q.magnitude(), Unit::si(q.dimension())),
+            Value::Array(a) => {
+                let entries = a.entries().iter().map(|&x| Expr::Number(x)).collect();
+                linalg::from_entries(a.shape(), entries)
+            }
+        }
+    }
+}
+
+/// Trait for evaluating what can be evaluated
+pub trait PartialEvaluate {
+    /// Fold every subtree whose variables are bound, keep the rest
+    fn partial_evaluate(&self, ctx: &Context) -> Result<Expr>;
+
+    /// Bind one variable and fold, as a recordable step
+    fn specialize(&self, var: &str, value: Scalar, ctx: &Context) -> Result<ComputationStep>;
+}
+
+impl PartialEvaluate for Expr {
+    fn partial_evaluate(&self, ctx: &Context) -> Result<Expr> {
+        self.check_complexity(1000)?;
+
+        if is_closed(self, ctx) {
+            match self.evaluate(ctx) {
+                Ok(value) => return Ok(value.into()),
+                // A defined function whose body has free variables
+                Err(VeritasError::VariableNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        match self {
+            Expr::Function(name, args) => match ctx.function(name) {
+                Some(def) => def.apply(name, args)?.partial_evaluate(ctx),
+                None => self.try_map_children(|arg| arg.partial_evaluate(ctx)),
+            },
+
+            // Decided conditions select or drop their branch
+            Expr::Piecewise(branches, otherwise) => {
+                let mut kept = Vec::with_capacity(branches.len());
+                for (condition, value) in branches {
+                    match condition.partial_evaluate(ctx)? {
+                        Expr::Bool(false) => {}
+                        Expr::Bool(true) if kept.is_empty() => return value.partial_evaluate(ctx),
+                        Expr::Bool(true) => {
+                            return Ok(Expr::piecewise(kept, value.partial_evaluate(ctx)?));
+                        }
+                        condition => kept.push((condition, value.partial_evaluate(ctx)?)),
+                    }
+                }
+                let otherwise = otherwise.partial_evaluate(ctx)?;
+                Ok(if kept.is_empty() {
+                    otherwise
+                } else {
+                    Expr::piecewise(kept, otherwise)
+                })
+            }
+
+            // An outer binding of the index name must not reach the body
+            Expr::Sum(index, lower, upper, body) if ctx.contains(index) => Ok(Expr::sum(
+                index.clone(),
+                lower.partial_evaluate(ctx)?,
+                upper.partial_evaluate(ctx)?,
+                (**body).clone(),
+            )),
+            Expr::Product(index, lower, upper, body) if ctx.contains(index) => Ok(Expr::product(
+                index.clone(),
+                lower.partial_evaluate(ctx)?,
+                upper.partial_evaluate(ctx)?,
+                (**body).clone(),
+            )),
+
+            // Inlining the value keeps the body's other names in the outer scope
+            Expr::Let(name, value, body) => {
+                let value = value.partial_evaluate(ctx)?;
+                body.substitute(name, &value).partial_evaluate(ctx)
+            }
+
+            _ => self.try_map_children(|child| child.partial_evaluate(ctx)),
+        }
+    }
+
+    fn specialize(&self, var: &str, value: Scalar, ctx: &Context) -> Result<ComputationStep> {
+        let after = self
+            .substitute(var, &Expr::Number(value))
+            .partial_evaluate(ctx)?;
+
+        Ok(ComputationStep {
+            transformation: Transformation::Substitute {
+                var: var.to_string(),
+                value,
+            },
+            before: self.clone(),
+            after,
+            justification: Justification::VariableBinding,
+        })
+    }
+}
+
+/// Check that every variable is bound and every called function defined
+fn is_closed(expr: &Expr, ctx: &Context) -> bool {
+    expr.variables().iter().all(|v| ctx.contains(v))
+        && expr
+            .functions()
+            .iter()
+            .all(|f| ctx.function(f).is_some() || is_builtin(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        // (x + y)[x := 2y] = 2y + y
+        let x = Expr::var("x");
+        let y = Expr::var("y");
+        let expr = Expr::add(x, y.clone());
+        let two_y = Expr::mul(Expr::number(2), y.clone());
+
+        assert_eq!(expr.substitute("x", &two_y), Expr::add(two_y, y));
+    }
+
+    #[test]
+    fn test_substitution_is_simultaneous() {
+        let expr = Expr::sub(Expr::var("x"), Expr::var("y"));
+        let mut swap = HashMap::new();
+        swap.insert("x".to_string(), Expr::var("y"));
+        swap.insert("y".to_string(), Expr::var("x"));
+
+        assert_eq!(
+            expr.substitute_all(&swap),
+            Expr::sub(Expr::var("y"), Expr::var("x"))
+        );
+    }
+
+    #[test]
+    fn test_substitute_respects_bound_index() {
+        // Σ_{k=1}^{n} x·k: k is bound, x is free
+        let expr = Expr::sum(
+            "k",
+            Expr::number(1),
+            Expr::var("n"),
+            Expr::mul(Expr::var("x"), Expr::var("k")),
+        );
+        assert_eq!(expr.substitute("k", &Expr::number(7)), expr);
+
+        // x ↦ k renames the index instead of capturing k
+        let renamed = expr.substitute("x", &Expr::var("k"));
+        assert_eq!(
+            renamed,
+            Expr::sum(
+                "k′",
+                Expr::number(1),
+                Expr::var("n"),
+                Expr::mul(Expr::var("k"), Expr::var("k′")),
+            )
+        );
+        assert_eq!(renamed.variables(), vec!["k".to_string(), "n".to_string()]);
+    }
+
+    #[test]
+    fn test_partial_evaluate() {
+        // (a * x + b)[a = 2, b = 3] = 2x + 3, with the bound parts folded
+        let expr = Expr::add(
+            Expr::mul(Expr::var("a"), Expr::var("x")),
+            Expr::mul(Expr::var("b"), Expr::number(1)),
+        );
+        let mut ctx = Context::new();
+        ctx.bind("a", 2);
+        ctx.bind("b", 3);
+
+        let expected = Expr::add(Expr::mul(Expr::number(2), Expr::var("x")), Expr::number(3));
+        assert_eq!(expr.partial_evaluate(&ctx).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_partial_evaluate_inlines_functions() {
+        // f(x, y) = x * y; f(y, 2) with y free stays y * 2, not 2 * 2
+        let mut ctx = Context::new();
+        ctx.define(
+            "f",
+            vec!["x".to_string(), "y".to_string()],
+            Expr::mul(Expr::var("x"), Expr::var("y")),
+        )
+        .unwrap();
+        let call = Expr::Function("f".to_string(), vec![Expr::var("y"), Expr::number(2)]);
+
+        assert_eq!(
+            call.partial_evaluate(&ctx).unwrap(),
+            Expr::mul(Expr::var("y"), Expr::number(2))
+        );
+
+        ctx.bind("y", 5);
+        assert_eq!(call.partial_evaluate(&ctx).unwrap(), Expr::number(10));
+    }
+
+    #[test]
+    fn test_let_scopes() {
+        // let a = b + 1 in a·x: a is bound, b and x are free
+        let expr = Expr::let_in(
+            "a",
+            Expr::add(Expr::var("b"), Expr::number(1)),
+            Expr::mul(Expr::var("a"), Expr::var("x")),
+        );
+        assert_eq!(expr.substitute("a", &Expr::number(7)), expr);
+        assert_eq!(
+            expr.substitute("x", &Expr::var("a")),
+            Expr::let_in(
+                "a′",
+                Expr::add(Expr::var("b"), Expr::number(1)),
+                Expr::mul(Expr::var("a′"), Expr::var("a")),
+            )
+        );
+
+        // An outer a does not reach the body
+        let mut ctx = Context::new();
+        ctx.bind("a", 10);
+        ctx.bind("b", 2);
+        assert_eq!(
+            expr.partial_evaluate(&ctx).unwrap(),
+            Expr::mul(Expr::number(3), Expr::var("x"))
+        );
+    }
+
+    #[test]
+    fn test_specialize_step() {
+        let expr = Expr::add(Expr::var("x"), Expr::var("y"));
+        let step = expr.specialize("x", Scalar::from(4), &Context::new()).unwrap();
+
+        assert!(matches!(
+            step.transformation,
+            Transformation::Substitute { ref var, .. } if var == "x"
+        ));
+        assert_eq!(step.after, Expr::add(Expr::number(4), Expr::var("y")));
+    }
+}
@@ -0,0 +1,702 @@
+//! Closed forms for sums and products over integer ranges
+//!
+//! Σ_{k=a}^{b} f is reduced by linearity to polynomial terms (Faulhaber's
+//! formula, exact over the rationals) and geometric terms c·r^(pk+q);
+//! Π_{k=a}^{b} f by multiplicativity to constants, powers whose exponent
+//! is a summable sum, and telescoping ratios f(k+s)/f(k).
+//!
+//! The rules are applied with the bounds left symbolic, and the formula
+//! is only accepted after it agrees with term-by-term evaluation over
+//! several ranges (and the actual one, when it is small). Every closed
+//! form holds for b ≥ a - 1, where the range is empty or longer.
+
+use super::equivalence::equivalent;
+use super::polynomial::{expand, Polynomial};
+use super::rational::Rational;
+use super::{constants, Context, Evaluate, Expr, Simplify};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use crate::verification::{Claim, Proof, VerificationState};
+use std::collections::HashMap;
+
+/// Highest power of the index Faulhaber's formula is applied to
+const MAX_DEGREE: usize = 16;
+
+/// Largest s tried for a telescoping ratio f(k+s)/f(k)
+const MAX_SHIFT: i32 = 3;
+
+/// Ranges the formula is checked on besides those near the lower bound
+const SAMPLE_RANGES: [(i64, i64); 3] = [(1, 5), (-2, 3), (4, 9)];
+
+/// Fewest ranges that must agree before a closed form is accepted
+const MIN_CHECKS: usize = 3;
+
+/// Largest actual range that is also checked term by term
+const MAX_CHECK_TERMS: i64 = 4096;
+
+/// Summation rule applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummationRule {
+    /// Σ (f + g) = Σ f + Σ g, Σ c·f = c·Σ f
+    Linearity,
+    /// Π f·g = Π f · Π g
+    Multiplicativity,
+    /// Σ c = (b - a + 1)·c, Π c = c^(b - a + 1)
+    Constant,
+    /// Σ (c + d·k), a polynomial of degree one
+    Arithmetic,
+    /// Σ c·r^k = c·r^a·(r^(b-a+1) - 1)/(r - 1)
+    Geometric,
+    /// Σ kᵖ by Faulhaber's formula
+    Faulhaber,
+    /// Π f(k+1)/f(k) = f(b+1)/f(a)
+    Telescoping,
+    /// Π r^f(k) = r^(Σ f(k))
+    Exponent,
+}
+
+impl SummationRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SummationRule::Linearity => "linearity",
+            SummationRule::Multiplicativity => "multiplicativity",
+            SummationRule::Constant => "constant term",
+            SummationRule::Arithmetic => "arithmetic series",
+            SummationRule::Geometric => "geometric series",
+            SummationRule::Faulhaber => "Faulhaber's formula",
+            SummationRule::Telescoping => "telescoping product",
+            SummationRule::Exponent => "sum of exponents",
+        }
+    }
+}
+
+/// A closed form together with its verification
+#[derive(Debug, Clone)]
+pub struct ClosedForm {
+    /// The sum or product
+    pub expr: Expr,
+    /// Closed form in the bounds and the free variables
+    pub result: Expr,
+    /// Where the closed form holds: upper ≥ lower - 1
+    pub condition: Expr,
+    /// Rules applied, in order
+    pub rules: Vec<SummationRule>,
+    /// Term-by-term checks
+    pub proof: Proof,
+    pub state: VerificationState,
+}
+
+impl ClosedForm {
+    pub fn is_verified(&self) -> bool {
+        self.state.is_verified()
+    }
+}
+
+/// Trait for finding closed forms of sums and products
+pub trait Summation {
+    /// Closed form, checked against direct evaluation
+    fn closed_form(&self) -> Result<ClosedForm>;
+}
+
+impl Summation for Expr {
+    fn closed_form(&self) -> Result<ClosedForm> {
+        let (index, lower, upper, body) = match self {
+            Expr::Sum(index, lower, upper, body) | Expr::Product(index, lower, upper, body) => {
+                (index, &**lower, &**upper, &**body)
+            }
+            _ => {
+                return Err(VeritasError::InvalidInput(format!(
+                    "{} is not a sum or product",
+                    self
+                )))
+            }
+        };
+        let is_sum = matches!(self, Expr::Sum(..));
+
+        // Derive with symbolic bounds, so the same formula can be checked
+        // on ranges other than the one asked for
+        let mut taken = self.variables();
+        taken.extend(body.variables());
+        let a = fresh("a", &taken);
+        let b = fresh("b", &taken);
+        let (lo, hi) = (Expr::var(a.clone()), Expr::var(b.clone()));
+
+        let mut rules = Vec::new();
+        let generic = if is_sum {
+            sum_of(body, index, &lo, &hi, &mut rules)?
+        } else {
+            product_of(body, index, &lo, &hi, &mut rules)?
+        };
+
+        let condition = Expr::greater_eq(upper.clone(), Expr::sub(lower.clone(), Expr::number(1)))
+            .simplify()?;
+        let result = if condition == Expr::Bool(false) {
+            Expr::number(if is_sum { 0 } else { 1 })
+        } else {
+            let bounds = HashMap::from([(a.clone(), lower.clone()), (b.clone(), upper.clone())]);
+            expand(&generic.substitute_all(&bounds))?.simplify()?
+        };
+
+        let claim = Claim::new(format!("{} = {}", self, result)).with_symbolic(result.clone());
+        let mut proof = Proof::new(claim);
+        for rule in &rules {
+            proof.add_step(rule.name(), "summation table");
+        }
+
+        // Free variables of the body get fixed non-integer values
+        let mut ctx = Context::new();
+        let params = body.variables().into_iter().filter(|v| v != index);
+        for (i, param) in params.enumerate() {
+            ctx.bind(param, Scalar::from(2 * i as i32 + 5) / Scalar::from(3));
+        }
+
+        let mut ranges: Vec<(i64, i64)> = Vec::new();
+        if let Some(start) = integer(lower) {
+            ranges.extend([0, 1, 4, 8].map(|len| (start, start + len - 1)));
+        }
+        ranges.extend(SAMPLE_RANGES);
+
+        let direct = if is_sum {
+            Expr::sum(index.clone(), lo, hi, body.clone())
+        } else {
+            Expr::product(index.clone(), lo, hi, body.clone())
+        };
+        let mut checks = 0;
+        let mut failure = None;
+        for (start, end) in ranges {
+            ctx.bind(a.clone(), Scalar::from_i64(start));
+            ctx.bind(b.clone(), Scalar::from_i64(end));
+            // Ranges the terms are undefined on say nothing either way
+            let Ok(expected) = direct.evaluate_scalar(&ctx) else {
+                continue;
+            };
+            match generic.evaluate_scalar(&ctx) {
+                Ok(actual) if close(expected, actual) => {
+                    proof.add_step(
+                        format!("{} = {}..{}: {} = {}", index, start, end, expected, actual),
+                        "term-by-term evaluation",
+                    );
+                    checks += 1;
+                }
+                Ok(actual) => {
+                    failure.get_or_insert(format!(
+                        "over {}..{} the terms give {} but the closed form {}",
+                        start, end, expected, actual
+                    ));
+                }
+                Err(e) => {
+                    failure.get_or_insert(format!(
+                        "over {}..{} the closed form fails: {}",
+                        start, end, e
+                    ));
+                }
+            }
+        }
+
+        // The range asked for, if it is small enough to add up
+        if let (Some(start), Some(end)) = (integer(lower), integer(upper)) {
+            if end - start < MAX_CHECK_TERMS {
+                match (self.evaluate_scalar(&ctx), result.evaluate_scalar(&ctx)) {
+                    (Ok(expected), Ok(actual)) if close(expected, actual) => {
+                        proof.add_step(
+                            format!("{} = {}..{}: {} = {}", index, start, end, expected, actual),
+                            "term-by-term evaluation",
+                        );
+                        checks += 1;
+                    }
+                    (Ok(expected), actual) => {
+                        failure.get_or_insert(format!(
+                            "over {}..{} the terms give {} but the closed form {:?}",
+                            start, end, expected, actual
+                        ));
+                    }
+                    (Err(_), _) => {}
+                }
+            }
+        }
+
+        let state = match failure {
+            None if checks >= MIN_CHECKS => {
+                proof.verified = true;
+                VerificationState::Verified {
+                    proof_id: proof.id(),
+                }
+            }
+            None => VerificationState::Uncertain {
+                reason: format!("only {} ranges could be evaluated term by term", checks),
+            },
+            Some(reason) => VerificationState::Uncertain { reason },
+        };
+
+        Ok(ClosedForm {
+            expr: self.clone(),
+            result,
+            condition,
+            rules,
+            proof,
+            state,
+        })
+    }
+}
+
+/// Σ_{index=lower}^{upper} body
+fn sum_of(
+    body: &Expr,
+    index: &str,
+    lower: &Expr,
+    upper: &Expr,
+    rules: &mut Vec<SummationRule>,
+) -> Result<Expr> {
+    if let Some(result) = polynomial_sum(body, index, lower, upper, rules)? {
+        return Ok(result);
+    }
+
+    let sum = |e: &Expr, rules: &mut Vec<SummationRule>| sum_of(e, index, lower, upper, rules);
+    match body {
+        Expr::Add(a, b) => {
+            rules.push(SummationRule::Linearity);
+            Ok(Expr::add(sum(a, rules)?, sum(b, rules)?))
+        }
+        Expr::Sub(a, b) => {
+            rules.push(SummationRule::Linearity);
+            Ok(Expr::sub(sum(a, rules)?, sum(b, rules)?))
+        }
+        Expr::Neg(a) => {
+            rules.push(SummationRule::Linearity);
+            Ok(Expr::neg(sum(a, rules)?))
+        }
+        _ => {
+            if let Some(result) = geometric_sum(body, index, lower, upper)? {
+                rules.push(SummationRule::Geometric);
+                return Ok(result);
+            }
+            match body {
+                Expr::Mul(c, f) if !c.contains_variable(index) => {
+                    rules.push(SummationRule::Linearity);
+                    Ok(Expr::mul((**c).clone(), sum(f, rules)?))
+                }
+                Expr::Mul(f, c) if !c.contains_variable(index) => {
+                    rules.push(SummationRule::Linearity);
+                    Ok(Expr::mul(sum(f, rules)?, (**c).clone()))
+                }
+                Expr::Div(f, c) if !c.contains_variable(index) => {
+                    rules.push(SummationRule::Linearity);
+                    Ok(Expr::div(sum(f, rules)?, (**c).clone()))
+                }
+                _ => Err(no_rule("summation", body)),
+            }
+        }
+    }
+}
+
+/// Π_{index=lower}^{upper} body
+fn product_of(
+    body: &Expr,
+    index: &str,
+    lower: &Expr,
+    upper: &Expr,
+    rules: &mut Vec<SummationRule>,
+) -> Result<Expr> {
+    if !body.contains_variable(index) {
+        rules.push(SummationRule::Constant);
+        return Ok(Expr::pow(body.clone(), count(lower, upper)));
+    }
+
+    let product =
+        |e: &Expr, rules: &mut Vec<SummationRule>| product_of(e, index, lower, upper, rules);
+    match body {
+        Expr::Pow(base, exponent) if !base.contains_variable(index) => {
+            rules.push(SummationRule::Exponent);
+            let exponent = sum_of(exponent, index, lower, upper, rules)?;
+            Ok(Expr::pow((**base).clone(), exponent))
+        }
+        Expr::Exp(exponent) => {
+            rules.push(SummationRule::Exponent);
+            Ok(Expr::exp(sum_of(exponent, index, lower, upper, rules)?))
+        }
+        Expr::Div(numer, denom) => {
+            if let Some(result) = telescoping(numer, denom, index, lower, upper)? {
+                rules.push(SummationRule::Telescoping);
+                return Ok(result);
+            }
+            rules.push(SummationRule::Multiplicativity);
+            Ok(Expr::div(product(numer, rules)?, product(denom, rules)?))
+        }
+        Expr::Mul(a, b) => {
+            rules.push(SummationRule::Multiplicativity);
+            Ok(Expr::mul(product(a, rules)?, product(b, rules)?))
+        }
+        Expr::Neg(a) => {
+            rules.push(SummationRule::Multiplicativity);
+            let sign = Expr::pow(Expr::number(-1), count(lower, upper));
+            Ok(Expr::mul(sign, product(a, rules)?))
+        }
+        _ => Err(no_rule("product", body)),
+    }
+}
+
+/// Sum of a polynomial in the index, `None` if the body is not one
+///
+/// Σ_{k=a}^{b} kᵖ = Sₚ(b) - Sₚ(a - 1), which also holds for a ≤ 0.
+fn polynomial_sum(
+    body: &Expr,
+    index: &str,
+    lower: &Expr,
+    upper: &Expr,
+    rules: &mut Vec<SummationRule>,
+) -> Result<Option<Expr>> {
+    let coeffs = Polynomial::from_expr(body)?.coefficients_in(&Expr::var(index));
+    let polynomial = coeffs
+        .iter()
+        .all(|c| c.atoms().iter().all(|atom| !atom.contains_variable(index)));
+    if !polynomial || coeffs.len() > MAX_DEGREE + 1 {
+        return Ok(None);
+    }
+
+    let upper = Polynomial::from_expr(upper)?;
+    let before = Polynomial::from_expr(lower)?.sub(&Polynomial::constant(Rational::ONE))?;
+    let mut total = Polynomial::zero();
+    for (p, c) in coeffs.iter().enumerate() {
+        if c.is_zero() {
+            continue;
+        }
+        let power_sum = faulhaber(p)?;
+        let difference = at(&power_sum, &upper)?.sub(&at(&power_sum, &before)?)?;
+        total = total.add(&c.mul(&difference)?)?;
+    }
+
+    rules.push(match coeffs.len() {
+        0 | 1 => SummationRule::Constant,
+        2 => SummationRule::Arithmetic,
+        _ => SummationRule::Faulhaber,
+    });
+    Ok(Some(total.to_expr()?))
+}
+
+/// Sum of c·r^(pk + q), `None` if the body is not of that form
+fn geometric_sum(body: &Expr, index: &str, lower: &Expr, upper: &Expr) -> Result<Option<Expr>> {
+    let Some((coefficient, base, exponent)) = exponential(body, index) else {
+        return Ok(None);
+    };
+    let coeffs = Polynomial::from_expr(&exponent)?.coefficients_in(&Expr::var(index));
+    let linear = coeffs.len() <= 2
+        && coeffs
+            .iter()
+            .all(|c| c.atoms().iter().all(|atom| !atom.contains_variable(index)));
+    if !linear {
+        return Ok(None);
+    }
+    let slope = match coeffs.get(1) {
+        Some(p) => p.to_expr()?,
+        None => Expr::number(0),
+    };
+
+    // First term and common ratio
+    let first = Expr::mul(
+        coefficient,
+        Expr::pow(base.clone(), exponent.substitute(index, lower)),
+    );
+    let ratio = Expr::pow(base, slope).simplify()?;
+    let terms = count(lower, upper);
+    let constant = Expr::mul(terms.clone(), first.clone());
+    if ratio == Expr::number(1) {
+        return Ok(Some(constant));
+    }
+
+    let series = Expr::div(
+        Expr::mul(first, Expr::sub(Expr::pow(ratio.clone(), terms), Expr::number(1))),
+        Expr::sub(ratio.clone(), Expr::number(1)),
+    );
+    Ok(Some(if ratio.is_constant() {
+        series
+    } else {
+        Expr::piecewise(vec![(Expr::equals(ratio, Expr::number(1)), constant)], series)
+    }))
+}
+
+/// Split c·r^e into (c, r, e) where only e depends on the index
+fn exponential(body: &Expr, index: &str) -> Option<(Expr, Expr, Expr)> {
+    let free = |e: &Expr| !e.contains_variable(index);
+    match body {
+        Expr::Pow(base, exponent) if free(base) => {
+            Some((Expr::number(1), (**base).clone(), (**exponent).clone()))
+        }
+        Expr::Exp(exponent) => Some((Expr::number(1), constants::e(), (**exponent).clone())),
+        Expr::Neg(a) => exponential(a, index).map(|(c, r, e)| (Expr::neg(c), r, e)),
+        Expr::Mul(a, b) if free(a) => {
+            exponential(b, index).map(|(c, r, e)| (Expr::mul((**a).clone(), c), r, e))
+        }
+        Expr::Mul(a, b) if free(b) => {
+            exponential(a, index).map(|(c, r, e)| (Expr::mul(c, (**b).clone()), r, e))
+        }
+        Expr::Div(a, b) if free(b) => {
+            exponential(a, index).map(|(c, r, e)| (Expr::div(c, (**b).clone()), r, e))
+        }
+        _ => None,
+    }
+}
+
+/// Product of f(k+s)/f(k) (or its reciprocal) for a small shift s
+///
+/// Π_{k=a}^{b} f(k+s)/f(k) = f(b+1)···f(b+s) / f(a)···f(a+s-1)
+fn telescoping(
+    numer: &Expr,
+    denom: &Expr,
+    index: &str,
+    lower: &Expr,
+    upper: &Expr,
+) -> Result<Option<Expr>> {
+    let k = Expr::var(index);
+    let after = Expr::add(upper.clone(), Expr::number(1));
+    for shift in 1..=MAX_SHIFT {
+        let shifted = |f: &Expr| f.substitute(index, &Expr::add(k.clone(), Expr::number(shift)));
+        if equivalent(numer, &shifted(denom))? {
+            return Ok(Some(Expr::div(
+                run(denom, index, &after, shift),
+                run(denom, index, lower, shift),
+            )));
+        }
+        if equivalent(denom, &shifted(numer))? {
+            return Ok(Some(Expr::div(
+                run(numer, index, lower, shift),
+                run(numer, index, &after, shift),
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// f(start)·f(start+1)···f(start+len-1)
+fn run(f: &Expr, index: &str, start: &Expr, len: i32) -> Expr {
+    (0..len)
+        .map(|j| f.substitute(index, &Expr::add(start.clone(), Expr::number(j))))
+        .reduce(Expr::mul)
+        .unwrap_or(Expr::number(1))
+}
+
+/// Coefficients of Sₚ(n) = 1ᵖ + 2ᵖ + … + nᵖ, lowest power of n first
+///
+/// Faulhaber's formula: Sₚ(n) = Σⱼ C(p+1, j)·Bⱼ·n^(p+1-j)/(p+1), with the
+/// Bernoulli numbers taken as B₁ = +1/2.
+fn faulhaber(p: usize) -> Result<Vec<Rational>> {
+    let mut bernoulli = vec![Rational::ONE];
+    for m in 1..=p {
+        let mut acc = Rational::ZERO;
+        for (j, b) in bernoulli.iter().enumerate() {
+            acc = acc.checked_add(b.checked_mul(Rational::from_i64(binomial(m + 1, j)))?)?;
+        }
+        bernoulli.push(-acc.checked_div(Rational::from_i64(m as i64 + 1))?);
+    }
+    if p >= 1 {
+        bernoulli[1] = Rational::new(1, 2)?;
+    }
+
+    let scale = Rational::from_i64(p as i64 + 1);
+    let mut coeffs = vec![Rational::ZERO; p + 2];
+    for (j, b) in bernoulli.iter().enumerate() {
+        let term = b.checked_mul(Rational::from_i64(binomial(p + 1, j)))?;
+        coeffs[p + 1 - j] = term.checked_div(scale)?;
+    }
+    Ok(coeffs)
+}
+
+fn binomial(n: usize, k: usize) -> i64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as i64 / (i + 1) as i64)
+}
+
+/// Dense polynomial (lowest power first) evaluated at `x`
+fn at(coeffs: &[Rational], x: &Polynomial) -> Result<Polynomial> {
+    let mut result = Polynomial::zero();
+    for c in coeffs.iter().rev() {
+        result = result.mul(x)?.add(&Polynomial::constant(*c))?;
+    }
+    Ok(result)
+}
+
+/// Number of terms, b - a + 1
+fn count(lower: &Expr, upper: &Expr) -> Expr {
+    Expr::add(Expr::sub(upper.clone(), lower.clone()), Expr::number(1))
+}
+
+fn integer(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Number(n) => n.to_i64(),
+        _ => None,
+    }
+}
+
+/// `base`, primed until it is not among `taken`
+fn fresh(base: &str, taken: &[String]) -> String {
+    let mut name = base.to_string();
+    while taken.contains(&name) {
+        name.push('′');
+    }
+    name
+}
+
+/// Relative agreement to 2⁻³²
+fn close(a: Scalar, b: Scalar) -> bool {
+    let two_16 = Scalar::from(1 << 16);
+    let mut scale = Scalar::ONE;
+    for c in [a.abs(), b.abs()] {
+        if c.inner() > scale.inner() {
+            scale = c;
+        }
+    }
+    (a - b).abs().inner() <= (scale / (two_16 * two_16)).inner()
+}
+
+fn no_rule(kind: &str, body: &Expr) -> VeritasError {
+    VeritasError::UnverifiableClaim(format!("No {} rule matches {}", kind, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn k() -> Expr {
+        Expr::var("k")
+    }
+
+    fn n() -> Expr {
+        Expr::var("n")
+    }
+
+    fn value(expr: &Expr) -> Scalar {
+        expr.evaluate_scalar(&Context::new()).unwrap()
+    }
+
+    #[test]
+    fn test_faulhaber_coefficients() {
+        // S₃(n) = n⁴/4 + n³/2 + n²/4
+        let quarter = Rational::new(1, 4).unwrap();
+        let half = Rational::new(1, 2).unwrap();
+        assert_eq!(
+            faulhaber(3).unwrap(),
+            vec![Rational::ZERO, Rational::ZERO, quarter, half, quarter]
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_series() {
+        // Σ_{k=1}^{n} k = n²/2 + n/2
+        let sum = Expr::sum("k", Expr::number(1), n(), k());
+        let closed = sum.closed_form().unwrap();
+        assert!(closed.is_verified(), "{:?}", closed.state);
+        assert_eq!(closed.rules, vec![SummationRule::Arithmetic]);
+
+        let mut ctx = Context::new();
+        ctx.bind("n", 100);
+        assert_eq!(closed.result.evaluate_scalar(&ctx).unwrap(), Scalar::from(5050));
+    }
+
+    #[test]
+    fn test_faulhaber_with_numeric_bounds() {
+        // Σ_{k=3}^{10} (k³ - 2k) = 3016 - 104
+        let body = Expr::sub(
+            Expr::pow(k(), Expr::number(3)),
+            Expr::mul(Expr::number(2), k()),
+        );
+        let sum = Expr::sum("k", Expr::number(3), Expr::number(10), body);
+        let closed = sum.closed_form().unwrap();
+        assert!(closed.is_verified());
+        assert_eq!(closed.rules, vec![SummationRule::Faulhaber]);
+        assert_eq!(closed.result, Expr::number(2912));
+    }
+
+    #[test]
+    fn test_geometric_series() {
+        // Σ_{k=0}^{n} 3·2^k = 3·(2^(n+1) - 1)
+        let body = Expr::mul(Expr::number(3), Expr::pow(Expr::number(2), k()));
+        let sum = Expr::sum("k", Expr::number(0), n(), body);
+        let closed = sum.closed_form().unwrap();
+        assert!(closed.is_verified());
+        assert!(closed.rules.contains(&SummationRule::Geometric));
+
+        let mut ctx = Context::new();
+        ctx.bind("n", 9);
+        assert_eq!(closed.result.evaluate_scalar(&ctx).unwrap(), Scalar::from(3069));
+    }
+
+    #[test]
+    fn test_geometric_ratio_of_one() {
+        // Σ_{k=1}^{n} r^k needs r ≠ 1; at r = 1 it is n
+        let sum = Expr::sum("k", Expr::number(1), n(), Expr::pow(Expr::var("r"), k()));
+        let closed = sum.closed_form().unwrap();
+        assert!(closed.is_verified());
+
+        let mut ctx = Context::new();
+        ctx.bind("n", 6);
+        ctx.bind("r", 1);
+        assert_eq!(closed.result.evaluate_scalar(&ctx).unwrap(), Scalar::from(6));
+        ctx.bind("r", 2);
+        assert_eq!(closed.result.evaluate_scalar(&ctx).unwrap(), Scalar::from(126));
+    }
+
+    #[test]
+    fn test_mixed_sum() {
+        // Σ_{k=1}^{8} (k² + 2^k) = 204 + 510
+        let body = Expr::add(
+            Expr::pow(k(), Expr::number(2)),
+            Expr::pow(Expr::number(2), k()),
+        );
+        let sum = Expr::sum("k", Expr::number(1), Expr::number(8), body);
+        let closed = sum.closed_form().unwrap();
+        assert!(closed.is_verified());
+        assert_eq!(value(&closed.result), Scalar::from(714));
+    }
+
+    #[test]
+    fn test_telescoping_product() {
+        // Π_{k=1}^{n} (k+1)/k = n + 1
+        let body = Expr::div(Expr::add(k(), Expr::number(1)), k());
+        let product = Expr::product("k", Expr::number(1), n(), body);
+        let closed = product.closed_form().unwrap();
+        assert!(closed.is_verified(), "{:?}", closed.state);
+        assert_eq!(closed.rules, vec![SummationRule::Telescoping]);
+        assert!(equivalent(&closed.result, &Expr::add(n(), Expr::number(1))).unwrap());
+
+        // Π_{k=2}^{7} k/(k+2) = 2·3/(8·9)
+        let body = Expr::div(k(), Expr::add(k(), Expr::number(2)));
+        let product = Expr::product("k", Expr::number(2), Expr::number(7), body);
+        let closed = product.closed_form().unwrap();
+        assert!(closed.is_verified());
+        let expected = Scalar::from(6) / Scalar::from(72);
+        assert!(close(value(&closed.result), expected));
+    }
+
+    #[test]
+    fn test_product_of_powers() {
+        // Π_{k=1}^{4} 2^k = 2^10
+        let product = Expr::product(
+            "k",
+            Expr::number(1),
+            Expr::number(4),
+            Expr::pow(Expr::number(2), k()),
+        );
+        let closed = product.closed_form().unwrap();
+        assert!(closed.is_verified());
+        assert_eq!(closed.rules[0], SummationRule::Exponent);
+        assert_eq!(value(&closed.result), Scalar::from(1024));
+    }
+
+    #[test]
+    fn test_empty_range() {
+        // Σ_{k=5}^{2} k is empty, though the formula alone would say -7
+        let sum = Expr::sum("k", Expr::number(5), Expr::number(2), k());
+        let closed = sum.closed_form().unwrap();
+        assert_eq!(closed.condition, Expr::Bool(false));
+        assert_eq!(closed.result, Expr::number(0));
+        assert!(closed.is_verified());
+    }
+
+    #[test]
+    fn test_no_rule() {
+        // Σ 1/k (harmonic numbers) has no closed form here
+        let sum = Expr::sum("k", Expr::number(1), n(), Expr::div(Expr::number(1), k()));
+        assert!(matches!(
+            sum.closed_form(),
+            Err(VeritasError::UnverifiableClaim(_))
+        ));
+    }
+}
@@ -0,0 +1,496 @@
+//! Physical quantities and units
+//!
+//! A `Quantity` is a Spirix magnitude in SI base units plus its
+//! `Dimension`, the exponents of the seven SI base dimensions.
+//! Arithmetic checks dimensions: adding grams to millilitres is a
+//! `DimensionMismatch`, dividing them is a density. A `Unit` converts to
+//! and from SI with an exact rational scale and offset, so the affine
+//! temperature scales (°C, °F) convert correctly. Quantities are always
+//! absolute: 20 °C is stored as 293.15 K.
+
+use super::rational::Rational;
+use crate::error::{Result, VeritasError};
+use crate::numeric::Scalar;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// SI base unit symbols, in dimension order
+const BASE_SYMBOLS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+/// Exponents of mass, length, time, current, temperature, amount and
+/// luminous intensity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    pub const NONE: Self = Dimension([0; 7]);
+    pub const MASS: Self = Dimension::base(0);
+    pub const LENGTH: Self = Dimension::base(1);
+    pub const TIME: Self = Dimension::base(2);
+    pub const CURRENT: Self = Dimension::base(3);
+    pub const TEMPERATURE: Self = Dimension::base(4);
+    pub const AMOUNT: Self = Dimension::base(5);
+    pub const LUMINOSITY: Self = Dimension::base(6);
+    pub const VOLUME: Self = Dimension([0, 3, 0, 0, 0, 0, 0]);
+
+    const fn base(index: usize) -> Self {
+        let mut exponents = [0; 7];
+        exponents[index] = 1;
+        Dimension(exponents)
+    }
+
+    pub fn exponents(&self) -> [i8; 7] {
+        self.0
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        *self == Dimension::NONE
+    }
+
+    fn combine(&self, other: &Self, op: fn(i8, i8) -> Option<i8>) -> Result<Self> {
+        let mut exponents = [0; 7];
+        for (i, e) in exponents.iter_mut().enumerate() {
+            *e = op(self.0[i], other.0[i]).ok_or(VeritasError::NumericOverflow)?;
+        }
+        Ok(Dimension(exponents))
+    }
+
+    /// Dimension of a product
+    pub fn checked_mul(&self, other: &Self) -> Result<Self> {
+        self.combine(other, i8::checked_add)
+    }
+
+    /// Dimension of a quotient
+    pub fn checked_div(&self, other: &Self) -> Result<Self> {
+        self.combine(other, i8::checked_sub)
+    }
+
+    /// Dimension of an integer power
+    pub fn checked_pow(&self, exp: i64) -> Result<Self> {
+        let exp = i8::try_from(exp).map_err(|_| VeritasError::NumericOverflow)?;
+        let mut exponents = [0; 7];
+        for (i, e) in exponents.iter_mut().enumerate() {
+            *e = self.0[i].checked_mul(exp).ok_or(VeritasError::NumericOverflow)?;
+        }
+        Ok(Dimension(exponents))
+    }
+
+    /// Dimension of a square root (every exponent must be even)
+    pub fn checked_sqrt(&self) -> Result<Self> {
+        if self.0.iter().any(|e| e % 2 != 0) {
+            return Err(VeritasError::UndefinedOperation(format!(
+                "Square root of {} has no dimension",
+                self
+            )));
+        }
+        Ok(Dimension(self.0.map(|e| e / 2)))
+    }
+}
+
+impl fmt::Display for Dimension {
+    /// SI base units, e.g. "kg·m/s^2"; dimensionless is "1"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let factor = |symbol: &str, e: i8| match e.abs() {
+            1 => symbol.to_string(),
+            n => format!("{}^{}", symbol, n),
+        };
+        let pick = |positive: bool| -> Vec<String> {
+            BASE_SYMBOLS
+                .iter()
+                .zip(self.0)
+                .filter(|(_, e)| *e != 0 && (*e > 0) == positive)
+                .map(|(s, e)| factor(s, e))
+                .collect()
+        };
+        let (num, den) = (pick(true), pick(false));
+
+        match (num.is_empty(), den.is_empty()) {
+            (true, true) => write!(f, "1"),
+            (false, true) => write!(f, "{}", num.join("·")),
+            (true, false) => write!(f, "1/{}", den.join("·")),
+            (false, false) => write!(f, "{}/{}", num.join("·"), den.join("·")),
+        }
+    }
+}
+
+/// Unit of measurement: SI value = value × scale + offset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    symbol: String,
+    dimension: Dimension,
+    scale: Rational,
+    offset: Rational,
+}
+
+impl Unit {
+    /// Unit that is `scale` SI base units
+    pub fn linear(symbol: impl Into<String>, dimension: Dimension, scale: Rational) -> Self {
+        Unit {
+            symbol: symbol.into(),
+            dimension,
+            scale,
+            offset: Rational::ZERO,
+        }
+    }
+
+    /// Unit whose zero is shifted from the SI zero (temperature scales)
+    pub fn affine(
+        symbol: impl Into<String>,
+        dimension: Dimension,
+        scale: Rational,
+        offset: Rational,
+    ) -> Self {
+        Unit {
+            symbol: symbol.into(),
+            dimension,
+            scale,
+            offset,
+        }
+    }
+
+    /// Coherent SI unit for a dimension (kg, m^3, kg·m/s^2, ...)
+    pub fn si(dimension: Dimension) -> Self {
+        Unit::linear(dimension.to_string(), dimension, Rational::ONE)
+    }
+
+    /// Look up a unit by symbol
+    pub fn parse(symbol: &str) -> Result<Self> {
+        let ratio = |num: i128, den: i128| Rational::new(num, den);
+        // US customary cup: 236.5882365 ml
+        let cup = ratio(2_365_882_365, 10_000_000_000_000)?;
+        // International pound: 0.45359237 kg
+        let pound = ratio(45_359_237, 100_000_000)?;
+
+        let (dimension, scale) = match symbol {
+            "kg" => (Dimension::MASS, Rational::ONE),
+            "g" => (Dimension::MASS, ratio(1, 1_000)?),
+            "mg" => (Dimension::MASS, ratio(1, 1_000_000)?),
+            "lb" => (Dimension::MASS, pound),
+            "oz" => (Dimension::MASS, pound.checked_div(Rational::from_i64(16))?),
+
+            "m^3" => (Dimension::VOLUME, Rational::ONE),
+            "l" | "L" => (Dimension::VOLUME, ratio(1, 1_000)?),
+            "ml" | "mL" => (Dimension::VOLUME, ratio(1, 1_000_000)?),
+            "cup" => (Dimension::VOLUME, cup),
+            "tbsp" => (Dimension::VOLUME, cup.checked_div(Rational::from_i64(16))?),
+            "tsp" => (Dimension::VOLUME, cup.checked_div(Rational::from_i64(48))?),
+
+            "m" => (Dimension::LENGTH, Rational::ONE),
+            "km" => (Dimension::LENGTH, Rational::from_i64(1_000)),
+            "cm" => (Dimension::LENGTH, ratio(1, 100)?),
+            "mm" => (Dimension::LENGTH, ratio(1, 1_000)?),
+            "in" => (Dimension::LENGTH, ratio(254, 10_000)?),
+            "ft" => (Dimension::LENGTH, ratio(3_048, 10_000)?),
+
+            "s" => (Dimension::TIME, Rational::ONE),
+            "min" => (Dimension::TIME, Rational::from_i64(60)),
+            "h" => (Dimension::TIME, Rational::from_i64(3_600)),
+
+            "A" => (Dimension::CURRENT, Rational::ONE),
+            "mol" => (Dimension::AMOUNT, Rational::ONE),
+            "cd" => (Dimension::LUMINOSITY, Rational::ONE),
+
+            "K" => (Dimension::TEMPERATURE, Rational::ONE),
+            // K = °C + 273.15
+            "°C" | "degC" => {
+                return Ok(Unit::affine(
+                    symbol,
+                    Dimension::TEMPERATURE,
+                    Rational::ONE,
+                    ratio(27_315, 100)?,
+                ))
+            }
+            // K = (°F + 459.67) × 5/9
+            "°F" | "degF" => {
+                return Ok(Unit::affine(
+                    symbol,
+                    Dimension::TEMPERATURE,
+                    ratio(5, 9)?,
+                    ratio(45_967, 180)?,
+                ))
+            }
+
+            _ => {
+                return Err(VeritasError::InvalidInput(format!(
+                    "Unknown unit: {}",
+                    symbol
+                )))
+            }
+        };
+        Ok(Unit::linear(symbol, dimension, scale))
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// Check whether zero in this unit is not zero in SI
+    pub fn is_affine(&self) -> bool {
+        !self.offset.is_zero()
+    }
+
+    /// Value in SI base units
+    pub fn to_si(&self, value: Scalar) -> Result<Scalar> {
+        scale_by(value, self.scale)?.checked_add(self.offset.to_scalar()?)
+    }
+
+    /// Value in this unit from SI base units
+    pub fn from_si(&self, value: Scalar) -> Result<Scalar> {
+        scale_by(value.checked_sub(self.offset.to_scalar()?)?, self.scale.recip()?)
+    }
+
+    /// Exact factor and shift taking a value in this unit to `target`
+    pub fn conversion_to(&self, target: &Unit) -> Result<(Rational, Rational)> {
+        if self.dimension != target.dimension {
+            return Err(mismatch(target.dimension, self.dimension));
+        }
+        let factor = self.scale.checked_div(target.scale)?;
+        let shift = self.offset.checked_sub(target.offset)?.checked_div(target.scale)?;
+        Ok((factor, shift))
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol)
+    }
+}
+
+/// Multiply by an exact ratio: one product, one rounding division
+fn scale_by(value: Scalar, ratio: Rational) -> Result<Scalar> {
+    let num = i64::try_from(ratio.numer()).map_err(|_| VeritasError::NumericOverflow)?;
+    let den = i64::try_from(ratio.denom()).map_err(|_| VeritasError::NumericOverflow)?;
+    value
+        .checked_mul(Scalar::from_i64(num))?
+        .checked_div(Scalar::from_i64(den))
+}
+
+/// Error for a quantity of the wrong dimension
+pub(crate) fn mismatch(expected: Dimension, actual: Dimension) -> VeritasError {
+    VeritasError::DimensionMismatch {
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+/// Magnitude in SI base units with its dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    magnitude: Scalar,
+    dimension: Dimension,
+}
+
+impl Quantity {
+    /// `value` measured in `unit`
+    pub fn new(value: Scalar, unit: &Unit) -> Result<Self> {
+        Ok(Quantity {
+            magnitude: unit.to_si(value)?,
+            dimension: unit.dimension,
+        })
+    }
+
+    /// Pure number
+    pub fn dimensionless(value: Scalar) -> Self {
+        Quantity {
+            magnitude: value,
+            dimension: Dimension::NONE,
+        }
+    }
+
+    /// Magnitude in SI base units
+    pub fn magnitude(&self) -> Scalar {
+        self.magnitude
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// Error unless this quantity has `dimension`
+    pub fn expect_dimension(&self, dimension: Dimension) -> Result<()> {
+        if self.dimension == dimension {
+            Ok(())
+        } else {
+            Err(mismatch(dimension, self.dimension))
+        }
+    }
+
+    /// Value expressed in `unit`
+    pub fn to_unit(&self, unit: &Unit) -> Result<Scalar> {
+        self.expect_dimension(unit.dimension)?;
+        unit.from_si(self.magnitude)
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self> {
+        rhs.expect_dimension(self.dimension)?;
+        Ok(Quantity {
+            magnitude: self.magnitude.checked_add(rhs.magnitude)?,
+            dimension: self.dimension,
+        })
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self> {
+        rhs.expect_dimension(self.dimension)?;
+        Ok(Quantity {
+            magnitude: self.magnitude.checked_sub(rhs.magnitude)?,
+            dimension: self.dimension,
+        })
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self> {
+        Ok(Quantity {
+            magnitude: self.magnitude.checked_mul(rhs.magnitude)?,
+            dimension: self.dimension.checked_mul(&rhs.dimension)?,
+        })
+    }
+
+    pub fn checked_div(&self, rhs: &Self) -> Result<Self> {
+        Ok(Quantity {
+            magnitude: self.magnitude.checked_div(rhs.magnitude)?,
+            dimension: self.dimension.checked_div(&rhs.dimension)?,
+        })
+    }
+
+    /// Power; the exponent must be an integer unless the quantity is
+    /// dimensionless
+    pub fn checked_pow(&self, exp: Scalar) -> Result<Self> {
+        let dimension = if self.dimension.is_dimensionless() {
+            Dimension::NONE
+        } else {
+            let n = exp.to_i64().ok_or_else(|| {
+                VeritasError::UndefinedOperation(format!(
+                    "Non-integer power {} of a quantity in {}",
+                    exp, self.dimension
+                ))
+            })?;
+            self.dimension.checked_pow(n)?
+        };
+
+        Ok(Quantity {
+            magnitude: self.magnitude.pow(exp)?,
+            dimension,
+        })
+    }
+
+    pub fn sqrt(&self) -> Result<Self> {
+        Ok(Quantity {
+            magnitude: self.magnitude.sqrt()?,
+            dimension: self.dimension.checked_sqrt()?,
+        })
+    }
+
+    /// Order two quantities of the same dimension
+    pub fn compare(&self, other: &Self) -> Result<Ordering> {
+        other.expect_dimension(self.dimension)?;
+        self.magnitude.compare(&other.magnitude)
+    }
+}
+
+impl std::ops::Neg for Quantity {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Quantity {
+            magnitude: -self.magnitude,
+            dimension: self.dimension,
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dimension.is_dimensionless() {
+            write!(f, "{}", self.magnitude)
+        } else {
+            write!(f, "{} {}", self.magnitude, self.dimension)
+        }
+    }
+}
+
+/// Convert a value between two units by symbol
+///
+/// The unit ratio is combined exactly before it touches the value, so
+/// 100 °C is exactly 212 °F rather than a round trip through kelvin.
+pub fn convert(value: Scalar, from: &str, to: &str) -> Result<Scalar> {
+    let (factor, shift) = Unit::parse(from)?.conversion_to(&Unit::parse(to)?)?;
+    scale_by(value, factor)?.checked_add(shift.to_scalar()?)
+}
+
+/// Scale quantities proportionally so they sum to `total`
+///
+/// Every part must have the total's dimension. This is recipe scaling:
+/// the ratios between parts are kept and only the overall amount changes.
+pub fn scale_to_total(parts: &[Quantity], total: Quantity) -> Result<Vec<Quantity>> {
+    let mut sum = Quantity {
+        magnitude: Scalar::ZERO,
+        dimension: total.dimension,
+    };
+    for part in parts {
+        sum = sum.checked_add(part)?;
+    }
+    if sum.magnitude.is_zero() {
+        return Err(VeritasError::DivisionByZero);
+    }
+
+    let factor = total.checked_div(&sum)?;
+    parts.iter().map(|part| part.checked_mul(&factor)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grams(n: i32) -> Quantity {
+        Quantity::new(Scalar::from(n), &Unit::parse("g").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_dimension_display() {
+        let force = Dimension::MASS
+            .checked_mul(&Dimension::LENGTH)
+            .unwrap()
+            .checked_div(&Dimension::TIME.checked_pow(2).unwrap())
+            .unwrap();
+        assert_eq!(force.to_string(), "kg·m/s^2");
+        assert_eq!(Dimension::NONE.to_string(), "1");
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let flour = grams(500);
+        let milk = Quantity::new(Scalar::from(250), &Unit::parse("ml").unwrap()).unwrap();
+
+        assert!(matches!(
+            flour.checked_add(&milk),
+            Err(VeritasError::DimensionMismatch { .. })
+        ));
+        // Density is fine
+        let density = flour.checked_div(&milk).unwrap();
+        assert_eq!(density.dimension().to_string(), "kg/m^3");
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(convert(Scalar::from(2), "kg", "g").unwrap(), Scalar::from(2000));
+        assert_eq!(convert(Scalar::from(16), "oz", "lb").unwrap(), Scalar::ONE);
+        assert_eq!(convert(Scalar::from(2), "cup", "tbsp").unwrap(), Scalar::from(32));
+        assert_eq!(convert(Scalar::from(100), "°C", "°F").unwrap(), Scalar::from(212));
+        assert!(convert(Scalar::ONE, "g", "ml").is_err());
+    }
+
+    #[test]
+    fn test_scale_to_total() {
+        // 250 g + 125 g + 125 g of dough, scaled to 1 kg
+        let parts = [grams(250), grams(125), grams(125)];
+        let total = Quantity::new(Scalar::ONE, &Unit::parse("kg").unwrap()).unwrap();
+        let scaled = scale_to_total(&parts, total).unwrap();
+
+        let g = Unit::parse("g").unwrap();
+        let amounts: Vec<Scalar> = scaled.iter().map(|q| q.to_unit(&g).unwrap()).collect();
+        assert_eq!(amounts, vec![Scalar::from(500), Scalar::from(250), Scalar::from(250)]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Base-aware exact arithmetic for basecalc routing
+//!
+//! CONSTITUTION COMPLIANT:
+//! ✓ No IEEE-754
+//! ✓ Exact rationals, no rounding
+//! ✓ Any base from 2 to 36
+//!
+//! Numbers are read with signs and radix-point fractions ("-1A.6" in
+//! dozenal is -22.5), combined with the usual precedence and evaluated
+//! exactly. Results are written back in the source base; a fraction that
+//! does not terminate in that base shows its repeating digits in
+//! parentheses, so dozenal 1/5 is "0.(2497)".
+
+use super::expression_parser::Operation;
+use crate::error::{Result, VeritasError};
+use crate::symbolic::Rational;
+use std::fmt;
+
+/// Smallest supported base
+pub const MIN_BASE: u32 = 2;
+
+/// Largest supported base (digits 0-9 then A-Z)
+pub const MAX_BASE: u32 = 36;
+
+/// Most fractional digits written before a result is cut off with "…"
+pub const MAX_FRACTION_DIGITS: usize = 64;
+
+/// Check that a base is between `MIN_BASE` and `MAX_BASE`
+pub fn check_base(base: u32) -> Result<()> {
+    if !(MIN_BASE..=MAX_BASE).contains(&base) {
+        return Err(VeritasError::InvalidInput(format!(
+            "Base must be between {} and {}, got {}",
+            MIN_BASE, MAX_BASE, base
+        )));
+    }
+    Ok(())
+}
+
+/// Arithmetic expression over exact numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum BaseExpr {
+    Number(Rational),
+    Neg(Box<BaseExpr>),
+    Binary(Operation, Box<BaseExpr>, Box<BaseExpr>),
+}
+
+impl BaseExpr {
+    pub fn number(value: Rational) -> Self {
+        BaseExpr::Number(value)
+    }
+
+    pub fn binary(op: Operation, a: BaseExpr, b: BaseExpr) -> Self {
+        BaseExpr::Binary(op, Box::new(a), Box::new(b))
+    }
+
+    /// Exact value; division by zero is an error, never a special value
+    pub fn evaluate(&self) -> Result<Rational> {
+        match self {
+            BaseExpr::Number(value) => Ok(*value),
+            BaseExpr::Neg(a) => Ok(-a.evaluate()?),
+            BaseExpr::Binary(op, a, b) => {
+                let (a, b) = (a.evaluate()?, b.evaluate()?);
+                match op {
+                    Operation::Add => a.checked_add(b),
+                    Operation::Sub => a.checked_sub(b),
+                    Operation::Mul => a.checked_mul(b),
+                    Operation::Div => {
+                        if b.is_zero() {
+                            return Err(VeritasError::DivisionByZero);
+                        }
+                        a.checked_div(b)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the expression with numbers in `base`
+    pub fn render(&self, base: u32) -> String {
+        BaseDisplay { expr: self, base }.to_string()
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BaseExpr::Binary(Operation::Add | Operation::Sub, _, _) => 1,
+            BaseExpr::Binary(Operation::Mul | Operation::Div, _, _) => 2,
+            BaseExpr::Neg(_) => 3,
+            BaseExpr::Number(value) if value.is_negative() => 3,
+            BaseExpr::Number(_) => 4,
+        }
+    }
+}
+
+struct BaseDisplay<'a> {
+    expr: &'a BaseExpr,
+    base: u32,
+}
+
+impl fmt::Display for BaseDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let base = self.base;
+        let level = self.expr.precedence();
+        let operand = |f: &mut fmt::Formatter, a: &BaseExpr, strict: bool| {
+            let inner = BaseDisplay { expr: a, base };
+            if a.precedence() < level || (strict && a.precedence() == level) {
+                write!(f, "({})", inner)
+            } else {
+                write!(f, "{}", inner)
+            }
+        };
+
+        match self.expr {
+            BaseExpr::Number(value) => match format_in_base(*value, base) {
+                Ok(text) => write!(f, "{}", text),
+                // Too large to expand: the quotient of two whole numbers is still exact
+                Err(_) => {
+                    let radix = base.clamp(MIN_BASE, MAX_BASE) as u128;
+                    let sign = if value.is_negative() { "-" } else { "" };
+                    let num = whole_digits(value.numer().unsigned_abs(), radix);
+                    let den = whole_digits(value.denom() as u128, radix);
+                    write!(f, "({}{}/{})", sign, num, den)
+                }
+            },
+            BaseExpr::Neg(a) => {
+                write!(f, "-")?;
+                // "--x" would read as one token, so nested signs get parentheses
+                operand(f, a, true)
+            }
+            BaseExpr::Binary(op, a, b) => {
+                // Left-associative: an equal-precedence right operand needs parentheses
+                operand(f, a, false)?;
+                write!(f, " {} ", op)?;
+                operand(f, b, true)
+            }
+        }
+    }
+}
+
+/// Value of a digit character in `base`
+fn digit_value(c: char, base: u32) -> Option<u32> {
+    c.to_digit(36).filter(|&d| d < base)
+}
+
+fn digit_char(d: u32) -> char {
+    std::char::from_digit(d, 36)
+        .map(|c| c.to_ascii_uppercase())
+        .unwrap_or('?')
+}
+
+/// Parse an unsigned number such as "1A.6" in `base`
+pub fn parse_number(text: &str, base: u32) -> Result<Rational> {
+    check_base(base)?;
+    let invalid = || {
+        VeritasError::InvalidInput(format!("'{}' is not a number in base {}", text, base))
+    };
+
+    let (whole, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (text, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+
+    let radix = base as i128;
+    let mut num: i128 = 0;
+    let mut den: i128 = 1;
+    for c in whole.chars() {
+        let d = digit_value(c, base).ok_or_else(invalid)?;
+        num = num
+            .checked_mul(radix)
+            .and_then(|n| n.checked_add(d as i128))
+            .ok_or(VeritasError::NumericOverflow)?;
+    }
+    for c in fraction.chars() {
+        let d = digit_value(c, base).ok_or_else(invalid)?;
+        num = num
+            .checked_mul(radix)
+            .and_then(|n| n.checked_add(d as i128))
+            .ok_or(VeritasError::NumericOverflow)?;
+        den = den.checked_mul(radix).ok_or(VeritasError::NumericOverflow)?;
+    }
+    Rational::new(num, den)
+}
+
+/// Write an exact value in `base`, marking repeating fractional digits
+///
+/// Examples: 22.5 in base 12 → "1A.6"; 1/3 in base 10 → "0.(3)". Fails with
+/// `NumericOverflow` when the long division would step past `u128`, which a
+/// denominator above about 2¹²² can do in the larger bases.
+pub fn format_in_base(value: Rational, base: u32) -> Result<String> {
+    let base = base.clamp(MIN_BASE, MAX_BASE) as u128;
+    let num = value.numer().unsigned_abs();
+    let den = value.denom() as u128;
+
+    let mut out = String::new();
+    if value.is_negative() {
+        out.push('-');
+    }
+
+    // Remainders by subtracting the quotient's multiple, not with %
+    let whole = num / den;
+    let mut remainder = num - whole * den;
+    out.push_str(&whole_digits(whole, base));
+
+    if remainder == 0 {
+        return Ok(out);
+    }
+
+    // Long division; a remainder seen before starts the repeating block
+    let mut fraction = String::new();
+    let mut seen: Vec<u128> = Vec::new();
+    while remainder != 0 {
+        if let Some(start) = seen.iter().position(|&r| r == remainder) {
+            let (fixed, repeating) = fraction.split_at(start);
+            return Ok(format!("{}.{}({})", out, fixed, repeating));
+        }
+        if seen.len() == MAX_FRACTION_DIGITS {
+            return Ok(format!("{}.{}…", out, fraction));
+        }
+        seen.push(remainder);
+        let scaled = remainder.checked_mul(base).ok_or(VeritasError::NumericOverflow)?;
+        let digit = scaled / den;
+        fraction.push(digit_char(digit as u32));
+        remainder = scaled - digit * den;
+    }
+    Ok(format!("{}.{}", out, fraction))
+}
+
+/// Digits of a whole number in `base`, most significant first
+fn whole_digits(mut whole: u128, base: u128) -> String {
+    let mut digits = Vec::new();
+    loop {
+        let quotient = whole / base;
+        digits.push(digit_char((whole - quotient * base) as u32));
+        whole = quotient;
+        if whole == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Rational),
+    Op(Operation),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str, base: u32) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if digit_value(c, base).is_some() || c == '.' {
+            let start = i;
+            while i < chars.len() && (digit_value(chars[i], base).is_some() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(parse_number(&literal, base)?));
+            continue;
+        }
+
+        let token = match c {
+            '+' => Token::Op(Operation::Add),
+            '-' | '−' => Token::Op(Operation::Sub),
+            '*' | '×' => Token::Op(Operation::Mul),
+            '/' | '÷' => Token::Op(Operation::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ => {
+                return Err(VeritasError::InvalidInput(format!(
+                    "Unexpected '{}' in base {} expression",
+                    c, base
+                )))
+            }
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Parse an expression such as "1A.6 * -2 + (3 - B) / 4" in `base`
+///
+/// `*` and `/` bind tighter than `+` and `-`; all four are left-associative.
+pub fn parse_in_base(text: &str, base: u32) -> Result<BaseExpr> {
+    check_base(base)?;
+    let mut parser = Parser {
+        tokens: tokenize(text, base)?,
+        pos: 0,
+    };
+    let expr = parser.sum()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some(extra) => Err(VeritasError::InvalidInput(format!(
+            "Unexpected {:?} after expression",
+            extra
+        ))),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next_op(&self, ops: [Operation; 2]) -> Option<Operation> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<BaseExpr> {
+        let mut left = self.product()?;
+        while let Some(op) = self.next_op([Operation::Add, Operation::Sub]) {
+            self.pos += 1;
+            left = BaseExpr::binary(op, left, self.product()?);
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<BaseExpr> {
+        let mut left = self.signed()?;
+        while let Some(op) = self.next_op([Operation::Mul, Operation::Div]) {
+            self.pos += 1;
+            left = BaseExpr::binary(op, left, self.signed()?);
+        }
+        Ok(left)
+    }
+
+    fn signed(&mut self) -> Result<BaseExpr> {
+        match self.next_op([Operation::Add, Operation::Sub]) {
+            Some(Operation::Sub) => {
+                self.pos += 1;
+                Ok(match self.signed()? {
+                    BaseExpr::Number(value) if !value.is_negative() => BaseExpr::Number(-value),
+                    inner => BaseExpr::Neg(Box::new(inner)),
+                })
+            }
+            Some(_) => {
+                self.pos += 1;
+                self.signed()
+            }
+            None => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<BaseExpr> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(BaseExpr::Number(value)),
+            Some(Token::Open) => {
+                let inner = self.sum()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    return Err(VeritasError::InvalidInput("Expected ')'".to_string()));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            other => Err(VeritasError::InvalidInput(format!(
+                "Expected a number or '(', found {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str, base: u32) -> Result<String> {
+        let value = parse_in_base(text, base)?.evaluate()?;
+        format_in_base(value, base)
+    }
+
+    #[test]
+    fn test_multi_digit_and_fractions() {
+        assert_eq!(parse_number("1A.6", 12).unwrap(), Rational::new(45, 2).unwrap());
+        assert_eq!(eval("BB + 1", 12).unwrap(), "100");
+        assert_eq!(eval("101.1 * 10", 2).unwrap(), "1011");
+        assert_eq!(eval("ff.8 - 0.8", 16).unwrap(), "FF");
+        assert!(parse_number("19", 8).is_err());
+        assert!(parse_number(".", 10).is_err());
+    }
+
+    #[test]
+    fn test_precedence_and_signs() {
+        assert_eq!(eval("2 + 3 * 4", 10).unwrap(), "14");
+        assert_eq!(eval("(2 + 3) * 4", 10).unwrap(), "20");
+        assert_eq!(eval("10 - 4 - 3", 10).unwrap(), "3");
+        assert_eq!(eval("-3 * -(2 - 5)", 7).unwrap(), "-12");
+    }
+
+    #[test]
+    fn test_repeating_fractions() {
+        assert_eq!(eval("1 / 3", 10).unwrap(), "0.(3)");
+        assert_eq!(eval("1 / 5", 12).unwrap(), "0.(2497)");
+        assert_eq!(eval("1 / 6", 10).unwrap(), "0.1(6)");
+        assert_eq!(eval("1 / 3", 12).unwrap(), "0.4");
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(eval("5 / (3 - 3)", 10), Err(VeritasError::DivisionByZero));
+        assert_eq!(eval("1 / 0.0", 2), Err(VeritasError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_huge_denominator_overflows_cleanly() {
+        let tiny = Rational::new(1, i128::MAX).unwrap();
+        assert_eq!(format_in_base(tiny, 36), Err(VeritasError::NumericOverflow));
+        // Rendering keeps the exact quotient instead
+        let expr = BaseExpr::Number(tiny);
+        assert_eq!(parse_in_base(&expr.render(36), 36).unwrap().evaluate().unwrap(), tiny);
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        for text in ["1A.6 - (3 - B)", "-(2 + 3) * 4", "1 / (2 / 3)", "-5 - -5"] {
+            let expr = parse_in_base(text, 12).unwrap();
+            assert_eq!(parse_in_base(&expr.render(12), 12).unwrap(), expr);
+        }
+        assert!(parse_in_base("1 + 2", 37).is_err());
+    }
+}
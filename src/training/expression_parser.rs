@@ -2,12 +2,17 @@
 //!
 //! CONSTITUTION COMPLIANT:
 //! ✓ No IEEE-754
-//! ✓ Pure Spirix arithmetic
+//! ✓ Exact rational arithmetic, Spirix results
 //! ✓ Base-aware parsing
 //!
 //! Parses math expressions with explicit base markers for symbolic routing.
+//! The arithmetic itself lives in `basecalc`.
 
+use super::basecalc::{check_base, format_in_base, parse_in_base, BaseExpr};
+use crate::error::{Result, VeritasError};
+use crate::symbolic::Rational;
 use spirix::ScalarF4E4;
+use std::fmt;
 
 /// Mathematical operation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,92 +23,117 @@ pub enum Operation {
     Div,
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operation::Add => "+",
+            Operation::Sub => "-",
+            Operation::Mul => "*",
+            Operation::Div => "/",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
 /// Parsed mathematical expression
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParsedExpression {
-    pub base: u8,
-    pub operand_a: u8,
-    pub operand_b: u8,
-    pub operation: Operation,
+    pub base: u32,
+    pub expr: BaseExpr,
+}
+
+impl ParsedExpression {
+    /// Single operation on two integers
+    pub fn binary(base: u32, a: i64, operation: Operation, b: i64) -> Self {
+        ParsedExpression {
+            base,
+            expr: BaseExpr::binary(
+                operation,
+                BaseExpr::number(Rational::from_i64(a)),
+                BaseExpr::number(Rational::from_i64(b)),
+            ),
+        }
+    }
+
+    /// Exact value of the expression
+    pub fn evaluate(&self) -> Result<Rational> {
+        check_base(self.base)?;
+        self.expr.evaluate()
+    }
+
+    /// Exact value written in the source base, e.g. "1A.6" for dozenal 22.5
+    pub fn answer(&self) -> Result<String> {
+        format_in_base(self.evaluate()?, self.base)
+    }
+}
+
+/// Expression with numbers in its own base
+impl fmt::Display for ParsedExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr.render(self.base))
+    }
 }
 
 /// Parse math expression with base marker
 ///
-/// Format: "dozenal: A + B = " → base=12, a=10, b=11, op=Add
-///         "octal: 7 + 3 = "   → base=8, a=7, b=3, op=Add
+/// Format: "dozenal: A + B = "      → base=12, A + B
+///         "octal: 17 - 3 * 2 = "   → base=8, 17 - 3 * 2
+///         "base7: -1.3 / 2 = "     → base=7, -1.3 / 2
+///
+/// Markers are "binary", "octal", "decimal", "dozenal", "hex" and
+/// "baseN" for any N from 2 to 36. Anything after "=" is ignored.
 pub fn parse_math_expression(input: &[u8]) -> Option<ParsedExpression> {
     let text = String::from_utf8_lossy(input);
 
-    // Check for base markers
-    let base = if text.starts_with("dozenal:") {
-        12
-    } else if text.starts_with("octal:") {
-        8
-    } else if text.starts_with("binary:") {
-        2
-    } else if text.starts_with("decimal:") {
-        10
-    } else {
-        return None;
-    };
-
-    // Find the colon and skip past it
+    // Find the colon and read the marker before it
     let colon_pos = text.find(':')?;
-    let math_part = text[colon_pos + 1..].trim();
+    let base = parse_base_marker(text[..colon_pos].trim())?;
 
-    // Parse "A + B =" or "7 + 3 ="
-    let parts: Vec<&str> = math_part.split_whitespace().collect();
-    if parts.len() < 3 {
+    let math_part = &text[colon_pos + 1..];
+    let math_part = math_part.split('=').next().unwrap_or("").trim();
+    if math_part.is_empty() {
         return None;
     }
 
-    let operand_a = parse_digit_in_base(parts[0], base)?;
-    let operation = parse_operation(parts[1])?;
-    let operand_b = parse_digit_in_base(parts[2], base)?;
-
-    Some(ParsedExpression {
-        base,
-        operand_a,
-        operand_b,
-        operation,
-    })
+    let expr = parse_in_base(math_part, base).ok()?;
+    Some(ParsedExpression { base, expr })
 }
 
-/// Parse digit string in given base
+/// Base named by a marker such as "dozenal" or "base7"
 ///
-/// Examples: "7" in base 12 → 7
-///           "A" in base 12 → 10
-///           "B" in base 12 → 11
-fn parse_digit_in_base(s: &str, base: u8) -> Option<u8> {
-    u8::from_str_radix(s, base as u32).ok()
-}
-
-/// Parse operation symbol
-fn parse_operation(s: &str) -> Option<Operation> {
-    match s {
-        "+" => Some(Operation::Add),
-        "-" => Some(Operation::Sub),
-        "×" | "*" => Some(Operation::Mul),
-        "÷" | "/" => Some(Operation::Div),
-        _ => None,
-    }
+/// Examples: "dozenal" → 12
+///           "base7"   → 7
+///           "base40"  → None (out of range)
+fn parse_base_marker(marker: &str) -> Option<u32> {
+    let marker = marker.to_ascii_lowercase();
+    let base = match marker.as_str() {
+        "binary" => 2,
+        "octal" => 8,
+        "decimal" => 10,
+        "dozenal" => 12,
+        "hex" | "hexadecimal" => 16,
+        _ => marker.strip_prefix("base")?.parse().ok()?,
+    };
+    check_base(base).ok()?;
+    Some(base)
 }
 
-/// Call basecalc (pure Spirix symbolic computation)
+/// Call basecalc (exact symbolic computation)
 ///
 /// This is the ground truth - always correct, no learning needed.
 /// Network learns WHEN to route here, not HOW to compute.
-pub fn call_basecalc(expr: &ParsedExpression) -> ScalarF4E4 {
-    let a = ScalarF4E4::from(expr.operand_a);
-    let b = ScalarF4E4::from(expr.operand_b);
-
-    // Pure Spirix arithmetic - symbolic ground truth
-    match expr.operation {
-        Operation::Add => a + b,
-        Operation::Sub => a - b,
-        Operation::Mul => a * b,
-        Operation::Div => a / b,
+/// Division by zero is an error rather than an undefined scalar.
+pub fn call_basecalc(expr: &ParsedExpression) -> Result<ScalarF4E4> {
+    let value = expr.evaluate()?;
+
+    // Exact rational first, one Spirix division at the end
+    let num = i32::try_from(value.numer()).map_err(|_| VeritasError::NumericOverflow)?;
+    let den = i32::try_from(value.denom()).map_err(|_| VeritasError::NumericOverflow)?;
+    let a = ScalarF4E4::from(num);
+    if den == 1 {
+        return Ok(a);
     }
+    Ok(a / ScalarF4E4::from(den))
 }
 
 #[cfg(test)]
@@ -116,9 +146,8 @@ mod tests {
         let parsed = parse_math_expression(input).unwrap();
 
         assert_eq!(parsed.base, 12);
-        assert_eq!(parsed.operand_a, 10);
-        assert_eq!(parsed.operand_b, 11);
-        assert_eq!(parsed.operation, Operation::Add);
+        assert_eq!(parsed, ParsedExpression::binary(12, 10, Operation::Add, 11));
+        assert_eq!(parsed.answer().unwrap(), "19"); // 21 decimal
     }
 
     #[test]
@@ -127,31 +156,48 @@ mod tests {
         let parsed = parse_math_expression(input).unwrap();
 
         assert_eq!(parsed.base, 8);
-        assert_eq!(parsed.operand_a, 7);
-        assert_eq!(parsed.operand_b, 3);
-        assert_eq!(parsed.operation, Operation::Add);
+        assert_eq!(parsed, ParsedExpression::binary(8, 7, Operation::Add, 3));
+        assert_eq!(parsed.answer().unwrap(), "12");
+    }
+
+    #[test]
+    fn test_parse_any_base_chain() {
+        let parsed = parse_math_expression(b"base7: 16 + 2 * -3.5 = ").unwrap();
+        assert_eq!(parsed.base, 7);
+        // 13 + 2 * -(26/7) = 39/7, which is exact in base 7
+        assert_eq!(parsed.evaluate().unwrap(), Rational::new(39, 7).unwrap());
+        assert_eq!(parsed.answer().unwrap(), "5.4");
+        assert_eq!(parsed.to_string(), "16 + 2 * -3.5");
+
+        let hex = parse_math_expression(b"hex: FF / 10 =").unwrap();
+        assert_eq!(hex.answer().unwrap(), "F.F");
+        assert!(parse_math_expression(b"base37: 1 + 1 =").is_none());
+        assert!(parse_math_expression(b"octal: 8 + 1 =").is_none());
     }
 
     #[test]
     fn test_basecalc_dozenal() {
-        let expr = ParsedExpression {
-            base: 12,
-            operand_a: 10,  // A
-            operand_b: 11,  // B
-            operation: Operation::Add,
-        };
+        let expr = ParsedExpression::binary(12, 10, Operation::Add, 11); // A + B
 
-        let result = call_basecalc(&expr);
+        let result = call_basecalc(&expr).unwrap();
         let expected = ScalarF4E4::from(21u8);  // A + B = 21 (decimal)
 
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_basecalc_division_by_zero() {
+        let expr = parse_math_expression(b"decimal: 4 / (2 - 2) = ").unwrap();
+        assert_eq!(call_basecalc(&expr), Err(VeritasError::DivisionByZero));
+        assert_eq!(expr.answer(), Err(VeritasError::DivisionByZero));
+    }
+
     #[test]
     fn test_no_base_marker() {
         let input = b"The weather is nice";
         let parsed = parse_math_expression(input);
 
         assert!(parsed.is_none(), "Should not parse text as math");
+        assert!(parse_math_expression(b"Note: call me").is_none());
     }
 }
@@ -3,9 +3,9 @@
 //! Generates verified mathematical problems for training.
 
 use crate::persistence::TrainingExample;
-use crate::symbolic::{Expr, Context, Evaluate};
+use crate::symbolic::{Expr, Context, Evaluate, Summation};
 use crate::numeric::Scalar;
-use crate::error::Result;
+use crate::error::{Result, VeritasError};
 
 /// Generates verified training examples
 pub struct TrainingGenerator {
@@ -27,7 +27,7 @@ impl TrainingGenerator {
     /// and provides a proof that the solution is correct.
     pub fn generate(&mut self) -> Result<TrainingExample> {
         // Generate different problem types based on count
-        let problem_type = self.problems_generated % 5;
+        let problem_type = self.problems_generated % 7;
 
         let (problem, solution) = match problem_type {
             0 => self.generate_addition()?,
@@ -35,6 +35,8 @@ impl TrainingGenerator {
             2 => self.generate_power()?,
             3 => self.generate_sqrt()?,
             4 => self.generate_combined()?,
+            5 => self.generate_sum()?,
+            6 => self.generate_product()?,
             _ => unreachable!(),
         };
 
@@ -110,6 +112,51 @@ impl TrainingGenerator {
         Ok((problem, solution))
     }
 
+    /// Generate a finite sum: arithmetic, geometric or a power sum
+    fn generate_sum(&self) -> Result<(Expr, Scalar)> {
+        let k = Expr::var("k");
+        let n = (self.problems_generated % 9) as i32 + 2; // 2-10
+        let body = match (self.problems_generated / 7) % 3 {
+            // a + d·k
+            0 => Expr::add(
+                Expr::number(self.random_small_int()),
+                Expr::mul(Expr::number(self.difficulty as i32 + 1), k),
+            ),
+            // r^k
+            1 => Expr::pow(Expr::number((self.random_small_int().abs() % 3) + 2), k),
+            // k^p
+            _ => Expr::pow(k, Expr::number((self.difficulty % 4) as i32 + 1)),
+        };
+
+        let problem = Expr::sum("k", Expr::number(1), Expr::number(n), body);
+        let solution = Self::closed_form_value(&problem)?;
+        Ok((problem, solution))
+    }
+
+    /// Generate a telescoping product Π (k+s)/k
+    fn generate_product(&self) -> Result<(Expr, Scalar)> {
+        let k = Expr::var("k");
+        let n = (self.problems_generated % 9) as i32 + 2; // 2-10
+        let shift = (self.problems_generated / 7) as i32 % 2 + 1; // 1-2
+
+        let body = Expr::div(Expr::add(k.clone(), Expr::number(shift)), k);
+        let problem = Expr::product("k", Expr::number(1), Expr::number(n), body);
+        let solution = Self::closed_form_value(&problem)?;
+        Ok((problem, solution))
+    }
+
+    /// Value of a sum or product through its checked closed form
+    fn closed_form_value(problem: &Expr) -> Result<Scalar> {
+        let closed = problem.closed_form()?;
+        if !closed.is_verified() {
+            return Err(VeritasError::GenerationFailed(format!(
+                "Closed form of {} not verified: {:?}",
+                problem, closed.state
+            )));
+        }
+        closed.result.evaluate_scalar(&Context::new())
+    }
+
     /// Generate random small integer (for simplicity)
     fn random_small_int(&self) -> i32 {
         // Simple deterministic "random" for now
@@ -18,6 +18,7 @@ pub mod pid_lr;
 pub mod checkpoint;
 pub mod diagnostics;
 pub mod expression_parser;
+pub mod basecalc;
 pub mod code_module;
 pub mod simple_math_parser;
 
@@ -31,5 +32,6 @@ pub use pid_lr::PIDLearningRate;
 pub use checkpoint::Checkpoint;
 pub use diagnostics::Diagnostics;
 pub use expression_parser::{ParsedExpression, Operation, parse_math_expression, call_basecalc};
+pub use basecalc::{BaseExpr, format_in_base, parse_in_base, parse_number};
 pub use code_module::{verify_rust_code, generate_code_examples, generate_test_code_examples, generate_non_code_examples};
 pub use simple_math_parser::{SimpleMathOp, ParsedMath, parse_natural_language_math, contains_math_keywords};
@@ -0,0 +1,541 @@
+//! Certificates for number-theoretic results
+//!
+//! Finding a factor, a primality proof or a gcd can take a long search;
+//! checking the answer should take a handful of multiplications. A
+//! `Certificate` carries exactly what the check needs, and
+//! `Certificate::check` replays it into a `Proof`, one step per fact.
+//!
+//! The checker shares nothing with `number_theory` but `Integer`
+//! arithmetic, so a bug in a search cannot vouch for itself. Primality is
+//! certified in Pratt's form: p is prime when some a has order p - 1
+//! modulo p, shown by a^(p-1) ≡ 1 and a^((p-1)/q) ≢ 1 for every prime q
+//! dividing p - 1, each q certified the same way down to 2.
+
+use super::{Claim, Proof};
+use crate::error::{Result, VeritasError};
+use crate::numeric::Integer;
+use std::collections::HashSet;
+
+/// Pratt certificate that `prime` is prime
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrattCertificate {
+    pub prime: Integer,
+    /// Element of order prime - 1; unused for 2
+    pub witness: Integer,
+    /// Prime factorization of prime - 1, each factor certified
+    pub factors: Vec<(PrattCertificate, u32)>,
+}
+
+impl PrattCertificate {
+    /// Certificate for 2, the base of every chain
+    pub fn two() -> Self {
+        PrattCertificate {
+            prime: Integer::from(2),
+            witness: Integer::one(),
+            factors: Vec::new(),
+        }
+    }
+}
+
+/// Evidence for a number-theoretic result
+#[derive(Debug, Clone, PartialEq)]
+pub enum Certificate {
+    /// gcd(a, b) = gcd = a·x + b·y
+    Bezout {
+        a: Integer,
+        b: Integer,
+        gcd: Integer,
+        x: Integer,
+        y: Integer,
+    },
+    /// lcm(a, b)·gcd(a, b) = |a·b|, the gcd with its Bézout coefficients
+    Lcm {
+        a: Integer,
+        b: Integer,
+        lcm: Integer,
+        gcd: Integer,
+        x: Integer,
+        y: Integer,
+    },
+    /// base^exponent ≡ result (mod modulus) by square and multiply:
+    /// `steps[i]` is base raised to the top i + 1 bits of the exponent
+    Power {
+        base: Integer,
+        exponent: Integer,
+        modulus: Integer,
+        result: Integer,
+        steps: Vec<Integer>,
+    },
+    /// a·inverse ≡ 1 (mod modulus)
+    Inverse {
+        a: Integer,
+        modulus: Integer,
+        inverse: Integer,
+    },
+    /// n < 2 is neither prime nor composite
+    BelowTwo { n: Integer },
+    Prime(PrattCertificate),
+    /// A divisor strictly between 1 and n
+    Composite { n: Integer, factor: Integer },
+    /// `base` fails the strong probable-prime test for n, which no prime can
+    StrongWitness { n: Integer, base: Integer },
+    /// n as a product of certified primes, in increasing order
+    Factorization {
+        n: Integer,
+        factors: Vec<(PrattCertificate, u32)>,
+    },
+    /// numer/denom = [a₀; a₁, …, aₖ] with aᵢ ≥ 1 for i ≥ 1 and aₖ ≥ 2 for k ≥ 1
+    ContinuedFraction {
+        numer: Integer,
+        denom: Integer,
+        terms: Vec<Integer>,
+    },
+}
+
+impl Certificate {
+    /// The result the certificate vouches for
+    pub fn statement(&self) -> String {
+        match self {
+            Certificate::Bezout { a, b, gcd, .. } => format!("gcd({}, {}) = {}", a, b, gcd),
+            Certificate::Lcm { a, b, lcm, .. } => format!("lcm({}, {}) = {}", a, b, lcm),
+            Certificate::Power {
+                base,
+                exponent,
+                modulus,
+                result,
+                ..
+            } => format!("{}^{} ≡ {} (mod {})", base, exponent, result, modulus),
+            Certificate::Inverse { a, modulus, inverse } => {
+                format!("{}⁻¹ ≡ {} (mod {})", a, inverse, modulus)
+            }
+            Certificate::BelowTwo { n } => format!("{} is neither prime nor composite", n),
+            Certificate::Prime(pratt) => format!("{} is prime", pratt.prime),
+            Certificate::Composite { n, .. } | Certificate::StrongWitness { n, .. } => {
+                format!("{} is composite", n)
+            }
+            Certificate::Factorization { n, factors } => {
+                format!("{} = {}", n, product(factors))
+            }
+            Certificate::ContinuedFraction { numer, denom, terms } => {
+                format!("{}/{} = {}", numer, denom, continued_fraction(terms))
+            }
+        }
+    }
+
+    /// Check every fact the certificate asserts
+    ///
+    /// The returned proof lists the facts as steps and is marked verified;
+    /// the first fact that fails is a `ProofInvalid` error.
+    pub fn check(&self) -> Result<Proof> {
+        let mut proof = Proof::new(Claim::new(self.statement()));
+        match self {
+            Certificate::Bezout { a, b, gcd, x, y } => check_bezout(&mut proof, a, b, gcd, x, y)?,
+            Certificate::Lcm {
+                a,
+                b,
+                lcm,
+                gcd,
+                x,
+                y,
+            } => {
+                check_bezout(&mut proof, a, b, gcd, x, y)?;
+                if lcm.is_negative() {
+                    return Err(invalid(format!("lcm {} is negative", lcm)));
+                }
+                let ab = (a * b).abs();
+                if (gcd.is_zero() && !lcm.is_zero()) || lcm * gcd != ab {
+                    return Err(invalid(format!("{}·{} ≠ |{}·{}|", lcm, gcd, a, b)));
+                }
+                proof.add_step(
+                    format!("{}·{} = {}", lcm, gcd, ab),
+                    "lcm(a, b)·gcd(a, b) = |a·b|",
+                );
+            }
+            Certificate::Power {
+                base,
+                exponent,
+                modulus,
+                result,
+                steps,
+            } => check_power(&mut proof, base, exponent, modulus, result, steps)?,
+            Certificate::Inverse { a, modulus, inverse } => {
+                if !modulus.is_positive() || inverse.is_negative() || inverse >= modulus {
+                    return Err(invalid(format!("{} is not a residue mod {}", inverse, modulus)));
+                }
+                let product = a * inverse;
+                if !(&product - Integer::one()).is_divisible_by(modulus) {
+                    return Err(invalid(format!("{}·{} ≢ 1 (mod {})", a, inverse, modulus)));
+                }
+                proof.add_step(
+                    format!("{}·{} = {} ≡ 1 (mod {})", a, inverse, product, modulus),
+                    "multiplication",
+                );
+            }
+            Certificate::BelowTwo { n } => {
+                if *n >= Integer::from(2) {
+                    return Err(invalid(format!("{} is not below 2", n)));
+                }
+                proof.add_step(format!("{} < 2", n), "primes and composites are at least 2");
+            }
+            Certificate::Prime(pratt) => check_pratt(&mut proof, pratt, &mut HashSet::new())?,
+            Certificate::Composite { n, factor } => {
+                if *factor <= Integer::one() || factor >= n || !n.is_divisible_by(factor) {
+                    return Err(invalid(format!("{} is not a proper divisor of {}", factor, n)));
+                }
+                let (cofactor, _) = n.div_rem(factor)?;
+                proof.add_step(format!("{} = {}·{}", n, factor, cofactor), "multiplication");
+            }
+            Certificate::StrongWitness { n, base } => check_witness(&mut proof, n, base)?,
+            Certificate::Factorization { n, factors } => {
+                let mut checked = HashSet::new();
+                for (i, (pratt, exponent)) in factors.iter().enumerate() {
+                    if *exponent == 0 || (i > 0 && factors[i - 1].0.prime >= pratt.prime) {
+                        return Err(invalid(format!("factors of {} are not canonical", n)));
+                    }
+                    check_pratt(&mut proof, pratt, &mut checked)?;
+                }
+                let total = factors
+                    .iter()
+                    .fold(Integer::one(), |acc, (pratt, e)| acc * pratt.prime.pow(*e));
+                if total != *n {
+                    let factors = product(factors);
+                    return Err(invalid(format!("{} = {}, not {}", factors, total, n)));
+                }
+                proof.add_step(format!("{} = {}", product(factors), n), "multiplication");
+            }
+            Certificate::ContinuedFraction { numer, denom, terms } => {
+                check_continued_fraction(&mut proof, numer, denom, terms)?
+            }
+        }
+        proof.verified = true;
+        Ok(proof)
+    }
+}
+
+fn invalid(reason: String) -> VeritasError {
+    VeritasError::ProofInvalid(reason)
+}
+
+fn check_bezout(
+    proof: &mut Proof,
+    a: &Integer,
+    b: &Integer,
+    gcd: &Integer,
+    x: &Integer,
+    y: &Integer,
+) -> Result<()> {
+    if gcd.is_negative() || !a.is_divisible_by(gcd) || !b.is_divisible_by(gcd) {
+        return Err(invalid(format!("{} is not a common divisor of {} and {}", gcd, a, b)));
+    }
+    proof.add_step(format!("{} divides {} and {}", gcd, a, b), "division");
+    if a * x + b * y != *gcd {
+        return Err(invalid(format!("{}·{} + {}·{} ≠ {}", a, x, b, y, gcd)));
+    }
+    proof.add_step(
+        format!("{}·{} + {}·{} = {}", a, x, b, y, gcd),
+        "Bézout: every common divisor of a and b divides a·x + b·y",
+    );
+    Ok(())
+}
+
+fn check_power(
+    proof: &mut Proof,
+    base: &Integer,
+    exponent: &Integer,
+    modulus: &Integer,
+    result: &Integer,
+    steps: &[Integer],
+) -> Result<()> {
+    if !modulus.is_positive() || exponent.is_negative() {
+        return Err(invalid(format!("{}^{} mod {} is not defined", base, exponent, modulus)));
+    }
+    if steps.len() as u64 != exponent.bits() {
+        let bits = exponent.bits();
+        return Err(invalid(format!("{} steps for a {}-bit exponent", steps.len(), bits)));
+    }
+    let reduced = base.rem_euclid(modulus)?;
+    let mut previous = Integer::one().rem_euclid(modulus)?;
+    for (i, step) in steps.iter().enumerate() {
+        let mut expected = &previous * &previous;
+        if exponent.bit(exponent.bits() - 1 - i as u64) {
+            expected = expected * &reduced;
+        }
+        if expected.rem_euclid(modulus)? != *step {
+            return Err(invalid(format!("step {} of {}^{} is wrong", i + 1, base, exponent)));
+        }
+        previous = step.clone();
+    }
+    if previous != *result {
+        return Err(invalid(format!("{}^{} ends at {}, not {}", base, exponent, previous, result)));
+    }
+    proof.add_step(
+        format!("{}^{} ≡ {} (mod {})", base, exponent, result, modulus),
+        format!("{} square-and-multiply steps", steps.len()),
+    );
+    Ok(())
+}
+
+/// Replay a Pratt certificate, skipping primes already checked
+fn check_pratt(
+    proof: &mut Proof,
+    pratt: &PrattCertificate,
+    checked: &mut HashSet<Integer>,
+) -> Result<()> {
+    let p = &pratt.prime;
+    if checked.contains(p) {
+        return Ok(());
+    }
+    if *p == Integer::from(2) {
+        proof.add_step("2 is prime", "base case");
+        checked.insert(p.clone());
+        return Ok(());
+    }
+    if *p < Integer::from(2) {
+        return Err(invalid(format!("{} is not prime", p)));
+    }
+
+    let order = p - Integer::one();
+    let mut total = Integer::one();
+    for (factor, exponent) in &pratt.factors {
+        check_pratt(proof, factor, checked)?;
+        total = total * factor.prime.pow(*exponent);
+    }
+    if total != order {
+        let factors = product(&pratt.factors);
+        return Err(invalid(format!("{} is not a factorization of {}", factors, order)));
+    }
+
+    let a = &pratt.witness;
+    if !power_mod(a, &order, p)?.is_one() {
+        return Err(invalid(format!("{}^{} ≢ 1 (mod {})", a, order, p)));
+    }
+    for (factor, _) in &pratt.factors {
+        let (cofactor, _) = order.div_rem(&factor.prime)?;
+        if power_mod(a, &cofactor, p)?.is_one() {
+            return Err(invalid(format!("{}^{} ≡ 1 (mod {})", a, cofactor, p)));
+        }
+    }
+    proof.add_step(
+        format!("{} is prime", p),
+        format!(
+            "{} has order {} = {} mod {}: a^(p-1) ≡ 1, a^((p-1)/q) ≢ 1 for each prime q",
+            a,
+            order,
+            product(&pratt.factors),
+            p
+        ),
+    );
+    checked.insert(p.clone());
+    Ok(())
+}
+
+/// Strong probable-prime test: for prime n = 2ˢ·d + 1 every base has
+/// a^d ≡ 1 or a^(2ʳ·d) ≡ -1 for some r < s
+fn check_witness(proof: &mut Proof, n: &Integer, base: &Integer) -> Result<()> {
+    let minus_one = n - Integer::one();
+    if *n < Integer::from(5) || n.is_even() || *base <= Integer::one() || *base >= minus_one {
+        return Err(invalid(format!("{} is not a strong witness for {}", base, n)));
+    }
+    let s = minus_one.trailing_zeros().unwrap_or(0);
+    let d = &minus_one >> s;
+    let mut x = power_mod(base, &d, n)?;
+    if x.is_one() || x == minus_one {
+        return Err(invalid(format!("{}^{} ≡ ±1 (mod {})", base, d, n)));
+    }
+    for _ in 1..s {
+        x = (&x * &x).rem_euclid(n)?;
+        if x == minus_one {
+            return Err(invalid(format!("{} reaches -1 mod {}", base, n)));
+        }
+    }
+    proof.add_step(
+        format!("{} - 1 = 2^{}·{}", n, s, d),
+        "factor out powers of two",
+    );
+    proof.add_step(
+        format!(
+            "{}^{} ≢ ±1 and no square up to {}^{} is -1 (mod {})",
+            base, d, base, minus_one, n
+        ),
+        "a prime passes the strong probable-prime test for every base",
+    );
+    Ok(())
+}
+
+fn check_continued_fraction(
+    proof: &mut Proof,
+    numer: &Integer,
+    denom: &Integer,
+    terms: &[Integer],
+) -> Result<()> {
+    let Some(last) = terms.last() else {
+        return Err(invalid("a continued fraction needs a term".to_string()));
+    };
+    if !denom.is_positive() {
+        return Err(invalid(format!("denominator {} is not positive", denom)));
+    }
+    let padded = terms.len() > 1 && *last <= Integer::one();
+    if terms[1..].iter().any(|a| !a.is_positive()) || padded {
+        return Err(invalid(format!("{} is not in canonical form", continued_fraction(terms))));
+    }
+
+    // hᵢ/kᵢ from hᵢ = aᵢ·hᵢ₋₁ + hᵢ₋₂, kᵢ = aᵢ·kᵢ₋₁ + kᵢ₋₂; each convergent
+    // is in lowest terms, so the last equals numer/denom exactly when
+    // their cross products agree
+    let (mut h, mut h_prev) = (Integer::one(), Integer::ZERO);
+    let (mut k, mut k_prev) = (Integer::ZERO, Integer::one());
+    for a in terms {
+        (h, h_prev) = (a * &h + &h_prev, h);
+        (k, k_prev) = (a * &k + &k_prev, k);
+    }
+    if &h * denom != numer * &k {
+        let terms = continued_fraction(terms);
+        return Err(invalid(format!("{} = {}/{}, not {}/{}", terms, h, k, numer, denom)));
+    }
+    proof.add_step(
+        format!("{} = {}/{} = {}/{}", continued_fraction(terms), h, k, numer, denom),
+        format!("{} convergents", terms.len()),
+    );
+    Ok(())
+}
+
+/// base^exponent mod modulus, for the checker's own use
+fn power_mod(base: &Integer, exponent: &Integer, modulus: &Integer) -> Result<Integer> {
+    let base = base.rem_euclid(modulus)?;
+    let mut result = Integer::one().rem_euclid(modulus)?;
+    for i in (0..exponent.bits()).rev() {
+        result = (&result * &result).rem_euclid(modulus)?;
+        if exponent.bit(i) {
+            result = (&result * &base).rem_euclid(modulus)?;
+        }
+    }
+    Ok(result)
+}
+
+fn product(factors: &[(PrattCertificate, u32)]) -> String {
+    if factors.is_empty() {
+        return "1".to_string();
+    }
+    factors
+        .iter()
+        .map(|(pratt, e)| match e {
+            1 => pratt.prime.to_string(),
+            _ => format!("{}^{}", pratt.prime, e),
+        })
+        .collect::<Vec<_>>()
+        .join("·")
+}
+
+fn continued_fraction(terms: &[Integer]) -> String {
+    match terms.split_first() {
+        None => "[]".to_string(),
+        Some((first, [])) => format!("[{}]", first),
+        Some((first, rest)) => {
+            let rest: Vec<String> = rest.iter().map(|a| a.to_string()).collect();
+            format!("[{}; {}]", first, rest.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> Integer {
+        Integer::from(n)
+    }
+
+    fn pratt(p: i64, witness: i64, factors: Vec<(PrattCertificate, u32)>) -> PrattCertificate {
+        PrattCertificate {
+            prime: int(p),
+            witness: int(witness),
+            factors,
+        }
+    }
+
+    #[test]
+    fn test_pratt_certificate() {
+        // 7 - 1 = 2·3 with 3 as a primitive root; 3 - 1 = 2 with 2
+        let three = pratt(3, 2, vec![(PrattCertificate::two(), 1)]);
+        let seven = pratt(7, 3, vec![(PrattCertificate::two(), 1), (three.clone(), 1)]);
+        let proof = Certificate::Prime(seven.clone()).check().unwrap();
+        assert!(proof.verified);
+        assert_eq!(proof.steps.len(), 3);
+
+        // 2 has order 3 mod 7, not 6
+        let mut wrong = seven.clone();
+        wrong.witness = int(2);
+        assert!(Certificate::Prime(wrong).check().is_err());
+
+        // 9 - 1 = 2³, but nothing has order 8 mod 9
+        let nine = pratt(9, 2, vec![(PrattCertificate::two(), 3)]);
+        assert!(Certificate::Prime(nine).check().is_err());
+
+        let factorization = Certificate::Factorization {
+            n: int(63),
+            factors: vec![(three.clone(), 2), (seven.clone(), 1)],
+        };
+        assert!(factorization.check().is_ok());
+        let unordered = Certificate::Factorization {
+            n: int(63),
+            factors: vec![(seven, 1), (three, 2)],
+        };
+        assert!(unordered.check().is_err());
+    }
+
+    #[test]
+    fn test_compositeness() {
+        assert!(Certificate::Composite { n: int(91), factor: int(7) }.check().is_ok());
+        assert!(Certificate::Composite { n: int(91), factor: int(91) }.check().is_err());
+
+        // 2 is a strong witness for 2047 = 23·89 only in part: 2047 is a
+        // strong pseudoprime to base 2, while 3 exposes it
+        assert!(Certificate::StrongWitness { n: int(2047), base: int(2) }.check().is_err());
+        assert!(Certificate::StrongWitness { n: int(2047), base: int(3) }.check().is_ok());
+        assert!(Certificate::StrongWitness { n: int(13), base: int(2) }.check().is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_certificates() {
+        let bezout = Certificate::Bezout {
+            a: int(12),
+            b: int(18),
+            gcd: int(6),
+            x: int(-1),
+            y: int(1),
+        };
+        assert!(bezout.check().is_ok());
+        // 3 = 12·(-1) + 18·... has no solution, and 3 is not the gcd anyway
+        let common = Certificate::Bezout {
+            a: int(12),
+            b: int(18),
+            gcd: int(3),
+            x: int(-1),
+            y: int(1),
+        };
+        assert!(common.check().is_err());
+
+        // 3^5 = 243 ≡ 5 (mod 7) via 3, 3² = 9 ≡ 2, 2²·3 = 12 ≡ 5
+        let power = Certificate::Power {
+            base: int(3),
+            exponent: int(5),
+            modulus: int(7),
+            result: int(5),
+            steps: vec![int(3), int(2), int(5)],
+        };
+        assert!(power.check().is_ok());
+
+        let fraction = Certificate::ContinuedFraction {
+            numer: int(415),
+            denom: int(93),
+            terms: vec![int(4), int(2), int(6), int(7)],
+        };
+        assert!(fraction.check().is_ok());
+        let padded = Certificate::ContinuedFraction {
+            numer: int(415),
+            denom: int(93),
+            terms: vec![int(4), int(2), int(6), int(6), int(1)],
+        };
+        assert!(padded.check().is_err());
+    }
+}
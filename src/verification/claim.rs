@@ -1,6 +1,9 @@
 //! Claims about computations
 
 use crate::numeric::{Circle, Scalar};
+use crate::symbolic::context::Value;
+use crate::symbolic::linalg::Array;
+use crate::symbolic::units::Quantity;
 use crate::symbolic::Expr;
 
 /// A claim about a computation
@@ -22,6 +25,20 @@ pub enum ClaimValue {
     Scalar(Scalar),
     Circle(Circle),
     Boolean(bool),
+    Quantity(Quantity),
+    Array(Array),
+}
+
+impl From<Value> for ClaimValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Scalar(s) => ClaimValue::Scalar(s),
+            Value::Circle(c) => ClaimValue::Circle(c),
+            Value::Bool(b) => ClaimValue::Boolean(b),
+            Value::Quantity(q) => ClaimValue::Quantity(q),
+            Value::Array(a) => ClaimValue::Array(a),
+        }
+    }
 }
 
 impl Claim {
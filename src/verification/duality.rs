@@ -0,0 +1,414 @@
+//! Certificates for linear programs
+//!
+//! Duality makes every answer of the simplex method checkable with a few
+//! exact dot products, however it was found. Write the program as
+//! maximize c·x subject to rows aᵢ·x (≤, ≥, =) bᵢ, with xⱼ ≥ 0 on some
+//! variables (a minimization is checked as the maximization of -c).
+//! Weights y on the rows with yᵢ ≥ 0 on ≤ rows and yᵢ ≤ 0 on ≥ rows give
+//! y·A·x ≤ y·b for every feasible x. So:
+//! - Optimal: a feasible x, and y with (yᵀA)ⱼ ≥ cⱼ on the restricted
+//!   variables and = cⱼ on the free ones, bound every feasible c·x by
+//!   y·b; when c·x = y·b, x attains the bound.
+//! - Infeasible: y with yᵀA ≥ 0 on the restricted variables, = 0 on the
+//!   free ones, and y·b < 0 would give 0 ≤ y·A·x ≤ y·b < 0 (Farkas).
+//! - Unbounded: a feasible x and a ray d keeping every row and sign
+//!   restriction, with c·d > 0.
+//!
+//! The checker uses nothing from the solver but the program itself.
+
+use super::{Claim, Proof};
+use crate::error::{Result, VeritasError};
+use crate::optimization::{LinearProgram, Relation, Sense};
+use crate::symbolic::Rational;
+
+/// Evidence for the outcome of a linear program
+#[derive(Debug, Clone, PartialEq)]
+pub enum DualCertificate {
+    /// `primal` is feasible with objective `value`, and `dual` weights on
+    /// the rows prove nothing feasible does better
+    Optimal {
+        program: LinearProgram,
+        primal: Vec<Rational>,
+        dual: Vec<Rational>,
+        value: Rational,
+    },
+    /// Weights on the rows whose combination reads 0 ≤ (negative)
+    Infeasible {
+        program: LinearProgram,
+        farkas: Vec<Rational>,
+    },
+    /// `point` is feasible and the objective grows along `ray`
+    Unbounded {
+        program: LinearProgram,
+        point: Vec<Rational>,
+        ray: Vec<Rational>,
+    },
+}
+
+impl DualCertificate {
+    pub fn program(&self) -> &LinearProgram {
+        match self {
+            DualCertificate::Optimal { program, .. }
+            | DualCertificate::Infeasible { program, .. }
+            | DualCertificate::Unbounded { program, .. } => program,
+        }
+    }
+
+    /// The result the certificate vouches for
+    pub fn statement(&self) -> String {
+        let program = self.program();
+        let objective = program.render(&program.objective, program.constant);
+        let rows = program.constraints.len();
+        let (extreme, direction) = match program.sense {
+            Sense::Maximize => ("maximum", "above"),
+            Sense::Minimize => ("minimum", "below"),
+        };
+        match self {
+            DualCertificate::Optimal { value, .. } => {
+                format!("the {} of {} under {} constraints is {}", extreme, objective, rows, value)
+            }
+            DualCertificate::Infeasible { .. } => {
+                format!("no point satisfies the {} constraints", rows)
+            }
+            DualCertificate::Unbounded { .. } => {
+                format!("{} is unbounded {} under {} constraints", objective, direction, rows)
+            }
+        }
+    }
+
+    /// Check every fact the certificate asserts
+    ///
+    /// The returned proof lists the facts as steps and is marked verified;
+    /// the first fact that fails is a `ProofInvalid` error.
+    pub fn check(&self) -> Result<Proof> {
+        let mut proof = Proof::new(Claim::new(self.statement()));
+        let program = self.program();
+        check_shape(program)?;
+        let cost = objective(program);
+        match self {
+            DualCertificate::Optimal {
+                primal,
+                dual,
+                value,
+                ..
+            } => {
+                check_feasible(&mut proof, program, primal)?;
+                check_weights(&mut proof, program, dual, &cost)?;
+                let attained = dot(&cost, primal)?;
+                let bound = dot(dual, &bounds(program))?;
+                if attained != bound {
+                    return Err(invalid(format!("c·x = {} but y·b = {}", attained, bound)));
+                }
+                proof.add_step(
+                    format!("c·x = y·b = {}", bound),
+                    "weak duality: no feasible point exceeds y·b",
+                );
+                let total = dot(&program.objective, primal)?.checked_add(program.constant)?;
+                if total != *value {
+                    return Err(invalid(format!("the objective is {}, not {}", total, value)));
+                }
+                proof.add_step(format!("objective at x = {}", value), "substitution");
+            }
+            DualCertificate::Infeasible { farkas, .. } => {
+                let zero = vec![Rational::ZERO; program.variables.len()];
+                check_weights(&mut proof, program, farkas, &zero)?;
+                let combined = dot(farkas, &bounds(program))?;
+                if !combined.is_negative() {
+                    return Err(invalid(format!("y·b = {} is not negative", combined)));
+                }
+                proof.add_step(
+                    format!("y·b = {} < 0", combined),
+                    "Farkas: every feasible x would give 0 ≤ y·A·x ≤ y·b",
+                );
+            }
+            DualCertificate::Unbounded { point, ray, .. } => {
+                check_feasible(&mut proof, program, point)?;
+                check_ray(&mut proof, program, ray)?;
+                let gain = dot(&cost, ray)?;
+                if gain.is_zero() || gain.is_negative() {
+                    return Err(invalid(format!("c·d = {} does not improve", gain)));
+                }
+                proof.add_step(
+                    format!("c·d = {} > 0", gain),
+                    "x + t·d is feasible for every t ≥ 0",
+                );
+            }
+        }
+        proof.verified = true;
+        Ok(proof)
+    }
+}
+
+fn invalid(reason: String) -> VeritasError {
+    VeritasError::ProofInvalid(reason)
+}
+
+fn dot(a: &[Rational], b: &[Rational]) -> Result<Rational> {
+    let mut sum = Rational::ZERO;
+    for (x, y) in a.iter().zip(b) {
+        sum = sum.checked_add(x.checked_mul(*y)?)?;
+    }
+    Ok(sum)
+}
+
+fn bounds(program: &LinearProgram) -> Vec<Rational> {
+    program.constraints.iter().map(|row| row.bound).collect()
+}
+
+/// c, or -c for a minimization
+fn objective(program: &LinearProgram) -> Vec<Rational> {
+    match program.sense {
+        Sense::Maximize => program.objective.clone(),
+        Sense::Minimize => program.objective.iter().map(|&c| -c).collect(),
+    }
+}
+
+fn check_shape(program: &LinearProgram) -> Result<()> {
+    let n = program.variables.len();
+    let rows_fit = program.constraints.iter().all(|row| row.coefficients.len() == n);
+    if program.objective.len() != n || program.nonnegative.len() != n || !rows_fit {
+        return Err(invalid(format!("the program is not over {} variables throughout", n)));
+    }
+    Ok(())
+}
+
+/// Whether `value` (relation) `bound` holds
+fn holds(value: Rational, relation: Relation, bound: Rational) -> Result<bool> {
+    let difference = value.checked_sub(bound)?;
+    Ok(match relation {
+        Relation::Le => difference.is_negative() || difference.is_zero(),
+        Relation::Ge => !difference.is_negative(),
+        Relation::Eq => difference.is_zero(),
+    })
+}
+
+fn check_feasible(proof: &mut Proof, program: &LinearProgram, x: &[Rational]) -> Result<()> {
+    if x.len() != program.variables.len() {
+        let n = program.variables.len();
+        return Err(invalid(format!("{} values for {} variables", x.len(), n)));
+    }
+    for (j, (value, &restricted)) in x.iter().zip(&program.nonnegative).enumerate() {
+        if restricted && value.is_negative() {
+            let name = &program.variables[j];
+            return Err(invalid(format!("{} = {} breaks {} ≥ 0", name, value, name)));
+        }
+    }
+    for row in &program.constraints {
+        let lhs = dot(&row.coefficients, x)?;
+        let text = program.render(&row.coefficients, Rational::ZERO);
+        let symbol = row.relation.symbol();
+        if !holds(lhs, row.relation, row.bound)? {
+            return Err(invalid(format!(
+                "{} = {} breaks {} {} {}",
+                text, lhs, text, symbol, row.bound
+            )));
+        }
+        proof.add_step(format!("{} = {} {} {}", text, lhs, symbol, row.bound), "substitution");
+    }
+    Ok(())
+}
+
+/// Signs of y match the rows, and yᵀA ≥ c on the restricted variables
+/// and = c on the free ones
+fn check_weights(
+    proof: &mut Proof,
+    program: &LinearProgram,
+    y: &[Rational],
+    c: &[Rational],
+) -> Result<()> {
+    if y.len() != program.constraints.len() {
+        let rows = program.constraints.len();
+        return Err(invalid(format!("{} weights for {} rows", y.len(), rows)));
+    }
+    for (i, (weight, row)) in y.iter().zip(&program.constraints).enumerate() {
+        let wrong = match row.relation {
+            Relation::Le => weight.is_negative(),
+            Relation::Ge => !weight.is_negative() && !weight.is_zero(),
+            Relation::Eq => false,
+        };
+        if wrong {
+            let symbol = row.relation.symbol();
+            return Err(invalid(format!(
+                "weight {} on {} row {} has the wrong sign",
+                weight,
+                symbol,
+                i + 1
+            )));
+        }
+    }
+    let weights: Vec<String> = y.iter().map(|w| w.to_string()).collect();
+    proof.add_step(
+        format!("y = ({}) is ≥ 0 on ≤ rows and ≤ 0 on ≥ rows", weights.join(", ")),
+        "so y·A·x ≤ y·b for every feasible x",
+    );
+
+    for (j, name) in program.variables.iter().enumerate() {
+        let mut column = Rational::ZERO;
+        for (weight, row) in y.iter().zip(&program.constraints) {
+            column = column.checked_add(weight.checked_mul(row.coefficients[j])?)?;
+        }
+        let relation = if program.nonnegative[j] { Relation::Ge } else { Relation::Eq };
+        if !holds(column, relation, c[j])? {
+            return Err(invalid(format!(
+                "(yᵀA) at {} is {}, not {} {}",
+                name,
+                column,
+                relation.symbol(),
+                c[j]
+            )));
+        }
+        let reason = if program.nonnegative[j] {
+            format!("{} ≥ 0, so only (yᵀA) ≥ c is needed there", name)
+        } else {
+            format!("{} is free, so (yᵀA) must equal c there", name)
+        };
+        proof.add_step(
+            format!("(yᵀA) at {} = {} {} {}", name, column, relation.symbol(), c[j]),
+            reason,
+        );
+    }
+    Ok(())
+}
+
+/// d keeps every row and sign restriction: A·d ≤ 0 on ≤ rows, ≥ 0 on ≥
+/// rows, = 0 on = rows, dⱼ ≥ 0 on the restricted variables
+fn check_ray(proof: &mut Proof, program: &LinearProgram, d: &[Rational]) -> Result<()> {
+    if d.len() != program.variables.len() {
+        let n = program.variables.len();
+        return Err(invalid(format!("a ray of {} entries for {} variables", d.len(), n)));
+    }
+    for (j, (value, &restricted)) in d.iter().zip(&program.nonnegative).enumerate() {
+        if restricted && value.is_negative() {
+            let name = &program.variables[j];
+            return Err(invalid(format!("the ray lowers {}, which must stay ≥ 0", name)));
+        }
+    }
+    for row in &program.constraints {
+        let change = dot(&row.coefficients, d)?;
+        if !holds(change, row.relation, Rational::ZERO)? {
+            let text = program.render(&row.coefficients, Rational::ZERO);
+            return Err(invalid(format!("along the ray {} changes by {}", text, change)));
+        }
+    }
+    let ray: Vec<String> = d.iter().map(|v| v.to_string()).collect();
+    proof.add_step(
+        format!("d = ({}) keeps every constraint", ray.join(", ")),
+        "each row changes by aᵢ·d in its own direction",
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::Constraint;
+
+    fn int(n: i64) -> Rational {
+        Rational::from_i64(n)
+    }
+
+    fn ints(values: &[i64]) -> Vec<Rational> {
+        values.iter().map(|&v| int(v)).collect()
+    }
+
+    /// maximize x + y subject to x + 2y ≤ 4, 3x + y ≤ 6, x, y ≥ 0
+    fn program() -> LinearProgram {
+        LinearProgram {
+            variables: vec!["x".to_string(), "y".to_string()],
+            sense: Sense::Maximize,
+            objective: ints(&[1, 1]),
+            constant: Rational::ZERO,
+            constraints: vec![
+                Constraint {
+                    coefficients: ints(&[1, 2]),
+                    relation: Relation::Le,
+                    bound: int(4),
+                },
+                Constraint {
+                    coefficients: ints(&[3, 1]),
+                    relation: Relation::Le,
+                    bound: int(6),
+                },
+            ],
+            nonnegative: vec![true, true],
+        }
+    }
+
+    #[test]
+    fn test_optimal_certificate() {
+        // x = (8/5, 6/5) with y = (2/5, 1/5): both sides are 14/5
+        let fifths = |values: &[i128]| -> Vec<Rational> {
+            values.iter().map(|&v| Rational::new(v, 5).unwrap()).collect()
+        };
+        let certificate = DualCertificate::Optimal {
+            program: program(),
+            primal: fifths(&[8, 6]),
+            dual: fifths(&[2, 1]),
+            value: Rational::new(14, 5).unwrap(),
+        };
+        let proof = certificate.check().unwrap();
+        assert!(proof.verified);
+        assert!(certificate.statement().contains("maximum of x + y"));
+
+        // A feasible but suboptimal point cannot match the dual bound
+        let suboptimal = DualCertificate::Optimal {
+            program: program(),
+            primal: ints(&[1, 1]),
+            dual: fifths(&[2, 1]),
+            value: int(2),
+        };
+        assert!(suboptimal.check().is_err());
+
+        // Nor can weights of the wrong sign
+        let signs = DualCertificate::Optimal {
+            program: program(),
+            primal: fifths(&[8, 6]),
+            dual: fifths(&[-2, 1]),
+            value: Rational::new(14, 5).unwrap(),
+        };
+        assert!(signs.check().is_err());
+    }
+
+    #[test]
+    fn test_farkas_and_ray() {
+        // Adding x + y ≥ 10 makes the program infeasible: weights
+        // (1, 1, -2) combine the rows into 2x + y ≤ -10 with x, y ≥ 0
+        let mut infeasible = program();
+        infeasible.constraints.push(Constraint {
+            coefficients: ints(&[1, 1]),
+            relation: Relation::Ge,
+            bound: int(10),
+        });
+        let farkas = DualCertificate::Infeasible {
+            program: infeasible.clone(),
+            farkas: ints(&[1, 1, -2]),
+        };
+        assert!(farkas.check().unwrap().verified);
+        let wrong = DualCertificate::Infeasible {
+            program: infeasible,
+            farkas: ints(&[1, 1, -1]),
+        };
+        assert!(wrong.check().is_err());
+
+        // Under x - 2y ≤ 4 alone, x + y grows along (2, 1) but not along
+        // (2, -1), which would take y below zero
+        let mut open = program();
+        open.constraints = vec![Constraint {
+            coefficients: ints(&[1, -2]),
+            relation: Relation::Le,
+            bound: int(4),
+        }];
+        let unbounded = DualCertificate::Unbounded {
+            program: open.clone(),
+            point: ints(&[0, 0]),
+            ray: ints(&[2, 1]),
+        };
+        assert!(unbounded.check().unwrap().verified);
+        let lowering = DualCertificate::Unbounded {
+            program: open,
+            point: ints(&[0, 0]),
+            ray: ints(&[2, -1]),
+        };
+        assert!(lowering.check().is_err());
+    }
+}
@@ -3,11 +3,15 @@
 //! This module provides the verification layer that ensures
 //! symbolic computations match their claimed results.
 
+pub mod certificate;
 pub mod claim;
+pub mod duality;
 pub mod proof;
 pub mod state;
 
+pub use certificate::{Certificate, PrattCertificate};
 pub use claim::Claim;
+pub use duality::DualCertificate;
 pub use proof::Proof;
 pub use state::VerificationState;
 
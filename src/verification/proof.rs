@@ -1,6 +1,8 @@
 //! Proofs of correctness
 
 use super::Claim;
+use crate::error::Result;
+use crate::logic::cnf::Clause;
 
 /// A proof that a claim is correct
 #[derive(Debug, Clone)]
@@ -23,6 +25,32 @@ pub struct ProofStep {
 
     /// Justification (axiom, previous step, etc.)
     pub justification: String,
+
+    /// Machine-checkable content, for steps that have one
+    pub inference: Option<Inference>,
+}
+
+/// Step of a resolution refutation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inference {
+    /// Clause of the formula being refuted
+    Premise(Clause),
+    /// Resolvent of two earlier steps (by index) on a variable
+    Resolution {
+        left: usize,
+        right: usize,
+        pivot: usize,
+        clause: Clause,
+    },
+}
+
+impl Inference {
+    /// Clause this step derives
+    pub fn clause(&self) -> &Clause {
+        match self {
+            Inference::Premise(clause) | Inference::Resolution { clause, .. } => clause,
+        }
+    }
 }
 
 impl Proof {
@@ -38,6 +66,42 @@ impl Proof {
         self.steps.push(ProofStep {
             description: description.into(),
             justification: justification.into(),
+            inference: None,
+        });
+    }
+
+    /// Add a step that a checker can replay
+    pub fn add_inference(
+        &mut self,
+        description: impl Into<String>,
+        justification: impl Into<String>,
+        inference: Inference,
+    ) {
+        self.steps.push(ProofStep {
+            description: description.into(),
+            justification: justification.into(),
+            inference: Some(inference),
         });
     }
+
+    /// Replay a resolution refutation and mark the proof verified
+    ///
+    /// The claim statement must be a propositional formula; see
+    /// `logic::check_refutation` for what is checked.
+    pub fn check_resolution(&mut self) -> Result<()> {
+        crate::logic::check_refutation(self)?;
+        self.verified = true;
+        Ok(())
+    }
+
+    /// Content hash of the claim and every step
+    pub fn id(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.claim.statement.as_bytes());
+        for step in &self.steps {
+            hasher.update(step.description.as_bytes());
+            hasher.update(step.justification.as_bytes());
+        }
+        hasher.finalize().to_hex().to_string()
+    }
 }